@@ -0,0 +1,116 @@
+//! Poke at a persisted store
+//!
+//! All this needs is the model, and the store. The domain doesn't have to be
+//! compiled, which is sort of the point.
+use std::{path::PathBuf, process::ExitCode};
+
+use ansi_term::Colour;
+use clap::{Parser, Subcommand};
+use grace::inspect::{Link, StoreInspector};
+use uuid::Uuid;
+
+#[derive(Debug, Parser)]
+#[command(author, version, about, long_about = None)]
+struct Arguments {
+    /// The model file
+    model: PathBuf,
+    /// The store
+    ///
+    /// This is the directory that was passed to `persist`, or the file written
+    /// by `persist_json_file` or `persist_jsonl`. Vec stores aren't supported.
+    store: PathBuf,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Count the instances of each object
+    Counts,
+    /// Dump the instances of an object as a table
+    Dump { object: String },
+    /// Show an instance, and everything related to it
+    Follow { id: Uuid },
+    /// List references to instances that don't exist
+    ///
+    /// Exits with a failure if there are any.
+    Dangling,
+}
+
+fn main() -> ExitCode {
+    let args = Arguments::parse();
+
+    let inspector = match StoreInspector::open(&args.model, &args.store) {
+        Ok(inspector) => inspector,
+        Err(e) => {
+            eprintln!("{}: {e}", Colour::Red.paint("error"));
+            return ExitCode::FAILURE;
+        }
+    };
+
+    for warning in inspector.warnings() {
+        eprintln!("{}: {warning}", Colour::Yellow.paint("warning"));
+    }
+
+    match args.command.unwrap_or(Command::Counts) {
+        Command::Counts => {
+            println!("{}", Colour::Blue.paint(inspector.domain_name()));
+            let width = inspector
+                .counts()
+                .iter()
+                .map(|(name, _)| name.len())
+                .max()
+                .unwrap_or_default();
+            for (name, count) in inspector.counts() {
+                println!("{name:width$}  {count}");
+            }
+        }
+        Command::Dump { object } => match inspector.table(&object) {
+            Ok(table) => print!("{table}"),
+            Err(e) => {
+                eprintln!("{}: {e}", Colour::Red.paint("error"));
+                return ExitCode::FAILURE;
+            }
+        },
+        Command::Follow { id } => match inspector.follow(&id) {
+            Ok(neighborhood) => {
+                println!("{}", Colour::Blue.paint(&neighborhood.object));
+                print!("{}", neighborhood.instance);
+                print_links("outgoing", &neighborhood.outgoing);
+                print_links("incoming", &neighborhood.incoming);
+            }
+            Err(e) => {
+                eprintln!("{}: {e}", Colour::Red.paint("error"));
+                return ExitCode::FAILURE;
+            }
+        },
+        Command::Dangling => {
+            let dangling = inspector.dangling();
+            for link in &dangling {
+                println!("{link}");
+            }
+            if !dangling.is_empty() {
+                eprintln!(
+                    "{}: {} dangling references",
+                    Colour::Red.paint("error"),
+                    dangling.len()
+                );
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
+fn print_links(heading: &str, links: &[Link]) {
+    println!();
+    println!("{}", Colour::White.dimmed().paint(heading));
+    for link in links {
+        if link.present {
+            println!("{link}");
+        } else {
+            println!("{}", Colour::Red.paint(link.to_string()));
+        }
+    }
+}
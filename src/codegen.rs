@@ -3,19 +3,25 @@
 pub(crate) mod buffer;
 pub(crate) mod diff_engine;
 pub(crate) mod generator;
+pub(crate) mod model_diff;
 pub(crate) mod render;
 mod rustfmt;
 
-use std::{fmt::Write, iter::zip, sync::Arc};
+use std::{
+    fmt::Write,
+    iter::zip,
+    sync::{Arc, RwLock},
+};
 
 use rustc_hash::FxHashMap as HashMap;
 use sarzak::{
     lu_dog::{
+        store::ObjectStore as LuDogStore,
         types::{ValueType, WoogOption},
         Reference,
     },
     mc::{CompilerSnafu, FormatSnafu, Result},
-    sarzak::types::{Conditionality, External, Object, Ty},
+    sarzak::types::{Cardinality, Conditionality, External, Object, Ty},
     v2::domain::Domain,
     woog::{
         store::ObjectStore as WoogStore,
@@ -36,8 +42,8 @@ use crate::{
         diff_engine::DirectiveKind,
         render::{ForStore, RenderIdent, RenderType},
     },
-    options::{GraceConfig, UberStoreOptions},
-    target::dwarf::LU_DOG,
+    options::{AsyncRuntime, GraceConfig, UberStoreOptions, UuidMode},
+    s_read,
     todo::{GType, LValue, ObjectMethod, RValue},
     types::domain::hybrid::SUBTYPE_ATTR,
     Lock,
@@ -433,6 +439,65 @@ pub(crate) fn render_method_definition_new(
     Ok(())
 }
 
+/// Collect the parameters of a method
+///
+/// This walks the parameter list just like [`render_method_definition_new`],
+/// but instead of writing the signature, it returns the name, the rendered
+/// type, and whether the parameter is optional. The store is included, so
+/// filter it out if you don't want it.
+pub(crate) fn get_method_parameters(
+    method: &WoogObjectMethod,
+    config: &GraceConfig,
+    woog: &WoogStore,
+    domain: &Domain,
+) -> Vec<(String, String, bool)> {
+    let is_uber = config.is_uber_store();
+    let mut result = Vec::new();
+
+    let param = woog.iter_parameter().find(|p| {
+        if let Some(func_id) = p.function {
+            func_id == method.r25_function(woog).pop().unwrap().id
+                && p.r1c_parameter(woog).is_empty()
+        } else {
+            false
+        }
+    });
+
+    if let Some(mut param) = param {
+        loop {
+            let value = param
+                .r8_variable(woog)
+                .pop()
+                .unwrap()
+                .r7_x_value(woog)
+                .pop()
+                .unwrap();
+            let ty = value.r3_grace_type(woog)[0];
+            let access = value.r16_access(woog)[0];
+            let mutability = access.r15_ownership(woog)[0];
+
+            let param_name = param.r8_variable(woog)[0].name.as_ident();
+            let is_optional = matches!(ty, GraceType::WoogOption(_));
+
+            let ty = if is_uber && param_name != "store" {
+                ty.for_store(mutability, config, woog, domain)
+            } else {
+                ty.as_type(mutability, woog, domain)
+            };
+
+            result.push((param_name, ty, is_optional));
+
+            if let Some(next_param) = param.r1_parameter(woog).pop() {
+                param = next_param;
+            } else {
+                break;
+            }
+        }
+    }
+
+    result
+}
+
 /// Generate code to create a new UUID
 ///
 /// Hmmm. This is a function call. I happen to be modeling one of these. Let's
@@ -459,7 +524,9 @@ pub(crate) fn render_make_uuid(
     buffer: &mut Buffer,
     lval: &LValue,
     rvals: &Vec<RValue>,
-    _domain: &Domain,
+    config: &GraceConfig,
+    imports: &Option<&HashMap<String, Domain>>,
+    domain: &Domain,
 ) -> Result<()> {
     ensure!(
         lval.ty == GType::Uuid,
@@ -475,41 +542,53 @@ pub(crate) fn render_make_uuid(
     let mut args = String::new();
     for val in rvals {
         match &val.ty {
-            GType::Reference(_) => {
-                format_string.extend(["{:?}:"]);
+            GType::Reference(obj_id) => {
+                let obj = domain.sarzak().exhume_object(obj_id).unwrap();
+                format_string.extend(["{}:"]);
+                args.extend([
+                    referent_id(&val.name, obj, config, imports, domain)?,
+                    ",".to_owned(),
+                ]);
             }
-            GType::Option(_) => {
+            GType::Option(inner) => {
                 format_string.extend(["{:?}:"]);
+                if let GType::Reference(obj_id) = **inner {
+                    let obj = domain.sarzak().exhume_object(&obj_id).unwrap();
+                    args.extend([
+                        optional_referent_id(&val.name, obj, config, imports, domain)?,
+                        ",".to_owned(),
+                    ]);
+                } else {
+                    args.extend([val.name.to_owned(), ",".to_owned()]);
+                }
+            }
+            // See the comment in `render_make_uuid_new`.
+            GType::External(ext) => {
+                if ext.name == "SystemTime" {
+                    format_string.extend(["{:?}:"]);
+                    args.extend([val.name.to_owned(), ",".to_owned()]);
+                }
             }
             _ => {
                 format_string.extend(["{}:"]);
+                args.extend([val.name.to_owned(), ",".to_owned()]);
             }
         }
-
-        args.extend([val.name.to_owned(), ",".to_owned()]);
     }
     // Remove the trailing ":"
     format_string.pop();
     // And the trailining ","
     args.pop();
 
-    // emit!(
-    //     buffer,
-    //     "let {} = Uuid::new_v5(&UUID_NS, format!(\"{}\", {}).as_bytes());",
-    //     lval.name,
-    //     format_string,
-    //     args
-    // );
-
-    emit!(buffer, "let {} = Uuid::new_v4();", lval.name);
-
-    Ok(())
+    emit_new_uuid(buffer, &lval.name, &format_string, &args, config)
 }
 
 pub(crate) fn render_make_uuid_new(
     buffer: &mut Buffer,
     var: &Local,
     method: &WoogObjectMethod,
+    config: &GraceConfig,
+    imports: &Option<&HashMap<String, Domain>>,
     woog: &WoogStore,
     domain: &Domain,
 ) -> Result<()> {
@@ -551,12 +630,12 @@ pub(crate) fn render_make_uuid_new(
         }
     });
 
+    let mut format_string = String::new();
+    let mut args = String::new();
+
     if param.is_some() {
         let mut param = param.unwrap();
 
-        let mut format_string = String::new();
-        let mut args = String::new();
-
         loop {
             let value = param
                 .r8_variable(woog)
@@ -568,13 +647,44 @@ pub(crate) fn render_make_uuid_new(
             let ty = value.r3_grace_type(woog)[0];
 
             match &ty {
-                GraceType::Reference(_) => {
-                    format_string.extend(["{:?}:"]);
-                    args.extend([param.r8_variable(woog)[0].name.as_ident(), ",".to_owned()]);
+                GraceType::Reference(id) => {
+                    let obj = woog
+                        .exhume_reference(id)
+                        .unwrap()
+                        .r13_object(domain.sarzak())[0];
+                    format_string.extend(["{}:"]);
+                    args.extend([
+                        referent_id(
+                            &param.r8_variable(woog)[0].name.as_ident(),
+                            obj,
+                            config,
+                            imports,
+                            domain,
+                        )?,
+                        ",".to_owned(),
+                    ]);
                 }
-                GraceType::WoogOption(_) => {
+                GraceType::WoogOption(id) => {
+                    let opt = woog.exhume_woog_option(id).unwrap();
                     format_string.extend(["{:?}:"]);
-                    args.extend([param.r8_variable(woog)[0].name.as_ident(), ",".to_owned()]);
+                    if let GraceType::Reference(id) = opt.r20_grace_type(woog)[0] {
+                        let obj = woog
+                            .exhume_reference(id)
+                            .unwrap()
+                            .r13_object(domain.sarzak())[0];
+                        args.extend([
+                            optional_referent_id(
+                                &param.r8_variable(woog)[0].name.as_ident(),
+                                obj,
+                                config,
+                                imports,
+                                domain,
+                            )?,
+                            ",".to_owned(),
+                        ]);
+                    } else {
+                        args.extend([param.r8_variable(woog)[0].name.as_ident(), ",".to_owned()]);
+                    }
                 }
                 GraceType::Ty(id) => {
                     let ty = domain.sarzak().exhume_ty(id).unwrap();
@@ -622,19 +732,137 @@ pub(crate) fn render_make_uuid_new(
         args.pop();
     }
 
-    // emit!(
-    //     buffer,
-    //     "let {} = Uuid::new_v5(&UUID_NS, format!(\"{}\", {}).as_bytes());",
-    //     var.r8_variable(woog)[0].name,
-    //     format_string,
-    //     args
-    // );
-
-    emit!(
+    emit_new_uuid(
         buffer,
-        "let {} = Uuid::new_v4();",
-        var.r8_variable(woog)[0].name
-    );
+        &var.r8_variable(woog)[0].name,
+        &format_string,
+        &args,
+        config,
+    )
+}
+
+/// The id of a constructor argument that refers to another object
+///
+/// This is what goes into the name of a v5 UUID. It has to be the same every
+/// time for the same referent, which is not true of the `Debug` output of the
+/// lock (or cell) that the uber store wraps it in.
+fn referent_id(
+    name: &str,
+    obj: &Object,
+    config: &GraceConfig,
+    imports: &Option<&HashMap<String, Domain>>,
+    domain: &Domain,
+) -> Result<String> {
+    let id = if object_is_enum(obj, config, imports, domain)? {
+        "id()"
+    } else {
+        "id"
+    };
+
+    // Imported objects aren't wrapped.
+    Ok(if config.is_uber_store() && !config.is_imported(&obj.id) {
+        let (read, _write) = get_uber_read_write(config);
+        format!("{name}{read}.{id}")
+    } else {
+        format!("{name}.{id}")
+    })
+}
+
+/// Same as [`referent_id`], for an optional referent
+///
+/// This is a `match` rather than a `map` so that the async store can `.await`
+/// the read.
+fn optional_referent_id(
+    name: &str,
+    obj: &Object,
+    config: &GraceConfig,
+    imports: &Option<&HashMap<String, Domain>>,
+    domain: &Domain,
+) -> Result<String> {
+    Ok(format!(
+        "match {name} {{ Some(r) => Some({}), None => None }}",
+        referent_id("r", obj, config, imports, domain)?
+    ))
+}
+
+/// Emit the `let` statement that creates a new UUID
+///
+/// How that happens depends on the [`UuidMode`]. The format string and args
+/// are what's used to build the name for the v5 UUID. They are built by the
+/// callers from the constructor arguments.
+fn emit_new_uuid(
+    buffer: &mut Buffer,
+    lval: &str,
+    format_string: &str,
+    args: &str,
+    config: &GraceConfig,
+) -> Result<()> {
+    match config.get_uuid_mode() {
+        UuidMode::Random => {
+            emit!(buffer, "let {} = Uuid::new_v4();", lval);
+        }
+        UuidMode::Arguments => {
+            if args.is_empty() {
+                // This is going to be the same every time. That's what you asked for.
+                emit!(
+                    buffer,
+                    "let {} = Uuid::new_v5(&UUID_NS, \"{}\".as_bytes());",
+                    lval,
+                    format_string
+                );
+            } else {
+                emit!(
+                    buffer,
+                    "let {} = Uuid::new_v5(&UUID_NS, format!(\"{}\", {}).as_bytes());",
+                    lval,
+                    format_string,
+                    args
+                );
+            }
+        }
+        UuidMode::Seeded => {
+            if args.is_empty() {
+                emit!(
+                    buffer,
+                    "let {} = Uuid::new_v5(&UUID_NS, format!(\"{{}}\", store.next_uuid_seed()).as_bytes());",
+                    lval
+                );
+            } else {
+                emit!(
+                    buffer,
+                    "let {} = Uuid::new_v5(&UUID_NS, format!(\"{{}}:{}\", store.next_uuid_seed(), {}).as_bytes());",
+                    lval,
+                    format_string,
+                    args
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Emit the use statements that [`emit_new_uuid`] output depends on
+///
+/// There's nothing to do for random UUIDs, aside from the `Uuid` import that
+/// everyone already has. Nor is there anything to do for the `Vec` store, since
+/// the ids are handed out by the store.
+pub(crate) fn emit_uuid_mode_uses(
+    buffer: &mut Buffer,
+    module: &str,
+    config: &GraceConfig,
+) -> Result<()> {
+    if let crate::options::OptimizationLevel::Vec = config.get_optimization_level() {
+        return Ok(());
+    }
+
+    match config.get_uuid_mode() {
+        UuidMode::Random => {}
+        // The seed lives on the store, which the constructors are handed.
+        UuidMode::Arguments | UuidMode::Seeded => {
+            emit!(buffer, "use crate::{}::UUID_NS;", module);
+        }
+    }
 
     Ok(())
 }
@@ -1535,7 +1763,14 @@ pub(crate) fn render_methods(
                     // create (let) statements in the block whilst populating woog. Then
                     // someplace else, maybe here, we iterate over the statements and generate
                     // code. Maybe an as_statement trait, or something?
-                    render_make_uuid_new(buffer, id, method, woog, domain)?;
+                    render_make_uuid_new(buffer, id, method, config, imports, woog, domain)?;
+                    if local_object_has_id_type(obj, config, domain) {
+                        emit!(
+                            buffer,
+                            "let id = {}::from(id);",
+                            object_id_type(obj, config, woog, domain)
+                        );
+                    }
 
                     // Look up the properly scoped variable named `new`.
                     let var = &table
@@ -1895,6 +2130,105 @@ fn local_object_is_referrer(object: &Object, _config: &GraceConfig, domain: &Dom
     !referrers.is_empty() || !assoc_referrers.is_empty()
 }
 
+/// Is the object on the non-formalizing side of any relationships?
+///
+/// This is mostly interesting for imported objects. If something in our domain
+/// formalizes against an imported object we can generate navigation for it.
+pub(crate) fn local_object_is_referent(
+    object: &Object,
+    _config: &GraceConfig,
+    domain: &Domain,
+) -> bool {
+    let referents = object.r16_referent(domain.sarzak());
+    let assoc_referents = object.r25_associative_referent(domain.sarzak());
+    log::debug!("{} is_referent referents: {:?}", object.name, referents);
+    log::debug!(
+        "{} is_referent assoc_referents: {:?}",
+        object.name,
+        assoc_referents
+    );
+
+    !referents.is_empty() || !assoc_referents.is_empty()
+}
+
+/// Does this object get it's own id type?
+///
+/// Only when typed ids are turned on, and only for objects that we own and
+/// that have an `id`. Imported and external objects stick with `Uuid`, as do
+/// singletons, since their const _is_ their id.
+pub(crate) fn local_object_has_id_type(
+    object: &Object,
+    config: &GraceConfig,
+    domain: &Domain,
+) -> bool {
+    config.get_typed_ids()
+        && !config.is_imported(&object.id)
+        && !config.is_external(&object.id)
+        && !local_object_is_singleton(object, config, domain)
+}
+
+/// The type of an object's `id`
+///
+/// This is `FooId` for objects that have an id type, and `Uuid` for everyone
+/// else.
+pub(crate) fn object_id_type(
+    object: &Object,
+    config: &GraceConfig,
+    woog: &WoogStore,
+    domain: &Domain,
+) -> String {
+    if local_object_has_id_type(object, config, domain) {
+        format!(
+            "{}Id",
+            object.as_type(&Ownership::new_borrowed(), woog, domain)
+        )
+    } else {
+        "Uuid".to_owned()
+    }
+}
+
+/// Emit the use statement for the typed ids
+///
+/// They all live in the `ids` module, under `types`.
+pub(crate) fn emit_typed_id_uses(
+    buffer: &mut Buffer,
+    module: &str,
+    config: &GraceConfig,
+) -> Result<()> {
+    if config.get_typed_ids() {
+        emit!(buffer, "use crate::{}::types::ids::*;", module);
+    }
+
+    Ok(())
+}
+
+/// Emit the use statements for the async uber store's `Arc` and `RwLock`
+///
+/// These come from whichever runtime the application is using. async-std
+/// re-exports the standard `Arc`, so it's really just the lock that differs.
+pub(crate) fn emit_async_lock_uses(buffer: &mut Buffer, config: &GraceConfig) -> Result<()> {
+    match config.get_async_runtime() {
+        AsyncRuntime::AsyncStd => {
+            emit!(buffer, "use async_std::sync::Arc;");
+            emit!(buffer, "use async_std::sync::RwLock;");
+        }
+        AsyncRuntime::Tokio => {
+            emit!(buffer, "use std::sync::Arc;");
+            emit!(buffer, "use tokio::sync::RwLock;");
+        }
+    }
+
+    Ok(())
+}
+
+/// The async `Mutex` to use, for the free lists in the Vec store
+pub(crate) fn async_mutex(config: &GraceConfig) -> &'static str {
+    match config.get_async_runtime() {
+        AsyncRuntime::AsyncStd => "async_std::sync::Mutex",
+        AsyncRuntime::Tokio => "tokio::sync::Mutex",
+    }
+}
+
 /// Generate struct/enum Documentation
 ///
 /// The text from the tool is really long lines separated by `\n`. We split
@@ -2091,11 +2425,14 @@ pub(crate) trait AttributeBuilder<A> {
 ///
 /// This is only applicable to generating dwarf code, and I think it should be
 /// moved.
-pub(crate) fn collect_attributes<A>(obj: &Object, domain: &Domain) -> Vec<A>
+pub(crate) fn collect_attributes<A>(
+    obj: &Object,
+    lu_dog: &RwLock<LuDogStore>,
+    domain: &Domain,
+) -> Vec<A>
 where
     A: AttributeBuilder<A>,
 {
-    let lu_dog = &LU_DOG;
     let mut result: Vec<A> = Vec::new();
 
     // Collect the local attributes
@@ -2174,6 +2511,283 @@ where
     result
 }
 
+/// A relationship navigation method on a generated type
+///
+/// `name` is the method name, e.g., `r1_foo`, or `r2c_bar`. `target` is the
+/// object on the other end. `many` and `conditional` describe what comes back
+/// when you navigate. The generated method always returns a `Vec`, so these
+/// are what you'd use to decide what to do with it.
+pub(crate) struct RelNav<'a> {
+    pub name: String,
+    pub target: &'a Object,
+    pub many: bool,
+    pub conditional: bool,
+}
+
+/// Collect the relationship navigation methods generated for an object
+///
+/// These are the methods generated in `types::domain::rels`, in the same order.
+/// 🚧 The names are duplicated here, so if you change one, you need to change
+/// the other. I suppose I could generate the names in one place...
+///
+/// Navigation to imported objects is skipped.
+///
+/// This is only applicable to generating dwarf code, just like `collect_attributes`.
+pub(crate) fn collect_rel_navs<'a>(
+    obj: &Object,
+    config: &GraceConfig,
+    domain: &'a Domain,
+) -> Vec<RelNav<'a>> {
+    let mut result = Vec::new();
+    let mut push = |prefix: String, target: &'a Object, many: bool, conditional: bool| {
+        if !config.is_imported(&target.id) {
+            result.push(RelNav {
+                name: format!("{prefix}_{}", target.as_ident()),
+                target,
+                many,
+                conditional,
+            });
+        }
+    };
+
+    // Binary referrer
+    for referrer in get_binary_referrers_sorted!(obj, domain.sarzak()) {
+        let binary = referrer.r6_binary(domain.sarzak())[0];
+        let referent = binary.r5_referent(domain.sarzak())[0];
+        let r_obj = referent.r16_object(domain.sarzak())[0];
+        let cond = referrer.r11_conditionality(domain.sarzak())[0];
+
+        let conditional = matches!(cond, Conditionality::Conditional(_));
+        push(format!("r{}", binary.number), r_obj, false, conditional);
+    }
+
+    // Binary referent
+    for referent in get_binary_referents_sorted!(obj, domain.sarzak()) {
+        let binary = referent.r5_binary(domain.sarzak())[0];
+        let referrer = binary.r6_referrer(domain.sarzak())[0];
+        let r_obj = referrer.r17_object(domain.sarzak())[0];
+        let my_cond = referent.r12_conditionality(domain.sarzak())[0];
+        let card = referrer.r9_cardinality(domain.sarzak())[0];
+
+        match (card, my_cond) {
+            (Cardinality::One(_), Conditionality::Unconditional(_)) => {
+                push(format!("r{}", binary.number), r_obj, false, false)
+            }
+            (Cardinality::One(_), Conditionality::Conditional(_)) => {
+                push(format!("r{}c", binary.number), r_obj, false, true)
+            }
+            (Cardinality::Many(_), _) => push(format!("r{}", binary.number), r_obj, true, true),
+        }
+    }
+
+    // Associative referrer
+    for assoc_referrer in obj.r26_associative_referrer(domain.sarzak()) {
+        let assoc = assoc_referrer.r21_associative(domain.sarzak())[0];
+        let referents = get_assoc_referent_from_referrer_sorted!(assoc_referrer, domain.sarzak());
+
+        for referent in referents {
+            let assoc_obj = referent.r25_object(domain.sarzak())[0];
+            push(format!("r{}", assoc.number), assoc_obj, false, false);
+        }
+    }
+
+    // Associative referent
+    for assoc_referent in obj.r25_associative_referent(domain.sarzak()) {
+        let an_ass = assoc_referent.r22_an_associative_referent(domain.sarzak())[0];
+        let assoc = an_ass.r22_associative(domain.sarzak())[0];
+        let referrer = assoc.r21_associative_referrer(domain.sarzak())[0];
+        let card = assoc_referent.r88_cardinality(domain.sarzak())[0];
+        let cond = assoc_referent.r77_conditionality(domain.sarzak())[0];
+        let r_obj = referrer.r26_object(domain.sarzak())[0];
+
+        match (card, cond) {
+            (Cardinality::One(_), Conditionality::Unconditional(_)) => {
+                push(format!("r{}", assoc.number), r_obj, false, false)
+            }
+            (Cardinality::One(_), Conditionality::Conditional(_)) => {
+                push(format!("r{}", assoc.number), r_obj, false, true)
+            }
+            (Cardinality::Many(_), _) => push(format!("r{}", assoc.number), r_obj, true, true),
+        }
+    }
+
+    // Subtype to supertype
+    for subtype in get_subtypes_sorted!(obj, domain.sarzak()) {
+        let isa = subtype.r27_isa(domain.sarzak())[0];
+        let supertype = isa.r13_supertype(domain.sarzak())[0];
+        let s_obj = supertype.r14_object(domain.sarzak())[0];
+
+        push(format!("r{}", isa.number), s_obj, false, false);
+    }
+
+    result
+}
+
+/// How an external attribute gets across to dwarf
+///
+/// This is only applicable to generating dwarf code.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum DwarfExternal {
+    /// Passed as a float, seconds since the Unix epoch.
+    SystemTime,
+    /// Passed as a string.
+    PathBuf,
+    /// A newtype around a `String`, also passed as a string.
+    StringBacked,
+    /// Anything else. Dwarf can't look inside, and the constructor uses `ctor`
+    /// to make one.
+    Opaque,
+}
+
+impl DwarfExternal {
+    pub(crate) fn new(external: &External, config: &GraceConfig) -> Self {
+        match external.name.as_str() {
+            "SystemTime" => Self::SystemTime,
+            "PathBuf" => Self::PathBuf,
+            name => match config.get_external_by_name(name) {
+                Some(entity) if entity.string_backed => Self::StringBacked,
+                _ => Self::Opaque,
+            },
+        }
+    }
+}
+
+/// Return the external type of an attribute, if it has one
+///
+/// This is for use with attributes from `collect_attributes`.
+pub(crate) fn attribute_external<'a>(
+    ty: &Arc<Lock<ValueType>>,
+    domain: &'a Domain,
+) -> Option<&'a External> {
+    let id = match *s_read!(ty) {
+        ValueType::Ty(id) => id,
+        _ => return None,
+    };
+
+    match domain.sarzak().exhume_ty(&id) {
+        Some(Ty::External(e)) => domain.sarzak().exhume_external(e),
+        _ => None,
+    }
+}
+
+/// Collect the external types used by attributes of local objects
+///
+/// They are sorted by name, and there are no duplicates.
+pub(crate) fn collect_attribute_externals<'a>(
+    config: &GraceConfig,
+    domain: &'a Domain,
+) -> Vec<&'a External> {
+    let mut result: Vec<&External> = Vec::new();
+
+    for obj in domain.sarzak().iter_object() {
+        if config.is_imported(&obj.id) {
+            continue;
+        }
+
+        for attr in obj.r1_attribute(domain.sarzak()) {
+            if let Ty::External(e) = attr.r2_ty(domain.sarzak())[0] {
+                let external = domain.sarzak().exhume_external(e).unwrap();
+                if !result.iter().any(|ext| ext.name == external.name) {
+                    result.push(external);
+                }
+            }
+        }
+    }
+    result.sort_by(|a, b| a.name.cmp(&b.name));
+
+    result
+}
+
+/// Hash the model
+///
+/// This is a v5 UUID, in the domain's namespace, over the things that make
+/// the generated code what it is: objects, attributes and their types,
+/// binary and associative relationships, along with their cardinality and
+/// conditionality, and subtypes. It's not a hash of the model file, so moving
+/// things around on the canvas doesn't change it.
+///
+/// Everything is sorted by id, so the order in which the store hands things
+/// back doesn't matter.
+pub(crate) fn model_hash(domain: &Domain) -> Uuid {
+    let domain_id = Uuid::from_slice(domain.id().as_bytes()).unwrap();
+    let sarzak = domain.sarzak();
+    let mut model = String::new();
+
+    let card = |card: &Cardinality| match card {
+        Cardinality::One(_) => "1",
+        Cardinality::Many(_) => "M",
+    };
+    let cond = |cond: &Conditionality| match cond {
+        Conditionality::Conditional(_) => "c",
+        Conditionality::Unconditional(_) => "u",
+    };
+
+    let mut objects: Vec<&Object> = sarzak.iter_object().collect();
+    objects.sort_by(|a, b| a.id.cmp(&b.id));
+    for obj in objects {
+        model.push_str(&format!("{}:{};", obj.id, obj.name));
+
+        let mut attrs = obj.r1_attribute(sarzak);
+        attrs.sort_by(|a, b| a.id.cmp(&b.id));
+        for attr in attrs {
+            let ty = attr.r2_ty(sarzak)[0];
+            model.push_str(&format!("{}:{}:{}", attr.id, attr.name, ty.id()));
+            // The id doesn't change when the external is pointed somewhere else.
+            if let Ty::External(id) = ty {
+                let ext = sarzak.exhume_external(id).unwrap();
+                model.push_str(&format!(":{}:{}", ext.path, ext.name));
+            }
+            model.push(';');
+        }
+
+        let mut referrers = obj.r17_referrer(sarzak);
+        referrers.sort_by(|a, b| a.id.cmp(&b.id));
+        for referrer in referrers {
+            let binary = referrer.r6_binary(sarzak)[0];
+            let referent = binary.r5_referent(sarzak)[0];
+            let r_obj = referent.r16_object(sarzak)[0];
+            model.push_str(&format!(
+                "R{}:{}:{}:{}{}:{};",
+                binary.number,
+                referrer.referential_attribute,
+                r_obj.id,
+                card(referrer.r9_cardinality(sarzak)[0]),
+                cond(referrer.r11_conditionality(sarzak)[0]),
+                cond(referent.r12_conditionality(sarzak)[0]),
+            ));
+        }
+
+        let mut assoc_referrers = obj.r26_associative_referrer(sarzak);
+        assoc_referrers.sort_by(|a, b| a.id.cmp(&b.id));
+        for assoc_referrer in assoc_referrers {
+            let assoc = assoc_referrer.r21_associative(sarzak)[0];
+            let mut an_asses = assoc.r22_an_associative_referent(sarzak);
+            an_asses.sort_by(|a, b| a.id.cmp(&b.id));
+            for an_ass in an_asses {
+                let referent = an_ass.r22_associative_referent(sarzak)[0];
+                let r_obj = referent.r25_object(sarzak)[0];
+                model.push_str(&format!(
+                    "R{}:{}:{}:{}{};",
+                    assoc.number,
+                    an_ass.referential_attribute,
+                    r_obj.id,
+                    card(referent.r88_cardinality(sarzak)[0]),
+                    cond(referent.r77_conditionality(sarzak)[0]),
+                ));
+            }
+        }
+
+        let mut subtypes = obj.r15_subtype(sarzak);
+        subtypes.sort_by(|a, b| a.id.cmp(&b.id));
+        for subtype in subtypes {
+            let isa = subtype.r27_isa(sarzak)[0];
+            model.push_str(&format!("R{}:{};", isa.number, subtype.id));
+        }
+    }
+
+    Uuid::new_v5(&domain_id, model.as_bytes())
+}
+
 fn get_uber_read_write(config: &GraceConfig) -> (&str, &str) {
     use UberStoreOptions::*;
     let write = match config.get_uber_store().unwrap() {
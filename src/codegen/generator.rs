@@ -5,10 +5,12 @@ use std::{
     fs::{self, File},
     io::prelude::*,
     path::{Path, PathBuf},
+    sync::RwLock,
 };
 
 use rustc_hash::FxHashMap as HashMap;
 use sarzak::{
+    lu_dog::store::ObjectStore as LuDogStore,
     mc::{CompilerSnafu, FileSnafu, IOSnafu, Result},
     v2::domain::Domain,
     woog::store::ObjectStore as WoogStore,
@@ -80,6 +82,13 @@ pub(crate) struct GeneratorBuilder<'a> {
     /// eventually as well.
     obj_id: Option<&'a Uuid>,
     imports: Option<&'a HashMap<String, Domain>>,
+    /// Lu-Dog Store
+    ///
+    /// The dwarf generators build Lu-Dog types as they go. It used to be a
+    /// global, but now whoever is driving the generation owns it, and passes
+    /// it in here. It's behind a lock so that objects may be generated in
+    /// parallel.
+    lu_dog: Option<&'a RwLock<LuDogStore>>,
 }
 
 impl<'a> GeneratorBuilder<'a> {
@@ -94,6 +103,7 @@ impl<'a> GeneratorBuilder<'a> {
             module: None,
             obj_id: None,
             imports: None,
+            lu_dog: None,
         }
     }
 
@@ -153,6 +163,12 @@ impl<'a> GeneratorBuilder<'a> {
         self
     }
 
+    pub(crate) fn lu_dog(mut self, lu_dog: &'a RwLock<LuDogStore>) -> Self {
+        self.lu_dog = Some(lu_dog);
+
+        self
+    }
+
     pub fn generate(self) -> Result<()> {
         ensure!(
             self.config.is_some(),
@@ -202,6 +218,7 @@ impl<'a> GeneratorBuilder<'a> {
             self.domain.unwrap(),
             &self.woog,
             &self.imports,
+            &self.lu_dog,
             self.package.unwrap(),
             self.module.unwrap().as_str(),
             self.obj_id,
@@ -373,6 +390,7 @@ pub(crate) trait FileGenerator {
         domain: &Domain,
         woog: &Option<&mut WoogStore>,
         imports: &Option<&HashMap<String, Domain>>,
+        lu_dog: &Option<&RwLock<LuDogStore>>,
         package: &str,
         module: &str,
         obj_id: Option<&Uuid>,
@@ -392,6 +410,7 @@ pub(crate) trait CodeWriter {
         domain: &Domain,
         woog: &Option<&mut WoogStore>,
         imports: &Option<&HashMap<String, Domain>>,
+        lu_dog: &Option<&RwLock<LuDogStore>>,
         package: &str,
         module: &str,
         obj_id: Option<&Uuid>,
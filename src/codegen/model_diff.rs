@@ -0,0 +1,439 @@
+//! Model Diff
+//!
+//! Compare two revisions of a domain model. Objects and attributes are matched
+//! by id, and not by name, so a rename shows up as a rename, and not as a drop
+//! followed by an add. This is what drives the generated store migrations.
+//!
+//! Only the things that end up in a persisted store are considered: objects
+//! that the store holds, and the fields that get serialized for them.
+use sarzak::{
+    sarzak::types::{Conditionality, Object, Ty},
+    v2::domain::Domain,
+};
+use uuid::Uuid;
+
+use crate::{
+    codegen::{
+        get_assoc_referent_from_referrer_sorted, get_subtypes_sorted_from_super_obj,
+        local_object_is_enum, local_object_is_singleton, render::RenderIdent,
+    },
+    options::GraceConfig,
+};
+
+/// A field as it's serialized in the store
+///
+/// `name` is the field name in the JSON, which is the Rust identifier. `ty`
+/// is a human readable type name. Referential attributes are fields too, and
+/// they are `optional` when the relationship is conditional.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Field {
+    pub id: Uuid,
+    pub name: String,
+    pub ty: String,
+    pub optional: bool,
+    pub referential: bool,
+}
+
+impl Field {
+    /// The type, as it would be written in Rust
+    pub(crate) fn type_name(&self) -> String {
+        if self.optional {
+            format!("Option<{}>", self.ty)
+        } else {
+            self.ty.clone()
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum FieldDiff {
+    /// The field is in both revisions, possibly with a new name
+    Kept { old: Field, new: Field },
+    /// The field is in both revisions, with a different type
+    ///
+    /// It may have been renamed as well.
+    Retyped { old: Field, new: Field },
+    /// The field is new
+    Added(Field),
+    /// The field went away
+    Dropped(Field),
+}
+
+/// An object that exists in both revisions
+pub(crate) struct ObjectDiff<'a, 'b> {
+    pub old: &'a Object,
+    pub new: &'b Object,
+    /// Enums serialize as a variant, and not as a map of fields
+    pub is_enum: bool,
+    pub fields: Vec<FieldDiff>,
+    /// Subtypes that are in both revisions, as (old, new)
+    ///
+    /// The subtype is the variant of an enum, or of a hybrid's `subtype`.
+    pub subtypes: Vec<(&'a Object, &'b Object)>,
+    /// Subtypes that are only in the old revision
+    pub dropped_subtypes: Vec<&'a Object>,
+}
+
+impl<'a, 'b> ObjectDiff<'a, 'b> {
+    pub(crate) fn is_renamed(&self) -> bool {
+        self.old.as_ident() != self.new.as_ident()
+    }
+
+    /// Subtypes that are in both revisions, but with a new name
+    pub(crate) fn renamed_subtypes(&self) -> Vec<(&'a Object, &'b Object)> {
+        self.subtypes
+            .iter()
+            .filter(|(old, new)| old.as_ident() != new.as_ident())
+            .cloned()
+            .collect()
+    }
+
+    /// True if the persisted subtype variants need attention
+    pub(crate) fn variants_changed(&self) -> bool {
+        !self.dropped_subtypes.is_empty() || !self.renamed_subtypes().is_empty()
+    }
+
+    pub(crate) fn is_unchanged(&self) -> bool {
+        self.fields.iter().all(|field| match field {
+            FieldDiff::Kept { old, new } => old.name == new.name,
+            _ => false,
+        })
+    }
+}
+
+/// The difference between two revisions of a model
+pub(crate) struct ModelDiff<'a, 'b> {
+    pub old_domain: &'a Domain,
+    pub new_domain: &'b Domain,
+    /// Objects that are only in the new revision
+    pub added: Vec<&'b Object>,
+    /// Objects that are only in the old revision
+    pub dropped: Vec<&'a Object>,
+    /// Objects that are in both
+    pub kept: Vec<ObjectDiff<'a, 'b>>,
+}
+
+impl<'a, 'b> ModelDiff<'a, 'b> {
+    /// Diff `old` against `new`
+    ///
+    /// Each domain gets it's own config, since the object descriptions carry
+    /// annotations that may have changed between revisions.
+    pub(crate) fn new(
+        old_config: &GraceConfig,
+        old_domain: &'a Domain,
+        new_config: &GraceConfig,
+        new_domain: &'b Domain,
+    ) -> Self {
+        let old_objects = persisted_objects(old_config, old_domain);
+        let new_objects = persisted_objects(new_config, new_domain);
+
+        let added = new_objects
+            .iter()
+            .filter(|new| !old_objects.iter().any(|old| old.id == new.id))
+            .cloned()
+            .collect();
+        let dropped = old_objects
+            .iter()
+            .filter(|old| !new_objects.iter().any(|new| old.id == new.id))
+            .cloned()
+            .collect();
+
+        let kept = new_objects
+            .iter()
+            .filter_map(|new| {
+                old_objects
+                    .iter()
+                    .find(|old| old.id == new.id)
+                    .map(|old| (*old, *new))
+            })
+            .map(|(old, new)| {
+                let is_enum = local_object_is_enum(old, old_config, old_domain)
+                    || local_object_is_enum(new, new_config, new_domain);
+                let fields = if is_enum {
+                    Vec::new()
+                } else {
+                    diff_fields(
+                        &collect_fields(old, old_domain),
+                        &collect_fields(new, new_domain),
+                    )
+                };
+
+                let old_subtypes = collect_subtypes(old, old_domain);
+                let new_subtypes = collect_subtypes(new, new_domain);
+                let subtypes = new_subtypes
+                    .iter()
+                    .filter_map(|new| {
+                        old_subtypes
+                            .iter()
+                            .find(|old| old.id == new.id)
+                            .map(|old| (*old, *new))
+                    })
+                    .collect();
+                let dropped_subtypes = old_subtypes
+                    .iter()
+                    .filter(|old| !new_subtypes.iter().any(|new| old.id == new.id))
+                    .cloned()
+                    .collect();
+
+                ObjectDiff {
+                    old,
+                    new,
+                    is_enum,
+                    fields,
+                    subtypes,
+                    dropped_subtypes,
+                }
+            })
+            .collect();
+
+        Self {
+            old_domain,
+            new_domain,
+            added,
+            dropped,
+            kept,
+        }
+    }
+
+    /// True if the old store can be loaded as is
+    pub(crate) fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.dropped.is_empty()
+            && self
+                .kept
+                .iter()
+                .all(|obj| !obj.is_renamed() && obj.is_unchanged() && !obj.variants_changed())
+    }
+}
+
+/// The objects that the store holds, sorted by name
+///
+/// This is the same filter that the store generator uses.
+fn persisted_objects<'a>(config: &GraceConfig, domain: &'a Domain) -> Vec<&'a Object> {
+    let mut objects: Vec<&Object> = domain
+        .sarzak()
+        .iter_object()
+        .filter(|obj| {
+            !config.is_imported(&obj.id)
+                && (local_object_is_enum(obj, config, domain)
+                    || !local_object_is_singleton(obj, config, domain))
+        })
+        .collect();
+    objects.sort_by(|a, b| a.name.cmp(&b.name));
+
+    objects
+}
+
+/// The objects that are subtypes of `obj`, if it's a supertype
+fn collect_subtypes<'a>(obj: &Object, domain: &'a Domain) -> Vec<&'a Object> {
+    if obj.r14_supertype(domain.sarzak()).is_empty() {
+        return Vec::new();
+    }
+
+    get_subtypes_sorted_from_super_obj!(obj, domain.sarzak())
+        .iter()
+        .map(|subtype| subtype.r15_object(domain.sarzak())[0])
+        .collect()
+}
+
+/// The fields of an object, as they are serialized
+pub(crate) fn collect_fields(obj: &Object, domain: &Domain) -> Vec<Field> {
+    let mut fields = Vec::new();
+
+    for attr in obj.r1_attribute(domain.sarzak()) {
+        // This is the attribute that gets added to enums in the Vec store.
+        if attr.name == "hack" {
+            continue;
+        }
+
+        let ty = attr.r2_ty(domain.sarzak())[0];
+        fields.push(Field {
+            id: attr.id,
+            name: attr.as_ident(),
+            ty: ty_name(ty, domain),
+            optional: false,
+            referential: false,
+        });
+    }
+
+    for referrer in obj.r17_referrer(domain.sarzak()) {
+        let cond = referrer.r11_conditionality(domain.sarzak())[0];
+        fields.push(Field {
+            id: referrer.id,
+            name: referrer.referential_attribute.as_ident(),
+            ty: "Uuid".to_owned(),
+            optional: matches!(cond, Conditionality::Conditional(_)),
+            referential: true,
+        });
+    }
+
+    for assoc_referrer in obj.r26_associative_referrer(domain.sarzak()) {
+        for referent in get_assoc_referent_from_referrer_sorted!(assoc_referrer, domain.sarzak()) {
+            let an_ass = referent.r22_an_associative_referent(domain.sarzak())[0];
+            fields.push(Field {
+                id: an_ass.id,
+                name: an_ass.referential_attribute.as_ident(),
+                ty: "Uuid".to_owned(),
+                optional: false,
+                referential: true,
+            });
+        }
+    }
+
+    fields.sort_by(|a, b| a.name.cmp(&b.name));
+
+    fields
+}
+
+/// Match fields between two unrelated domains
+///
+/// This is for extrusion, where the source domain is a different model, and
+/// the ids may, or may not, line up. So we fall back to the name.
+pub(crate) fn match_fields(old: &[Field], new: &[Field]) -> Vec<FieldDiff> {
+    diff_fields_by(old, new, |old, new| {
+        old.id == new.id || old.name == new.name
+    })
+}
+
+fn diff_fields(old: &[Field], new: &[Field]) -> Vec<FieldDiff> {
+    diff_fields_by(old, new, |old, new| old.id == new.id)
+}
+
+fn diff_fields_by<F>(old: &[Field], new: &[Field], matches: F) -> Vec<FieldDiff>
+where
+    F: Fn(&Field, &Field) -> bool,
+{
+    let mut result = Vec::new();
+
+    for field in new {
+        match old.iter().find(|old| matches(old, field)) {
+            Some(old) if old.type_name() == field.type_name() => result.push(FieldDiff::Kept {
+                old: old.clone(),
+                new: field.clone(),
+            }),
+            Some(old) => result.push(FieldDiff::Retyped {
+                old: old.clone(),
+                new: field.clone(),
+            }),
+            None => result.push(FieldDiff::Added(field.clone())),
+        }
+    }
+
+    for field in old {
+        if !new.iter().any(|new| matches(field, new)) {
+            result.push(FieldDiff::Dropped(field.clone()));
+        }
+    }
+
+    result
+}
+
+fn ty_name(ty: &Ty, domain: &Domain) -> String {
+    match ty {
+        Ty::Boolean(_) => "bool".to_owned(),
+        Ty::Float(_) => "f64".to_owned(),
+        Ty::Integer(_) => "i64".to_owned(),
+        Ty::SString(_) => "String".to_owned(),
+        Ty::SUuid(_) => "Uuid".to_owned(),
+        Ty::Object(ref id) => {
+            let obj = domain.sarzak().exhume_object(id).unwrap();
+            obj.name.to_owned()
+        }
+        Ty::External(ref id) => {
+            let ext = domain.sarzak().exhume_external(id).unwrap();
+            ext.name.to_owned()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::GraceCompilerOptions;
+
+    #[test]
+    fn test_model_diff_same_model() {
+        let domain = sarzak::domain::DomainBuilder::new()
+            .cuckoo_model("tests/mdd/models/everything.json")
+            .unwrap()
+            .build_v2()
+            .unwrap();
+        let config: GraceConfig = (&GraceCompilerOptions::default(), &domain).into();
+
+        let diff = ModelDiff::new(&config, &domain, &config, &domain);
+        assert!(diff.is_empty());
+        assert!(diff.added.is_empty());
+        assert!(diff.dropped.is_empty());
+        assert!(!diff.kept.is_empty());
+        assert!(diff.kept.iter().all(|obj| !obj.variants_changed()));
+    }
+
+    #[test]
+    fn test_diff_fields() {
+        let field = |id: u128, name: &str, ty: &str, optional: bool| Field {
+            id: Uuid::from_u128(id),
+            name: name.to_owned(),
+            ty: ty.to_owned(),
+            optional,
+            referential: false,
+        };
+
+        let old = vec![
+            field(1, "id", "Uuid", false),
+            field(2, "name", "String", false),
+            field(3, "count", "i64", false),
+            field(4, "gone", "bool", false),
+            field(5, "owner", "Uuid", false),
+        ];
+        let new = vec![
+            field(1, "id", "Uuid", false),
+            field(2, "title", "String", false),
+            field(3, "count", "f64", false),
+            field(5, "owner", "Uuid", true),
+            field(6, "fresh", "bool", false),
+        ];
+
+        let diff = diff_fields(&old, &new);
+        assert_eq!(
+            diff[0],
+            FieldDiff::Kept {
+                old: old[0].clone(),
+                new: new[0].clone()
+            }
+        );
+        assert_eq!(
+            diff[1],
+            FieldDiff::Kept {
+                old: old[1].clone(),
+                new: new[1].clone()
+            }
+        );
+        assert_eq!(
+            diff[2],
+            FieldDiff::Retyped {
+                old: old[2].clone(),
+                new: new[2].clone()
+            }
+        );
+        assert_eq!(
+            diff[3],
+            FieldDiff::Retyped {
+                old: old[4].clone(),
+                new: new[3].clone()
+            }
+        );
+        assert_eq!(diff[4], FieldDiff::Added(new[4].clone()));
+        assert_eq!(diff[5], FieldDiff::Dropped(old[3].clone()));
+
+        // Different ids, same name, as when extruding from another model.
+        let other = vec![field(7, "title", "String", false)];
+        let diff = match_fields(&other, &new[1..2]);
+        assert_eq!(
+            diff,
+            vec![FieldDiff::Kept {
+                old: other[0].clone(),
+                new: new[1].clone()
+            }]
+        );
+    }
+}
@@ -24,6 +24,7 @@ use crate::{
     codegen::{
         buffer::{emit, Buffer},
         get_assoc_referent_from_referrer_sorted, get_binary_referrers_sorted,
+        local_object_has_id_type, object_id_type,
     },
     options::{GraceConfig, UberStoreOptions},
     todo::{External, GType, ObjectMethod, Parameter as todoP},
@@ -569,6 +570,13 @@ pub(crate) fn render_attributes(
         // Anyway, I'm doing the really ugly thing here.
         if attr.name == "id" && config.get_optimization_level() == &crate::OptimizationLevel::Vec {
             emit!(buffer, "pub {}: usize,", attr.as_ident());
+        } else if attr.name == "id" && local_object_has_id_type(obj, config, domain) {
+            emit!(
+                buffer,
+                "pub {}: {},",
+                attr.as_ident(),
+                object_id_type(obj, config, woog, domain)
+            );
         } else if attr.name != "hack" {
             // Ugly thing times two. The "hack" thing is added when we first process
             // the domain. If it's a Vec store, we want to promote any enums to hybrids.
@@ -654,14 +662,16 @@ pub(crate) fn render_binary_referential_attributes(
                 config.get_optimization_level(),
                 config.get_uber_store().unwrap(),
             ) {
-                (Vec, AsyncRwLock | StdRwLock | Single | NDRwLock | ParkingLotRwLock) => "usize",
-                (None, StdRwLock | Single) => "Uuid",
+                (Vec, AsyncRwLock | StdRwLock | Single | NDRwLock | ParkingLotRwLock) => {
+                    "usize".to_owned()
+                }
+                (None, StdRwLock | Single) => object_id_type(r_obj, config, woog, domain),
                 (lvl, store) => {
                     panic!("{store} with optimization {lvl} is not currently supported")
                 }
             }
         } else {
-            "Uuid"
+            object_id_type(r_obj, config, woog, domain)
         };
 
         emit!(
@@ -720,12 +730,12 @@ pub(crate) fn render_associative_attributes(
                 // reference really since I'm not planning on any IPC. That's what
                 // xuder is for.
                 if config.is_imported(&assoc_obj.id) {
-                    "Uuid"
+                    "Uuid".to_owned()
                 } else {
-                    "usize"
+                    "usize".to_owned()
                 }
             } else {
-                "Uuid"
+                object_id_type(assoc_obj, config, woog, domain)
             };
 
             emit!(
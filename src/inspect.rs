@@ -0,0 +1,837 @@
+//! Look inside a persisted store, without the generated code
+//!
+//! Everything we need to know about a store is in the model. The objects tell
+//! us where the instances live, the attributes tell us what columns to show,
+//! and the referential attributes tell us where the pointers are. So we read
+//! the JSON as plain `serde_json::Value`s, and let the model sort it out.
+//!
+//! This is what backs `grace-inspect`. It understands the directory layout
+//! written by `persist`, as well as the single file and JSON Lines formats.
+//!
+//! Stores generated with the `Vec` optimization level aren't supported. Their
+//! ids are slots, which are only unique within an object, so there's no way to
+//! say which instance an id means. They are refused when opened.
+//!
+//! 🚧 Imported objects live in some other store, so references to them aren't
+//! checked.
+use std::{
+    collections::BTreeMap,
+    fmt, fs,
+    io::{self, BufRead},
+    path::{Path, PathBuf},
+};
+
+use sarzak::{domain::DomainBuilder, sarzak::types::Object, v2::domain::Domain};
+use serde_json::Value;
+use snafu::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    codegen::{
+        get_subtypes_sorted_from_super_obj, local_object_is_enum, local_object_is_hybrid,
+        local_object_is_singleton, model_hash, render::RenderIdent,
+    },
+    options::{GraceCompilerOptions, GraceConfig},
+    types::domain::hybrid::SUBTYPE_ATTR,
+};
+
+#[derive(Debug, Snafu)]
+pub enum InspectError {
+    #[snafu(display("could not load the model {}: {description}", path.display()))]
+    Model { path: PathBuf, description: String },
+    #[snafu(display("could not read {}: {source}", path.display()))]
+    Read { path: PathBuf, source: io::Error },
+    #[snafu(display("could not parse {}: {source}", path.display()))]
+    Parse {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    #[snafu(display("there is no object named `{name}` in the store"))]
+    UnknownObject { name: String },
+    #[snafu(display("there is no instance with id {id} in the store"))]
+    UnknownInstance { id: Uuid },
+    #[snafu(display(
+        "{} is a Vec store, and its ids are slots, not UUIDs, so it can't be inspected",
+        path.display()
+    ))]
+    VecStore { path: PathBuf },
+}
+
+pub type Result<T, E = InspectError> = std::result::Result<T, E>;
+
+/// A persisted store, and the model that describes it
+pub struct StoreInspector {
+    domain: Domain,
+    config: GraceConfig,
+    /// The instances, by object ident, and then by id
+    instances: BTreeMap<String, BTreeMap<Uuid, Value>>,
+    /// Things that looked off while loading
+    warnings: Vec<String>,
+}
+
+/// A table of instances, ready for printing
+pub struct Table {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+/// A reference from one instance to another
+///
+/// `present` is false when the instance pointed at isn't in the store.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Link {
+    pub from_object: String,
+    pub from: Uuid,
+    pub relationship: String,
+    pub attribute: String,
+    pub to_object: String,
+    pub to: Uuid,
+    pub present: bool,
+}
+
+/// An instance, and the instances that it's related to
+pub struct Neighborhood {
+    pub object: String,
+    pub instance: Table,
+    pub outgoing: Vec<Link>,
+    pub incoming: Vec<Link>,
+}
+
+/// Where to find the id of the instance at the other end of a relationship
+enum Pointer {
+    /// A referential attribute
+    Attribute(String),
+    /// The subtype of a supertype enum
+    Enum,
+    /// The `subtype` field of a hybrid
+    Hybrid,
+}
+
+/// A relationship, seen from the referring object
+struct Reference<'a> {
+    relationship: String,
+    pointer: Pointer,
+    /// The object pointed at. `None` for the subtypes, since it depends on the
+    /// variant.
+    target: Option<&'a Object>,
+}
+
+impl StoreInspector {
+    /// Load the model, and then the store
+    ///
+    /// `store` is either the directory passed to `persist`, the `<domain>.json`
+    /// directory inside of it, a single JSON file, or a JSON Lines file.
+    pub fn open<M: AsRef<Path>, S: AsRef<Path>>(model: M, store: S) -> Result<Self> {
+        let model = model.as_ref();
+        let domain = DomainBuilder::new()
+            .cuckoo_model(model)
+            .map_err(|e| InspectError::Model {
+                path: model.to_owned(),
+                description: e.to_string(),
+            })?
+            .build_v2()
+            .map_err(|e| InspectError::Model {
+                path: model.to_owned(),
+                description: e.to_string(),
+            })?;
+        let config = GraceConfig::from((&GraceCompilerOptions::default(), &domain));
+
+        let mut inspector = Self {
+            domain,
+            config,
+            instances: BTreeMap::new(),
+            warnings: Vec::new(),
+        };
+        let idents = inspector
+            .objects()
+            .iter()
+            .map(|obj| obj.as_ident())
+            .collect::<Vec<_>>();
+        for ident in idents {
+            inspector.instances.insert(ident, BTreeMap::new());
+        }
+
+        let store = store.as_ref();
+        let nested = store.join(format!("{}.json", inspector.domain.name()));
+        if nested.is_dir() {
+            inspector.load_directory(&nested)?;
+        } else if store.is_dir() {
+            inspector.load_directory(store)?;
+        } else if store.extension().map(|ext| ext == "jsonl").unwrap_or(false) {
+            inspector.load_jsonl(store)?;
+        } else {
+            inspector.load_json_file(store)?;
+        }
+
+        Ok(inspector)
+    }
+
+    pub fn domain_name(&self) -> &str {
+        self.domain.name()
+    }
+
+    /// Things that looked off while loading the store
+    ///
+    /// Files that we don't know what to do with, a model hash that doesn't
+    /// match, that sort of thing.
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// The number of instances of each object, sorted by object name
+    pub fn counts(&self) -> Vec<(String, usize)> {
+        let mut counts = self
+            .objects()
+            .iter()
+            .map(|obj| (obj.name.clone(), self.instances[&obj.as_ident()].len()))
+            .collect::<Vec<_>>();
+        counts.sort();
+
+        counts
+    }
+
+    /// All of the instances of an object, as a table
+    ///
+    /// The object may be named as it is in the model, or as an identifier or a
+    /// type, e.g., `Object Store`, `object_store`, or `ObjectStore`.
+    pub fn table(&self, object: &str) -> Result<Table> {
+        let obj = self.find_object(object)?;
+        let instances = &self.instances[&obj.as_ident()];
+
+        Ok(self.make_table(obj, instances.values()))
+    }
+
+    /// The instance with `id`, and everything it's related to
+    pub fn follow(&self, id: &Uuid) -> Result<Neighborhood> {
+        let (obj, instance) = self
+            .objects()
+            .into_iter()
+            .find_map(|obj| {
+                self.instances[&obj.as_ident()]
+                    .get(id)
+                    .map(|instance| (obj, instance))
+            })
+            .context(UnknownInstanceSnafu { id: *id })?;
+
+        let outgoing = self.links_from(obj, id, instance);
+        let incoming = self
+            .all_links()
+            .into_iter()
+            .filter(|link| &link.to == id)
+            .collect();
+
+        Ok(Neighborhood {
+            object: obj.name.clone(),
+            instance: self.make_table(obj, [instance]),
+            outgoing,
+            incoming,
+        })
+    }
+
+    /// References to instances that aren't in the store
+    pub fn dangling(&self) -> Vec<Link> {
+        self.all_links()
+            .into_iter()
+            .filter(|link| !link.present)
+            .collect()
+    }
+
+    /// The objects that have instances in the store
+    ///
+    /// This is the same filter that the store generator uses.
+    fn objects(&self) -> Vec<&Object> {
+        let mut objects = self
+            .domain
+            .sarzak()
+            .iter_object()
+            .filter(|obj| {
+                !self.config.is_imported(&obj.id)
+                    && (local_object_is_enum(obj, &self.config, &self.domain)
+                        || !local_object_is_singleton(obj, &self.config, &self.domain))
+            })
+            .collect::<Vec<_>>();
+        objects.sort_by(|a, b| a.name.cmp(&b.name));
+
+        objects
+    }
+
+    fn find_object(&self, name: &str) -> Result<&Object> {
+        let ident = name.as_ident();
+        self.objects()
+            .into_iter()
+            .find(|obj| obj.as_ident() == ident)
+            .context(UnknownObjectSnafu { name })
+    }
+
+    fn make_table<'a, I: IntoIterator<Item = &'a Value>>(
+        &self,
+        obj: &Object,
+        instances: I,
+    ) -> Table {
+        let mut columns = Vec::new();
+        if local_object_is_enum(obj, &self.config, &self.domain) {
+            columns.push(SUBTYPE_ATTR.to_owned());
+        } else {
+            let mut attrs = obj
+                .r1_attribute(self.domain.sarzak())
+                .iter()
+                .map(|attr| attr.as_ident())
+                .collect::<Vec<_>>();
+            // The id goes first, and the rest are in alphabetical order.
+            attrs.sort_by_key(|attr| (*attr != "id", attr.clone()));
+            columns.extend(attrs);
+
+            for reference in self.references(obj) {
+                match reference.pointer {
+                    Pointer::Attribute(attribute) => columns.push(attribute),
+                    Pointer::Hybrid => columns.push(SUBTYPE_ATTR.to_owned()),
+                    Pointer::Enum => {}
+                }
+            }
+        }
+
+        let rows = instances
+            .into_iter()
+            .map(|instance| {
+                if local_object_is_enum(obj, &self.config, &self.domain) {
+                    vec![cell(instance)]
+                } else {
+                    columns
+                        .iter()
+                        .map(|column| cell(&instance[column.as_str()]))
+                        .collect()
+                }
+            })
+            .collect();
+
+        Table { columns, rows }
+    }
+
+    /// The relationships that `obj` refers across
+    fn references(&self, obj: &Object) -> Vec<Reference<'_>> {
+        let sarzak = self.domain.sarzak();
+        let mut result = Vec::new();
+
+        for referrer in obj.r17_referrer(sarzak) {
+            let binary = referrer.r6_binary(sarzak)[0];
+            let referent = binary.r5_referent(sarzak)[0];
+            result.push(Reference {
+                relationship: format!("R{}", binary.number),
+                pointer: Pointer::Attribute(referrer.referential_attribute.as_ident()),
+                target: Some(referent.r16_object(sarzak)[0]),
+            });
+        }
+
+        for assoc_referrer in obj.r26_associative_referrer(sarzak) {
+            let assoc = assoc_referrer.r21_associative(sarzak)[0];
+            for an_ass in assoc.r22_an_associative_referent(sarzak) {
+                let referent = an_ass.r22_associative_referent(sarzak)[0];
+                result.push(Reference {
+                    relationship: format!("R{}", assoc.number),
+                    pointer: Pointer::Attribute(an_ass.referential_attribute.as_ident()),
+                    target: Some(referent.r25_object(sarzak)[0]),
+                });
+            }
+        }
+
+        if !obj.r14_supertype(sarzak).is_empty() {
+            let isa = obj.r14_supertype(sarzak)[0].r13_isa(sarzak)[0];
+            let pointer = if local_object_is_hybrid(obj, &self.config, &self.domain) {
+                Pointer::Hybrid
+            } else {
+                Pointer::Enum
+            };
+            result.push(Reference {
+                relationship: format!("R{}", isa.number),
+                pointer,
+                target: None,
+            });
+        }
+
+        result
+    }
+
+    /// Follow the references out of an instance
+    fn links_from(&self, obj: &Object, id: &Uuid, instance: &Value) -> Vec<Link> {
+        let mut result = Vec::new();
+
+        for reference in self.references(obj) {
+            let (attribute, value) = match reference.pointer {
+                Pointer::Attribute(ref attribute) => {
+                    (attribute.clone(), &instance[attribute.as_str()])
+                }
+                Pointer::Enum => (SUBTYPE_ATTR.to_owned(), instance),
+                Pointer::Hybrid => (SUBTYPE_ATTR.to_owned(), &instance[SUBTYPE_ATTR]),
+            };
+
+            let target = match reference.target {
+                Some(target) => uuid_of(value).map(|to| (target, to)),
+                None => self.subtype_of(obj, value),
+            };
+
+            // A `None` here is a conditional relationship that isn't, or a
+            // singleton subtype. Neither are stored.
+            if let Some((target, to)) = target {
+                if self.config.is_imported(&target.id) {
+                    continue;
+                }
+                if let Some(instances) = self.instances.get(&target.as_ident()) {
+                    result.push(Link {
+                        from_object: obj.name.clone(),
+                        from: *id,
+                        relationship: reference.relationship.clone(),
+                        attribute,
+                        to_object: target.name.clone(),
+                        to,
+                        present: instances.contains_key(&to),
+                    });
+                }
+            }
+        }
+
+        result
+    }
+
+    fn all_links(&self) -> Vec<Link> {
+        let mut result = Vec::new();
+        for obj in self.objects() {
+            for (id, instance) in &self.instances[&obj.as_ident()] {
+                result.extend(self.links_from(obj, id, instance));
+            }
+        }
+
+        result
+    }
+
+    /// Figure out which subtype a supertype points at
+    ///
+    /// The value is something like `{"Foo": "<uuid>"}`. Singletons are
+    /// constants, and not in the store, so they don't count.
+    fn subtype_of(&self, obj: &Object, value: &Value) -> Option<(&Object, Uuid)> {
+        let (variant, id) = value.as_object()?.iter().next()?;
+        let variant = variant.as_ident();
+        let subtype = get_subtypes_sorted_from_super_obj!(obj, self.domain.sarzak())
+            .into_iter()
+            .map(|subtype| subtype.r15_object(self.domain.sarzak())[0])
+            .find(|subtype| subtype.as_ident() == variant)?;
+
+        if local_object_is_singleton(subtype, &self.config, &self.domain) {
+            None
+        } else {
+            uuid_of(id).map(|id| (subtype, id))
+        }
+    }
+
+    fn load_directory(&mut self, path: &Path) -> Result<()> {
+        let header = path.join("header.json");
+        if header.exists() {
+            let header = read_json(&header)?;
+            self.check_header(&header);
+        }
+
+        for entry in fs::read_dir(path).context(ReadSnafu { path })? {
+            let entry = entry.context(ReadSnafu { path })?;
+            let dir = entry.path();
+            if !dir.is_dir() {
+                continue;
+            }
+
+            let ident = entry.file_name().to_string_lossy().to_string();
+            if !self.instances.contains_key(&ident) {
+                self.warnings
+                    .push(format!("{} isn't an object in the model", dir.display()));
+                continue;
+            }
+
+            for file in fs::read_dir(&dir).context(ReadSnafu { path: &dir })? {
+                let file = file.context(ReadSnafu { path: &dir })?.path();
+                let id = file
+                    .file_stem()
+                    .and_then(|stem| Uuid::parse_str(&stem.to_string_lossy()).ok());
+                match id {
+                    Some(id) => {
+                        let instance = untimestamp(read_json(&file)?);
+                        self.instances.get_mut(&ident).unwrap().insert(id, instance);
+                    }
+                    None if is_slot(&file) => return VecStoreSnafu { path }.fail(),
+                    None => self
+                        .warnings
+                        .push(format!("{} isn't named for an id", file.display())),
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn load_json_file(&mut self, path: &Path) -> Result<()> {
+        let document = read_json(path)?;
+        self.check_header(&document["header"]);
+
+        if let Some(store) = document["store"].as_object() {
+            // The store has other fields, like the name maps, and we don't care
+            // about those.
+            for (field, instances) in store {
+                if self.instances.contains_key(field) {
+                    self.insert_instances(path, field, instances)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn load_jsonl(&mut self, path: &Path) -> Result<()> {
+        let file = fs::File::open(path).context(ReadSnafu { path })?;
+        for line in io::BufReader::new(file).lines() {
+            let line = line.context(ReadSnafu { path })?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let record: Value = serde_json::from_str(&line).context(ParseSnafu { path })?;
+            let ty = record["type"].as_str().unwrap_or_default().to_owned();
+            match ty.as_str() {
+                "StoreHeader" => self.check_header(&record["instance"]),
+                // The exorcised instances are gone, and that's all we need to know.
+                "Tombstone" => {}
+                _ => {
+                    let instance = untimestamp(record["instance"].clone());
+                    self.insert_instance(path, &ty.as_ident(), instance)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The `store` member of a single file store
+    ///
+    /// A field is a map from id to instance. The Vec store uses an array.
+    fn insert_instances(&mut self, path: &Path, field: &str, instances: &Value) -> Result<()> {
+        match instances {
+            Value::Object(map) => {
+                for instance in map.values() {
+                    self.insert_instance(path, field, untimestamp(instance.clone()))?;
+                }
+            }
+            Value::Array(_) => return VecStoreSnafu { path }.fail(),
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// `path` is where the instance came from, for the error.
+    fn insert_instance(&mut self, path: &Path, ident: &str, instance: Value) -> Result<()> {
+        // Enums are `{"Variant": "<uuid>"}`, and the id is the subtype's.
+        let id = instance
+            .get("id")
+            .or_else(|| instance.as_object().and_then(|map| map.values().next()));
+        if id.map(Value::is_u64).unwrap_or(false) {
+            return VecStoreSnafu { path }.fail();
+        }
+        let id = id.and_then(uuid_of);
+
+        match (self.instances.get_mut(ident), id) {
+            (Some(instances), Some(id)) => {
+                instances.insert(id, instance);
+            }
+            (None, _) => self
+                .warnings
+                .push(format!("`{ident}` isn't an object in the model")),
+            (_, None) => self
+                .warnings
+                .push(format!("an instance of `{ident}` doesn't have an id")),
+        }
+
+        Ok(())
+    }
+
+    /// The store is still worth a look if the header doesn't match, so this
+    /// just warns.
+    fn check_header(&mut self, header: &Value) {
+        let expected = model_hash(&self.domain).simple().to_string();
+        match header["model_hash"].as_str() {
+            Some(found) if found != expected => self.warnings.push(format!(
+                "the store was written by a different model: {found}, and the model is {expected}"
+            )),
+            Some(_) => {}
+            None => self
+                .warnings
+                .push("the store doesn't have a header".to_owned()),
+        }
+    }
+}
+
+impl fmt::Display for Table {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut widths = self
+            .columns
+            .iter()
+            .map(|column| column.chars().count())
+            .collect::<Vec<_>>();
+        for row in &self.rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let line = |f: &mut fmt::Formatter<'_>, cells: &[String]| -> fmt::Result {
+            let cells = cells
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{cell:width$}"))
+                .collect::<Vec<_>>();
+            writeln!(f, "{}", cells.join(" | ").trim_end())
+        };
+
+        line(f, &self.columns)?;
+        let rule = widths
+            .iter()
+            .map(|width| "-".repeat(*width))
+            .collect::<Vec<_>>();
+        writeln!(f, "{}", rule.join("-+-"))?;
+        for row in &self.rows {
+            line(f, row)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Link {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} -[{} {}]-> {} {}",
+            self.from_object, self.from, self.relationship, self.attribute, self.to_object, self.to
+        )?;
+        if !self.present {
+            write!(f, " (dangling)")?;
+        }
+
+        Ok(())
+    }
+}
+
+fn read_json(path: &Path) -> Result<Value> {
+    let file = fs::File::open(path).context(ReadSnafu { path })?;
+    serde_json::from_reader(io::BufReader::new(file)).context(ParseSnafu { path })
+}
+
+/// Timestamped stores write `[instance, timestamp]`
+fn untimestamp(value: Value) -> Value {
+    match value {
+        Value::Array(mut pair) if pair.len() == 2 && !pair[0].is_array() => pair.swap_remove(0),
+        value => value,
+    }
+}
+
+fn uuid_of(value: &Value) -> Option<Uuid> {
+    value.as_str().and_then(|s| Uuid::parse_str(s).ok())
+}
+
+/// The Vec store names the files for the instance's slot
+fn is_slot(file: &Path) -> bool {
+    file.file_stem()
+        .map(|stem| stem.to_string_lossy().parse::<usize>().is_ok())
+        .unwrap_or(false)
+}
+
+/// Render a value for a table
+///
+/// Strings lose their quotes, `null` is empty, and enum values are shown as
+/// `Variant(value)`.
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Object(map) if map.len() == 1 => {
+            let (variant, value) = map.iter().next().unwrap();
+            format!("{variant}({})", cell(value))
+        }
+        value => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    const MODEL: &str = "tests/mdd/models/one_to_one.json";
+
+    /// The header of a store written by the model
+    fn header() -> (String, Value) {
+        let domain = DomainBuilder::new()
+            .cuckoo_model(MODEL)
+            .unwrap()
+            .build_v2()
+            .unwrap();
+        let header = json!({"model_hash": model_hash(&domain).simple().to_string()});
+
+        (domain.name().to_owned(), header)
+    }
+
+    /// Write `instances`, `(ident, instance)`, to a store in `dir`
+    ///
+    /// This is the layout that `persist` writes.
+    fn write_store(dir: &Path, instances: &[(&str, Value)]) -> PathBuf {
+        let (name, header) = header();
+        let root = dir.join(format!("{name}.json"));
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("header.json"), header.to_string()).unwrap();
+
+        for (ident, instance) in instances {
+            let path = root.join(ident);
+            fs::create_dir_all(&path).unwrap();
+            let id = cell(&instance["id"]);
+            fs::write(path.join(format!("{id}.json")), instance.to_string()).unwrap();
+        }
+
+        dir.to_owned()
+    }
+
+    #[test]
+    fn test_inspect_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let [referent, a, b, c, missing] = [1, 2, 3, 4, 5].map(Uuid::from_u128);
+        let store = write_store(
+            dir.path(),
+            &[
+                (
+                    "referent",
+                    json!({"id": referent.to_string(), "name": "fred"}),
+                ),
+                (
+                    "a",
+                    json!({"id": a.to_string(), "number": 42, "ptr": referent.to_string()}),
+                ),
+                (
+                    "b",
+                    json!({"bit": true, "id": b.to_string(), "ptr": missing.to_string()}),
+                ),
+                (
+                    "c",
+                    json!({"id": c.to_string(), "like_water": 1.5, "ptr": null}),
+                ),
+            ],
+        );
+
+        let inspector = StoreInspector::open(MODEL, store).unwrap();
+        assert!(inspector.warnings().is_empty());
+
+        assert_eq!(
+            inspector.counts(),
+            vec![
+                ("A".to_owned(), 1),
+                ("B".to_owned(), 1),
+                ("C".to_owned(), 1),
+                ("Parameter".to_owned(), 0),
+                ("Referent".to_owned(), 1),
+            ]
+        );
+
+        let r1 = Link {
+            from_object: "A".to_owned(),
+            from: a,
+            relationship: "R1".to_owned(),
+            attribute: "ptr".to_owned(),
+            to_object: "Referent".to_owned(),
+            to: referent,
+            present: true,
+        };
+
+        let neighborhood = inspector.follow(&referent).unwrap();
+        assert_eq!(neighborhood.object, "Referent");
+        assert!(neighborhood.outgoing.is_empty());
+        assert_eq!(neighborhood.incoming, vec![r1.clone()]);
+
+        let neighborhood = inspector.follow(&a).unwrap();
+        assert_eq!(neighborhood.outgoing, vec![r1]);
+        assert!(neighborhood.incoming.is_empty());
+
+        assert!(matches!(
+            inspector.follow(&missing),
+            Err(InspectError::UnknownInstance { .. })
+        ));
+
+        assert_eq!(
+            inspector.dangling(),
+            vec![Link {
+                from_object: "B".to_owned(),
+                from: b,
+                relationship: "R2".to_owned(),
+                attribute: "ptr".to_owned(),
+                to_object: "Referent".to_owned(),
+                to: missing,
+                present: false,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_inspect_jsonl_tombstones() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.jsonl");
+        let [fred, gene] = [1, 2].map(Uuid::from_u128);
+        let when = json!({"secs_since_epoch": 1, "nanos_since_epoch": 2});
+        let records = [
+            json!({"type": "StoreHeader", "instance": header().1}),
+            json!({
+                "type": "Referent",
+                "instance": [{"id": fred.to_string(), "name": "fred"}, when.clone()]
+            }),
+            json!({
+                "type": "Tombstone",
+                "instance": [gene.to_string(), ["Referent", when]]
+            }),
+        ];
+        let lines = records.iter().map(Value::to_string).collect::<Vec<_>>();
+        fs::write(&path, lines.join("\n")).unwrap();
+
+        let inspector = StoreInspector::open(MODEL, &path).unwrap();
+        assert!(inspector.warnings().is_empty());
+        assert!(inspector.follow(&fred).is_ok());
+        assert!(inspector.follow(&gene).is_err());
+    }
+
+    #[test]
+    fn test_inspect_vec_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = write_store(
+            dir.path(),
+            &[("referent", json!({"id": 0, "name": "fred"}))],
+        );
+
+        assert!(matches!(
+            StoreInspector::open(MODEL, store),
+            Err(InspectError::VecStore { .. })
+        ));
+    }
+
+    #[test]
+    fn test_untimestamp() {
+        let instance = serde_json::json!({"id": "00000000-0000-0000-0000-000000000000"});
+        let timestamped = serde_json::json!([
+            instance,
+            {"secs_since_epoch": 1, "nanos_since_epoch": 2}
+        ]);
+
+        assert_eq!(untimestamp(timestamped), instance);
+        assert_eq!(untimestamp(instance.clone()), instance);
+    }
+
+    #[test]
+    fn test_cell() {
+        assert_eq!(cell(&Value::Null), "");
+        assert_eq!(cell(&serde_json::json!("foo")), "foo");
+        assert_eq!(cell(&serde_json::json!(42)), "42");
+        assert_eq!(
+            cell(&serde_json::json!({"Foo": "00000000-0000-0000-0000-000000000000"})),
+            "Foo(00000000-0000-0000-0000-000000000000)"
+        );
+    }
+}
@@ -1,8 +1,9 @@
 use std::path::Path;
 
-use sarzak::mc::ModelCompilerOptions;
+use sarzak::mc::{CompilerSnafu, ModelCompilerOptions};
 
 mod codegen;
+pub mod inspect;
 pub mod options;
 mod target;
 mod todo;
@@ -10,7 +11,8 @@ mod types;
 mod woog;
 
 pub use options::{
-    DomainConfig, DwarfConfig, GraceCompilerOptions, OptimizationLevel, Target, UberStoreOptions,
+    AsyncRuntime, DomainConfig, DwarfConfig, GraceCompilerOptions, OptimizationLevel, Target,
+    UberStoreOptions, UuidMode,
 };
 pub use sarzak::mc::{FileSnafu, ModelCompilerError, SarzakModelCompiler};
 
@@ -66,6 +68,12 @@ impl SarzakModelCompiler for ModelCompiler {
             None => GraceCompilerOptions::default(),
         };
 
+        // Catch the option combinations that we can't generate code for before
+        // we do any work.
+        options
+            .validate()
+            .map_err(|description| CompilerSnafu { description }.build())?;
+
         tracy_client::Client::start();
 
         let mut target = match options.target {
@@ -93,6 +93,31 @@ impl fmt::Display for UberStoreOptions {
     }
 }
 
+/// Async Runtime
+///
+/// The `AsyncRwLock` uber store needs async locks, and async locks come from
+/// an async runtime. Use the one that the application runs on. Mixing
+/// executors is a fine way to end up blocking in async code.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize, ValueEnum)]
+pub enum AsyncRuntime {
+    /// async-std
+    ///
+    /// This is the default, because it's what we've always used.
+    #[default]
+    AsyncStd,
+    /// tokio
+    Tokio,
+}
+
+impl fmt::Display for AsyncRuntime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AsyncRuntime::AsyncStd => write!(f, "async-std"),
+            AsyncRuntime::Tokio => write!(f, "tokio"),
+        }
+    }
+}
+
 /// Domain Target Configuration
 ///
 /// The domain target has the following, target-specific, configuration options.
@@ -120,6 +145,28 @@ pub struct DomainConfig {
     /// This option requires the `--from-module` option.
     #[arg(long, requires = "from_module")]
     pub from_path: Option<PathBuf>,
+    /// Generate `TryFrom` trait implementations
+    ///
+    /// Instead of `From`, generate `TryFrom` implementations that return an
+    /// `ExtrusionError`. Fields that don't line up between the two domains get
+    /// a mapping hook that you can edit, and the things that couldn't be mapped
+    /// are reported at the top of the generated file.
+    ///
+    /// This option requires the `--from-module` option.
+    #[arg(long, action=ArgAction::SetTrue, requires = "from_module")]
+    #[serde(default)]
+    pub try_from: bool,
+    /// Generate a store migration
+    ///
+    /// This is the path to the model file of a previous revision of this
+    /// domain. The two are diffed, matching objects and attributes by id, and
+    /// a `migrate` module is generated that upgrades a store persisted by the
+    /// old revision into one that this revision can load.
+    ///
+    /// This is a file system path, relative to the current package.
+    #[arg(long)]
+    #[serde(default)]
+    pub migrate_from: Option<PathBuf>,
     /// Persist ObjectStore
     ///
     /// Wheen this option is specified, code will be generated that will persist
@@ -137,8 +184,32 @@ pub struct DomainConfig {
     /// is the time that the object was interred into the store. This is useful
     /// to the compiler so that it can only generate code for types that have
     /// changed.
+    ///
+    /// The store also gets `iter_{obj}_changed_since` methods, and a store-wide
+    /// `changes_since`, which includes exorcised instances.
+    ///
+    /// This can't be used with the `Vec` optimization level.
     #[arg(long, short = 't', action=ArgAction::SetTrue, requires = "persist")]
     pub persist_timestamps: bool,
+    /// Persist in parallel
+    ///
+    /// Generate `persist` and `load` that fan out across object types, and
+    /// instances, using `rayon`. `load_streaming` reads the instances in
+    /// parallel too. The generated code depends on `rayon`.
+    ///
+    /// This can't be used with the `Single` or `AsyncRwLock` uber stores.
+    #[arg(long, action=ArgAction::SetTrue, requires = "persist")]
+    #[serde(default)]
+    pub persist_parallel: bool,
+    /// Persist with git
+    ///
+    /// Generate `persist_git`, which persists the store, stages the changes
+    /// in the enclosing git repository, and optionally commits them. Also
+    /// generate `history_of_{obj}` methods, which read past versions of an
+    /// instance back out of git. These shell out to the `git` binary.
+    #[arg(long, action=ArgAction::SetTrue, requires = "persist")]
+    #[serde(default)]
+    pub persist_git: bool,
     /// Better Store
     ///
     /// This enables multi-threaded support in the store. It also integrates better
@@ -152,11 +223,49 @@ pub struct DomainConfig {
     /// to interact with the store, not Rust code.
     #[arg(short, long, value_enum, default_value_t=UberStoreOptions::Disabled)]
     pub uber_store: UberStoreOptions,
+    /// Async Runtime
+    ///
+    /// Where the locks for the `async-rw-lock` uber store come from. The
+    /// generated `iter_*` methods return a `futures::Stream` either way.
+    ///
+    /// The persistence methods are async too, and await the locks. The store
+    /// isn't `Serialize`, since serde isn't async.
+    #[arg(long, value_enum, default_value_t=AsyncRuntime::AsyncStd, requires = "uber_store")]
+    #[serde(default)]
+    pub async_runtime: AsyncRuntime,
     /// Optimization Level
     ///
     /// Determines the data structures used to store objects in the ObjectStore.
     #[arg(long, short = 'O', default_value = "none", requires = "uber_store")]
     pub optimization_level: OptimizationLevel,
+    /// UUID Generation
+    ///
+    /// Determines how generated constructors create the `id` of a new instance.
+    /// The default is random, version 4, UUIDs. The other options derive the
+    /// `id` from the constructor arguments using `Uuid::new_v5(&UUID_NS, ..)`,
+    /// which makes persisted stores reproducible.
+    #[arg(long, value_enum, default_value_t=UuidMode::Random)]
+    #[serde(default)]
+    pub uuid_mode: UuidMode,
+    /// Generate Builders
+    ///
+    /// Generate a `FooBuilder` for each struct, with named setters and a
+    /// `build` method that interns the instance in the store. It's a lot
+    /// easier to read than `new` with fifteen positional arguments.
+    #[arg(long, action=ArgAction::SetTrue)]
+    #[serde(default)]
+    pub builders: bool,
+    /// Typed Ids
+    ///
+    /// Generate a `FooId` newtype for each object, and use it for the `id`,
+    /// referential attributes, and the store methods. That way the compiler
+    /// will tell you when you pass an `A` id where a `B` id is expected. The
+    /// newtypes serialize as a plain UUID, so existing stores still load.
+    ///
+    /// This can't be used with the `Vec` optimization level.
+    #[arg(long, action=ArgAction::SetTrue)]
+    #[serde(default)]
+    pub typed_ids: bool,
     /// This Domain is Sarzak
     ///
     /// There can be only one! 💥😱🤣
@@ -173,10 +282,18 @@ pub struct DomainConfig {
 
 const DOMAIN_FROM_MODULE: Option<String> = None;
 const DOMAIN_FROM_PATH: Option<PathBuf> = None;
+const DOMAIN_TRY_FROM: bool = false;
+const DOMAIN_MIGRATE_FROM: Option<PathBuf> = None;
 const DOMAIN_PERSIST: bool = true;
 const DOMAIN_PERSIST_TIMESTAMPS: bool = false;
+const DOMAIN_PERSIST_PARALLEL: bool = false;
+const DOMAIN_PERSIST_GIT: bool = false;
 const DOMAIN_OPTIMIZATION_LEVEL: OptimizationLevel = OptimizationLevel::None;
+const DOMAIN_UUID_MODE: UuidMode = UuidMode::Random;
+const DOMAIN_BUILDERS: bool = false;
+const DOMAIN_TYPED_IDS: bool = false;
 const DOMAIN_UBER_STORE: UberStoreOptions = UberStoreOptions::Disabled;
+const DOMAIN_ASYNC_RUNTIME: AsyncRuntime = AsyncRuntime::AsyncStd;
 const DOMAIN_IS_SARZAK: bool = false;
 const DOMAIN_IS_META_MODEL: bool = false;
 
@@ -193,23 +310,64 @@ impl Default for DomainConfig {
         DomainConfig {
             from_module: DOMAIN_FROM_MODULE,
             from_path: DOMAIN_FROM_PATH,
+            try_from: DOMAIN_TRY_FROM,
+            migrate_from: DOMAIN_MIGRATE_FROM,
             persist: DOMAIN_PERSIST,
             persist_timestamps: DOMAIN_PERSIST_TIMESTAMPS,
+            persist_parallel: DOMAIN_PERSIST_PARALLEL,
+            persist_git: DOMAIN_PERSIST_GIT,
             optimization_level: DOMAIN_OPTIMIZATION_LEVEL,
+            uuid_mode: DOMAIN_UUID_MODE,
+            builders: DOMAIN_BUILDERS,
+            typed_ids: DOMAIN_TYPED_IDS,
             uber_store: DOMAIN_UBER_STORE,
+            async_runtime: DOMAIN_ASYNC_RUNTIME,
             is_sarzak: DOMAIN_IS_SARZAK,
             is_meta_model: DOMAIN_IS_META_MODEL,
         }
     }
 }
 
-#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, ValueEnum)]
+impl DomainConfig {
+    /// Check the options for combinations that we don't support
+    ///
+    /// The Vec store keys everything on `usize`, so there's nothing for the
+    /// typed ids to wrap.
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        if self.typed_ids && self.optimization_level == OptimizationLevel::Vec {
+            return Err("typed ids are not supported with the `vec` optimization level".to_owned());
+        }
+
+        if self.persist_timestamps && self.optimization_level == OptimizationLevel::Vec {
+            return Err(
+                "timestamps are not supported with the `vec` optimization level".to_owned(),
+            );
+        }
+
+        if self.persist_parallel
+            && matches!(
+                self.uber_store,
+                UberStoreOptions::Single | UberStoreOptions::AsyncRwLock
+            )
+        {
+            return Err(format!(
+                "parallel persistence is not supported with the `{}` uber store",
+                self.uber_store
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize, ValueEnum)]
 pub enum OptimizationLevel {
     /// Use  HashMaps
     ///
     /// Uses a hash map to index each Object by it's UUID.
     ///
     /// This is the default.
+    #[default]
     None,
     /// Use Vectors
     ///
@@ -231,6 +389,45 @@ impl fmt::Display for OptimizationLevel {
     }
 }
 
+/// UUID Generation Mode
+///
+/// This is how the `id` is created in generated constructors.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize, ValueEnum)]
+pub enum UuidMode {
+    /// Random UUIDs
+    ///
+    /// Uses `Uuid::new_v4()`. This is the default.
+    #[default]
+    Random,
+    /// Derive the UUID from the constructor arguments
+    ///
+    /// Uses `Uuid::new_v5(&UUID_NS, ..)`, where the name is built from the
+    /// arguments passed to the constructor. Same arguments, same `id`. Note
+    /// that this means two instances with the same arguments will collide.
+    ///
+    /// References to other objects contribute their `id`. Externals, other than
+    /// `SystemTime`, don't contribute at all, since we can't count on them
+    /// rendering the same way twice.
+    Arguments,
+    /// Derive the UUID from a seed and the constructor arguments
+    ///
+    /// The generated `ObjectStore` has a `seed_uuids` method, and the chacha
+    /// plugin, which keeps its own seed, exports a `seed_uuids` function. The
+    /// seed is combined with the arguments, and is bumped on every constructor
+    /// call. The same seed and the same sequence of calls yields the same `id`s.
+    Seeded,
+}
+
+impl fmt::Display for UuidMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UuidMode::Random => write!(f, "Random"),
+            UuidMode::Arguments => write!(f, "Arguments"),
+            UuidMode::Seeded => write!(f, "Seeded"),
+        }
+    }
+}
+
 /// Dwarf Target Configuration
 ///
 /// The dwarf target has the following, target-specific, configuration options.
@@ -261,6 +458,156 @@ pub struct DwarfConfig {
     /// to interact with the store, not Rust code.
     #[arg(short, long, value_enum, default_value_t=UberStoreOptions::Disabled)]
     pub uber_store: UberStoreOptions,
+    /// Optimization Level
+    ///
+    /// The optimization level of the domain store that the plugin wraps. The
+    /// plugin hands ids across as UUIDs, so only `none` is supported.
+    #[arg(long, short = 'O', default_value = "none")]
+    #[serde(default)]
+    pub optimization_level: OptimizationLevel,
+    /// Persist in parallel
+    ///
+    /// Set this if the domain store that the plugin wraps was generated with
+    /// `persist_parallel`, so that the plugin crate depends on `rayon`.
+    #[arg(long, action=ArgAction::SetTrue)]
+    #[serde(default)]
+    pub persist_parallel: bool,
+    /// UUID Generation
+    ///
+    /// Determines how the generated plugin constructors create the `id` of a
+    /// new instance. See the domain target for details.
+    #[arg(long, value_enum, default_value_t=UuidMode::Random)]
+    #[serde(default)]
+    pub uuid_mode: UuidMode,
+    /// Output Directory
+    ///
+    /// Where the dwarf file goes. Without it the dwarf file lands next to the
+    /// source directory, in a directory named for the extension. With the
+    /// `crate` plugin layout this is the root of the plugin crate, and it's
+    /// required.
+    #[arg(long)]
+    #[serde(default)]
+    pub output_dir: Option<PathBuf>,
+    /// Dwarf File Extension
+    ///
+    /// `ore`, or if you are feeling fancy, `道`.
+    #[arg(long, value_enum, default_value_t=DwarfExtension::Ore)]
+    #[serde(default)]
+    pub extension: DwarfExtension,
+    /// Plugin Layout
+    ///
+    /// Either a module in the source directory, like it's always been, or a
+    /// complete `cdylib` crate that can live in it's own workspace member.
+    #[arg(long, value_enum, default_value_t=PluginLayout::Module)]
+    #[serde(default)]
+    pub plugin_layout: PluginLayout,
+    /// Dwarf Revision
+    ///
+    /// The git revision of dwarf that the plugin crate is built against. It's
+    /// either a commit hash or a tag, and it's required for the `crate` plugin
+    /// layout. The plugin ABI changes with dwarf, so we don't float on the
+    /// default branch.
+    #[arg(long)]
+    #[serde(default)]
+    pub dwarf_rev: Option<String>,
+}
+
+/// Dwarf File Extension
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize, ValueEnum)]
+pub enum DwarfExtension {
+    /// `.ore`
+    ///
+    /// This is the default.
+    #[default]
+    Ore,
+    /// `.道`
+    Dao,
+}
+
+impl DwarfExtension {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            DwarfExtension::Ore => "ore",
+            DwarfExtension::Dao => "道",
+        }
+    }
+}
+
+impl fmt::Display for DwarfExtension {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Plugin Layout
+///
+/// How the generated plugin code is laid out on disk.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize, ValueEnum)]
+pub enum PluginLayout {
+    /// A module in the source directory
+    ///
+    /// The plugin is written to `<src>/<domain>.rs`. It's up to you to turn it
+    /// into a dylib. This is the default.
+    #[default]
+    Module,
+    /// A `cdylib` crate
+    ///
+    /// The output directory becomes a crate. We write a `Cargo.toml` and a
+    /// `src/lib.rs`, if they don't already exist, and the plugin goes in
+    /// `src/<domain>.rs`. The dwarf file goes in the crate root. Point the
+    /// domain target at the same `src` directory to fill in the store and types.
+    Crate,
+}
+
+impl fmt::Display for PluginLayout {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PluginLayout::Module => write!(f, "Module"),
+            PluginLayout::Crate => write!(f, "Crate"),
+        }
+    }
+}
+
+impl DwarfConfig {
+    /// Check that we can generate a plugin for the store layout
+    ///
+    /// The plugin hands out shared pointers to instances across the FFI
+    /// boundary, and `invoke_func` is synchronous. That rules out a few of the
+    /// store options. The plugin also expects the store to be keyed on `Uuid`.
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        use UberStoreOptions::*;
+
+        if self.optimization_level != OptimizationLevel::None {
+            return Err(format!(
+                "the dwarf target does not support the `{}` optimization level: the plugin needs a store keyed on `Uuid`",
+                self.optimization_level
+            ));
+        }
+
+        match self.uber_store {
+            Disabled => Err(
+                "the dwarf target requires an uber store, e.g., `--uber-store std-rw-lock`"
+                    .to_owned(),
+            ),
+            Single => Err(format!(
+                "the dwarf target does not support the `{}` uber store: `Rc<RefCell<T>>` can't cross the plugin boundary",
+                self.uber_store
+            )),
+            AsyncRwLock => Err(format!(
+                "the dwarf target does not support the `{}` uber store: plugin functions are synchronous",
+                self.uber_store
+            )),
+            NDRwLock | StdRwLock | StdMutex | ParkingLotRwLock | ParkingLotMutex => {
+                if self.plugin_layout == PluginLayout::Crate && self.output_dir.is_none() {
+                    Err("the `crate` plugin layout requires an output directory, e.g., `--output-dir ../plugins/my_domain`".to_owned())
+                } else if self.plugin_layout == PluginLayout::Crate && self.dwarf_rev.is_none() {
+                    Err("the `crate` plugin layout requires a dwarf revision, e.g., `--dwarf-rev v0.6.0`".to_owned())
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
 }
 
 #[derive(Args, Clone, Debug, Deserialize, Serialize)]
@@ -330,6 +677,17 @@ pub struct GraceCompilerOptions {
     pub tracy: Option<bool>,
 }
 
+impl GraceCompilerOptions {
+    /// Check the options for combinations that we don't support
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        match &self.target {
+            Target::Domain(config) => config.validate(),
+            Target::Dwarf(config) => config.validate(),
+            _ => Ok(()),
+        }
+    }
+}
+
 impl ModelCompilerOptions for GraceCompilerOptions {
     fn as_any(&self) -> &dyn Any {
         self
@@ -423,6 +781,26 @@ impl GraceConfig {
         }
     }
 
+    /// Get the `try_from` value for the target.
+    ///
+    /// Only meaningful when there is a `from_domain`.
+    pub(crate) fn get_try_from(&self) -> bool {
+        match self.get_target() {
+            Target::Domain(config) => config.try_from,
+            _ => false,
+        }
+    }
+
+    /// Get the `migrate_from` value for the target.
+    ///
+    /// This is the model file of the revision that we generate a migration from.
+    pub(crate) fn get_migrate_from(&self) -> Option<&PathBuf> {
+        match self.get_target() {
+            Target::Domain(config) => config.migrate_from.as_ref(),
+            _ => None,
+        }
+    }
+
     /// Get the `persist` value for the target.
     ///
     /// As above, this is sort of a special purpose function.
@@ -442,6 +820,25 @@ impl GraceConfig {
         }
     }
 
+    /// Get the `persist_parallel` value for the target.
+    ///
+    pub(crate) fn get_persist_parallel(&self) -> bool {
+        match self.get_target() {
+            Target::Domain(config) => config.persist_parallel,
+            Target::Dwarf(config) => config.persist_parallel,
+            _ => false,
+        }
+    }
+
+    /// Get the `persist_git` value for the target.
+    ///
+    pub(crate) fn get_persist_git(&self) -> bool {
+        match self.get_target() {
+            Target::Domain(config) => config.persist_git,
+            _ => false,
+        }
+    }
+
     pub(crate) fn is_uber_store(&self) -> bool {
         match self.get_target() {
             Target::Domain(config) => config.uber_store != UberStoreOptions::Disabled,
@@ -457,6 +854,13 @@ impl GraceConfig {
         }
     }
 
+    pub(crate) fn get_async_runtime(&self) -> &AsyncRuntime {
+        match self.get_target() {
+            Target::Domain(config) => &config.async_runtime,
+            _ => &AsyncRuntime::AsyncStd,
+        }
+    }
+
     pub(crate) fn is_sarzak(&self) -> bool {
         match self.get_target() {
             Target::Domain(config) => config.is_sarzak,
@@ -468,10 +872,63 @@ impl GraceConfig {
     pub(crate) fn get_optimization_level(&self) -> &OptimizationLevel {
         match self.get_target() {
             Target::Domain(config) => &config.optimization_level,
+            Target::Dwarf(config) => &config.optimization_level,
             _ => &OptimizationLevel::None,
         }
     }
 
+    pub(crate) fn get_uuid_mode(&self) -> &UuidMode {
+        match self.get_target() {
+            Target::Domain(config) => &config.uuid_mode,
+            Target::Dwarf(config) => &config.uuid_mode,
+            _ => &UuidMode::Random,
+        }
+    }
+
+    pub(crate) fn get_dwarf_output_dir(&self) -> Option<&PathBuf> {
+        match self.get_target() {
+            Target::Dwarf(config) => config.output_dir.as_ref(),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn get_dwarf_extension(&self) -> &DwarfExtension {
+        match self.get_target() {
+            Target::Dwarf(config) => &config.extension,
+            _ => &DwarfExtension::Ore,
+        }
+    }
+
+    pub(crate) fn get_plugin_layout(&self) -> &PluginLayout {
+        match self.get_target() {
+            Target::Dwarf(config) => &config.plugin_layout,
+            _ => &PluginLayout::Module,
+        }
+    }
+
+    pub(crate) fn get_dwarf_rev(&self) -> Option<&str> {
+        match self.get_target() {
+            Target::Dwarf(config) => config.dwarf_rev.as_deref(),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn get_builders(&self) -> bool {
+        match self.get_target() {
+            Target::Domain(config) => config.builders,
+            _ => false,
+        }
+    }
+
+    pub(crate) fn get_typed_ids(&self) -> bool {
+        match self.get_target() {
+            Target::Domain(config) => {
+                config.typed_ids && config.optimization_level == OptimizationLevel::None
+            }
+            _ => false,
+        }
+    }
+
     pub(crate) fn is_meta_model(&self) -> bool {
         match self.get_target() {
             Target::Domain(config) => config.is_meta_model,
@@ -540,6 +997,18 @@ impl GraceConfig {
         }
     }
 
+    pub(crate) fn get_indices(&self, key: &Uuid) -> Option<&Vec<Index>> {
+        if let Some(config_value) = self.get(*key) {
+            if let Some(ref indices) = config_value.indices {
+                Some(indices)
+            } else {
+                None
+            }
+        } else {
+            None
+        }
+    }
+
     pub(crate) fn get_imported(&self, key: &Uuid) -> Option<&ImportedObject> {
         if let Some(config_value) = self.get(*key) {
             if let Some(ref imported_object) = config_value.imported_object {
@@ -571,6 +1040,19 @@ impl GraceConfig {
     pub(crate) fn is_external(&self, key: &Uuid) -> bool {
         self.get_external(key).is_some()
     }
+
+    /// Look up an external entity by the name of the type
+    ///
+    /// Attributes with an external type only know the name, and not the object
+    /// that the configuration is attached to.
+    pub(crate) fn get_external_by_name(&self, name: &str) -> Option<&ExternalEntity> {
+        self.inner.values().find_map(|config_value| {
+            config_value
+                .external_entity
+                .as_ref()
+                .filter(|external_entity| external_entity.name == name)
+        })
+    }
 }
 
 /// Create a GraceConfig from GraceCompilerOptions and a Domain
@@ -635,6 +1117,7 @@ pub(crate) struct ConfigValue {
     pub(crate) doc_test: Option<bool>,
     pub(crate) always_process: Option<bool>,
     pub(crate) tracy: Option<bool>,
+    pub(crate) indices: Option<Vec<Index>>,
 }
 
 impl ConfigValue {
@@ -648,6 +1131,7 @@ impl ConfigValue {
             doc_test: None,
             always_process: None,
             tracy: None,
+            indices: None,
         }
     }
 }
@@ -667,6 +1151,7 @@ impl From<&GraceCompilerOptions> for ConfigValue {
             doc_test: options.doc_test,
             always_process: options.always_process,
             tracy: options.tracy,
+            indices: None,
         }
     }
 }
@@ -707,6 +1192,30 @@ pub(crate) struct ExternalEntity {
     pub name: String,
     pub ctor: String,
     pub path: String,
+    /// The entity is a newtype around a `String`
+    ///
+    /// This is only used by the dwarf target. It passes the value across the
+    /// FFI boundary as a string, so the type needs to implement `Display` and
+    /// `From<String>`.
+    #[serde(default)]
+    pub string_backed: bool,
+}
+
+/// An index on an object, maintained by the ObjectStore
+///
+/// The attributes are named as they are in the model. The store keeps a map
+/// from the attribute values to the id, or ids, of the instances that have
+/// them. If the index is unique, a second instance with the same values is
+/// reported as a conflict, rather than quietly replacing the first.
+///
+/// ```json
+/// {"indices": [{"attributes": ["name"], "unique": true}]}
+/// ```
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub(crate) struct Index {
+    pub attributes: Vec<String>,
+    #[serde(default)]
+    pub unique: bool,
 }
 
 pub(crate) fn parse_config_value(input: &str) -> ConfigValue {
@@ -768,6 +1277,24 @@ mod tests {
         assert_eq!(actual.use_paths, Some(expected));
     }
 
+    #[test]
+    fn test_parse_indices() {
+        let input = "🐶 {\"indices\": [{\"attributes\": [\"name\"], \"unique\": true}, {\"attributes\": [\"kind\", \"size\"]}]}";
+        let expected = vec![
+            Index {
+                attributes: vec!["name".to_owned()],
+                unique: true,
+            },
+            Index {
+                attributes: vec!["kind".to_owned(), "size".to_owned()],
+                unique: false,
+            },
+        ];
+
+        let actual: ConfigValue = parse_config_value(input);
+        assert_eq!(actual.indices, Some(expected));
+    }
+
     #[test]
     fn test_external_entity() {
         let input = "🐶 {\"external_entity\": {\"ctor\": \"now\", \"name\": \"SystemTime\", \"path\": \"std::time\"}}";
@@ -775,6 +1302,21 @@ mod tests {
             ctor: "now".to_owned(),
             name: "SystemTime".to_owned(),
             path: "std::time".to_owned(),
+            string_backed: false,
+        };
+
+        let actual: ConfigValue = parse_config_value(input);
+        assert_eq!(actual.external_entity, Some(expected));
+    }
+
+    #[test]
+    fn test_string_backed_external_entity() {
+        let input = "🐶 {\"external_entity\": {\"ctor\": \"default\", \"name\": \"Name\", \"path\": \"crate::name\", \"string_backed\": true}}";
+        let expected = ExternalEntity {
+            ctor: "default".to_owned(),
+            name: "Name".to_owned(),
+            path: "crate::name".to_owned(),
+            string_backed: true,
         };
 
         let actual: ConfigValue = parse_config_value(input);
@@ -864,4 +1406,109 @@ mod tests {
             assert_eq!(config_value.use_paths, None);
         }
     }
+
+    #[test]
+    fn test_domain_typed_ids_need_uuids() {
+        let mut config = DomainConfig {
+            typed_ids: true,
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+
+        config.optimization_level = OptimizationLevel::Vec;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_domain_timestamps_need_uuids() {
+        let mut config = DomainConfig {
+            persist: true,
+            persist_timestamps: true,
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+
+        config.optimization_level = OptimizationLevel::Vec;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_domain_persist_parallel_needs_send() {
+        let mut config = DomainConfig {
+            persist: true,
+            persist_parallel: true,
+            ..Default::default()
+        };
+
+        for (store, ok) in [
+            (UberStoreOptions::Disabled, true),
+            (UberStoreOptions::Single, false),
+            (UberStoreOptions::AsyncRwLock, false),
+            (UberStoreOptions::StdRwLock, true),
+            (UberStoreOptions::ParkingLotMutex, true),
+        ] {
+            config.uber_store = store;
+            assert_eq!(config.validate().is_ok(), ok);
+        }
+    }
+
+    #[test]
+    fn test_dwarf_config_validate() {
+        let mut config = DwarfConfig {
+            is_sarzak: false,
+            is_meta_model: false,
+            uber_store: UberStoreOptions::Disabled,
+            optimization_level: OptimizationLevel::None,
+            persist_parallel: false,
+            uuid_mode: UuidMode::Random,
+            output_dir: None,
+            extension: DwarfExtension::Ore,
+            plugin_layout: PluginLayout::Module,
+            dwarf_rev: None,
+        };
+
+        for (store, ok) in [
+            (UberStoreOptions::Disabled, false),
+            (UberStoreOptions::Single, false),
+            (UberStoreOptions::AsyncRwLock, false),
+            (UberStoreOptions::NDRwLock, true),
+            (UberStoreOptions::StdRwLock, true),
+            (UberStoreOptions::StdMutex, true),
+            (UberStoreOptions::ParkingLotRwLock, true),
+            (UberStoreOptions::ParkingLotMutex, true),
+        ] {
+            config.uber_store = store;
+            assert_eq!(config.validate().is_ok(), ok, "{}", config.uber_store);
+        }
+
+        config.uber_store = UberStoreOptions::StdRwLock;
+        for level in [OptimizationLevel::Vec, OptimizationLevel::Unsafe] {
+            config.optimization_level = level;
+            assert!(config.validate().is_err(), "{}", config.optimization_level);
+        }
+    }
+
+    #[test]
+    fn test_dwarf_crate_layout_needs_output_dir() {
+        let mut config = DwarfConfig {
+            is_sarzak: false,
+            is_meta_model: false,
+            uber_store: UberStoreOptions::StdRwLock,
+            optimization_level: OptimizationLevel::None,
+            persist_parallel: false,
+            uuid_mode: UuidMode::Random,
+            output_dir: None,
+            extension: DwarfExtension::Dao,
+            plugin_layout: PluginLayout::Crate,
+            dwarf_rev: Some("v0.6.0".to_owned()),
+        };
+        assert!(config.validate().is_err());
+
+        config.output_dir = Some(PathBuf::from("plugins/everything"));
+        assert!(config.validate().is_ok());
+
+        config.dwarf_rev = None;
+        assert!(config.validate().is_err());
+        assert_eq!(config.extension.as_str(), "道");
+    }
 }
@@ -0,0 +1,256 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::RwLock,
+};
+
+use rustc_hash::FxHashMap as HashMap;
+use sarzak::{
+    domain::DomainBuilder,
+    lu_dog::store::ObjectStore as LuDogStore,
+    mc::{FileSnafu, ModelCompilerError, Result},
+    sarzak::types::Object,
+    woog::store::ObjectStore as WoogStore,
+};
+use snafu::prelude::*;
+
+use crate::{
+    codegen::{generator::GeneratorBuilder, render::RenderIdent},
+    options::{GraceCompilerOptions, GraceConfig, PluginLayout},
+    target::Target,
+    types::dwarf::{
+        ChaChaBuilder, ChaChaFile, DwarfBuilder, DwarfFile, DwarfHarness, PluginBuilder, PluginLib,
+        PluginManifest,
+    },
+    woog::init_woog,
+    LIB_NAME, RS_EXT,
+};
+
+const SRC_DIR: &str = "src";
+const CARGO_TOML: &str = "Cargo.toml";
+
+pub(crate) struct DwarfTarget<'a> {
+    config: GraceConfig,
+    package: &'a str,
+    module: &'a str,
+    src_path: &'a Path,
+    domain: sarzak::v2::domain::Domain,
+    woog: WoogStore,
+    /// The Lu-Dog store
+    ///
+    /// This used to be a global. Now it's ours, and we hand it to the generators.
+    /// It's got it's own locking, but it still needs to be behind an RwLock so
+    /// that the generators can share it across threads.
+    lu_dog: RwLock<LuDogStore>,
+    _test: bool,
+}
+
+impl<'a> DwarfTarget<'a> {
+    pub(crate) fn new(
+        options: &'a GraceCompilerOptions,
+        package: &'a str,
+        module: &'a str,
+        src_path: &'a Path,
+        domain: sarzak::v2::domain::Domain,
+        _test: bool,
+    ) -> Result<Box<dyn Target + 'a>> {
+        let config: GraceConfig = (options, &domain).into();
+
+        // Create our local compiler domain.
+        let woog = init_woog(src_path, &config, &domain);
+
+        let lu_dog = RwLock::new(LuDogStore::new());
+
+        Ok(Box::new(Self {
+            config,
+            package,
+            module,
+            src_path,
+            domain,
+            woog,
+            lu_dog,
+            _test,
+        }))
+    }
+}
+
+impl<'a> DwarfTarget<'a> {
+    /// Generate the plugin crate skeleton
+    ///
+    /// This is the `Cargo.toml`, and a `src/lib.rs` that pulls in the plugin
+    /// module. Neither is touched if it already exists. They are yours to
+    /// edit after the first run.
+    fn generate_plugin_crate(&self, root: &Path) -> Result<(), ModelCompilerError> {
+        let mut src = root.to_path_buf();
+        src.push(SRC_DIR);
+        fs::create_dir_all(&src).context(FileSnafu {
+            description: "creating plugin crate directory".to_owned(),
+            path: &src,
+        })?;
+
+        let mut manifest = root.to_path_buf();
+        manifest.push(CARGO_TOML);
+        if !manifest.exists() {
+            GeneratorBuilder::new()
+                .path(&manifest)?
+                .package(self.package)
+                .config(&self.config)
+                .domain(&self.domain)
+                .module(self.module)
+                .generator(
+                    PluginBuilder::new()
+                        .definition(PluginManifest::new())
+                        .build()?,
+                )
+                .generate()?;
+        }
+
+        let mut lib = src;
+        lib.push("discard");
+        lib.set_file_name(LIB_NAME);
+        lib.set_extension(RS_EXT);
+        if !lib.exists() {
+            GeneratorBuilder::new()
+                .path(&lib)?
+                .package(self.package)
+                .config(&self.config)
+                .domain(&self.domain)
+                .module(self.module)
+                .generator(PluginBuilder::new().definition(PluginLib::new()).build()?)
+                .generate()?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> Target for DwarfTarget<'a> {
+    fn compile(&mut self) -> Result<usize, ModelCompilerError> {
+        let ext = self.config.get_dwarf_extension().as_str();
+
+        let (dwarf_dir, plugin_dir) = match self.config.get_plugin_layout() {
+            PluginLayout::Module => {
+                // Unless we're told otherwise, the dwarf file goes next to the
+                // source directory, in a directory named for the extension.
+                let dwarf_dir = match self.config.get_dwarf_output_dir() {
+                    Some(dir) => dir.clone(),
+                    None => {
+                        let mut dir = PathBuf::from(self.src_path);
+                        dir.pop();
+                        dir.push(ext);
+                        dir
+                    }
+                };
+
+                (dwarf_dir, PathBuf::from(self.src_path))
+            }
+            PluginLayout::Crate => {
+                // The options are validated before we get here, so there is an
+                // output directory.
+                let root = self.config.get_dwarf_output_dir().unwrap().clone();
+                self.generate_plugin_crate(&root)?;
+
+                let mut src = root.clone();
+                src.push(SRC_DIR);
+
+                (root, src)
+            }
+        };
+
+        for dir in [&dwarf_dir, &plugin_dir] {
+            fs::create_dir_all(dir).context(FileSnafu {
+                description: "creating dwarf output directory".to_owned(),
+                path: dir,
+            })?;
+        }
+
+        let mut harness_file = dwarf_dir.clone();
+        harness_file.push("discard");
+        harness_file.set_file_name(format!("{}_test", self.domain.name().as_ident()));
+        harness_file.set_extension(ext);
+
+        let mut dwarf_file = dwarf_dir;
+        dwarf_file.push("discard");
+        dwarf_file.set_file_name(self.domain.name().as_ident());
+        dwarf_file.set_extension(ext);
+
+        let mut chacha_file = plugin_dir;
+        chacha_file.push("discard");
+        chacha_file.set_file_name(self.domain.name().as_ident());
+        chacha_file.set_extension(RS_EXT);
+
+        // Sort the objects -- I need to figure out how to do this automagically.
+        let mut objects: Vec<&Object> = self.domain.sarzak().iter_object().collect();
+        objects.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut imported_domains = HashMap::default();
+        for obj in &objects {
+            if self.config.is_imported(&obj.id) {
+                let io = self.config.get_imported(&obj.id).unwrap();
+                // Only import the domain once.
+                if !imported_domains.contains_key(&io.domain) {
+                    let domain = DomainBuilder::new()
+                        .cuckoo_model(&io.model_file)
+                        .unwrap_or_else(|_| {
+                            panic!("Failed to load domain {}", io.model_file.display())
+                        })
+                        .build_v2()
+                        .expect("Failed to build domain");
+
+                    log::debug!("Loaded imported domain {}", io.domain);
+                    imported_domains.insert(io.domain.clone(), domain);
+                }
+            }
+        }
+
+        // The per-object parts of each file are generated in parallel by the
+        // generators themselves.
+        let mut woog = self.woog.clone();
+
+        GeneratorBuilder::new()
+            .path(&dwarf_file)?
+            .package(self.package)
+            .config(&self.config)
+            .domain(&self.domain)
+            .module(self.module)
+            .woog(&mut woog)
+            .lu_dog(&self.lu_dog)
+            .generator(DwarfBuilder::new().definition(DwarfFile::new()).build()?)
+            .generate()?;
+
+        // A smoke test for the plugin, to be run under the interpreter.
+        GeneratorBuilder::new()
+            .path(&harness_file)?
+            .package(self.package)
+            .config(&self.config)
+            .domain(&self.domain)
+            .module(self.module)
+            .woog(&mut woog)
+            .lu_dog(&self.lu_dog)
+            .generator(
+                DwarfBuilder::new()
+                    .definition(DwarfHarness::new())
+                    .build()?,
+            )
+            .imports(&imported_domains)
+            .generate()?;
+
+        GeneratorBuilder::new()
+            .path(&chacha_file)?
+            .package(self.package)
+            .config(&self.config)
+            .domain(&self.domain)
+            .module(self.module)
+            .woog(&mut woog)
+            .lu_dog(&self.lu_dog)
+            .generator(ChaChaBuilder::new().definition(ChaChaFile::new()).build()?)
+            .imports(&imported_domains)
+            .generate()?;
+
+        Ok(objects.len())
+    }
+
+    fn domain(&self) -> &str {
+        self.domain.domain()
+    }
+}
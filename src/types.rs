@@ -45,4 +45,6 @@ pub(crate) trait DwarfDefinition: CodeWriter {}
 
 pub(crate) trait ChaChaDefinition: CodeWriter {}
 
+pub(crate) trait PluginDefinition: CodeWriter {}
+
 pub(crate) trait ObjectStoreDefinition: CodeWriter {}
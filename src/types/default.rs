@@ -2,11 +2,12 @@
 //!
 //! This is the place to find all the default implementations for generating structs.
 //! These are meant to be used in an application domain.
-use std::fmt::Write;
+use std::{fmt::Write, sync::RwLock};
 
 use rustc_hash::FxHashMap as HashMap;
 
 use sarzak::{
+    lu_dog::store::ObjectStore as LuDogStore,
     mc::{CompilerSnafu, FormatSnafu, Result},
     sarzak::types::Object,
     v2::domain::Domain,
@@ -21,7 +22,9 @@ use crate::{
         diff_engine::DirectiveKind,
         emit_object_comments,
         generator::{CodeWriter, FileGenerator, GenerationAction},
-        get_binary_referrers_sorted, object_is_hybrid, object_is_singleton, object_is_supertype,
+        get_binary_referrers_sorted, get_subtypes_sorted_from_super_obj, local_object_has_id_type,
+        local_object_is_enum, local_object_is_referent, object_id_type, object_is_hybrid,
+        object_is_singleton, object_is_supertype,
         render::{render_attributes, RenderConst, RenderIdent, RenderType},
         render_make_uuid, render_method_definition, render_new_instance,
     },
@@ -90,6 +93,7 @@ impl FileGenerator for DefaultStructGenerator {
         domain: &Domain,
         woog: &Option<&mut WoogStore>,
         imports_map: &Option<&HashMap<String, Domain>>,
+        lu_dog: &Option<&RwLock<LuDogStore>>,
         package: &str,
         module: &str,
         obj_id: Option<&Uuid>,
@@ -114,6 +118,7 @@ impl FileGenerator for DefaultStructGenerator {
                         domain,
                         woog,
                         imports_map,
+                        lu_dog,
                         package,
                         module,
                         Some(obj_id),
@@ -127,6 +132,7 @@ impl FileGenerator for DefaultStructGenerator {
                         domain,
                         woog,
                         imports_map,
+                        lu_dog,
                         package,
                         module,
                         Some(obj_id),
@@ -140,6 +146,7 @@ impl FileGenerator for DefaultStructGenerator {
                         domain,
                         woog,
                         imports_map,
+                        lu_dog,
                         package,
                         module,
                         Some(obj_id),
@@ -176,6 +183,7 @@ impl CodeWriter for DefaultStruct {
         domain: &Domain,
         woog: &Option<&mut WoogStore>,
         _imports: &Option<&HashMap<String, Domain>>,
+        _lu_dog: &Option<&RwLock<LuDogStore>>,
         _package: &str,
         module: &str,
         obj_id: Option<&Uuid>,
@@ -330,6 +338,7 @@ impl CodeWriter for DefaultImplementation {
         domain: &Domain,
         woog: &Option<&mut WoogStore>,
         imports: &Option<&HashMap<String, Domain>>,
+        lu_dog: &Option<&RwLock<LuDogStore>>,
         package: &str,
         module: &str,
         obj_id: Option<&Uuid>,
@@ -380,6 +389,7 @@ impl CodeWriter for DefaultImplementation {
                         domain,
                         woog,
                         imports,
+                        lu_dog,
                         package,
                         module,
                         Some(obj_id),
@@ -423,6 +433,7 @@ impl CodeWriter for DefaultNewImpl {
         domain: &Domain,
         woog: &Option<&mut WoogStore>,
         imports: &Option<&HashMap<String, Domain>>,
+        _lu_dog: &Option<&RwLock<LuDogStore>>,
         _package: &str,
         _module: &str,
         obj_id: Option<&Uuid>,
@@ -568,7 +579,7 @@ impl CodeWriter for DefaultNewImpl {
 
                 // Output the code to create the `id`.
                 let id = LValue::new("id", GType::Usize, None);
-                render_make_uuid(buffer, &id, &rvals, domain)?;
+                render_make_uuid(buffer, &id, &rvals, config, imports, domain)?;
 
                 // Output code to create the instance
                 render_new_instance(
@@ -641,6 +652,7 @@ impl FileGenerator for DefaultModuleGenerator {
         domain: &Domain,
         woog: &Option<&mut WoogStore>,
         imports: &Option<&HashMap<String, Domain>>,
+        lu_dog: &Option<&RwLock<LuDogStore>>,
         package: &str,
         module: &str,
         obj_id: Option<&Uuid>,
@@ -656,7 +668,7 @@ impl FileGenerator for DefaultModuleGenerator {
             format!("{}-module-definition-file", module),
             |buffer| {
                 self.definition.write_code(
-                    config, domain, woog, imports, package, module, obj_id, buffer,
+                    config, domain, woog, imports, lu_dog, package, module, obj_id, buffer,
                 )?;
 
                 Ok(())
@@ -687,6 +699,7 @@ impl CodeWriter for DefaultModule {
         domain: &Domain,
         woog: &Option<&mut WoogStore>,
         imports: &Option<&HashMap<String, Domain>>,
+        _lu_dog: &Option<&RwLock<LuDogStore>>,
         _package: &str,
         module: &str,
         _obj_id: Option<&Uuid>,
@@ -706,6 +719,13 @@ impl CodeWriter for DefaultModule {
         // |buffer| {
         let mut objects: Vec<&Object> = domain.sarzak().iter_object().collect();
         objects.sort_by(|a, b| a.name.cmp(&b.name));
+        // Imported objects that we formalize against get an extension trait.
+        let extensions = objects
+            .iter()
+            .filter(|obj| {
+                config.is_imported(&obj.id) && local_object_is_referent(obj, config, domain)
+            })
+            .collect::<Vec<_>>();
         let objects = objects
             .iter()
             .filter(|obj| {
@@ -717,7 +737,19 @@ impl CodeWriter for DefaultModule {
         for obj in &objects {
             emit!(buffer, "pub mod {};", obj.as_ident());
         }
+        for obj in &extensions {
+            emit!(buffer, "pub mod {};", obj.as_ident());
+        }
         emit!(buffer, "");
+        for obj in &extensions {
+            emit!(
+                buffer,
+                "pub use crate::{}::{}::{}Ext;",
+                module,
+                obj.as_ident(),
+                obj.as_type(&Ownership::new_borrowed(), woog, domain)
+            );
+        }
         for obj in &objects {
             if object_is_singleton(obj, config, imports, domain)?
                 && !object_is_supertype(obj, config, imports, domain)?
@@ -756,6 +788,11 @@ impl CodeWriter for DefaultModule {
             }
         }
 
+        if config.get_typed_ids() {
+            emit!(buffer, "");
+            emit_typed_ids(buffer, &objects, config, woog, domain)?;
+        }
+
         // Ok(())
         // },
         // )?;
@@ -763,3 +800,92 @@ impl CodeWriter for DefaultModule {
         Ok(())
     }
 }
+
+/// Generate the id newtypes
+///
+/// These go into their own module, so that the generated types can pull them
+/// all in with a glob import. They are `serde(transparent)`, so they look
+/// exactly like a `Uuid` on disk. `Display` is the same too, which matters
+/// because that's how the persisted files are named.
+///
+/// Enums get their id from their subtypes, so we generate `From` impls to get
+/// from the subtype's id to the supertype's id.
+fn emit_typed_ids(
+    buffer: &mut Buffer,
+    objects: &[&&Object],
+    config: &GraceConfig,
+    woog: &WoogStore,
+    domain: &Domain,
+) -> Result<()> {
+    emit!(buffer, "pub mod ids {{");
+    emit!(buffer, "use serde::{{Deserialize, Serialize}};");
+    emit!(buffer, "use uuid::Uuid;");
+    emit!(buffer, "");
+
+    for obj in objects
+        .iter()
+        .filter(|obj| local_object_has_id_type(obj, config, domain))
+    {
+        let obj_type = obj.as_type(&Ownership::new_borrowed(), woog, domain);
+        let id_type = object_id_type(obj, config, woog, domain);
+
+        emit!(
+            buffer,
+            "/// The `id` of a [`{obj_type}`][super::{obj_type}]"
+        );
+        emit!(
+            buffer,
+            "#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]"
+        );
+        emit!(buffer, "#[serde(transparent)]");
+        emit!(buffer, "pub struct {id_type}(Uuid);");
+        emit!(buffer, "");
+        emit!(buffer, "impl {id_type} {{");
+        emit!(buffer, "pub fn as_uuid(&self) -> &Uuid {{");
+        emit!(buffer, "&self.0");
+        emit!(buffer, "}}");
+        emit!(buffer, "}}");
+        emit!(buffer, "");
+        emit!(buffer, "impl From<Uuid> for {id_type} {{");
+        emit!(buffer, "fn from(id: Uuid) -> Self {{");
+        emit!(buffer, "Self(id)");
+        emit!(buffer, "}}");
+        emit!(buffer, "}}");
+        emit!(buffer, "");
+        emit!(buffer, "impl From<{id_type}> for Uuid {{");
+        emit!(buffer, "fn from(id: {id_type}) -> Self {{");
+        emit!(buffer, "id.0");
+        emit!(buffer, "}}");
+        emit!(buffer, "}}");
+        emit!(buffer, "");
+        emit!(buffer, "impl std::fmt::Display for {id_type} {{");
+        emit!(
+            buffer,
+            "fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {{"
+        );
+        emit!(buffer, "self.0.fmt(f)");
+        emit!(buffer, "}}");
+        emit!(buffer, "}}");
+        emit!(buffer, "");
+
+        if local_object_is_enum(obj, config, domain) {
+            for subtype in get_subtypes_sorted_from_super_obj!(obj, domain.sarzak()) {
+                let s_obj = subtype.r15_object(domain.sarzak())[0];
+                if local_object_has_id_type(s_obj, config, domain) {
+                    let s_id_type = object_id_type(s_obj, config, woog, domain);
+                    emit!(buffer, "impl From<{s_id_type}> for {id_type} {{");
+                    emit!(buffer, "fn from(id: {s_id_type}) -> Self {{");
+                    emit!(buffer, "Self(id.0)");
+                    emit!(buffer, "}}");
+                    emit!(buffer, "}}");
+                    emit!(buffer, "");
+                }
+            }
+        }
+    }
+
+    emit!(buffer, "}}");
+    emit!(buffer, "pub use ids::*;");
+
+    Ok(())
+}
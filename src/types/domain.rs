@@ -6,6 +6,8 @@ pub mod consts;
 pub mod enums;
 pub mod from;
 pub mod hybrid;
+pub mod imported;
+pub mod migrate;
 pub mod rels;
 pub mod store;
 pub mod store_vec;
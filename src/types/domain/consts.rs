@@ -1,10 +1,11 @@
 //! Domain Const Generation
 //!
 //! There we were.
-use std::fmt::Write;
+use std::{fmt::Write, sync::RwLock};
 
 use rustc_hash::FxHashMap as HashMap;
 use sarzak::{
+    lu_dog::store::ObjectStore as LuDogStore,
     mc::{CompilerSnafu, FormatSnafu, Result},
     v2::domain::Domain,
     woog::{store::ObjectStore as WoogStore, types::Ownership},
@@ -42,6 +43,7 @@ impl CodeWriter for DomainConst {
         domain: &Domain,
         woog: &Option<&mut WoogStore>,
         _imports: &Option<&HashMap<String, Domain>>,
+        _lu_dog: &Option<&RwLock<LuDogStore>>,
         _package: &str,
         _module: &str,
         obj_id: Option<&Uuid>,
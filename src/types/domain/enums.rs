@@ -1,10 +1,11 @@
 //! Domain Enum Generation
 //!
 //! Here we are.
-use std::fmt::Write;
+use std::{fmt::Write, sync::RwLock};
 
 use rustc_hash::{FxHashMap as HashMap, FxHashSet as HashSet};
 use sarzak::{
+    lu_dog::store::ObjectStore as LuDogStore,
     mc::{CompilerSnafu, FormatSnafu, Result},
     v2::domain::Domain,
     woog::{store::ObjectStore as WoogStore, Ownership},
@@ -16,12 +17,12 @@ use crate::{
     codegen::{
         buffer::{emit, Buffer},
         diff_engine::DirectiveKind,
-        emit_object_comments, find_store, get_assoc_referrer_obj_from_obj_via_assoc_referent,
-        get_binary_referents_sorted, get_binary_referrers_sorted,
-        get_objs_for_assoc_referrers_sorted, get_objs_for_binary_referents_sorted,
-        get_objs_for_binary_referrers_sorted, get_subtypes_sorted,
-        get_subtypes_sorted_from_super_obj, object_is_enum, object_is_hybrid, object_is_singleton,
-        object_is_supertype,
+        emit_async_lock_uses, emit_object_comments, find_store,
+        get_assoc_referrer_obj_from_obj_via_assoc_referent, get_binary_referents_sorted,
+        get_binary_referrers_sorted, get_objs_for_assoc_referrers_sorted,
+        get_objs_for_binary_referents_sorted, get_objs_for_binary_referrers_sorted,
+        get_subtypes_sorted, get_subtypes_sorted_from_super_obj, local_object_has_id_type,
+        object_id_type, object_is_enum, object_is_hybrid, object_is_singleton, object_is_supertype,
         render::{RenderConst, RenderIdent, RenderType},
     },
     options::{GraceConfig, OptimizationLevel, UberStoreOptions},
@@ -29,6 +30,7 @@ use crate::{
         domain::rels::{
             generate_assoc_referent_rels, generate_assoc_referrer_rels,
             generate_binary_referent_rels, generate_binary_referrer_rels, generate_subtype_rels,
+            NavMethods,
         },
         CodeWriter, MethodImplementation, TypeDefinition,
     },
@@ -53,6 +55,7 @@ impl CodeWriter for Enum {
         domain: &Domain,
         woog: &Option<&mut WoogStore>,
         imports: &Option<&HashMap<String, Domain>>,
+        _lu_dog: &Option<&RwLock<LuDogStore>>,
         _package: &str,
         module: &str,
         obj_id: Option<&Uuid>,
@@ -116,8 +119,7 @@ impl CodeWriter for Enum {
                     match config.get_uber_store().unwrap() {
                         Disabled => unreachable!(),
                         AsyncRwLock => {
-                            emit!(buffer, "use async_std::sync::Arc;");
-                            emit!(buffer, "use async_std::sync::RwLock;");
+                            emit_async_lock_uses(buffer, config)?;
                             // emit!(buffer, "use futures::{{future::OptionFuture, stream::{{self, StreamExt}}}};");
                         }
                         NDRwLock => {
@@ -153,6 +155,9 @@ impl CodeWriter for Enum {
 
                 // Everything has an `id`, everything needs this.
                 uses.insert("use uuid::Uuid;".to_owned());
+                if config.get_typed_ids() {
+                    uses.insert(format!("use crate::{module}::types::ids::*;"));
+                }
 
                 // Add the use statements from the options.
                 if let Some(use_paths) = config.get_use_paths(&obj.id) {
@@ -365,8 +370,9 @@ impl CodeWriter for Enum {
 
                         emit!(
                             buffer,
-                            "{}(Uuid),",
+                            "{}({}),",
                             s_obj.as_type(&Ownership::new_borrowed(), woog, domain),
+                            object_id_type(s_obj, config, woog, domain)
                         );
                     }
                 }
@@ -396,6 +402,7 @@ impl CodeWriter for EnumGetIdImpl {
         domain: &Domain,
         woog: &Option<&mut WoogStore>,
         _imports: &Option<&HashMap<String, Domain>>,
+        _lu_dog: &Option<&RwLock<LuDogStore>>,
         _package: &str,
         _module: &str,
         obj_id: Option<&Uuid>,
@@ -426,8 +433,18 @@ impl CodeWriter for EnumGetIdImpl {
                 if let crate::options::OptimizationLevel::Vec = config.get_optimization_level() {
                     emit!(buffer, "pub fn id(&self) -> usize {{");
                 } else {
-                    emit!(buffer, "pub fn id(&self) -> Uuid {{");
+                    emit!(
+                        buffer,
+                        "pub fn id(&self) -> {} {{",
+                        object_id_type(obj, config, woog, domain)
+                    );
                 }
+                // The subtype ids need to be converted to ours.
+                let into = if local_object_has_id_type(obj, config, domain) {
+                    "(*id).into()"
+                } else {
+                    "*id"
+                };
                 emit!(buffer, "match self {{");
                 for subtype in subtypes {
                     let s_obj = subtype.r15_object(domain.sarzak())[0];
@@ -442,7 +459,7 @@ impl CodeWriter for EnumGetIdImpl {
                     } else {
                         emit!(
                             buffer,
-                            "Self::{}(id) => *id,",
+                            "Self::{}(id) => {into},",
                             s_obj.as_type(&Ownership::new_borrowed(), woog, domain),
                         );
                     }
@@ -474,6 +491,7 @@ impl CodeWriter for EnumRelNavImpl {
         domain: &Domain,
         woog: &Option<&mut WoogStore>,
         _imports: &Option<&HashMap<String, Domain>>,
+        _lu_dog: &Option<&RwLock<LuDogStore>>,
         _package: &str,
         module: &str,
         obj_id: Option<&Uuid>,
@@ -496,9 +514,27 @@ impl CodeWriter for EnumRelNavImpl {
         let woog = woog.as_ref().unwrap();
 
         generate_binary_referrer_rels(buffer, config, module, obj, woog, domain)?;
-        generate_binary_referent_rels(buffer, config, module, obj, "id()", woog, domain)?;
+        generate_binary_referent_rels(
+            buffer,
+            &mut NavMethods::Inherent,
+            config,
+            module,
+            obj,
+            "id()",
+            woog,
+            domain,
+        )?;
         generate_assoc_referrer_rels(buffer, config, module, obj, woog, domain)?;
-        generate_assoc_referent_rels(buffer, config, module, obj, "id()", woog, domain)?;
+        generate_assoc_referent_rels(
+            buffer,
+            &mut NavMethods::Inherent,
+            config,
+            module,
+            obj,
+            "id()",
+            woog,
+            domain,
+        )?;
         generate_subtype_rels(buffer, config, module, obj, woog, domain)?;
 
         Ok(())
@@ -522,6 +558,7 @@ impl CodeWriter for EnumNewImpl {
         domain: &Domain,
         woog: &Option<&mut WoogStore>,
         imports: &Option<&HashMap<String, Domain>>,
+        _lu_dog: &Option<&RwLock<LuDogStore>>,
         _package: &str,
         module: &str,
         obj_id: Option<&Uuid>,
@@ -553,6 +590,8 @@ impl CodeWriter for EnumNewImpl {
             format!("{}-new-impl", obj.as_ident()),
             |buffer| {
                 let is_uber = config.is_uber_store() && !config.is_imported(&obj.id);
+                // Our store is keyed on our id type, not the subtype's.
+                let into = if config.get_typed_ids() { ".into()" } else { "" };
 
                 for subtype in subtypes {
                     let s_obj = subtype.r15_object(domain.sarzak())[0];
@@ -592,7 +631,7 @@ impl CodeWriter for EnumNewImpl {
                                 );
                                 emit!(
                                     buffer,
-                                    "store.exhume_{obj_ident}(&{}).await.unwrap()",
+                                    "store.exhume_{obj_ident}(&{}{into}).await.unwrap()",
                                     s_obj.as_const()
                                 );
                             } else {
@@ -624,7 +663,7 @@ impl CodeWriter for EnumNewImpl {
                                     );
                                     emit!(
                                         buffer,
-                                        "store.exhume_{obj_ident}(&{}).unwrap()",
+                                        "store.exhume_{obj_ident}(&{}{into}).unwrap()",
                                         s_obj.as_const()
                                     );
                                 }
@@ -776,7 +815,7 @@ impl CodeWriter for EnumNewImpl {
 
                                 if let AsyncRwLock = config.get_uber_store().unwrap() {
                                     emit!(
-                                        buffer, "if let Some({s_obj_ident}) = store.exhume_{obj_ident}(&id).await {{"
+                                        buffer, "if let Some({s_obj_ident}) = store.exhume_{obj_ident}(&id{into}).await {{"
                                     );
                                     emit!(buffer, "{s_obj_ident}");
                                     emit!(buffer, "}} else {{");
@@ -791,7 +830,7 @@ impl CodeWriter for EnumNewImpl {
                                     match config.get_uber_store().unwrap() {
                                         StdRwLock => {
                                             emit!(
-                                                buffer, "if let Some({s_obj_ident}) = store.exhume_{obj_ident}(&id) {{"
+                                                buffer, "if let Some({s_obj_ident}) = store.exhume_{obj_ident}(&id{into}) {{"
                                             );
                                             emit!(buffer, "{s_obj_ident}");
                                             emit!(buffer, "}} else {{");
@@ -806,7 +845,7 @@ impl CodeWriter for EnumNewImpl {
                                             match config.get_optimization_level() {
                                                 None => {
                                                     emit!(
-                                                        buffer, "if let Some({s_obj_ident}) = store.exhume_{obj_ident}(&id) {{"
+                                                        buffer, "if let Some({s_obj_ident}) = store.exhume_{obj_ident}(&id{into}) {{"
                                                     );
                                                     emit!(buffer, "{s_obj_ident}");
                                                     emit!(buffer, "}} else {{");
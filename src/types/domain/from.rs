@@ -1,11 +1,12 @@
 //! Generate From trait implementations for use in sarzak Domain
 //!
-use std::fmt::Write;
+use std::{fmt::Write, sync::RwLock};
 
 use rustc_hash::FxHashMap as HashMap;
 use sarzak::{
+    lu_dog::store::ObjectStore as LuDogStore,
     mc::{CompilerSnafu, FormatSnafu, Result},
-    sarzak::types::{Object, Ty},
+    sarzak::types::{Conditionality, Object, Ty},
     v2::domain::Domain,
     woog::{store::ObjectStore as WoogStore, types::Ownership},
 };
@@ -19,7 +20,8 @@ use crate::{
         generator::{CodeWriter, FileGenerator, GenerationAction},
         get_assoc_referent_from_referrer_sorted, get_binary_referrers_sorted,
         get_subtypes_sorted_from_super_obj, local_object_is_singleton, local_object_is_supertype,
-        object_is_supertype,
+        model_diff::{collect_fields, match_fields, FieldDiff},
+        object_id_type, object_is_supertype,
         render::{RenderConst, RenderIdent, RenderType},
     },
     options::{FromDomain, GraceConfig},
@@ -85,17 +87,30 @@ impl FileGenerator for DomainFromGenerator {
         domain: &Domain,
         woog: &Option<&mut WoogStore>,
         imports: &Option<&HashMap<String, Domain>>,
+        lu_dog: &Option<&RwLock<LuDogStore>>,
         package: &str,
         module: &str,
         obj_id: Option<&Uuid>,
         buffer: &mut Buffer,
     ) -> Result<GenerationAction> {
+        let (trait_name, directive) = if config.get_try_from() {
+            ("TryFrom", DirectiveKind::AllowEditing)
+        } else {
+            ("From", DirectiveKind::IgnoreGenerated)
+        };
+
         // Output the domain/module documentation/description
-        emit!(buffer, "//! {} Object From Trait Implementations", module);
+        emit!(
+            buffer,
+            "//! {} Object {} Trait Implementations",
+            module,
+            trait_name
+        );
         emit!(buffer, "//!");
         emit!(
             buffer,
-            "//! These are [`From`] trait implementations for the domain: _{}_. They are",
+            "//! These are [`{}`] trait implementations for the domain: _{}_. They are",
+            trait_name,
             domain.name()
         );
         emit!(
@@ -112,31 +127,39 @@ impl FileGenerator for DomainFromGenerator {
             self.from_domain.module
         );
         emit!(buffer, "//!");
-        emit!(
-            buffer,
-            "//! It is hoped that the model has not changed enough to render"
-        );
-        emit!(
-            buffer,
-            "//! these implementations useless. In any case it's expected that"
-        );
-        emit!(
-            buffer,
-            "//! the generated code will need to be manually edited."
-        );
+        if config.get_try_from() {
+            emit!(
+                buffer,
+                "//! Fields that couldn't be mapped automatically have a hook at the bottom"
+            );
+            emit!(
+                buffer,
+                "//! of the file. Edit those, and leave the rest to the generator."
+            );
+        } else {
+            emit!(
+                buffer,
+                "//! It is hoped that the model has not changed enough to render"
+            );
+            emit!(
+                buffer,
+                "//! these implementations useless. In any case it's expected that"
+            );
+            emit!(
+                buffer,
+                "//! the generated code will need to be manually edited."
+            );
+        }
 
-        // It's expected that this code will be edited, block accordingly.
-        buffer.block(
-            DirectiveKind::IgnoreGenerated,
-            format!("{}-from-impl-file", module),
-            |buffer| {
-                self.definition.write_code(
-                    config, domain, woog, imports, package, module, obj_id, buffer,
-                )?;
+        // It's expected that this code will be edited, block accordingly. The
+        // `TryFrom` flavor keeps the edits in hooks, so we can regenerate the rest.
+        buffer.block(directive, format!("{}-from-impl-file", module), |buffer| {
+            self.definition.write_code(
+                config, domain, woog, imports, lu_dog, package, module, obj_id, buffer,
+            )?;
 
-                Ok(())
-            },
-        )?;
+            Ok(())
+        })?;
 
         Ok(GenerationAction::FormatWrite)
     }
@@ -159,6 +182,7 @@ impl CodeWriter for DomainFromImpl {
         domain: &Domain,
         woog: &Option<&mut WoogStore>,
         imports: &Option<&HashMap<String, Domain>>,
+        _lu_dog: &Option<&RwLock<LuDogStore>>,
         _package: &str,
         module: &str,
         _obj_id: Option<&Uuid>,
@@ -237,6 +261,10 @@ impl CodeWriter for DomainFromImpl {
             format!("{}-from-impl-definition", module),
             |buffer| {
                 // Generate the use statements
+                if config.get_typed_ids() {
+                    emit!(buffer, "use uuid::Uuid;");
+                    emit!(buffer, "");
+                }
                 emit!(buffer, "use crate::{}::ObjectStore;", module,);
                 emit!(buffer, "use crate::{}::types::{{", module);
                 for obj in &objects {
@@ -357,6 +385,16 @@ impl CodeWriter for DomainFromImpl {
                         );
                         emit!(buffer, "Self {{");
 
+                        // The source domain may, or may not, have typed ids. Going
+                        // through `Uuid` works either way.
+                        let id_value = |ident: String| {
+                            if config.get_typed_ids() {
+                                format!("Uuid::from(src.{ident}).into()")
+                            } else {
+                                format!("src.{ident}")
+                            }
+                        };
+
                         // Attributes
                         let mut attrs = obj.r1_attribute(domain.sarzak());
                         attrs.sort_by(|a, b| a.name.cmp(&b.name));
@@ -379,6 +417,9 @@ impl CodeWriter for DomainFromImpl {
                                         attr.as_ident()
                                     )
                                 }
+                                _ if attr.name == "id" => {
+                                    emit!(buffer, "id: {},", id_value(attr.as_ident()))
+                                }
                                 _ => {
                                     emit!(buffer, "{}: src.{},", attr.as_ident(), attr.as_ident())
                                 }
@@ -389,9 +430,9 @@ impl CodeWriter for DomainFromImpl {
                         for referrer in get_binary_referrers_sorted!(obj, domain.sarzak()) {
                             emit!(
                                 buffer,
-                                "{}: src.{},",
-                                referrer.referential_attribute.as_ident(),
+                                "{}: {},",
                                 referrer.referential_attribute.as_ident(),
+                                id_value(referrer.referential_attribute.as_ident()),
                             );
                         }
                         for assoc_referrer in obj.r26_associative_referrer(domain.sarzak()) {
@@ -406,9 +447,9 @@ impl CodeWriter for DomainFromImpl {
 
                                 emit!(
                                     buffer,
-                                    "{}: src.{},",
+                                    "{}: {},",
                                     an_ass.referential_attribute.as_ident(),
-                                    an_ass.referential_attribute.as_ident()
+                                    id_value(an_ass.referential_attribute.as_ident())
                                 );
                             }
                         }
@@ -426,3 +467,507 @@ impl CodeWriter for DomainFromImpl {
         Ok(())
     }
 }
+
+/// How a field gets it's value in a `TryFrom` implementation
+enum Conversion {
+    /// Plain old copy
+    Copy,
+    /// It's not `Copy`, so clone it
+    Clone,
+    /// An id, which may be typed on this side
+    Id,
+    /// An optional id, as above
+    OptionId,
+    /// Only a human knows
+    Hook,
+}
+
+/// The Rust type and conversion for each field of a target object, by name
+fn target_fields(
+    obj: &Object,
+    config: &GraceConfig,
+    woog: &WoogStore,
+    domain: &Domain,
+) -> HashMap<String, (String, Conversion)> {
+    let mut result = HashMap::default();
+
+    for attr in obj.r1_attribute(domain.sarzak()) {
+        let ty = attr.r2_ty(domain.sarzak())[0];
+        let entry = if attr.name == "id" {
+            (object_id_type(obj, config, woog, domain), Conversion::Id)
+        } else {
+            let conversion = match ty {
+                Ty::Boolean(_) | Ty::Float(_) | Ty::Integer(_) | Ty::SUuid(_) => Conversion::Copy,
+                Ty::SString(_) | Ty::External(_) => Conversion::Clone,
+                // These are types from the other domain.
+                Ty::Object(_) => Conversion::Hook,
+            };
+            (
+                ty.as_type(&Ownership::new_borrowed(), woog, domain),
+                conversion,
+            )
+        };
+        result.insert(attr.as_ident(), entry);
+    }
+
+    for referrer in get_binary_referrers_sorted!(obj, domain.sarzak()) {
+        let binary = referrer.r6_binary(domain.sarzak())[0];
+        let referent = binary.r5_referent(domain.sarzak())[0];
+        let r_obj = referent.r16_object(domain.sarzak())[0];
+        let ty = object_id_type(r_obj, config, woog, domain);
+        let entry = match referrer.r11_conditionality(domain.sarzak())[0] {
+            Conditionality::Conditional(_) => (format!("Option<{ty}>"), Conversion::OptionId),
+            Conditionality::Unconditional(_) => (ty, Conversion::Id),
+        };
+        result.insert(referrer.referential_attribute.as_ident(), entry);
+    }
+
+    for assoc_referrer in obj.r26_associative_referrer(domain.sarzak()) {
+        for referent in get_assoc_referent_from_referrer_sorted!(assoc_referrer, domain.sarzak()) {
+            let an_ass = referent.r22_an_associative_referent(domain.sarzak())[0];
+            let assoc_obj = referent.r25_object(domain.sarzak())[0];
+            result.insert(
+                an_ass.referential_attribute.as_ident(),
+                (
+                    object_id_type(assoc_obj, config, woog, domain),
+                    Conversion::Id,
+                ),
+            );
+        }
+    }
+
+    result
+}
+
+/// Find the counterpart of an object in another domain
+///
+/// By id, if we can, and then by name.
+fn find_object<'a>(obj: &Object, domain: &'a Domain) -> Option<&'a Object> {
+    domain
+        .sarzak()
+        .exhume_object(&obj.id)
+        .or_else(|| domain.sarzak().iter_object().find(|o| o.name == obj.name))
+}
+
+/// Fallible From Implementation Generator / CodeWriter
+///
+/// This is the `TryFrom` flavor of [`DomainFromImpl`]. Fields that are in both
+/// domains, with the same type, are copied. Everything else gets a mapping
+/// hook, in an `allow-editing` block, that returns an error until you decide
+/// what it ought to do. The things that we couldn't map are listed at the top
+/// of the file, and logged.
+pub(crate) struct DomainTryFromImpl;
+
+impl DomainTryFromImpl {
+    pub(crate) fn new() -> Box<dyn ObjectStoreDefinition> {
+        Box::new(Self)
+    }
+}
+
+impl ObjectStoreDefinition for DomainTryFromImpl {}
+
+impl CodeWriter for DomainTryFromImpl {
+    fn write_code(
+        &self,
+        config: &GraceConfig,
+        domain: &Domain,
+        woog: &Option<&mut WoogStore>,
+        imports: &Option<&HashMap<String, Domain>>,
+        _lu_dog: &Option<&RwLock<LuDogStore>>,
+        _package: &str,
+        module: &str,
+        _obj_id: Option<&Uuid>,
+        buffer: &mut Buffer,
+    ) -> Result<()> {
+        ensure!(
+            imports.is_some(),
+            CompilerSnafu {
+                description: "DomainTryFromImpl::write_code called without imports"
+            }
+        );
+        let imports = imports.unwrap();
+        ensure!(
+            woog.is_some(),
+            CompilerSnafu {
+                description: "woog is required by DomainTryFromImpl"
+            }
+        );
+        let woog = woog.as_ref().unwrap();
+        let from_domain = config.get_from_domain().context(CompilerSnafu {
+            description: "DomainTryFromImpl::write_code called without From Domain configuration",
+        })?;
+        let from_name = from_domain
+            .module
+            .split("::")
+            .last()
+            .context(CompilerSnafu {
+                description: "failed to split path",
+            })?
+            .to_owned();
+        let source = imports.get(&from_name).context(CompilerSnafu {
+            description: format!("failed to find import for {}", from_domain.module),
+        })?;
+        let from_module = from_domain.module;
+        let from_store = from_name.as_type(&Ownership::new_borrowed(), woog, domain);
+
+        let mut objects: Vec<&Object> = domain
+            .sarzak()
+            .iter_object()
+            .filter(|obj| {
+                !config.is_imported(&obj.id)
+                    && (local_object_is_supertype(obj, config, domain)
+                        || !local_object_is_singleton(obj, config, domain))
+            })
+            .collect();
+        objects.sort_by(|a, b| a.name.cmp(&b.name));
+
+        // Pair up the objects, and make note of the ones that we can't.
+        let mut report = Vec::new();
+        let mut mapped = Vec::new();
+        for obj in &objects {
+            match find_object(obj, source) {
+                Some(src_obj) => mapped.push((*obj, src_obj)),
+                None => report.push(format!("object {}: not in {from_name}", obj.name)),
+            }
+        }
+        let mut orphans: Vec<&Object> = source
+            .sarzak()
+            .iter_object()
+            .filter(|src_obj| {
+                !local_object_is_singleton(src_obj, config, source)
+                    && find_object(src_obj, domain).is_none()
+            })
+            .collect();
+        orphans.sort_by(|a, b| a.name.cmp(&b.name));
+        for src_obj in orphans {
+            report.push(format!(
+                "object {from_name}::{}: not in {}",
+                src_obj.name,
+                domain.name()
+            ));
+        }
+
+        // Render the implementations first, so that the report is complete
+        // when we write it out.
+        let mut impls = Buffer::new();
+        let mut hooks = Buffer::new();
+        for (obj, src_obj) in &mapped {
+            let obj_type = obj.as_type(&Ownership::new_borrowed(), woog, domain);
+            let obj_ident = obj.as_ident();
+
+            if object_is_supertype(obj, config, &Some(imports), domain)? {
+                emit!(impls, "impl TryFrom<&From{obj_type}> for {obj_type} {{");
+                emit!(impls, "type Error = ExtrusionError;");
+                emit!(impls, "");
+                emit!(
+                    impls,
+                    "fn try_from(src: &From{obj_type}) -> Result<Self, Self::Error> {{"
+                );
+                emit!(impls, "match src {{");
+
+                let mut subtypes = Vec::new();
+                if !src_obj.r14_supertype(source.sarzak()).is_empty() {
+                    subtypes = get_subtypes_sorted_from_super_obj!(src_obj, source.sarzak());
+                }
+                let targets = get_subtypes_sorted_from_super_obj!(obj, domain.sarzak())
+                    .iter()
+                    .map(|subtype| subtype.r15_object(domain.sarzak())[0])
+                    .collect::<Vec<_>>();
+                for subtype in subtypes {
+                    let src_s_obj = subtype.r15_object(source.sarzak())[0];
+                    let src_s_type = src_s_obj.as_type(&Ownership::new_borrowed(), woog, domain);
+                    match targets
+                        .iter()
+                        .find(|s_obj| s_obj.id == src_s_obj.id || s_obj.name == src_s_obj.name)
+                    {
+                        Some(s_obj) => emit!(
+                            impls,
+                            "From{obj_type}::{src_s_type}(_) => Ok({obj_type}::{}({})),",
+                            s_obj.as_type(&Ownership::new_borrowed(), woog, domain),
+                            s_obj.as_const()
+                        ),
+                        None => {
+                            report.push(format!(
+                                "subtype {}::{}: not in {}",
+                                obj.name,
+                                src_s_obj.name,
+                                domain.name()
+                            ));
+                            emit!(
+                                impls,
+                                "From{obj_type}::{src_s_type}(_) => Err(ExtrusionError::Subtype {{ object: \"{}\", subtype: \"{}\" }}),",
+                                obj.name,
+                                src_s_obj.name
+                            );
+                        }
+                    }
+                }
+                emit!(impls, "}}");
+                emit!(impls, "}}");
+                emit!(impls, "}}");
+                emit!(impls, "");
+                continue;
+            }
+
+            let types = target_fields(obj, config, woog, domain);
+            let fields = match_fields(
+                &collect_fields(src_obj, source),
+                &collect_fields(obj, domain),
+            );
+
+            emit!(impls, "impl TryFrom<&From{obj_type}> for {obj_type} {{");
+            emit!(impls, "type Error = ExtrusionError;");
+            emit!(impls, "");
+            emit!(
+                impls,
+                "fn try_from(src: &From{obj_type}) -> Result<Self, Self::Error> {{"
+            );
+            emit!(impls, "Ok(Self {{");
+            for field in &fields {
+                let (new, old) = match field {
+                    FieldDiff::Kept { old, new } => (new, Some(old)),
+                    FieldDiff::Retyped { old, new } => {
+                        report.push(format!(
+                            "{} {}.{}: {} in {from_name}, {} here",
+                            if new.referential {
+                                "relationship"
+                            } else {
+                                "attribute"
+                            },
+                            obj.name,
+                            new.name,
+                            old.type_name(),
+                            new.type_name()
+                        ));
+                        (new, None)
+                    }
+                    FieldDiff::Added(new) => {
+                        report.push(format!(
+                            "{} {}.{}: not in {from_name}",
+                            if new.referential {
+                                "relationship"
+                            } else {
+                                "attribute"
+                            },
+                            obj.name,
+                            new.name
+                        ));
+                        (new, None)
+                    }
+                    FieldDiff::Dropped(old) => {
+                        report.push(format!(
+                            "{} {from_name}::{}.{}: not in {}",
+                            if old.referential {
+                                "relationship"
+                            } else {
+                                "attribute"
+                            },
+                            src_obj.name,
+                            old.name,
+                            domain.name()
+                        ));
+                        continue;
+                    }
+                };
+
+                let name = &new.name;
+                let (ty, conversion) = match types.get(name) {
+                    Some(entry) => entry,
+                    None => continue,
+                };
+                match (old, conversion) {
+                    (Some(old), Conversion::Copy) => emit!(impls, "{name}: src.{},", old.name),
+                    (Some(old), Conversion::Clone) => {
+                        emit!(impls, "{name}: src.{}.clone(),", old.name)
+                    }
+                    // The source domain may, or may not, have typed ids. Going
+                    // through `Uuid` works either way.
+                    (Some(old), Conversion::Id) if config.get_typed_ids() => {
+                        emit!(impls, "{name}: Uuid::from(src.{}).into(),", old.name)
+                    }
+                    (Some(old), Conversion::OptionId) if config.get_typed_ids() => emit!(
+                        impls,
+                        "{name}: src.{}.map(|id| Uuid::from(id).into()),",
+                        old.name
+                    ),
+                    (Some(old), Conversion::Id | Conversion::OptionId) => {
+                        emit!(impls, "{name}: src.{},", old.name)
+                    }
+                    (old, _) => {
+                        if old.is_some() {
+                            report.push(format!(
+                                "attribute {}.{name}: can't be copied from {from_name}",
+                                obj.name
+                            ));
+                        }
+                        emit!(impls, "{name}: map_{obj_ident}_{name}(src)?,");
+
+                        hooks.block(
+                            DirectiveKind::AllowEditing,
+                            format!("{module}-map-{obj_ident}-{name}"),
+                            |buffer| {
+                                emit!(
+                                    buffer,
+                                    "/// Map `{name}` of [`{obj_type}`] from the source domain"
+                                );
+                                emit!(
+                                    buffer,
+                                    "fn map_{obj_ident}_{name}(_src: &From{obj_type}) -> Result<{ty}, ExtrusionError> {{"
+                                );
+                                emit!(buffer, "Err(ExtrusionError::Field {{");
+                                emit!(buffer, "object: \"{}\",", obj.name);
+                                emit!(buffer, "field: \"{name}\",");
+                                emit!(
+                                    buffer,
+                                    "description: \"no mapping from {from_name}\".to_owned(),"
+                                );
+                                emit!(buffer, "}})");
+                                emit!(buffer, "}}");
+                                emit!(buffer, "");
+
+                                Ok(())
+                            },
+                        )?;
+                    }
+                }
+            }
+            emit!(impls, "}})");
+            emit!(impls, "}}");
+            emit!(impls, "}}");
+            emit!(impls, "");
+        }
+
+        for line in &report {
+            log::warn!("extrusion from {from_name}: {line}");
+        }
+
+        buffer.block(
+            DirectiveKind::IgnoreOrig,
+            format!("{}-try-from-impl-definition", module),
+            |buffer| {
+                if report.is_empty() {
+                    emit!(buffer, "// Everything was mapped automatically.");
+                } else {
+                    emit!(buffer, "// These couldn't be mapped automatically:");
+                    for line in &report {
+                        emit!(buffer, "//  - {line}");
+                    }
+                }
+                emit!(buffer, "use std::fmt;");
+                emit!(buffer, "");
+                if config.get_typed_ids() {
+                    emit!(buffer, "use uuid::Uuid;");
+                    emit!(buffer, "");
+                }
+                emit!(buffer, "use crate::{}::ObjectStore;", module);
+                emit!(buffer, "use crate::{}::types::{{", module);
+                for (obj, _) in &mapped {
+                    emit!(
+                        buffer,
+                        "{},",
+                        obj.as_type(&Ownership::new_borrowed(), woog, domain)
+                    );
+                }
+                emit!(buffer, "}};");
+                emit!(buffer, "");
+                emit!(
+                    buffer,
+                    "use crate::{from_module}::ObjectStore as {from_store}Store;"
+                );
+                emit!(buffer, "use crate::{from_module}::types::{{");
+                for (obj, src_obj) in &mapped {
+                    emit!(
+                        buffer,
+                        "{} as From{},",
+                        src_obj.as_type(&Ownership::new_borrowed(), woog, source),
+                        obj.as_type(&Ownership::new_borrowed(), woog, domain)
+                    );
+                }
+                emit!(buffer, "}};");
+                emit!(buffer, "");
+
+                emit!(
+                    buffer,
+                    r#"/// Extrusion Error
+///
+/// Returned when an instance in the source store can't be turned into one in
+/// this domain.
+#[derive(Debug)]
+pub enum ExtrusionError {{
+    /// A field that couldn't be mapped
+    Field {{
+        object: &'static str,
+        field: &'static str,
+        description: String,
+    }},
+    /// A subtype that doesn't exist in this domain
+    Subtype {{
+        object: &'static str,
+        subtype: &'static str,
+    }},
+}}
+
+impl fmt::Display for ExtrusionError {{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {{
+        match self {{
+            Self::Field {{
+                object,
+                field,
+                description,
+            }} => write!(f, "{{object}}.{{field}}: {{description}}"),
+            Self::Subtype {{ object, subtype }} => {{
+                write!(f, "{{object}}: no subtype {{subtype}}")
+            }}
+        }}
+    }}
+}}
+
+impl std::error::Error for ExtrusionError {{}}
+"#
+                );
+
+                // Generate the ObjectStore TryFrom implementation
+                emit!(
+                    buffer,
+                    "impl TryFrom<&{from_store}Store> for ObjectStore {{"
+                );
+                emit!(buffer, "type Error = ExtrusionError;");
+                emit!(buffer, "");
+                emit!(
+                    buffer,
+                    "fn try_from(from: &{from_store}Store) -> Result<Self, Self::Error> {{"
+                );
+                emit!(buffer, "let mut to = ObjectStore::new();");
+                for (obj, src_obj) in &mapped {
+                    emit!(buffer, "");
+                    emit!(
+                        buffer,
+                        "for instance in from.iter_{}() {{",
+                        src_obj.as_ident()
+                    );
+                    emit!(
+                        buffer,
+                        "let instance = {}::try_from(instance)?;",
+                        obj.as_type(&Ownership::new_borrowed(), woog, domain)
+                    );
+                    emit!(buffer, "to.inter_{}(instance);", obj.as_ident());
+                    emit!(buffer, "}}");
+                }
+                emit!(buffer, "");
+                emit!(buffer, "Ok(to)");
+                emit!(buffer, "}}");
+                emit!(buffer, "}}");
+                emit!(buffer, "");
+
+                *buffer += impls;
+
+                Ok(())
+            },
+        )?;
+        emit!(buffer, "");
+
+        *buffer += hooks;
+
+        Ok(())
+    }
+}
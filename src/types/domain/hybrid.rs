@@ -1,10 +1,11 @@
 //! Domain Enum with extras Generation
 //!
 //! Here we are.
-use std::{fmt::Write, iter::zip};
+use std::{fmt::Write, iter::zip, sync::RwLock};
 
 use rustc_hash::{FxHashMap as HashMap, FxHashSet as HashSet};
 use sarzak::{
+    lu_dog::store::ObjectStore as LuDogStore,
     mc::{CompilerSnafu, FormatSnafu, Result},
     sarzak::types::Conditionality,
     v2::domain::Domain,
@@ -20,17 +21,19 @@ use crate::{
     codegen::{
         buffer::{emit, Buffer},
         diff_engine::DirectiveKind,
-        emit_object_comments, find_store, get_assoc_referent_from_referrer_sorted,
+        emit_async_lock_uses, emit_object_comments, emit_typed_id_uses, emit_uuid_mode_uses,
+        find_store, get_assoc_referent_from_referrer_sorted,
         get_assoc_referrer_obj_from_obj_via_assoc_referent, get_binary_referents_sorted,
         get_binary_referrers_sorted, get_objs_for_assoc_referrers_sorted,
         get_objs_for_binary_referents_sorted, get_objs_for_binary_referrers_sorted,
-        get_subtypes_sorted, get_subtypes_sorted_from_super_obj, local_object_is_enum,
-        object_is_enum, object_is_hybrid, object_is_singleton, object_is_supertype,
+        get_subtypes_sorted, get_subtypes_sorted_from_super_obj, local_object_has_id_type,
+        local_object_is_enum, object_id_type, object_is_enum, object_is_hybrid,
+        object_is_singleton, object_is_supertype,
         render::{
             render_associative_attributes, render_attributes, render_binary_referential_attributes,
             RenderConst, RenderIdent, RenderType,
         },
-        render_method_definition, render_new_instance,
+        render_make_uuid, render_method_definition, render_new_instance,
     },
     options::{GraceConfig, UberStoreOptions},
     todo::{GType, LValue, ObjectMethod, Parameter, RValue},
@@ -59,6 +62,7 @@ impl CodeWriter for Hybrid {
         domain: &Domain,
         woog: &Option<&mut WoogStore>,
         imports: &Option<&HashMap<String, Domain>>,
+        _lu_dog: &Option<&RwLock<LuDogStore>>,
         _package: &str,
         module: &str,
         obj_id: Option<&Uuid>,
@@ -120,8 +124,7 @@ impl CodeWriter for Hybrid {
                     match config.get_uber_store().unwrap() {
                         Disabled => unreachable!(),
                         AsyncRwLock => {
-                            emit!(buffer, "use async_std::sync::Arc;");
-                            emit!(buffer, "use async_std::sync::RwLock;");
+                            emit_async_lock_uses(buffer, config)?;
                             emit!(buffer, "use futures::stream::{{self, StreamExt}};");
                         }
                         NDRwLock => {
@@ -156,6 +159,8 @@ impl CodeWriter for Hybrid {
 
                 // Everything has an `id`, everything needs this.
                 emit!(buffer, "use uuid::Uuid;");
+                emit_uuid_mode_uses(buffer, module, config)?;
+                emit_typed_id_uses(buffer, module, config)?;
                 emit!(buffer, "");
 
                 // Add the use statements from the options.
@@ -366,8 +371,9 @@ impl CodeWriter for Hybrid {
                     } else {
                         emit!(
                             buffer,
-                            "{}(Uuid),",
+                            "{}({}),",
                             s_obj.as_type(&Ownership::new_borrowed(), woog, domain),
+                            object_id_type(s_obj, config, woog, domain)
                         );
                     }
                 }
@@ -389,6 +395,10 @@ impl CodeWriter for Hybrid {
 /// the Hybrid enum, and struct. So we have multiple new methods and we never
 /// surface the existence of the enum
 ///
+/// Each of the new methods takes the supertype's own attributes, followed by the
+/// referential attributes, and finally the subtype. Singleton subtypes aren't
+/// passed in -- we just use the const.
+///
 /// __NB__ --- this implies that the lexicographical sum of it's attributes,
 /// across all instances, must be unique.
 pub(crate) struct HybridNewImpl;
@@ -408,6 +418,7 @@ impl CodeWriter for HybridNewImpl {
         domain: &Domain,
         woog: &Option<&mut WoogStore>,
         imports: &Option<&HashMap<String, Domain>>,
+        _lu_dog: &Option<&RwLock<LuDogStore>>,
         _package: &str,
         module: &str,
         obj_id: Option<&Uuid>,
@@ -796,7 +807,22 @@ impl CodeWriter for HybridNewImpl {
                         }
                         emit!(buffer, "}}");
                     } else {
-                        emit!(buffer, "let id = Uuid::new_v4();");
+                        // The rvals don't include the store, which is what we want.
+                        render_make_uuid(
+                            buffer,
+                            &LValue::new("id", GType::Uuid, None),
+                            &rvals,
+                            config,
+                            imports,
+                            domain,
+                        )?;
+                        if local_object_has_id_type(obj, config, domain) {
+                            emit!(
+                                buffer,
+                                "let id = {}::from(id);",
+                                object_id_type(obj, config, woog, domain)
+                            );
+                        }
 
                         // Output code to create the instance
                         let new = LValue::new("new", GType::Reference(obj.id), None);
@@ -0,0 +1,178 @@
+//! Imported Object Extension Generation
+//!
+//! We don't own imported objects, so we can't add methods to them directly.
+//! What we can do is generate an extension trait that contains navigation
+//! methods for the relationships that _our_ domain formalizes against the
+//! imported object. Bring the trait into scope, and off you go.
+use std::{fmt::Write, sync::RwLock};
+
+use rustc_hash::FxHashMap as HashMap;
+use sarzak::{
+    lu_dog::store::ObjectStore as LuDogStore,
+    mc::{CompilerSnafu, FormatSnafu, Result},
+    v2::domain::Domain,
+    woog::{store::ObjectStore as WoogStore, types::Ownership},
+};
+use snafu::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    codegen::{
+        buffer::{emit, Buffer},
+        diff_engine::DirectiveKind,
+        generator::CodeWriter,
+        object_is_enum,
+        render::{RenderIdent, RenderType},
+    },
+    options::GraceConfig,
+    types::{
+        domain::rels::{generate_assoc_referent_rels, generate_binary_referent_rels, NavMethods},
+        TypeDefinition,
+    },
+};
+
+/// Imported Object Extension Trait Generator / CodeWriter
+///
+/// This generates a trait, `FooExt`, for an imported object `Foo`, as well as
+/// the implementation of that trait for `Foo`. The methods are exactly what
+/// we'd generate for a local object, modulo the `pub` keyword, and they all
+/// take our local store.
+///
+/// __NB__ --- If the imported domain generated an inherent method with the same
+/// name, the inherent method wins. Use the fully qualified syntax in that case.
+pub(crate) struct ImportedExt;
+
+impl ImportedExt {
+    pub(crate) fn new() -> Box<dyn TypeDefinition> {
+        Box::new(Self)
+    }
+}
+
+impl TypeDefinition for ImportedExt {}
+
+impl CodeWriter for ImportedExt {
+    fn write_code(
+        &self,
+        config: &GraceConfig,
+        domain: &Domain,
+        woog: &Option<&mut WoogStore>,
+        imports: &Option<&HashMap<String, Domain>>,
+        _lu_dog: &Option<&RwLock<LuDogStore>>,
+        _package: &str,
+        module: &str,
+        obj_id: Option<&Uuid>,
+        buffer: &mut Buffer,
+    ) -> Result<()> {
+        ensure!(
+            obj_id.is_some(),
+            CompilerSnafu {
+                description: "obj_id is required by ImportedExt"
+            }
+        );
+        let obj_id = obj_id.unwrap();
+        let obj = domain.sarzak().exhume_object(obj_id).unwrap();
+        ensure!(
+            woog.is_some(),
+            CompilerSnafu {
+                description: "woog is required by ImportedExt"
+            }
+        );
+        let woog = woog.as_ref().unwrap();
+        ensure!(
+            config.is_imported(obj_id),
+            CompilerSnafu {
+                description: format!("ImportedExt called on local object `{}`", obj.name)
+            }
+        );
+        let imported = config.get_imported(obj_id).unwrap();
+
+        let obj_ident = obj.as_ident();
+        let obj_type = obj.as_type(&Ownership::new_borrowed(), woog, domain);
+        let trait_name = format!("{obj_type}Ext");
+
+        let id = if object_is_enum(obj, config, imports, domain)? {
+            "id()"
+        } else {
+            "id"
+        };
+
+        // Render the implementation of the navigation methods into a scratch
+        // buffer. Along the way we collect their signatures for the trait
+        // definition, which has to come first.
+        let mut nav = NavMethods::Ext(Vec::new());
+        let mut methods = Buffer::new();
+        generate_binary_referent_rels(
+            &mut methods,
+            &mut nav,
+            config,
+            module,
+            obj,
+            id,
+            woog,
+            domain,
+        )?;
+        generate_assoc_referent_rels(
+            &mut methods,
+            &mut nav,
+            config,
+            module,
+            obj,
+            id,
+            woog,
+            domain,
+        )?;
+        let sigs = nav.into_methods();
+
+        buffer.block(
+            DirectiveKind::IgnoreOrig,
+            format!("{obj_ident}-imported-use-statements"),
+            |buffer| {
+                emit!(
+                    buffer,
+                    "use {}::types::{obj_ident}::{obj_type};",
+                    imported.domain
+                );
+                Ok(())
+            },
+        )?;
+        emit!(buffer, "");
+
+        buffer.block(
+            DirectiveKind::IgnoreOrig,
+            format!("{obj_ident}-ext-trait-definition"),
+            |buffer| {
+                emit!(
+                    buffer,
+                    "/// Relationship navigation from [`{obj_type}`] into `{module}`."
+                );
+                emit!(buffer, "///");
+                emit!(
+                    buffer,
+                    "/// [`{obj_type}`] is imported from `{}`.",
+                    imported.domain
+                );
+                emit!(buffer, "pub trait {trait_name} {{");
+                for method in &sigs {
+                    emit!(buffer, "/// {}", method.doc);
+                    emit!(buffer, "{};", method.sig);
+                }
+                emit!(buffer, "}}");
+
+                Ok(())
+            },
+        )?;
+        emit!(buffer, "");
+
+        buffer.block(
+            DirectiveKind::IgnoreOrig,
+            format!("{obj_ident}-ext-trait-implementation"),
+            |buffer| {
+                emit!(buffer, "impl {trait_name} for {obj_type} {{");
+                *buffer += methods;
+                emit!(buffer, "}}");
+
+                Ok(())
+            },
+        )
+    }
+}
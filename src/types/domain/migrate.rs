@@ -0,0 +1,918 @@
+//! Generate a Store Migration for a Domain
+//!
+//! This diffs the previous revision of the model against the current one, and
+//! generates code that upgrades a store persisted by the old revision. It works
+//! on the JSON directly, so the old revision's code isn't required.
+use std::{fmt::Write, sync::RwLock};
+
+use rustc_hash::FxHashMap as HashMap;
+use sarzak::{
+    lu_dog::store::ObjectStore as LuDogStore,
+    mc::{CompilerSnafu, FormatSnafu, Result},
+    sarzak::types::Object,
+    v2::domain::Domain,
+    woog::{store::ObjectStore as WoogStore, types::Ownership},
+};
+use snafu::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    codegen::{
+        buffer::{emit, Buffer},
+        diff_engine::DirectiveKind,
+        generator::{CodeWriter, FileGenerator, GenerationAction},
+        model_diff::{Field, FieldDiff, ModelDiff},
+        model_hash,
+        render::{RenderIdent, RenderType},
+    },
+    options::GraceConfig,
+    types::{domain::hybrid::SUBTYPE_ATTR, ObjectStoreDefinition},
+};
+
+pub(crate) struct DomainMigrateBuilder {
+    definition: Option<Box<dyn ObjectStoreDefinition>>,
+}
+
+impl DomainMigrateBuilder {
+    pub(crate) fn new() -> Self {
+        Self { definition: None }
+    }
+
+    pub(crate) fn definition(mut self, definition: Box<dyn ObjectStoreDefinition>) -> Self {
+        self.definition = Some(definition);
+
+        self
+    }
+
+    pub(crate) fn build(self) -> Result<Box<DomainMigrateGenerator>> {
+        ensure!(
+            self.definition.is_some(),
+            CompilerSnafu {
+                description: "DomainMigrateBuilder::build called before definition".to_owned()
+            }
+        );
+
+        Ok(Box::new(DomainMigrateGenerator {
+            definition: self.definition.unwrap(),
+        }))
+    }
+}
+
+pub(crate) struct DomainMigrateGenerator {
+    definition: Box<dyn ObjectStoreDefinition>,
+}
+
+impl FileGenerator for DomainMigrateGenerator {
+    fn generate(
+        &self,
+        config: &GraceConfig,
+        domain: &Domain,
+        woog: &Option<&mut WoogStore>,
+        imports: &Option<&HashMap<String, Domain>>,
+        lu_dog: &Option<&RwLock<LuDogStore>>,
+        package: &str,
+        module: &str,
+        obj_id: Option<&Uuid>,
+        buffer: &mut Buffer,
+    ) -> Result<GenerationAction> {
+        // Output the domain/module documentation/description
+        emit!(buffer, "//! {} Store Migration", module);
+        emit!(buffer, "//!");
+        emit!(
+            buffer,
+            "//! Upgrade a store persisted by the previous revision of the domain: _{}_.",
+            domain.name()
+        );
+        emit!(
+            buffer,
+            "//! Objects and attributes were matched by id, so renames are handled. The"
+        );
+        emit!(
+            buffer,
+            "//! hooks at the bottom are called for anything that needs a decision, and"
+        );
+        emit!(buffer, "//! they are yours to edit.");
+
+        buffer.block(
+            DirectiveKind::AllowEditing,
+            format!("{}-migrate-file", module),
+            |buffer| {
+                self.definition.write_code(
+                    config, domain, woog, imports, lu_dog, package, module, obj_id, buffer,
+                )?;
+
+                Ok(())
+            },
+        )?;
+
+        Ok(GenerationAction::FormatWrite)
+    }
+}
+
+/// Store Migration Generator / CodeWriter
+///
+/// This one is a little different in that it carries the previous revision of
+/// the model around with it. It gets it's own config, because the annotations
+/// in the object descriptions may have changed.
+pub(crate) struct DomainMigrateImpl {
+    from_config: GraceConfig,
+    from_domain: Domain,
+}
+
+impl DomainMigrateImpl {
+    pub(crate) fn new(
+        from_config: GraceConfig,
+        from_domain: Domain,
+    ) -> Box<dyn ObjectStoreDefinition> {
+        Box::new(Self {
+            from_config,
+            from_domain,
+        })
+    }
+}
+
+impl ObjectStoreDefinition for DomainMigrateImpl {}
+
+impl CodeWriter for DomainMigrateImpl {
+    fn write_code(
+        &self,
+        config: &GraceConfig,
+        domain: &Domain,
+        woog: &Option<&mut WoogStore>,
+        _imports: &Option<&HashMap<String, Domain>>,
+        _lu_dog: &Option<&RwLock<LuDogStore>>,
+        _package: &str,
+        module: &str,
+        _obj_id: Option<&Uuid>,
+        buffer: &mut Buffer,
+    ) -> Result<()> {
+        ensure!(
+            woog.is_some(),
+            CompilerSnafu {
+                description: "woog is required by DomainMigrateImpl"
+            }
+        );
+        let woog = woog.as_ref().unwrap();
+
+        let diff = ModelDiff::new(&self.from_config, &self.from_domain, config, domain);
+        // The type names are what end up in the JSON, as enum variants. Objects
+        // render their type from the name alone, so the old domain may use the
+        // new woog.
+        let type_name =
+            |obj: &Object, domain: &Domain| obj.as_type(&Ownership::new_borrowed(), woog, domain);
+
+        // Only emit the helpers that get used, so as not to litter the domain
+        // with warnings.
+        let needs_rename = diff.kept.iter().any(|obj| {
+            obj.fields.iter().any(|field| match field {
+                FieldDiff::Kept { old, new } | FieldDiff::Retyped { old, new } => {
+                    old.name != new.name
+                }
+                _ => false,
+            })
+        });
+        let needs_map = diff
+            .kept
+            .iter()
+            .any(|obj| !obj.is_enum && !obj.is_unchanged());
+        let needs_variants = diff.kept.iter().any(|obj| obj.variants_changed());
+        // Tombstones only exist when the store has timestamps.
+        let tombstones = config.get_persist_timestamps();
+
+        buffer.block(
+            DirectiveKind::IgnoreOrig,
+            format!("{}-migrate-definition", module),
+            |buffer| {
+                if config.get_persist() {
+                    emit!(buffer, "use std::{{fs, io::{{self, BufRead}}, path::Path}};");
+                } else {
+                    emit!(buffer, "use std::{{fs, io, path::Path}};");
+                }
+                emit!(buffer, "");
+                if needs_map || tombstones {
+                    emit!(buffer, "use serde_json::{{Map, Value}};");
+                } else {
+                    emit!(buffer, "use serde_json::Value;");
+                }
+                emit!(buffer, "");
+                emit!(
+                    buffer,
+                    "/// The revision of the model that this migrates from"
+                );
+                emit!(
+                    buffer,
+                    "pub const FROM_MODEL_HASH: &str = \"{}\";",
+                    model_hash(&self.from_domain).simple()
+                );
+                emit!(buffer, "/// The revision of the model that this migrates to");
+                emit!(
+                    buffer,
+                    "pub const TO_MODEL_HASH: &str = \"{}\";",
+                    model_hash(domain).simple()
+                );
+                emit!(buffer, "");
+
+                emit!(buffer, "/// Migrate a persisted store");
+                emit!(buffer, "///");
+                emit!(
+                    buffer,
+                    "/// `from` is the directory that the old store was persisted to, and `to`"
+                );
+                emit!(
+                    buffer,
+                    "/// is where the migrated store is written. Load the result with"
+                );
+                emit!(buffer, "/// `ObjectStore::load(to)`.");
+                if diff.is_empty() {
+                    emit!(buffer, "///");
+                    emit!(
+                        buffer,
+                        "/// The two revisions store the same things, so this is just a copy."
+                    );
+                }
+                emit!(
+                    buffer,
+                    "pub fn migrate<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> io::Result<()> {{"
+                );
+                emit!(
+                    buffer,
+                    "let from = from.as_ref().join(\"{}.json\");",
+                    self.from_domain.name()
+                );
+                emit!(
+                    buffer,
+                    "let to = to.as_ref().join(\"{}.json\");",
+                    domain.name()
+                );
+                emit!(buffer, "");
+
+                for obj in &diff.kept {
+                    let old_ident = obj.old.as_ident();
+                    let new_ident = obj.new.as_ident();
+
+                    if obj.is_renamed() {
+                        emit!(buffer, "// {}, which was {}.", obj.new.name, obj.old.name);
+                    } else {
+                        emit!(buffer, "// {}.", obj.new.name);
+                    }
+                    emit!(buffer, "fs::create_dir_all(to.join(\"{new_ident}\"))?;");
+                    emit!(
+                        buffer,
+                        "for (id, instance) in read_instances(&from.join(\"{old_ident}\"))? {{"
+                    );
+
+                    if obj.variants_changed() {
+                        let renames = obj
+                            .renamed_subtypes()
+                            .iter()
+                            .map(|(old, new)| {
+                                format!(
+                                    "(\"{}\", \"{}\")",
+                                    type_name(old, &self.from_domain),
+                                    type_name(new, domain)
+                                )
+                            })
+                            .collect::<Vec<_>>();
+                        let dropped = obj
+                            .dropped_subtypes
+                            .iter()
+                            .map(|old| format!("\"{}\"", type_name(old, &self.from_domain)))
+                            .collect::<Vec<_>>();
+                        let field = if obj.is_enum {
+                            "None".to_owned()
+                        } else {
+                            format!("Some(\"{SUBTYPE_ATTR}\")")
+                        };
+                        emit!(
+                            buffer,
+                            "let instance = rename_variant(instance, {field}, &[{}], &[{}])?;",
+                            renames.join(", "),
+                            dropped.join(", ")
+                        );
+                    }
+
+                    if !obj.is_enum && !obj.is_unchanged() {
+                        emit!(
+                            buffer,
+                            "let instance = map_instance(instance, |fields| {{"
+                        );
+                        emit_field_migration(&new_ident, &obj.fields, buffer)?;
+                        emit!(buffer, "Ok(())");
+                        emit!(buffer, "}})?;");
+                    }
+
+                    emit!(
+                        buffer,
+                        "write_instance(&to.join(\"{new_ident}\"), &id, &instance)?;"
+                    );
+                    emit!(buffer, "}}");
+                    emit!(buffer, "");
+                }
+
+                for obj in &diff.added {
+                    emit!(buffer, "// {} is new.", obj.name);
+                    emit!(buffer, "fs::create_dir_all(to.join(\"{}\"))?;", obj.as_ident());
+                    emit!(buffer, "");
+                }
+
+                for obj in &diff.dropped {
+                    let old_ident = obj.as_ident();
+                    emit!(buffer, "// {} was dropped.", obj.name);
+                    emit!(
+                        buffer,
+                        "for (id, instance) in read_instances(&from.join(\"{old_ident}\"))? {{"
+                    );
+                    emit!(buffer, "dropped_{old_ident}(&id, instance, &to)?;");
+                    emit!(buffer, "}}");
+                    emit!(buffer, "");
+                }
+
+                if tombstones {
+                    let mut renames = diff
+                        .kept
+                        .iter()
+                        .filter(|obj| obj.is_renamed())
+                        .map(|obj| {
+                            format!(
+                                "(\"{}\", \"{}\")",
+                                type_name(obj.old, &self.from_domain),
+                                type_name(obj.new, domain)
+                            )
+                        })
+                        .collect::<Vec<_>>();
+                    renames.sort();
+                    let dropped = diff
+                        .dropped
+                        .iter()
+                        .map(|obj| format!("\"{}\"", type_name(obj, &self.from_domain)))
+                        .collect::<Vec<_>>();
+
+                    emit!(
+                        buffer,
+                        "// The tombstones are keyed by the kind of thing that was exorcised."
+                    );
+                    emit!(
+                        buffer,
+                        "let tombstones = from.join(\"tombstones.json\");"
+                    );
+                    emit!(buffer, "if tombstones.exists() {{");
+                    emit!(
+                        buffer,
+                        "migrate_tombstones(&tombstones, &to.join(\"tombstones.json\"), &[{}], &[{}])?;",
+                        renames.join(", "),
+                        dropped.join(", ")
+                    );
+                    emit!(buffer, "}}");
+                    emit!(buffer, "");
+                }
+
+                if config.get_persist() {
+                    emit!(buffer, "// The migrated store is current.");
+                    emit!(
+                        buffer,
+                        "let file = fs::File::create(to.join(\"header.json\"))?;"
+                    );
+                    emit!(
+                        buffer,
+                        "serde_json::to_writer_pretty(io::BufWriter::new(file), &super::store::StoreHeader::current())?;"
+                    );
+                    emit!(buffer, "");
+                }
+
+                emit!(buffer, "Ok(())");
+                emit!(buffer, "}}");
+                emit!(buffer, "");
+
+                if config.get_persist() {
+                    let mut from_objects = diff
+                        .kept
+                        .iter()
+                        .map(|obj| obj.old)
+                        .chain(diff.dropped.iter().cloned())
+                        .map(|obj| {
+                            format!(
+                                "(\"{}\", \"{}\")",
+                                obj.as_ident(),
+                                type_name(obj, &self.from_domain)
+                            )
+                        })
+                        .collect::<Vec<_>>();
+                    from_objects.sort();
+                    emit_explode(&self.from_domain, &from_objects, buffer)?;
+                }
+
+                emit_helpers(needs_rename, needs_map, needs_variants, tombstones, buffer)?;
+
+                Ok(())
+            },
+        )?;
+        emit!(buffer, "");
+
+        //
+        // The hooks go outside of the generated block, so that edits survive.
+        //
+        for obj in &diff.kept {
+            let obj_ident = obj.new.as_ident();
+            for field in &obj.fields {
+                match field {
+                    FieldDiff::Added(new) if !new.optional => {
+                        emit!(
+                            buffer,
+                            "/// The value of the new `{}` field of `{}`",
+                            new.name,
+                            obj.new.name
+                        );
+                        emit!(buffer, "///");
+                        emit!(
+                            buffer,
+                            "/// `fields` are the rest of the instance, already migrated."
+                        );
+                        emit!(
+                            buffer,
+                            "fn add_{obj_ident}_{}(_fields: &Map<String, Value>) -> io::Result<Value> {{",
+                            new.name
+                        );
+                        emit!(buffer, "{}", default_value(new, &obj.new.name));
+                        emit!(buffer, "}}");
+                        emit!(buffer, "");
+                    }
+                    FieldDiff::Retyped { old, new } => {
+                        emit!(
+                            buffer,
+                            "/// Convert the `{}` field of `{}` from `{}` to `{}`",
+                            new.name,
+                            obj.new.name,
+                            old.type_name(),
+                            new.type_name()
+                        );
+                        emit!(
+                            buffer,
+                            "fn convert_{obj_ident}_{}(value: Value) -> io::Result<Value> {{",
+                            new.name
+                        );
+                        emit!(buffer, "{}", convert_value(old, new, &obj.new.name));
+                        emit!(buffer, "}}");
+                        emit!(buffer, "");
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        for obj in &diff.dropped {
+            emit!(
+                buffer,
+                "/// Called for each instance of `{}`, which was dropped from the model",
+                obj.name
+            );
+            emit!(buffer, "///");
+            emit!(
+                buffer,
+                "/// The instance is discarded, unless you do something with it here. `to`"
+            );
+            emit!(
+                buffer,
+                "/// is the directory that the new store is being written to."
+            );
+            emit!(
+                buffer,
+                "fn dropped_{}(_id: &str, _instance: Value, _to: &Path) -> io::Result<()> {{",
+                obj.as_ident()
+            );
+            emit!(buffer, "Ok(())");
+            emit!(buffer, "}}");
+            emit!(buffer, "");
+        }
+
+        Ok(())
+    }
+}
+
+/// Migrate the fields of an instance, inside of the `map_instance` closure
+///
+/// Dropped fields go first, then renames, so that a new field can take an old
+/// name. Then conversions, and last additions, so that the add hooks see the
+/// migrated instance.
+fn emit_field_migration(obj_ident: &str, fields: &[FieldDiff], buffer: &mut Buffer) -> Result<()> {
+    for field in fields {
+        if let FieldDiff::Dropped(old) = field {
+            emit!(buffer, "fields.remove(\"{}\");", old.name);
+        }
+    }
+
+    let renames = fields
+        .iter()
+        .filter_map(|field| match field {
+            FieldDiff::Kept { old, new } | FieldDiff::Retyped { old, new }
+                if old.name != new.name =>
+            {
+                Some(format!("(\"{}\", \"{}\")", old.name, new.name))
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if !renames.is_empty() {
+        emit!(buffer, "rename_fields(fields, &[{}]);", renames.join(", "));
+    }
+
+    for field in fields {
+        if let FieldDiff::Retyped { new, .. } = field {
+            let name = &new.name;
+            emit!(
+                buffer,
+                "let value = fields.remove(\"{name}\").unwrap_or(Value::Null);"
+            );
+            emit!(
+                buffer,
+                "fields.insert(\"{name}\".to_owned(), convert_{obj_ident}_{name}(value)?);"
+            );
+        }
+    }
+
+    for field in fields {
+        if let FieldDiff::Added(new) = field {
+            let name = &new.name;
+            if new.optional {
+                emit!(buffer, "fields.insert(\"{name}\".to_owned(), Value::Null);");
+            } else {
+                emit!(buffer, "let value = add_{obj_ident}_{name}(fields)?;");
+                emit!(buffer, "fields.insert(\"{name}\".to_owned(), value);");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Emit the functions that turn the single file formats into a directory
+///
+/// [`migrate`] works on a store directory, so the JSON file and JSON Lines
+/// formats are written out as one first. `from_objects` are the objects in the
+/// previous revision's store, as `("directory", "Type")`.
+fn emit_explode(from_domain: &Domain, from_objects: &[String], buffer: &mut Buffer) -> Result<()> {
+    emit!(
+        buffer,
+        "/// The objects in the previous revision's store, as (directory, type)"
+    );
+    emit!(
+        buffer,
+        "const FROM_OBJECTS: &[(&str, &str)] = &[{}];",
+        from_objects.join(", ")
+    );
+    emit!(buffer, "");
+    emit!(
+        buffer,
+        r#"/// Write the `store` of a document persisted by `persist_json_file` as a directory
+///
+/// The directory goes in `to`, ready for [`migrate`].
+pub fn explode_json_file(store: Value, to: &Path) -> io::Result<()> {{
+    let to = to.join("{}.json");
+    let mut store = match store {{
+        Value::Object(store) => store,
+        _ => {{
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the store isn't an object",
+            ))
+        }}
+    }};
+
+    for (ident, _) in FROM_OBJECTS {{
+        let dir = to.join(ident);
+        fs::create_dir_all(&dir)?;
+        match store.remove(*ident) {{
+            Some(Value::Object(instances)) => {{
+                for (id, instance) in instances {{
+                    write_instance(&dir, &id, &instance)?;
+                }}
+            }}
+            // The Vec store is indexed by position.
+            Some(Value::Array(instances)) => {{
+                for (id, instance) in instances.iter().enumerate() {{
+                    if !instance.is_null() {{
+                        write_instance(&dir, &id.to_string(), instance)?;
+                    }}
+                }}
+            }}
+            _ => {{}}
+        }}
+    }}
+
+    if let Some(tombstones) = store.remove("tombstones") {{
+        let file = fs::File::create(to.join("tombstones.json"))?;
+        serde_json::to_writer_pretty(io::BufWriter::new(file), &tombstones)?;
+    }}
+
+    Ok(())
+}}
+
+/// Write a store persisted by `persist_jsonl` as a directory
+///
+/// The directory goes in `to`, ready for [`migrate`]. A later record for an
+/// instance replaces an earlier one, just like when it's loaded. `Tombstone`
+/// records are gathered up into `tombstones.json`.
+pub fn explode_jsonl(from: &Path, to: &Path) -> io::Result<()> {{
+    let to = to.join("{}.json");
+    for (ident, _) in FROM_OBJECTS {{
+        fs::create_dir_all(to.join(ident))?;
+    }}
+
+    let mut tombstones = serde_json::Map::new();
+    let file = fs::File::open(from)?;
+    for line in io::BufReader::new(file).lines() {{
+        let line = line?;
+        if line.trim().is_empty() {{
+            continue;
+        }}
+        let record: Value = serde_json::from_str(&line)?;
+        let ty = record["type"].as_str().unwrap_or_default();
+        if ty == "StoreHeader" {{
+            continue;
+        }}
+        if ty == "Tombstone" {{
+            // An `(id, (kind, timestamp))` tuple.
+            match &record["instance"] {{
+                Value::Array(tuple) if tuple.len() == 2 => {{
+                    let id = tuple[0].as_str().unwrap_or_default().to_owned();
+                    tombstones.insert(id, tuple[1].clone());
+                }}
+                instance => {{
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("malformed tombstone: {{instance}}"),
+                    ))
+                }}
+            }}
+            continue;
+        }}
+        match FROM_OBJECTS.iter().find(|(_, from_ty)| *from_ty == ty) {{
+            Some((ident, _)) => {{
+                let instance = &record["instance"];
+                write_instance(&to.join(ident), &instance_id(instance)?, instance)?;
+            }}
+            None => {{
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("unknown type in store: {{ty}}"),
+                ))
+            }}
+        }}
+    }}
+
+    if !tombstones.is_empty() {{
+        let file = fs::File::create(to.join("tombstones.json"))?;
+        serde_json::to_writer_pretty(io::BufWriter::new(file), &tombstones)?;
+    }}
+
+    Ok(())
+}}
+
+/// The id of an instance in a JSON Lines record
+///
+/// Structs have an `id` field, and enums hold the id as their only value.
+/// Stores persisted with timestamps hold `(instance, timestamp)` tuples.
+fn instance_id(instance: &Value) -> io::Result<String> {{
+    let inner = match instance {{
+        Value::Array(tuple) => tuple.first().unwrap_or(&Value::Null),
+        instance => instance,
+    }};
+    let id = match inner {{
+        Value::Object(fields) if fields.contains_key("id") => &fields["id"],
+        Value::Object(fields) if fields.len() == 1 => fields.values().next().unwrap(),
+        _ => &Value::Null,
+    }};
+
+    match id {{
+        Value::String(id) => Ok(id.clone()),
+        Value::Number(id) => Ok(id.to_string()),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("unable to find the id of {{instance}}"),
+        )),
+    }}
+}}
+"#,
+        from_domain.name(),
+        from_domain.name()
+    );
+
+    Ok(())
+}
+
+fn emit_helpers(
+    needs_rename: bool,
+    needs_map: bool,
+    needs_variants: bool,
+    tombstones: bool,
+    buffer: &mut Buffer,
+) -> Result<()> {
+    emit!(
+        buffer,
+        r#"/// Read every instance in an object's directory, keyed by it's file name
+fn read_instances(path: &Path) -> io::Result<Vec<(String, Value)>> {{
+    let mut result = Vec::new();
+    if !path.exists() {{
+        return Ok(result);
+    }}
+
+    for entry in fs::read_dir(path)? {{
+        let path = entry?.path();
+        let id = match path.file_stem().and_then(|stem| stem.to_str()) {{
+            Some(id) => id.to_owned(),
+            None => continue,
+        }};
+        let file = fs::File::open(&path)?;
+        let reader = io::BufReader::new(file);
+        result.push((id, serde_json::from_reader(reader)?));
+    }}
+
+    Ok(result)
+}}
+
+fn write_instance(path: &Path, id: &str, instance: &Value) -> io::Result<()> {{
+    let file = fs::File::create(path.join(format!("{{}}.json", id)))?;
+    let mut writer = io::BufWriter::new(file);
+    serde_json::to_writer_pretty(&mut writer, instance)?;
+
+    Ok(())
+}}
+"#
+    );
+
+    if needs_map {
+        emit!(
+            buffer,
+            r#"/// Apply `f` to the fields of an instance
+///
+/// Stores persisted with timestamps hold `(instance, timestamp)` tuples, so we
+/// look inside of those too.
+fn map_instance<F>(mut instance: Value, f: F) -> io::Result<Value>
+where
+    F: FnOnce(&mut Map<String, Value>) -> io::Result<()>,
+{{
+    match instance {{
+        Value::Object(ref mut fields) => f(fields)?,
+        Value::Array(ref mut tuple) => {{
+            if let Some(Value::Object(fields)) = tuple.first_mut() {{
+                f(fields)?
+            }}
+        }}
+        _ => {{}}
+    }}
+
+    Ok(instance)
+}}
+"#
+        );
+    }
+
+    if needs_variants {
+        emit!(
+            buffer,
+            r#"/// Rename the subtype variant of an enum, or of a hybrid's `field`
+///
+/// Variants are persisted as `{{"Name": value}}`, or just `"Name"`. Stores
+/// persisted with timestamps hold `(instance, timestamp)` tuples, so we look
+/// inside of those too. There's nothing sensible to do with a variant that
+/// was dropped, so that's an error.
+fn rename_variant(
+    mut instance: Value,
+    field: Option<&str>,
+    renames: &[(&str, &str)],
+    dropped: &[&str],
+) -> io::Result<Value> {{
+    let mut value = match instance {{
+        Value::Array(ref mut tuple) => tuple.first_mut(),
+        ref mut value => Some(value),
+    }};
+    if let Some(field) = field {{
+        value = match value {{
+            Some(Value::Object(fields)) => fields.get_mut(field),
+            _ => None,
+        }};
+    }}
+
+    let name = match value {{
+        Some(Value::Object(ref variant)) => variant.keys().next().cloned(),
+        Some(Value::String(ref name)) => Some(name.clone()),
+        _ => None,
+    }};
+    if let (Some(name), Some(value)) = (name, value) {{
+        if dropped.contains(&name.as_str()) {{
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("`{{name}}` is no longer a subtype"),
+            ));
+        }}
+        if let Some((_, to)) = renames.iter().find(|(from, _)| *from == name) {{
+            match value {{
+                Value::Object(variant) => {{
+                    let inner = variant.remove(&name).unwrap_or(Value::Null);
+                    variant.insert(to.to_string(), inner);
+                }}
+                value => *value = Value::from(*to),
+            }}
+        }}
+    }}
+
+    Ok(instance)
+}}
+"#
+        );
+    }
+
+    if tombstones {
+        emit!(
+            buffer,
+            r#"/// Rename the kinds in the tombstones, and forget the dropped ones
+///
+/// The tombstones map an id to a `(kind, timestamp)` tuple.
+fn migrate_tombstones(
+    from: &Path,
+    to: &Path,
+    renames: &[(&str, &str)],
+    dropped: &[&str],
+) -> io::Result<()> {{
+    let file = fs::File::open(from)?;
+    let tombstones: Map<String, Value> = serde_json::from_reader(io::BufReader::new(file))?;
+
+    let mut result = Map::new();
+    for (id, mut tombstone) in tombstones {{
+        if let Some(Value::String(kind)) = tombstone.get_mut(0) {{
+            if dropped.contains(&kind.as_str()) {{
+                continue;
+            }}
+            if let Some((_, to)) = renames.iter().find(|(from, _)| from == kind) {{
+                *kind = to.to_string();
+            }}
+        }}
+        result.insert(id, tombstone);
+    }}
+
+    let file = fs::File::create(to)?;
+    serde_json::to_writer_pretty(io::BufWriter::new(file), &result)?;
+
+    Ok(())
+}}
+"#
+        );
+    }
+
+    if needs_rename {
+        emit!(
+            buffer,
+            r#"/// Rename fields, all at once, so that names may be swapped
+fn rename_fields(fields: &mut Map<String, Value>, renames: &[(&str, &str)]) {{
+    let values = renames
+        .iter()
+        .map(|(from, to)| (to, fields.remove(*from)))
+        .collect::<Vec<_>>();
+    for (to, value) in values {{
+        if let Some(value) = value {{
+            fields.insert(to.to_string(), value);
+        }}
+    }}
+}}
+"#
+        );
+    }
+
+    Ok(())
+}
+
+/// The body of an add hook
+///
+/// Plain types get a zero value. Anything else needs a human, and until one
+/// comes along the migration fails.
+fn default_value(field: &Field, obj_name: &str) -> String {
+    match field.ty.as_str() {
+        "bool" => "Ok(Value::from(false))".to_owned(),
+        "i64" => "Ok(Value::from(0))".to_owned(),
+        "f64" => "Ok(Value::from(0.0))".to_owned(),
+        "String" => "Ok(Value::from(\"\"))".to_owned(),
+        _ => format!(
+            "Err(io::Error::new(io::ErrorKind::InvalidData, \"no value for the new `{}` field of `{obj_name}`\"))",
+            field.name
+        ),
+    }
+}
+
+/// The body of a conversion hook
+///
+/// Some conversions are obvious, the rest need a human, and until one comes
+/// along the migration fails.
+fn convert_value(old: &Field, new: &Field, obj_name: &str) -> String {
+    match (old.ty.as_str(), new.ty.as_str()) {
+        // Becoming optional is fine, and null is the only other option.
+        (old_ty, new_ty) if old_ty == new_ty && new.optional => "Ok(value)".to_owned(),
+        ("i64", "f64") => "Ok(Value::from(value.as_f64().unwrap_or_default()))".to_owned(),
+        (_, "String") => {
+            "Ok(Value::from(value.as_str().map(str::to_owned).unwrap_or(value.to_string())))"
+                .to_owned()
+        }
+        _ => format!(
+            "Err(io::Error::new(io::ErrorKind::InvalidData, format!(\"unable to convert the `{}` field of `{obj_name}`: {{}}\", value)))",
+            new.name
+        ),
+    }
+}
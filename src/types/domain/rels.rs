@@ -22,6 +22,47 @@ use crate::{
     options::{GraceConfig, UberStoreOptions},
 };
 
+/// Where the referent side navigation methods end up
+///
+/// Local objects get them as inherent methods. Imported objects get them as the
+/// implementation of an extension trait, so they aren't `pub`, and we keep the
+/// documentation and signature of each one to declare the trait with.
+pub(crate) enum NavMethods {
+    Inherent,
+    Ext(Vec<NavMethod>),
+}
+
+pub(crate) struct NavMethod {
+    pub(crate) doc: String,
+    pub(crate) sig: String,
+}
+
+impl NavMethods {
+    pub(crate) fn into_methods(self) -> Vec<NavMethod> {
+        match self {
+            NavMethods::Inherent => Vec::new(),
+            NavMethods::Ext(methods) => methods,
+        }
+    }
+
+    /// Emit the doc comment and signature of a method, leaving the body open
+    fn emit_method(&mut self, buffer: &mut Buffer, doc: &str, sig: String) -> Result<()> {
+        emit!(buffer, "/// {doc}");
+        match self {
+            NavMethods::Inherent => emit!(buffer, "pub {sig} {{"),
+            NavMethods::Ext(methods) => {
+                emit!(buffer, "{sig} {{");
+                methods.push(NavMethod {
+                    doc: doc.to_owned(),
+                    sig,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
 pub(crate) fn generate_binary_referrer_rels(
     buffer: &mut Buffer,
     config: &GraceConfig,
@@ -65,6 +106,7 @@ pub(crate) fn generate_binary_referrer_rels(
 
 pub(crate) fn generate_binary_referent_rels(
     buffer: &mut Buffer,
+    nav: &mut NavMethods,
     config: &GraceConfig,
     module: &str,
     obj: &Object,
@@ -97,14 +139,14 @@ pub(crate) fn generate_binary_referent_rels(
         match card {
             Cardinality::One(_) => match my_cond {
                 Conditionality::Unconditional(_) => backward_one(
-                    buffer, obj, r_obj, id, binary, store, referrer, config, woog, domain,
+                    buffer, nav, obj, r_obj, id, binary, store, referrer, config, woog, domain,
                 )?,
                 Conditionality::Conditional(_) => match other_cond {
                     Conditionality::Unconditional(_) => backward_one_conditional(
-                        buffer, obj, r_obj, id, binary, store, referrer, config, woog, domain,
+                        buffer, nav, obj, r_obj, id, binary, store, referrer, config, woog, domain,
                     )?,
                     Conditionality::Conditional(_) => backward_one_biconditional(
-                        buffer, obj, r_obj, id, binary, store, referrer, config, woog, domain,
+                        buffer, nav, obj, r_obj, id, binary, store, referrer, config, woog, domain,
                     )?,
                 },
             },
@@ -112,10 +154,10 @@ pub(crate) fn generate_binary_referent_rels(
             // that neither of them depend on the conditionality of this side.
             Cardinality::Many(_) => match other_cond {
                 Conditionality::Unconditional(_) => backward_1_m(
-                    buffer, obj, r_obj, id, binary, store, referrer, config, woog, domain,
+                    buffer, nav, obj, r_obj, id, binary, store, referrer, config, woog, domain,
                 )?,
                 Conditionality::Conditional(_) => backward_1_mc(
-                    buffer, obj, r_obj, id, binary, store, referrer, config, woog, domain,
+                    buffer, nav, obj, r_obj, id, binary, store, referrer, config, woog, domain,
                 )?,
             },
         }
@@ -169,6 +211,7 @@ pub(crate) fn generate_assoc_referrer_rels(
 
 pub(crate) fn generate_assoc_referent_rels(
     buffer: &mut Buffer,
+    nav: &mut NavMethods,
     config: &GraceConfig,
     module: &str,
     obj: &Object,
@@ -201,6 +244,7 @@ pub(crate) fn generate_assoc_referent_rels(
             Cardinality::One(_) => match cond {
                 Conditionality::Conditional(_) => backward_assoc_one_conditional(
                     buffer,
+                    nav,
                     obj,
                     r_obj,
                     id,
@@ -213,6 +257,7 @@ pub(crate) fn generate_assoc_referent_rels(
                 )?,
                 Conditionality::Unconditional(_) => backward_assoc_one(
                     buffer,
+                    nav,
                     obj,
                     r_obj,
                     id,
@@ -226,6 +271,7 @@ pub(crate) fn generate_assoc_referent_rels(
             },
             Cardinality::Many(_) => backward_assoc_many(
                 buffer,
+                nav,
                 obj,
                 r_obj,
                 id,
@@ -491,6 +537,7 @@ fn forward_conditional(
 
 fn backward_one(
     buffer: &mut Buffer,
+    nav: &mut NavMethods,
     obj: &Object,
     r_obj: &Object,
     id: &str,
@@ -522,9 +569,8 @@ fn backward_one(
             obj.as_ident(),
             ),
         |buffer| {
-            emit!(
-                buffer,
-                "/// Navigate to [`{}`] across R{}(1-1)",
+            let doc = format!(
+                "Navigate to [`{}`] across R{}(1-1)",
                 r_obj.as_type(&Ownership::new_borrowed(), woog, domain),
                 binary.number
             );
@@ -533,12 +579,15 @@ fn backward_one(
                 let store_type = get_value_wrapper(is_imported, config, r_obj, woog, domain);
                 let (read, _write) = get_uber_read_write(config);
                 if let UberStoreOptions::AsyncRwLock = config.get_uber_store().unwrap() {
-                    emit!(
+                    nav.emit_method(
                         buffer,
-                        "pub async fn r{}_{obj_ident}<'a>(&'a self, store: &'a {}) -> impl futures::Stream<Item = {store_type}> + '_ {{",
-                        binary.number,
-                        store.name
-                    );
+                        &doc,
+                        format!(
+                            "async fn r{}_{obj_ident}<'a>(&'a self, store: &'a {}) -> impl futures::Stream<Item = {store_type}> + '_",
+                            binary.number,
+                            store.name
+                        ),
+                    )?;
 
                     if config.get_tracy() {
                         emit!(
@@ -562,12 +611,15 @@ fn backward_one(
                     emit!(buffer, "}}");
                     emit!(buffer, "}})");
                 } else {
-                    emit!(
+                    nav.emit_method(
                         buffer,
-                        "pub fn r{}_{obj_ident}<'a>(&'a self, store: &'a {}) -> Vec<{store_type}> {{",
-                        binary.number,
-                        store.name
-                    );
+                        &doc,
+                        format!(
+                            "fn r{}_{obj_ident}<'a>(&'a self, store: &'a {}) -> Vec<{store_type}>",
+                            binary.number,
+                            store.name
+                        ),
+                    )?;
                     if config.get_tracy() {
                         emit!(
                             buffer,
@@ -585,13 +637,16 @@ fn backward_one(
             }
 
             } else {
-                emit!(
+                nav.emit_method(
                     buffer,
-                    "pub fn r{}_{obj_ident}<'a>(&'a self, store: &'a {}) -> Vec<&{}> {{",
-                    binary.number,
-                    store.name,
-                    r_obj.as_type(&Ownership::new_borrowed(), woog, domain)
-                );
+                    &doc,
+                    format!(
+                        "fn r{}_{obj_ident}<'a>(&'a self, store: &'a {}) -> Vec<&{}>",
+                        binary.number,
+                        store.name,
+                        r_obj.as_type(&Ownership::new_borrowed(), woog, domain)
+                    ),
+                )?;
                 emit!(buffer, "vec![store.iter_{}()", r_obj.as_ident());
                 emit!(
                     buffer,
@@ -610,6 +665,7 @@ fn backward_one(
 
 fn backward_one_conditional(
     buffer: &mut Buffer,
+    nav: &mut NavMethods,
     obj: &Object,
     r_obj: &Object,
     id: &str,
@@ -632,9 +688,8 @@ fn backward_one_conditional(
             obj.as_ident(),
             ),
         |buffer| {
-            emit!(
-                buffer,
-                "/// Navigate to [`{}`] across R{}(1-1c)",
+            let doc = format!(
+                "Navigate to [`{}`] across R{}(1-1c)",
                 r_obj.as_type(&Ownership::new_borrowed(), woog, domain),
                 binary.number
             );
@@ -642,12 +697,15 @@ fn backward_one_conditional(
             if is_uber {
                 let store_type = get_value_wrapper(is_imported, config, r_obj, woog, domain);
                 if let UberStoreOptions::AsyncRwLock = config.get_uber_store().unwrap() {
-                    emit!(
+                    nav.emit_method(
                         buffer,
-                        "pub async fn r{}c_{obj_ident}<'a>(&'a self, store: &'a {}) -> Vec<{store_type}> {{",
-                        binary.number,
-                        store.name
-                    );
+                        &doc,
+                        format!(
+                            "async fn r{}c_{obj_ident}<'a>(&'a self, store: &'a {}) -> Vec<{store_type}>",
+                            binary.number,
+                            store.name
+                        ),
+                    )?;
                     emit!(
                         buffer,
                         "store.iter_{obj_ident}().await.filter_map(|{obj_ident}| async {{"
@@ -663,12 +721,15 @@ fn backward_one_conditional(
                     emit!(buffer, "}}");
                     emit!(buffer, "}}).collect().await");
                 } else {
-                    emit!(
+                    nav.emit_method(
                         buffer,
-                        "pub fn r{}c_{obj_ident}<'a>(&'a self, store: &'a {}) -> Vec<{store_type}> {{",
-                        binary.number,
-                        store.name
-                    );
+                        &doc,
+                        format!(
+                            "fn r{}c_{obj_ident}<'a>(&'a self, store: &'a {}) -> Vec<{store_type}>",
+                            binary.number,
+                            store.name
+                        ),
+                    )?;
                     if config.get_tracy() {
                         emit!(
                             buffer,
@@ -695,13 +756,16 @@ fn backward_one_conditional(
                     emit!(buffer, "}}");
                 }
             } else {
-                emit!(
+                nav.emit_method(
                     buffer,
-                    "pub fn r{}c_{obj_ident}<'a>(&'a self, store: &'a {}) -> Vec<&{}> {{",
-                    binary.number,
-                    store.name,
-                    r_obj.as_type(&Ownership::new_borrowed(), woog, domain)
-                );
+                    &doc,
+                    format!(
+                        "fn r{}c_{obj_ident}<'a>(&'a self, store: &'a {}) -> Vec<&{}>",
+                        binary.number,
+                        store.name,
+                        r_obj.as_type(&Ownership::new_borrowed(), woog, domain)
+                    ),
+                )?;
                 emit!(
                     buffer,
                     "let {obj_ident} = store.iter_{obj_ident}()"
@@ -729,6 +793,7 @@ fn backward_one_conditional(
 
 fn backward_one_biconditional(
     buffer: &mut Buffer,
+    nav: &mut NavMethods,
     obj: &Object,
     r_obj: &Object,
     id: &str,
@@ -751,9 +816,8 @@ fn backward_one_biconditional(
             obj.as_ident(),
         ),
         |buffer| {
-            emit!(
-                buffer,
-                "/// Navigate to [`{}`] across R{}(1c-1c)",
+            let doc = format!(
+                "Navigate to [`{}`] across R{}(1c-1c)",
                 r_obj.as_type(&Ownership::new_borrowed(), woog, domain),
                 binary.number
             );
@@ -762,12 +826,15 @@ fn backward_one_biconditional(
                 let store_type = get_value_wrapper(is_imported, config, r_obj, woog, domain);
                 let (read, _write) = get_uber_read_write(config);
                 if let UberStoreOptions::AsyncRwLock = config.get_uber_store().unwrap() {
-                    emit!(
+                    nav.emit_method(
                         buffer,
-                        "pub async fn r{}c_{obj_ident}<'a>(&'a self, store: &'a {}) -> impl futures::Stream<Item = {store_type}> + '_ {{",
-                        binary.number,
-                        store.name
-                    );
+                        &doc,
+                        format!(
+                            "async fn r{}c_{obj_ident}<'a>(&'a self, store: &'a {}) -> impl futures::Stream<Item = {store_type}> + '_",
+                            binary.number,
+                            store.name
+                        ),
+                    )?;
                     if config.get_tracy() {
                         emit!(
                             buffer,
@@ -790,12 +857,15 @@ fn backward_one_biconditional(
                     emit!(buffer, "}}");
                     emit!(buffer, "}})");
                 } else {
-                    emit!(
+                    nav.emit_method(
                         buffer,
-                        "pub fn r{}c_{obj_ident}<'a>(&'a self, store: &'a {}) -> Vec<{store_type}> {{",
-                        binary.number,
-                        store.name
-                    );
+                        &doc,
+                        format!(
+                            "fn r{}c_{obj_ident}<'a>(&'a self, store: &'a {}) -> Vec<{store_type}>",
+                            binary.number,
+                            store.name
+                        ),
+                    )?;
                     if config.get_tracy() {
                         emit!(
                             buffer,
@@ -822,13 +892,16 @@ fn backward_one_biconditional(
                 }
 
             } else {
-                emit!(
+                nav.emit_method(
                     buffer,
-                    "pub fn r{}c_{obj_ident}<'a>(&'a self, store: &'a {}) -> Vec<&{}> {{",
-                    binary.number,
-                    store.name,
-                    r_obj.as_type(&Ownership::new_borrowed(), woog, domain)
-                );
+                    &doc,
+                    format!(
+                        "fn r{}c_{obj_ident}<'a>(&'a self, store: &'a {}) -> Vec<&{}>",
+                        binary.number,
+                        store.name,
+                        r_obj.as_type(&Ownership::new_borrowed(), woog, domain)
+                    ),
+                )?;
                 emit!(
                     buffer,
                     "let {obj_ident} = store.iter_{obj_ident}()"
@@ -857,6 +930,7 @@ fn backward_one_biconditional(
 
 fn backward_1_m(
     buffer: &mut Buffer,
+    nav: &mut NavMethods,
     obj: &Object,
     r_obj: &Object,
     id: &str,
@@ -879,9 +953,8 @@ fn backward_1_m(
             obj.as_ident(),
             ),
         |buffer| {
-            emit!(
-                buffer,
-                "/// Navigate to [`{}`] across R{}(1-M)",
+            let doc = format!(
+                "Navigate to [`{}`] across R{}(1-M)",
                 r_obj.as_type(&Ownership::new_borrowed(), woog, domain),
                 binary.number
             );
@@ -890,12 +963,15 @@ fn backward_1_m(
                 let store_type = get_value_wrapper(is_imported, config, r_obj, woog, domain);
                 let (read, _write) = get_uber_read_write(config);
                 if let UberStoreOptions::AsyncRwLock = config.get_uber_store().unwrap() {
-                    emit!(
+                    nav.emit_method(
                         buffer,
-                        "pub async fn r{}_{obj_ident}<'a>(&'a self, store: &'a {}) -> impl futures::Stream<Item = {store_type}> + '_ {{",
-                        binary.number,
-                        store.name
-                    );
+                        &doc,
+                        format!(
+                            "async fn r{}_{obj_ident}<'a>(&'a self, store: &'a {}) -> impl futures::Stream<Item = {store_type}> + '_",
+                            binary.number,
+                            store.name
+                        ),
+                    )?;
                     if config.get_tracy() {
                         emit!(buffer, "span!(\"r{}_{obj_ident}\");", binary.number,);
                     }
@@ -914,13 +990,16 @@ fn backward_1_m(
                     emit!(buffer, "}}");
                     emit!(buffer, "}})");
                 } else {
-                    emit!(
+                    nav.emit_method(
                         buffer,
-                        "pub fn r{}_{}<'a>(&'a self, store: &'a {}) -> Vec<{store_type}> {{",
-                        binary.number,
-                        r_obj.as_ident(),
-                        store.name
-                    );
+                        &doc,
+                        format!(
+                            "fn r{}_{}<'a>(&'a self, store: &'a {}) -> Vec<{store_type}>",
+                            binary.number,
+                            r_obj.as_ident(),
+                            store.name
+                        ),
+                    )?;
                     if config.get_tracy() {
                         emit!(buffer, "span!(\"r{}_{obj_ident}\");", binary.number,);
                     }
@@ -935,14 +1014,17 @@ fn backward_1_m(
                     emit!(buffer, ".collect()");
                 }
             } else {
-                emit!(
+                nav.emit_method(
                     buffer,
-                    "pub fn r{}_{}<'a>(&'a self, store: &'a {}) -> Vec<&{}> {{",
-                    binary.number,
-                    r_obj.as_ident(),
-                    store.name,
-                    r_obj.as_type(&Ownership::new_borrowed(), woog, domain)
-                );
+                    &doc,
+                    format!(
+                        "fn r{}_{}<'a>(&'a self, store: &'a {}) -> Vec<&{}>",
+                        binary.number,
+                        r_obj.as_ident(),
+                        store.name,
+                        r_obj.as_type(&Ownership::new_borrowed(), woog, domain)
+                    ),
+                )?;
                 emit!(buffer, "store.iter_{}()", r_obj.as_ident());
                 emit!(buffer, ".filter(|{}| {{", r_obj.as_ident(),);
                 emit!(
@@ -965,6 +1047,7 @@ fn backward_1_m(
 
 fn backward_1_mc(
     buffer: &mut Buffer,
+    nav: &mut NavMethods,
     obj: &Object,
     r_obj: &Object,
     id: &str,
@@ -988,9 +1071,8 @@ fn backward_1_mc(
             obj.as_ident(),
             ),
         |buffer| {
-            emit!(
-                buffer,
-                "/// Navigate to [`{}`] across R{}(1-Mc)",
+            let doc = format!(
+                "Navigate to [`{}`] across R{}(1-Mc)",
                 r_obj.as_type(&Ownership::new_borrowed(), woog, domain),
                 binary.number
             );
@@ -1000,12 +1082,15 @@ fn backward_1_mc(
                 let (read, _write) = get_uber_read_write(config);
 
                 if let UberStoreOptions::AsyncRwLock = config.get_uber_store().unwrap() {
-                    emit!(
+                    nav.emit_method(
                         buffer,
-                        "pub async fn r{}_{obj_ident}<'a>(&'a self, store: &'a {}) -> impl futures::Stream<Item = {store_type}> + '_ {{",
-                        binary.number,
-                        store.name
-                    );
+                        &doc,
+                        format!(
+                            "async fn r{}_{obj_ident}<'a>(&'a self, store: &'a {}) -> impl futures::Stream<Item = {store_type}> + '_",
+                            binary.number,
+                            store.name
+                        ),
+                    )?;
                     if config.get_tracy() {
                         emit!(
                             buffer,
@@ -1024,12 +1109,15 @@ fn backward_1_mc(
                     emit!(buffer, "}}");
                     emit!(buffer, "}})");
                 } else {
-                    emit!(
+                    nav.emit_method(
                         buffer,
-                        "pub fn r{}_{obj_ident}<'a>(&'a self, store: &'a {}) -> Vec<{store_type}> {{",
-                        binary.number,
-                        store.name
-                    );
+                        &doc,
+                        format!(
+                            "fn r{}_{obj_ident}<'a>(&'a self, store: &'a {}) -> Vec<{store_type}>",
+                            binary.number,
+                            store.name
+                        ),
+                    )?;
                     if config.get_tracy() {
                         emit!(
                             buffer,
@@ -1044,13 +1132,16 @@ fn backward_1_mc(
                     );
                 }
             } else {
-                emit!(
+                nav.emit_method(
                     buffer,
-                    "pub fn r{}_{obj_ident}<'a>(&'a self, store: &'a {}) -> Vec<&{}> {{",
-                    binary.number,
-                    store.name,
-                    r_obj.as_type(&Ownership::new_borrowed(), woog, domain)
-                );
+                    &doc,
+                    format!(
+                        "fn r{}_{obj_ident}<'a>(&'a self, store: &'a {}) -> Vec<&{}>",
+                        binary.number,
+                        store.name,
+                        r_obj.as_type(&Ownership::new_borrowed(), woog, domain)
+                    ),
+                )?;
                 emit!(buffer, "store.iter_{}()", r_obj.as_ident());
                 emit!(
                     buffer,
@@ -1166,6 +1257,7 @@ fn forward_assoc(
 
 fn backward_assoc_one(
     buffer: &mut Buffer,
+    nav: &mut NavMethods,
     obj: &Object,
     r_obj: &Object,
     id: &str,
@@ -1187,9 +1279,8 @@ fn backward_assoc_one(
             r_obj.as_ident()
         ),
         |buffer| {
-            emit!(
-                buffer,
-                "/// Navigate to [`{}`] across R{}(1-1)",
+            let doc = format!(
+                "Navigate to [`{}`] across R{}(1-1)",
                 r_obj.as_type(&Ownership::new_borrowed(), woog, domain),
                 number
             );
@@ -1197,33 +1288,42 @@ fn backward_assoc_one(
             if is_uber {
                 let store_type = get_value_wrapper(is_imported, config, r_obj, woog, domain);
                 if let UberStoreOptions::AsyncRwLock = config.get_uber_store().unwrap() {
-                    emit!(
+                    nav.emit_method(
                         buffer,
-                        "pub async fn r{number}_{}<'a>(&'a self, store: &'a {}) -> Vec<{store_type}> {{",
-                        r_obj.as_ident(),
-                        store.name
-                    );
+                        &doc,
+                        format!(
+                            "async fn r{number}_{}<'a>(&'a self, store: &'a {}) -> Vec<{store_type}>",
+                            r_obj.as_ident(),
+                            store.name
+                        ),
+                    )?;
                 } else {
-                    emit!(
+                    nav.emit_method(
                         buffer,
-                        "pub fn r{number}_{}<'a>(&'a self, store: &'a {}) -> Vec<{store_type}> {{",
-                        r_obj.as_ident(),
-                        store.name
-                    );
+                        &doc,
+                        format!(
+                            "fn r{number}_{}<'a>(&'a self, store: &'a {}) -> Vec<{store_type}>",
+                            r_obj.as_ident(),
+                            store.name
+                        ),
+                    )?;
                 }
 
                 if config.get_tracy() {
                     emit!(buffer, "span!(\"r{number}_{}\");", r_obj.as_ident());
                 }
             } else {
-                emit!(
+                nav.emit_method(
                     buffer,
-                    "pub fn r{}_{}<'a>(&'a self, store: &'a {}) -> Vec<&{}> {{",
-                    number,
-                    r_obj.as_ident(),
-                    store.name,
-                    r_obj.as_type(&Ownership::new_borrowed(), woog, domain)
-                );
+                    &doc,
+                    format!(
+                        "fn r{}_{}<'a>(&'a self, store: &'a {}) -> Vec<&{}>",
+                        number,
+                        r_obj.as_ident(),
+                        store.name,
+                        r_obj.as_type(&Ownership::new_borrowed(), woog, domain)
+                    ),
+                )?;
             }
 
             emit!(buffer, "vec![store.iter_{}()", r_obj.as_ident());
@@ -1255,6 +1355,7 @@ fn backward_assoc_one(
 
 fn backward_assoc_one_conditional(
     buffer: &mut Buffer,
+    nav: &mut NavMethods,
     obj: &Object,
     r_obj: &Object,
     id: &str,
@@ -1278,9 +1379,8 @@ fn backward_assoc_one_conditional(
             r_obj.as_ident()
         ),
         |buffer| {
-            emit!(
-                buffer,
-                "/// Navigate to [`{}`] across R{}(1-1c)",
+            let doc = format!(
+                "Navigate to [`{}`] across R{}(1-1c)",
                 r_obj.as_type(&Ownership::new_borrowed(), woog, domain),
                 number
             );
@@ -1288,29 +1388,38 @@ fn backward_assoc_one_conditional(
             if is_uber {
                 let store_type = get_value_wrapper(is_imported, config, r_obj, woog, domain);
                 if let UberStoreOptions::AsyncRwLock = config.get_uber_store().unwrap() {
-                    emit!(
+                    nav.emit_method(
                         buffer,
-                        "pub async fn r{number}_{obj_ident}<'a>(&'a self, store: &'a {}) -> Vec<{store_type}> {{",
-                        store.name
-                    );
+                        &doc,
+                        format!(
+                            "async fn r{number}_{obj_ident}<'a>(&'a self, store: &'a {}) -> Vec<{store_type}>",
+                            store.name
+                        ),
+                    )?;
                 } else {
-                    emit!(
+                    nav.emit_method(
                         buffer,
-                        "pub fn r{number}_{obj_ident}<'a>(&'a self, store: &'a {}) -> Vec<{store_type}> {{",
-                        store.name
-                    );
+                        &doc,
+                        format!(
+                            "fn r{number}_{obj_ident}<'a>(&'a self, store: &'a {}) -> Vec<{store_type}>",
+                            store.name
+                        ),
+                    )?;
                 }
 
                 if config.get_tracy() {
                     emit!(buffer, "span!(\"r{number}_{obj_ident}\");");
                 }
             } else {
-                emit!(
+                nav.emit_method(
                     buffer,
-                    "pub fn r{number}_{obj_ident}<'a>(&'a self, store: &'a {}) -> Vec<&{}> {{",
-                    store.name,
-                    r_obj.as_type(&Ownership::new_borrowed(), woog, domain)
-                );
+                    &doc,
+                    format!(
+                        "fn r{number}_{obj_ident}<'a>(&'a self, store: &'a {}) -> Vec<&{}>",
+                        store.name,
+                        r_obj.as_type(&Ownership::new_borrowed(), woog, domain)
+                    ),
+                )?;
             }
 
             emit!(
@@ -1347,6 +1456,7 @@ fn backward_assoc_one_conditional(
 
 fn backward_assoc_many(
     buffer: &mut Buffer,
+    nav: &mut NavMethods,
     obj: &Object,
     r_obj: &Object,
     id: &str,
@@ -1368,9 +1478,8 @@ fn backward_assoc_many(
             r_obj.as_ident()
         ),
         |buffer| {
-            emit!(
-                buffer,
-                "/// Navigate to [`{}`] across R{}(1-M)",
+            let doc = format!(
+                "Navigate to [`{}`] across R{}(1-M)",
                 r_obj.as_type(&Ownership::new_borrowed(), woog, domain),
                 number
             );
@@ -1378,33 +1487,42 @@ fn backward_assoc_many(
             if is_uber {
                 let store_type = get_value_wrapper(is_imported, config, r_obj, woog, domain);
                 if let UberStoreOptions::AsyncRwLock = config.get_uber_store().unwrap() {
-                    emit!(
+                    nav.emit_method(
                         buffer,
-                        "pub async fn r{number}_{}<'a>(&'a self, store: &'a {}) -> Vec<{store_type}> {{",
-                        r_obj.as_ident(),
-                        store.name
-                    );
+                        &doc,
+                        format!(
+                            "async fn r{number}_{}<'a>(&'a self, store: &'a {}) -> Vec<{store_type}>",
+                            r_obj.as_ident(),
+                            store.name
+                        ),
+                    )?;
                 } else {
-                    emit!(
+                    nav.emit_method(
                         buffer,
-                        "pub fn r{number}_{}<'a>(&'a self, store: &'a {}) -> Vec<{store_type}> {{",
-                        r_obj.as_ident(),
-                        store.name
-                    );
+                        &doc,
+                        format!(
+                            "fn r{number}_{}<'a>(&'a self, store: &'a {}) -> Vec<{store_type}>",
+                            r_obj.as_ident(),
+                            store.name
+                        ),
+                    )?;
                 }
 
                 if config.get_tracy() {
                     emit!(buffer, "span!(\"r{number}_{}\");", r_obj.as_ident());
                 }
             } else {
-                emit!(
+                nav.emit_method(
                     buffer,
-                    "pub fn r{}_{}<'a>(&'a self, store: &'a {}) -> Vec<&{}> {{",
-                    number,
-                    r_obj.as_ident(),
-                    store.name,
-                    r_obj.as_type(&Ownership::new_borrowed(), woog, domain)
-                );
+                    &doc,
+                    format!(
+                        "fn r{}_{}<'a>(&'a self, store: &'a {}) -> Vec<&{}>",
+                        number,
+                        r_obj.as_ident(),
+                        store.name,
+                        r_obj.as_type(&Ownership::new_borrowed(), woog, domain)
+                    ),
+                )?;
             }
 
             emit!(buffer, "store.iter_{}()", r_obj.as_ident());
@@ -1516,6 +1634,8 @@ fn subtype_to_supertype(
             } else {
                 "id"
             };
+            // We share our id with the supertype, but not our id type.
+            let into = if config.get_typed_ids() { ".into()" } else { "" };
             // I wish I'd left myself a note about why hybrid is special...
             // Oh! It's got the `subtype` attribute/field.
             if is_hybrid {
@@ -1549,20 +1669,20 @@ fn subtype_to_supertype(
                 if let UberStoreOptions::AsyncRwLock = config.get_uber_store().unwrap() {
                     emit!(
                         buffer,
-                        "vec![store.exhume_{}(&self.{id}).await.unwrap()]",
+                        "vec![store.exhume_{}(&self.{id}{into}).await.unwrap()]",
                         s_obj.as_ident()
                     );
                 } else {
                     emit!(
                         buffer,
-                        "vec![store.exhume_{}(&self.{id}).unwrap()]",
+                        "vec![store.exhume_{}(&self.{id}{into}).unwrap()]",
                         s_obj.as_ident()
                     );
                 }
             } else {
                 emit!(
                     buffer,
-                    "vec![store.exhume_{}(&self.{id}).unwrap()]",
+                    "vec![store.exhume_{}(&self.{id}{into}).unwrap()]",
                     s_obj.as_ident()
                 );
             }
@@ -1,11 +1,12 @@
 //! Generate ObjectStore for use in sarzak Domain
 //!
-use std::fmt::Write;
+use std::{fmt::Write, sync::RwLock};
 
 use rustc_hash::{FxHashMap as HashMap, FxHashSet as HashSet};
 use sarzak::{
+    lu_dog::store::ObjectStore as LuDogStore,
     mc::{CompilerSnafu, FormatSnafu, Result},
-    sarzak::types::Object,
+    sarzak::types::{Object, Ty},
     v2::domain::Domain,
     woog::{store::ObjectStore as WoogStore, types::Ownership},
 };
@@ -16,12 +17,14 @@ use crate::{
     codegen::{
         buffer::{emit, Buffer},
         diff_engine::DirectiveKind,
+        emit_async_lock_uses, emit_typed_id_uses,
         generator::{CodeWriter, FileGenerator, GenerationAction},
-        get_subtypes_sorted_from_super_obj, local_object_is_enum, local_object_is_hybrid,
-        local_object_is_singleton, local_object_is_subtype, local_object_is_supertype,
+        get_subtypes_sorted_from_super_obj, local_object_has_id_type, local_object_is_enum,
+        local_object_is_hybrid, local_object_is_singleton, local_object_is_subtype,
+        local_object_is_supertype, model_hash, object_id_type,
         render::{RenderConst, RenderIdent, RenderType},
     },
-    options::{GraceConfig, UberStoreOptions},
+    options::{AsyncRuntime, GraceConfig, UberStoreOptions, UuidMode},
     types::ObjectStoreDefinition,
 };
 
@@ -65,6 +68,7 @@ impl FileGenerator for DomainStoreGenerator {
         domain: &Domain,
         woog: &Option<&mut WoogStore>,
         imports: &Option<&HashMap<String, Domain>>,
+        lu_dog: &Option<&RwLock<LuDogStore>>,
         package: &str,
         module: &str,
         obj_id: Option<&Uuid>,
@@ -122,7 +126,7 @@ impl FileGenerator for DomainStoreGenerator {
                 }
 
                 self.definition.write_code(
-                    config, domain, woog, imports, package, module, obj_id, buffer,
+                    config, domain, woog, imports, lu_dog, package, module, obj_id, buffer,
                 )?;
 
                 Ok(())
@@ -159,7 +163,9 @@ impl DomainStore {
                 for obj in objects {
                     let obj_ident = obj.as_ident();
                     let obj_type = obj.as_type(&Ownership::new_borrowed(), woog, domain);
+                    let id_type = object_id_type(obj, config, woog, domain);
                     let thing = get_value_wrapper(is_uber, config, obj, woog, domain);
+                    let indices = object_indices(obj, config, woog, domain)?;
 
                     // 🚦
                     // Generate inter_ methods
@@ -168,6 +174,7 @@ impl DomainStore {
                         "/// Inter (insert) [`{obj_type}`] into the store.",
                     );
                     emit!(buffer, "///");
+                    emit_inter_index_doc(buffer, &indices)?;
 
                     if is_uber {
                         use UberStoreOptions::*;
@@ -204,6 +211,41 @@ impl DomainStore {
                         emit!(buffer, "let read = {}{read};", obj.as_ident());
                     }
 
+                    if timestamp {
+                        // It's back from the dead.
+                        if is_uber {
+                            emit!(buffer, "self.tombstones.remove(&Uuid::from(read.{id}));");
+                        } else {
+                            emit!(
+                                buffer,
+                                "self.tombstones.remove(&Uuid::from({obj_ident}.{id}));"
+                            );
+                        }
+                    }
+
+                    if !indices.is_empty() {
+                        // Whatever is being replaced needs to move out of its
+                        // old keys, or we'd be left with stale ones.
+                        if is_uber {
+                            // It may be the very same instance, changed in
+                            // place, so `rekey` doesn't go by the old one.
+                            let (read, _write) = get_uber_read_write(config);
+                            emit!(buffer, "let replacing = self.{obj_ident}{read}.contains_key(&read.{id});");
+                            emit!(buffer, "if replacing {{");
+                            emit!(buffer, "self.rekey_{obj_ident}(&read);");
+                            emit!(buffer, "}} else {{");
+                            emit!(buffer, "self.index_{obj_ident}(&read);");
+                            emit!(buffer, "}}");
+                        } else {
+                            let old = if timestamp { "old.0" } else { "old" };
+                            emit!(buffer, "if let Some(old) = self.{obj_ident}.remove(&{obj_ident}.{id}) {{");
+                            emit!(buffer, "self.rekey_{obj_ident}(&{old}, &{obj_ident});");
+                            emit!(buffer, "}} else {{");
+                            emit!(buffer, "self.index_{obj_ident}(&{obj_ident});");
+                            emit!(buffer, "}}");
+                        }
+                    }
+
                     if timestamp {
                         if object_has_name(obj, domain) {
 
@@ -307,20 +349,20 @@ impl DomainStore {
                             AsyncRwLock => {
                                 emit!(
                                     buffer,
-                                    "pub async fn exhume_{obj_ident}(&self, id: &Uuid) -> Option<{thing}> {{",
+                                    "pub async fn exhume_{obj_ident}(&self, id: &{id_type}) -> Option<{thing}> {{",
                                 );
                             }
                             _ => {
                                 emit!(
                                     buffer,
-                                    "pub fn exhume_{obj_ident}(&self, id: &Uuid) -> Option<{thing}> {{",
+                                    "pub fn exhume_{obj_ident}(&self, id: &{id_type}) -> Option<{thing}> {{",
                                 );
                             }
                         }
                     } else {
                         emit!(
                             buffer,
-                            "pub fn exhume_{obj_ident}(&self, id: &Uuid) -> Option<&{thing}> {{",
+                            "pub fn exhume_{obj_ident}(&self, id: &{id_type}) -> Option<&{thing}> {{",
                         );
                     }
 
@@ -368,20 +410,20 @@ impl DomainStore {
                             AsyncRwLock => {
                                 emit!(
                                     buffer,
-                                    "pub async fn exorcise_{obj_ident}(&mut self, id: &Uuid) -> Option<{thing}> {{",
+                                    "pub async fn exorcise_{obj_ident}(&mut self, id: &{id_type}) -> Option<{thing}> {{",
                                 );
                             }
                             _ => {
                                 emit!(
                                     buffer,
-                                    "pub fn exorcise_{obj_ident}(&mut self, id: &Uuid) -> Option<{thing}> {{",
+                                    "pub fn exorcise_{obj_ident}(&mut self, id: &{id_type}) -> Option<{thing}> {{",
                                 );
                             }
                         }
                     } else {
                         emit!(
                             buffer,
-                            "pub fn exorcise_{obj_ident}(&mut self, id: &Uuid) -> Option<{thing}> {{",
+                            "pub fn exorcise_{obj_ident}(&mut self, id: &{id_type}) -> Option<{thing}> {{",
                         );
                     }
 
@@ -390,9 +432,15 @@ impl DomainStore {
                         if timestamp {
                             emit!(
                                 buffer,
-                                "self.{0}{write}.remove(id).map(|{0}| {0}.0.clone())",
+                                "let result = self.{0}{write}.remove(id).map(|{0}| {0}.0.clone());",
                                 obj_ident
                             );
+                        } else if !indices.is_empty() {
+                            emit!(
+                                buffer,
+                                "let result = self.{0}{write}.remove(id).map(|{0}| {0}.clone());",
+                                obj_ident,
+                            );
                         } else {
                             emit!(
                                 buffer,
@@ -403,12 +451,37 @@ impl DomainStore {
                     } else if timestamp {
                         emit!(
                             buffer,
-                            "self.{0}.remove(id).map(|{0}| {0}.0)",
+                            "let result = self.{0}.remove(id).map(|{0}| {0}.0);",
                             obj_ident
                         );
+                    } else if !indices.is_empty() {
+                        emit!(buffer, "let result = self.{obj_ident}.remove(id);");
                     } else {
                         emit!(buffer, "self.{obj_ident}.remove(id)");
                     }
+                    if !indices.is_empty() {
+                        emit!(buffer, "if let Some(ref {obj_ident}) = result {{");
+                        if is_uber {
+                            let (read, _write) = get_uber_read_write(config);
+                            emit!(buffer, "let read = {obj_ident}{read};");
+                            emit!(buffer, "self.unindex_{obj_ident}(&read);");
+                        } else {
+                            emit!(buffer, "self.unindex_{obj_ident}({obj_ident});");
+                        }
+                        emit!(buffer, "}}");
+                    }
+                    if timestamp {
+                        // Leave a tombstone, so that `changes_since` knows it's gone.
+                        emit!(buffer, "if result.is_some() {{");
+                        emit!(
+                            buffer,
+                            "self.tombstones.insert(Uuid::from(*id), (ObjectKind::{obj_type}, SystemTime::now()));"
+                        );
+                        emit!(buffer, "}}");
+                    }
+                    if timestamp || !indices.is_empty() {
+                        emit!(buffer, "result");
+                    }
                     emit!(buffer, "}}");
                     emit!(buffer, "");
 
@@ -427,13 +500,13 @@ impl DomainStore {
                                 AsyncRwLock => {
                                     emit!(
                                         buffer,
-                                        "pub async fn exhume_{obj_ident}_id_by_name(&self, name: &str) -> Option<Uuid> {{",
+                                        "pub async fn exhume_{obj_ident}_id_by_name(&self, name: &str) -> Option<{id_type}> {{",
                                     );
                                 }
                                 _ => {
                                     emit!(
                                         buffer,
-                                        "pub fn exhume_{obj_ident}_id_by_name(&self, name: &str) -> Option<Uuid> {{",
+                                        "pub fn exhume_{obj_ident}_id_by_name(&self, name: &str) -> Option<{id_type}> {{",
                                     );
                                 }
                             }
@@ -449,7 +522,7 @@ impl DomainStore {
                         } else if timestamp {
                             emit!(
                                 buffer,
-                                "pub fn exhume_{obj_ident}_id_by_name(&self, name: &str) -> Option<Uuid> {{",
+                                "pub fn exhume_{obj_ident}_id_by_name(&self, name: &str) -> Option<{id_type}> {{",
                             );
                             emit!(
                                 buffer,
@@ -458,7 +531,7 @@ impl DomainStore {
                         } else {
                             emit!(
                                 buffer,
-                                "pub fn exhume_{obj_ident}_id_by_name(&self, name: &str) -> Option<&Uuid> {{",
+                                "pub fn exhume_{obj_ident}_id_by_name(&self, name: &str) -> Option<&{id_type}> {{",
                             );
                             emit!(buffer, "self.{obj_ident}_id_by_name.get(name)");
                         }
@@ -470,7 +543,7 @@ impl DomainStore {
                     // Generate iter_ methods
                     emit!(
                         buffer,
-                        "/// Get an iterator over the internal `HashMap<&Uuid, {}>`.",
+                        "/// Get an iterator over the internal `HashMap<&{id_type}, {}>`.",
                         obj.as_type(&Ownership::new_borrowed(), woog, domain)
                     );
                     emit!(buffer, "///");
@@ -498,9 +571,12 @@ impl DomainStore {
                         match config.get_uber_store().unwrap() {
                             Disabled => unreachable!(),
                             AsyncRwLock => {
+                                // The values are collected under the read lock, and the
+                                // lock is released before the stream is handed out. So
+                                // dropping the stream part way through is harmless.
                                 emit!(
                                     buffer,
-                                    "pub async fn iter_{obj_ident}(&self) -> impl Iterator<Item = {store_type}> + '_ {{",
+                                    "pub async fn iter_{obj_ident}(&self) -> impl futures::Stream<Item = {store_type}> + '_ {{",
                                 );
                             }
                             _ => {
@@ -541,27 +617,35 @@ impl DomainStore {
                                 buffer,
                                 "let values: {store_type} = self.{obj_ident}{read}.values().map(|{obj_ident}| {obj_ident}.0.clone()).collect();",
                             );
-                            emit!(
-                                buffer,
-                                "let len = values.len();"
-                            );
-                            emit!(
-                                buffer,
-                                "(0..len).map(move|i| values[i].clone())",
-                            );
+                            if let AsyncRwLock = config.get_uber_store().unwrap() {
+                                emit!(buffer, "futures::stream::iter(values)");
+                            } else {
+                                emit!(
+                                    buffer,
+                                    "let len = values.len();"
+                                );
+                                emit!(
+                                    buffer,
+                                    "(0..len).map(move|i| values[i].clone())",
+                                );
+                            }
                         } else {
                             emit!(
                                 buffer,
                                 "let values: {store_type} = self.{obj_ident}{read}.values().map(|{obj_ident}| {obj_ident}.clone()).collect();",
                             );
-                            emit!(
-                                buffer,
-                                "let len = values.len();"
-                            );
-                            emit!(
-                                buffer,
-                                "(0..len).map(move|i| values[i].clone())",
-                            );
+                            if let AsyncRwLock = config.get_uber_store().unwrap() {
+                                emit!(buffer, "futures::stream::iter(values)");
+                            } else {
+                                emit!(
+                                    buffer,
+                                    "let len = values.len();"
+                                );
+                                emit!(
+                                    buffer,
+                                    "(0..len).map(move|i| values[i].clone())",
+                                );
+                            }
                         }
                     } else if timestamp {
                         emit!(
@@ -575,6 +659,10 @@ impl DomainStore {
                     emit!(buffer, "}}");
                     emit!(buffer, "");
 
+                    // 🚦
+                    // Generate index methods
+                    emit_index_methods(buffer, obj, &indices, config, woog, domain)?;
+
                     // 🚦
                     // Generate code to get timestamp
                     if timestamp {
@@ -628,9 +716,19 @@ impl DomainStore {
                         }
                         emit!(buffer, "}}");
                         emit!(buffer, "");
+
+                        emit_iter_changed_since(buffer, obj, config, woog, domain)?;
                     }
                 }
 
+                if timestamp {
+                    emit_changes_since(buffer, objects, config, woog, domain)?;
+                }
+
+                if has_indices(objects, config) {
+                    emit_reindex_method(buffer, objects, timestamp, config, woog, domain)?;
+                }
+
                 Ok(())
             },
         )?;
@@ -648,6 +746,7 @@ impl CodeWriter for DomainStore {
         domain: &Domain,
         woog: &Option<&mut WoogStore>,
         _imports: &Option<&HashMap<String, Domain>>,
+        _lu_dog: &Option<&RwLock<LuDogStore>>,
         _package: &str,
         module: &str,
         _obj_id: Option<&Uuid>,
@@ -815,16 +914,16 @@ impl CodeWriter for DomainStore {
                     } else {
                         emit!(buffer, "use std::{{io::{{self, prelude::*}}, fs, path::Path}};");
                     }
+                    if persist_in_parallel(config) {
+                        emit!(buffer, "use rayon::prelude::*;");
+                    }
                 }
                 if is_uber {
                     use UberStoreOptions::*;
                     match config.get_uber_store().unwrap() {
                         Disabled => unreachable!(),
                         AsyncRwLock => {
-                            emit!(buffer, "use async_std::sync::Arc;");
-                            emit!(buffer, "use async_std::sync::RwLock;");
-                            emit!(buffer, "use std::fmt;");
-                            emit!(buffer, "use serde::{{ser::SerializeMap, Serializer, Deserializer, de::{{self, Visitor, MapAccess, SeqAccess}}}};");
+                            emit_async_lock_uses(buffer, config)?;
                         }
                         NDRwLock => {
                             emit!(buffer, "use std::sync::Arc;");
@@ -881,7 +980,9 @@ impl CodeWriter for DomainStore {
                     emit_singleton_subtype_uses(&supertypes, config, domain, woog, buffer)?;
 
                 emit!(buffer, "}};");
+                emit_typed_id_uses(buffer, module, config)?;
                 emit!(buffer, "");
+
                 if let UberStoreOptions::AsyncRwLock = config.get_uber_store().unwrap() {
                     emit!(buffer, "#[derive(Clone, Debug)]");
                 } else {
@@ -890,6 +991,7 @@ impl CodeWriter for DomainStore {
                 emit!(buffer, "pub struct ObjectStore {{");
                 for obj in &objects {
                     let value_type = get_value_wrapper(is_uber, config, obj, woog, domain);
+                    let id_type = object_id_type(obj, config, woog, domain);
 
                     if timestamp {
                         if is_uber {
@@ -897,18 +999,18 @@ impl CodeWriter for DomainStore {
                             let mother_of_all_types = match config.get_uber_store().unwrap() {
                                 Disabled => unreachable!(),
                                 Single => format!(
-                                    "Rc<RefCell<HashMap<Uuid, ({}, SystemTime)>>>",
+                                    "Rc<RefCell<HashMap<{id_type}, ({}, SystemTime)>>>",
                                     value_type
                                 ),
                                 StdRwLock |
                                 ParkingLotRwLock |
                                 AsyncRwLock |
                                 NDRwLock => format!(
-                                    "Arc<RwLock<HashMap<Uuid, ({}, SystemTime)>>>",
+                                    "Arc<RwLock<HashMap<{id_type}, ({}, SystemTime)>>>",
                                     value_type
                                 ),
                                 StdMutex | ParkingLotMutex => format!(
-                                    "Arc<Mutex<HashMap<Uuid, ({}, SystemTime)>>>",
+                                    "Arc<Mutex<HashMap<{id_type}, ({}, SystemTime)>>>",
                                     value_type
                                 ),
                             };
@@ -921,12 +1023,12 @@ impl CodeWriter for DomainStore {
                                 use UberStoreOptions::*;
                                 let by_name_type = match config.get_uber_store().unwrap() {
                                     Disabled => unreachable!(),
-                                    Single => "Rc<RefCell<HashMap<String, (Uuid, SystemTime)>>>",
+                                    Single => format!("Rc<RefCell<HashMap<String, ({id_type}, SystemTime)>>>"),
                                     StdRwLock |
                                     ParkingLotRwLock |
                                     AsyncRwLock |
-                                    NDRwLock => "Arc<RwLock<HashMap<String, (Uuid, SystemTime)>>>",
-                                    StdMutex | ParkingLotMutex => "Arc<Mutex<HashMap<String, (Uuid, SystemTime)>>>",
+                                    NDRwLock => format!("Arc<RwLock<HashMap<String, ({id_type}, SystemTime)>>>"),
+                                    StdMutex | ParkingLotMutex => format!("Arc<Mutex<HashMap<String, ({id_type}, SystemTime)>>>"),
                                 };
                                 emit!(
                                     buffer,
@@ -937,14 +1039,14 @@ impl CodeWriter for DomainStore {
                         } else {
                             emit!(
                                 buffer,
-                                "{}: HashMap<Uuid, ({}, SystemTime)>,",
+                                "{}: HashMap<{id_type}, ({}, SystemTime)>,",
                                 obj.as_ident(),
                                 value_type
                             );
                             if object_has_name(obj, domain) {
                                 emit!(
                                     buffer,
-                                    "{}_id_by_name: HashMap<String, (Uuid, SystemTime)>,",
+                                    "{}_id_by_name: HashMap<String, ({id_type}, SystemTime)>,",
                                     obj.as_ident()
                                 );
                             }
@@ -954,18 +1056,18 @@ impl CodeWriter for DomainStore {
                         let mother_of_all_types = match config.get_uber_store().unwrap() {
                             Disabled => unreachable!(),
                             Single => format!(
-                                "Rc<RefCell<HashMap<Uuid, {}>>>",
+                                "Rc<RefCell<HashMap<{id_type}, {}>>>",
                                 value_type
                             ),
                             StdRwLock |
                             ParkingLotRwLock |
                             AsyncRwLock |
                             NDRwLock => format!(
-                                "Arc<RwLock<HashMap<Uuid, {}>>>",
+                                "Arc<RwLock<HashMap<{id_type}, {}>>>",
                                 value_type
                             ),
                             StdMutex | ParkingLotMutex => format!(
-                                "Arc<Mutex<HashMap<Uuid, {}>>>",
+                                "Arc<Mutex<HashMap<{id_type}, {}>>>",
                                 value_type
                             ),
                         };
@@ -978,12 +1080,12 @@ impl CodeWriter for DomainStore {
                             use UberStoreOptions::*;
                             let by_name_type = match config.get_uber_store().unwrap() {
                                 Disabled => unreachable!(),
-                                Single => "Rc<RefCell<HashMap<String, Uuid>>>",
+                                Single => format!("Rc<RefCell<HashMap<String, {id_type}>>>"),
                                 StdRwLock |
                                 ParkingLotRwLock |
                                 AsyncRwLock |
-                                NDRwLock => "Arc<RwLock<HashMap<String, Uuid>>>",
-                                StdMutex | ParkingLotMutex => "Arc<Mutex<HashMap<Uuid, Uuid>>>",
+                                NDRwLock => format!("Arc<RwLock<HashMap<String, {id_type}>>>"),
+                                StdMutex | ParkingLotMutex => format!("Arc<Mutex<HashMap<String, {id_type}>>>"),
                             };
                             emit!(
                                 buffer,
@@ -994,149 +1096,50 @@ impl CodeWriter for DomainStore {
                     } else {
                         emit!(
                             buffer,
-                            "{}: HashMap<Uuid, {}>,",
+                            "{}: HashMap<{id_type}, {}>,",
                             obj.as_ident(),
                             value_type
                         );
                         if object_has_name(obj, domain) {
                             emit!(
                                 buffer,
-                                "{}_id_by_name: HashMap<String, Uuid>,",
+                                "{}_id_by_name: HashMap<String, {id_type}>,",
                                 obj.as_ident(),
                             );
                         }
                     }
+                    emit_index_fields(buffer, obj, config, woog, domain)?;
                 }
-                emit!(buffer, "}}");
-                emit!(buffer, "");
-
-                if let UberStoreOptions::AsyncRwLock = config.get_uber_store().unwrap() {
-                    emit!(buffer, "impl Serialize for ObjectStore {{");
-                    emit!(buffer, "fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>");
-                    emit!(buffer, "where S: Serializer, {{");
-                    for obj in &objects {
-                        let obj_ident = obj.as_ident();
-
-                        emit!(buffer, "let {obj_ident} = (*futures::executor::block_on(async {{self.{obj_ident}.read().await}})).clone();");
-                        emit!(buffer, "let mut map = serializer.serialize_map(Some({obj_ident}.len()))?;");
-                        emit!(buffer, "for (k, v) in {obj_ident} {{");
-                        emit!(buffer, "map.serialize_entry(&k, &((*futures::executor::block_on(async {{v.0.read().await}})).clone(), v.1))?;");
-                        emit!(buffer, "}}");
-                        emit!(buffer, "let result = map.end();\n");
+                if has_unique_indices(&objects, config) {
+                    emit!(buffer, "/// Instances that collided with another in a unique index");
+                    if store_derives_serde(config) {
+                        emit!(buffer, "#[serde(skip)]");
                     }
-                    emit!(buffer, "result");
-                    emit!(buffer, "}}");
-                    emit!(buffer, "}}\n");
-
-                    emit!(buffer, "impl<'de> Deserialize<'de> for ObjectStore {{");
-                    emit!(buffer, "fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>");
-                    emit!(buffer, "where D: Deserializer<'de>, {{");
-                    emit!(buffer, "enum Field {{");
-                    for obj in &objects {
-                        let obj_type = obj.as_type(&Ownership::new_borrowed(), woog, domain);
-                        emit!(
-                            buffer,
-                            "{obj_type},"
-                        );
+                    emit!(buffer, "index_conflicts: Vec<IndexConflict>,");
+                }
+                if timestamp {
+                    emit!(buffer, "/// The ids of exorcised instances, and when they were exorcised");
+                    if !matches!(config.get_uber_store(), Some(UberStoreOptions::AsyncRwLock)) {
+                        emit!(buffer, "#[serde(default)]");
                     }
-                    emit!(buffer, "}}");
-                    emit!(buffer, "impl<'de> Deserialize<'de> for Field {{");
-                    emit!(buffer, "fn deserialize<D>(deserializer: D) -> Result<Field, D::Error>");
-                    emit!(buffer, "where D: Deserializer<'de>, {{");
-                    emit!(buffer, "struct FieldVisitor;");
-                    emit!(buffer, "impl<'de> Visitor<'de> for FieldVisitor {{");
-                    emit!(buffer, "type Value = Field;");
-                    emit!(buffer, "fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {{");
-                    emit!(buffer, "formatter.write_str(\"field identifier\")");
-                    emit!(buffer, "}}");
-                    emit!(buffer, "fn visit_str<E>(self, value: &str) -> Result<Field, E>");
-                    emit!(buffer, "where E: de::Error, {{");
-                    emit!(buffer, "match value {{");
-                    for obj in &objects {
-                        let obj_ident = obj.as_ident();
-                        let obj_type = obj.as_type(&Ownership::new_borrowed(), woog, domain);
-                        emit!(
-                            buffer,
-                            "\"{obj_ident}\" => Ok(Field::{obj_type}),"
-                        );
+                    emit!(buffer, "tombstones: HashMap<Uuid, (ObjectKind, SystemTime)>,");
+                }
+                if let UuidMode::Seeded = config.get_uuid_mode() {
+                    emit!(buffer, "/// The next value in the UUID seed sequence");
+                    if store_derives_serde(config) {
+                        emit!(buffer, "#[serde(skip)]");
                     }
-                    emit!(buffer, "_ => Err(de::Error::unknown_field(value, FIELDS)),");
-                    emit!(buffer, "}}");
-                    emit!(buffer, "}}");
-                    emit!(buffer, "}}");
-                    emit!(buffer, "deserializer.deserialize_identifier(FieldVisitor)");
-                    emit!(buffer, "}}");
-                    emit!(buffer, "}}");
-                    emit!(buffer, "struct ObjectStoreVisitor;");
-                    emit!(buffer, "impl<'de> Visitor<'de> for ObjectStoreVisitor {{");
-                    emit!(buffer, "type Value = ObjectStore;");
-                    emit!(buffer, "fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {{");
-                    emit!(buffer, "formatter.write_str(\"struct ObjectStore\")");
-                    emit!(buffer, "}}");
-                    emit!(buffer, "fn visit_map<A>(self, mut map: A) -> Result<ObjectStore, A::Error>");
-                    emit!(buffer, "where A: MapAccess<'de>, {{");
-                    emit!(buffer, "let mut result = ObjectStore::new();");
-                    emit!(buffer, "while let Some(key) = map.next_key()? {{");
-                    emit!(buffer, "match key {{");
-                    for obj in &objects {
-                        let obj_ident = obj.as_ident();
-                        let obj_type = obj.as_type(&Ownership::new_borrowed(), woog, domain);
+                    emit!(buffer, "uuid_seed: u64,");
+                }
+                emit!(buffer, "}}");
+                emit!(buffer, "");
 
-                        emit!(
-                            buffer,
-                            "Field::{obj_type} => result.{obj_ident} = map.next_value()?,",
-                        );
-                    }
-                    emit!(buffer, "}}");
-                    emit!(buffer, "}}");
-                    emit!(buffer, "Ok(result)");
-                    emit!(buffer, "}}\n");
-                    emit!(buffer, "fn visit_seq<A>(self, mut seq: A) -> Result<ObjectStore, A::Error>");
-                    emit!(buffer, "where A: SeqAccess<'de>, {{");
-                    emit!(buffer, "let mut result = ObjectStore::new();");
-                    for (n, obj) in objects.iter().enumerate() {
-                        let obj_ident = obj.as_ident();
-
-                        if n == 0 {
-                            emit!(
-                                buffer,
-                                "result.{obj_ident} = Arc::new(RwLock::new(seq.next_element()?)).ok_or_else(|| de::Error::invalid_length({n}, &self))?;;",
-                            );
-                        }
-                    }
-                    emit!(buffer, "Ok(result)");
-                    emit!(buffer, "}}}}\n");
-
-                    for obj in &objects {
-                        let _obj_ident = obj.as_ident();
-                        let obj_type = obj.as_type(&Ownership::new_borrowed(), woog, domain);
-
-                        emit!(buffer, "struct {obj_type}Visitor;");
-                        emit!(buffer, "impl<'de> Visitor<'de> for {obj_type}Visitor {{");
-                        emit!(buffer, "type Value = Arc<RwLock<HashMap<Uuid, (Arc<RwLock<{obj_type}>>, SystemTime)>>>;");
-                        emit!(buffer, "fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {{");
-                        emit!(buffer, "formatter.write_str(\"{obj_type} map\")");
-                        emit!(buffer, "}}");
-                        emit!(buffer, "fn visit_map<M>(self, mut access: M) -> Result<Self::Value, M::Error>");
-                        emit!(buffer, "where M: MapAccess<'de>, {{");
-                        emit!(buffer, "let mut map = HashMap::default();");
-                        emit!(buffer, "while let Some((key, value)) = access.next_entry::<Uuid, ({obj_type}, SystemTime)>()? {{");
-                        emit!(buffer, "map.insert(key, (Arc::new(RwLock::new(value.0)), value.1));");
-                        emit!(buffer, "}}");
-                        emit!(buffer, "Ok(Arc::new(RwLock::new(map)))");
-                        emit!(buffer, "}}}}\n");
-                    }
-                    emit!(buffer, "const FIELDS: &'static [&'static str] = &[");
-                    for obj in &objects {
-                        let obj_ident = obj.as_ident();
-                        emit!(buffer, "\"{obj_ident}\",");
+                if persist {
+                    if let UberStoreOptions::AsyncRwLock = config.get_uber_store().unwrap() {
+                        emit_snapshot(buffer, &objects, timestamp, config, woog, domain)?;
                     }
-                    emit!(buffer, "];");
-                    emit!(buffer, "deserializer.deserialize_struct(\"ObjectStore\", FIELDS, ObjectStoreVisitor)");
-                    emit!(buffer, "}}}}\n");
                 }
 
-
                 // impl ObjectStore
                 emit!(buffer, "impl ObjectStore {{");
                 emit!(buffer, "pub fn new() -> Self {{");
@@ -1168,6 +1171,18 @@ impl CodeWriter for DomainStore {
                             emit!(buffer, "{}_id_by_name: HashMap::default(),", obj.as_ident());
                         }
                     }
+                    for index in object_indices(obj, config, woog, domain)? {
+                        emit!(buffer, "{}: HashMap::default(),", index.name);
+                    }
+                }
+                if has_unique_indices(&objects, config) {
+                    emit!(buffer, "index_conflicts: Vec::new(),");
+                }
+                if timestamp {
+                    emit!(buffer, "tombstones: HashMap::default(),");
+                }
+                if let UuidMode::Seeded = config.get_uuid_mode() {
+                    emit!(buffer, "uuid_seed: 0,");
                 }
                 emit!(buffer, "}};");
                 emit!(buffer, "");
@@ -1214,6 +1229,27 @@ impl CodeWriter for DomainStore {
                 emit!(buffer, "");
                 // End of new
 
+                // This is where the seeded UUIDs come from. It's a counter that
+                // the constructors bump when they make a new id. Set it before
+                // creating anything, and you'll get the same ids every time.
+                if let UuidMode::Seeded = config.get_uuid_mode() {
+                    emit!(buffer, "/// Set the seed used to generate deterministic UUIDs.");
+                    emit!(buffer, "///");
+                    emit!(buffer, "/// Call this before creating any instances. The same seed, and the same");
+                    emit!(buffer, "/// sequence of constructor calls, yields the same ids.");
+                    emit!(buffer, "pub fn seed_uuids(&mut self, seed: u64) {{");
+                    emit!(buffer, "self.uuid_seed = seed;");
+                    emit!(buffer, "}}");
+                    emit!(buffer, "");
+                    emit!(buffer, "/// Return the next value in the UUID seed sequence.");
+                    emit!(buffer, "pub fn next_uuid_seed(&mut self) -> u64 {{");
+                    emit!(buffer, "let seed = self.uuid_seed;");
+                    emit!(buffer, "self.uuid_seed = self.uuid_seed.wrapping_add(1);");
+                    emit!(buffer, "seed");
+                    emit!(buffer, "}}");
+                    emit!(buffer, "");
+                }
+
                 self.generate_store(buffer, &objects, timestamp, module, config, woog, domain)?;
 
                 emit!(buffer, "");
@@ -1226,6 +1262,23 @@ impl CodeWriter for DomainStore {
 
                 emit!(buffer, "}}");
 
+                if timestamp {
+                    emit!(buffer, "");
+                    emit_object_kind(buffer, &objects, module, woog, domain)?;
+                }
+
+                if has_unique_indices(&objects, config) {
+                    emit!(buffer, "");
+                    emit_index_conflict(buffer, module, config)?;
+                }
+
+                if persist {
+                    emit!(buffer, "");
+                    emit_store_header(buffer, module, config, domain)?;
+                    emit!(buffer, "");
+                    emit_store_instance(buffer, &objects, timestamp, module, config, woog, domain)?;
+                }
+
                 Ok(())
             },
         )?;
@@ -1234,6 +1287,156 @@ impl CodeWriter for DomainStore {
     }
 }
 
+/// Emit `iter_{obj}_changed_since`
+///
+/// This is just like `iter_{obj}`, but it only yields instances that were
+/// inter'd after `t`.
+fn emit_iter_changed_since(
+    buffer: &mut Buffer,
+    obj: &Object,
+    config: &GraceConfig,
+    woog: &WoogStore,
+    domain: &Domain,
+) -> Result<()> {
+    let is_uber = config.is_uber_store();
+    let obj_ident = obj.as_ident();
+    let obj_type = obj.as_type(&Ownership::new_borrowed(), woog, domain);
+
+    emit!(
+        buffer,
+        "/// Get an iterator over the [`{obj_type}`]s that changed since `t`."
+    );
+    emit!(buffer, "///");
+    if is_uber {
+        let (read, _write) = get_uber_read_write(config);
+        let store_type = get_value_wrapper(is_uber, config, obj, woog, domain);
+        let (asyncness, iterator, values) = match config.get_uber_store() {
+            Some(UberStoreOptions::AsyncRwLock) => {
+                ("async ", "futures::Stream", "futures::stream::iter(values)")
+            }
+            _ => ("", "Iterator", "values.into_iter()"),
+        };
+        emit!(
+            buffer,
+            "pub {asyncness}fn iter_{obj_ident}_changed_since(&self, t: SystemTime) -> impl {iterator}<Item = {store_type}> + '_ {{"
+        );
+        emit!(
+            buffer,
+            "let values: Vec<{store_type}> = self.{obj_ident}{read}.values().filter(|{obj_ident}| {obj_ident}.1 > t).map(|{obj_ident}| {obj_ident}.0.clone()).collect();"
+        );
+        emit!(buffer, "{values}");
+    } else {
+        emit!(
+            buffer,
+            "pub fn iter_{obj_ident}_changed_since(&self, t: SystemTime) -> impl Iterator<Item = &{obj_type}> {{"
+        );
+        emit!(
+            buffer,
+            "self.{obj_ident}.values().filter(move |{obj_ident}| {obj_ident}.1 > t).map(|{obj_ident}| &{obj_ident}.0)"
+        );
+    }
+    emit!(buffer, "}}");
+    emit!(buffer, "");
+
+    Ok(())
+}
+
+/// Emit `changes_since`
+///
+/// This is for incremental consumers, so that they can sync without diffing
+/// the whole store. Exorcised instances are found in the tombstones.
+fn emit_changes_since(
+    buffer: &mut Buffer,
+    objects: &[&&Object],
+    config: &GraceConfig,
+    woog: &WoogStore,
+    domain: &Domain,
+) -> Result<()> {
+    let is_uber = config.is_uber_store();
+    let asyncness = match config.get_uber_store() {
+        Some(UberStoreOptions::AsyncRwLock) => "async ",
+        _ => "",
+    };
+
+    emit!(
+        buffer,
+        "/// Get the ids of everything that changed since `t`."
+    );
+    emit!(buffer, "///");
+    emit!(
+        buffer,
+        "/// Instances that were inter'd after `t` are included, as are instances that"
+    );
+    emit!(
+        buffer,
+        "/// were exorcised after `t`. Exhume the id to tell the two apart."
+    );
+    emit!(
+        buffer,
+        "pub {asyncness}fn changes_since(&self, t: SystemTime) -> Vec<(ObjectKind, Uuid)> {{"
+    );
+    emit!(buffer, "let mut changes = Vec::new();");
+    for obj in objects {
+        let obj_ident = obj.as_ident();
+        let obj_type = obj.as_type(&Ownership::new_borrowed(), woog, domain);
+        let read = if is_uber {
+            get_uber_read_write(config).0
+        } else {
+            ""
+        };
+
+        emit!(
+            buffer,
+            "changes.extend(self.{obj_ident}{read}.iter().filter(|(_, {obj_ident})| {obj_ident}.1 > t).map(|(id, _)| (ObjectKind::{obj_type}, Uuid::from(*id))));"
+        );
+    }
+    emit!(
+        buffer,
+        "changes.extend(self.tombstones.iter().filter(|(_, (_, when))| *when > t).map(|(id, (kind, _))| (*kind, *id)));"
+    );
+    emit!(buffer, "changes");
+    emit!(buffer, "}}");
+    emit!(buffer, "");
+
+    Ok(())
+}
+
+/// Emit `ObjectKind`
+///
+/// This names the things in the store, for `changes_since`.
+fn emit_object_kind(
+    buffer: &mut Buffer,
+    objects: &[&&Object],
+    module: &str,
+    woog: &WoogStore,
+    domain: &Domain,
+) -> Result<()> {
+    buffer.block(
+        DirectiveKind::IgnoreOrig,
+        format!("{}-object-kind", module),
+        |buffer| {
+            emit!(buffer, "/// The kinds of things in the store");
+            emit!(
+                buffer,
+                "#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]"
+            );
+            emit!(buffer, "pub enum ObjectKind {{");
+            for obj in objects {
+                emit!(
+                    buffer,
+                    "{},",
+                    obj.as_type(&Ownership::new_borrowed(), woog, domain)
+                );
+            }
+            emit!(buffer, "}}");
+
+            Ok(())
+        },
+    )?;
+
+    Ok(())
+}
+
 /// Check to see if an object has a name attribute
 ///
 /// I'm using this to generate "by name" lookup for objects that have a name.
@@ -1246,7 +1449,10 @@ impl CodeWriter for DomainStore {
 /// // 🚧 This needs to return the type of string manipulation to use on the
 /// name. Or maybe we don't do one at all, and let the end user sort it out.
 /// I sort of like that option better. I wonder how many errors will ensue...
-fn object_has_name(obj: &Object, _domain: &Domain) -> bool {
+///
+/// They can be marked now, with `indices` in the object's configuration. This
+/// sticks around because there are stores out there that use `_id_by_name`.
+pub(crate) fn object_has_name(obj: &Object, _domain: &Domain) -> bool {
     obj.name == "Object"
         || obj.name == "Struct"
         || obj.name == "Function"
@@ -1256,241 +1462,1153 @@ fn object_has_name(obj: &Object, _domain: &Domain) -> bool {
         || obj.name == "Plugin"
 }
 
-fn generate_store_persistence(
-    buffer: &mut Buffer,
-    objects: &[&&Object],
-    timestamp: bool,
-    is_meta: bool,
-    module: &str,
+/// An index, as the store sees it
+pub(crate) struct StoreIndex {
+    /// The name of the map in the store, e.g., `foo_by_name`
+    pub(crate) name: String,
+    unique: bool,
+    key: Vec<IndexKey>,
+}
+
+/// One attribute of an index key
+struct IndexKey {
+    ident: String,
+    ty: String,
+    kind: IndexKeyKind,
+}
+
+/// How we get at the value of an attribute
+enum IndexKeyKind {
+    Copy,
+    String,
+    Clone,
+}
+
+impl StoreIndex {
+    /// The key type of the map
+    fn key_type(&self) -> String {
+        if self.key.len() == 1 {
+            self.key[0].ty.clone()
+        } else {
+            let types = self
+                .key
+                .iter()
+                .map(|key| key.ty.as_str())
+                .collect::<Vec<_>>();
+            format!("({})", types.join(", "))
+        }
+    }
+
+    /// The key for an instance, named `instance`
+    fn key_of(&self, instance: &str) -> String {
+        let values = self
+            .key
+            .iter()
+            .map(|key| match key.kind {
+                IndexKeyKind::Copy => format!("{instance}.{}", key.ident),
+                IndexKeyKind::String | IndexKeyKind::Clone => {
+                    format!("{instance}.{}.clone()", key.ident)
+                }
+            })
+            .collect::<Vec<_>>();
+
+        if values.len() == 1 {
+            values[0].clone()
+        } else {
+            format!("({})", values.join(", "))
+        }
+    }
+
+    /// True if the index has `instance` under `key`
+    fn holds(&self, instance: &str) -> String {
+        if self.unique {
+            format!("self.{}.get(&key) == Some(&{instance}.id)", self.name)
+        } else {
+            format!(
+                "self.{}.get(&key).map_or(false, |ids| ids.contains(&{instance}.id))",
+                self.name
+            )
+        }
+    }
+
+    /// True if the index doesn't have `instance` under `key`
+    fn lacks(&self, instance: &str) -> String {
+        if self.unique {
+            format!("self.{}.get(&key) != Some(&{instance}.id)", self.name)
+        } else {
+            format!(
+                "!self.{}.get(&key).map_or(false, |ids| ids.contains(&{instance}.id))",
+                self.name
+            )
+        }
+    }
+
+    /// Find the key that the index has `instance` under, by id
+    fn find_key(&self, instance: &str) -> String {
+        if self.unique {
+            format!(
+                "self.{}.iter().find(|(_, id)| **id == {instance}.id).map(|(key, _)| key.clone())",
+                self.name
+            )
+        } else {
+            format!(
+                "self.{}.iter().find(|(_, ids)| ids.contains(&{instance}.id)).map(|(key, _)| key.clone())",
+                self.name
+            )
+        }
+    }
+
+    /// The parameters of the lookup methods
+    fn parameters(&self) -> String {
+        self.key
+            .iter()
+            .map(|key| match key.kind {
+                IndexKeyKind::Copy => format!("{}: {}", key.ident, key.ty),
+                IndexKeyKind::String => format!("{}: &str", key.ident),
+                IndexKeyKind::Clone => format!("{}: &{}", key.ident, key.ty),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// The argument to `get`, built from the parameters
+    fn lookup(&self) -> String {
+        if self.key.len() == 1 {
+            match self.key[0].kind {
+                IndexKeyKind::Copy => format!("&{}", self.key[0].ident),
+                IndexKeyKind::String | IndexKeyKind::Clone => self.key[0].ident.clone(),
+            }
+        } else {
+            let values = self
+                .key
+                .iter()
+                .map(|key| match key.kind {
+                    IndexKeyKind::Copy => key.ident.clone(),
+                    IndexKeyKind::String => format!("{}.to_owned()", key.ident),
+                    IndexKeyKind::Clone => format!("{}.clone()", key.ident),
+                })
+                .collect::<Vec<_>>();
+            format!("&({})", values.join(", "))
+        }
+    }
+
+    /// For doc comments: "`kind` and `size`"
+    fn describe(&self) -> String {
+        self.key
+            .iter()
+            .map(|key| format!("`{}`", key.ident))
+            .collect::<Vec<_>>()
+            .join(" and ")
+    }
+}
+
+/// Gather up the indices configured for an object
+///
+/// This is where the configuration is checked. An index needs attributes, the
+/// attributes need to exist, and they need to be something that we can hash.
+pub(crate) fn object_indices(
+    obj: &Object,
     config: &GraceConfig,
     woog: &WoogStore,
     domain: &Domain,
-) -> Result<()> {
-    buffer.block(
-        DirectiveKind::IgnoreOrig,
-        format!("{}-object-store-persistence", module),
-        |buffer| {
-            let is_uber = config.is_uber_store();
+) -> Result<Vec<StoreIndex>> {
+    let mut result = Vec::new();
 
-            emit!(buffer, "/// Persist the store.");
-            emit!(buffer, "///");
-            emit!(
-                buffer,
-                "/// The store is persisted as a a bincode file."
-            );
-            emit!(
-                buffer,
-                "pub fn persist_bincode<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {{"
+    if let Some(indices) = config.get_indices(&obj.id) {
+        ensure!(
+            !local_object_is_enum(obj, config, domain),
+            CompilerSnafu {
+                description: format!(
+                    "{} is an enum, and enums don't have attributes to index",
+                    obj.name
+                )
+            }
+        );
+
+        let attrs = obj.r1_attribute(domain.sarzak());
+        for index in indices {
+            ensure!(
+                !index.attributes.is_empty(),
+                CompilerSnafu {
+                    description: format!("an index on {} has no attributes", obj.name)
+                }
             );
-            emit!(buffer, "let path = path.as_ref();");
-            emit!(buffer, "let mut bin_file = fs::File::create(path)?;");
-            emit!(
-                buffer,
-                "let encoded: Vec<u8> = bincode::serialize(&self).unwrap();"
+
+            let mut key = Vec::new();
+            for name in &index.attributes {
+                let attr = attrs.iter().find(|attr| &attr.name == name);
+                ensure!(
+                    attr.is_some(),
+                    CompilerSnafu {
+                        description: format!("{} has no attribute `{name}` to index", obj.name)
+                    }
+                );
+                let attr = attr.unwrap();
+
+                let ty = attr.r2_ty(domain.sarzak())[0];
+                let kind = match ty {
+                    Ty::Boolean(_) | Ty::Integer(_) | Ty::SUuid(_) => IndexKeyKind::Copy,
+                    Ty::SString(_) => IndexKeyKind::String,
+                    Ty::Object(_) | Ty::External(_) => IndexKeyKind::Clone,
+                    Ty::Float(_) => {
+                        return CompilerSnafu {
+                            description: format!(
+                                "{}.{} is a float, and floats can't be used as an index",
+                                obj.name, attr.name
+                            ),
+                        }
+                        .fail();
+                    }
+                };
+
+                key.push(IndexKey {
+                    ident: attr.as_ident(),
+                    ty: ty.as_type(&Ownership::new_borrowed(), woog, domain),
+                    kind,
+                });
+            }
+
+            let name = format!(
+                "{}_by_{}",
+                obj.as_ident(),
+                key.iter()
+                    .map(|key| key.ident.as_str())
+                    .collect::<Vec<_>>()
+                    .join("_")
             );
-            emit!(buffer, "bin_file.write_all(&encoded)?;");
-            emit!(buffer, "Ok(())");
-            emit!(buffer, "}}\n");
 
-            emit!(buffer, "/// Persist the store.");
-            emit!(buffer, "///");
+            result.push(StoreIndex {
+                name,
+                unique: index.unique,
+                key,
+            });
+        }
+    }
+
+    Ok(result)
+}
+
+/// Does anything in the store have an index?
+pub(crate) fn has_indices(objects: &[&&Object], config: &GraceConfig) -> bool {
+    objects
+        .iter()
+        .any(|obj| config.get_indices(&obj.id).is_some())
+}
+
+pub(crate) fn has_unique_indices(objects: &[&&Object], config: &GraceConfig) -> bool {
+    objects.iter().any(|obj| {
+        config
+            .get_indices(&obj.id)
+            .map(|indices| indices.iter().any(|index| index.unique))
+            .unwrap_or(false)
+    })
+}
+
+/// The type of the ids that the indices map to
+///
+/// The Vec store uses the instance's slot.
+fn index_id_type(obj: &Object, config: &GraceConfig, woog: &WoogStore, domain: &Domain) -> String {
+    if let crate::options::OptimizationLevel::Vec = config.get_optimization_level() {
+        "usize".to_owned()
+    } else {
+        object_id_type(obj, config, woog, domain)
+    }
+}
+
+/// True if the store derives `Serialize` and `Deserialize`
+///
+/// Those that don't can't have `#[serde(skip)]` on their fields.
+pub(crate) fn store_derives_serde(config: &GraceConfig) -> bool {
+    match config.get_uber_store() {
+        Some(UberStoreOptions::AsyncRwLock) => false,
+        Some(UberStoreOptions::NDRwLock) => !matches!(
+            config.get_optimization_level(),
+            crate::options::OptimizationLevel::Vec
+        ),
+        _ => true,
+    }
+}
+
+/// Emit the maps that back an object's indices
+///
+/// The indices aren't persisted. They are rebuilt, by `reindex`, when the
+/// store is loaded. So we skip them when serializing.
+pub(crate) fn emit_index_fields(
+    buffer: &mut Buffer,
+    obj: &Object,
+    config: &GraceConfig,
+    woog: &WoogStore,
+    domain: &Domain,
+) -> Result<()> {
+    let id_type = index_id_type(obj, config, woog, domain);
+
+    for index in object_indices(obj, config, woog, domain)? {
+        let key_type = index.key_type();
+
+        if store_derives_serde(config) {
+            emit!(buffer, "#[serde(skip)]");
+        }
+        if index.unique {
+            emit!(buffer, "{}: HashMap<{key_type}, {id_type}>,", index.name);
+        } else {
             emit!(
                 buffer,
-                "/// The store is persisted as a directory of JSON files. The intention"
+                "{}: HashMap<{key_type}, Vec<{id_type}>>,",
+                index.name
             );
+        }
+    }
+
+    Ok(())
+}
+
+/// Document what `inter_{obj}` does with a unique index conflict
+///
+/// Interring doesn't fail, so the conflict is only found by asking for it.
+pub(crate) fn emit_inter_index_doc(buffer: &mut Buffer, indices: &[StoreIndex]) -> Result<()> {
+    if indices.iter().any(|index| index.unique) {
+        emit!(
+            buffer,
+            "/// If it has the same key as another instance in a unique index, it's still"
+        );
+        emit!(
+            buffer,
+            "/// interred, but it's left out of that index. Check [`ObjectStore::index_conflicts`]"
+        );
+        emit!(buffer, "/// to find out.");
+        emit!(buffer, "///");
+    }
+
+    Ok(())
+}
+
+/// Emit the methods that maintain, and use, an object's indices
+///
+/// `index_{obj}`, `rekey_{obj}` and `unindex_{obj}` are called by `inter_{obj}`
+/// and `exorcise_{obj}`. A unique index gets `exhume_{obj}_by_{attrs}`, and the
+/// others get `iter_{obj}_by_{attrs}`.
+///
+/// The uber stores hand out shared pointers, and nothing stops you from
+/// changing an indexed attribute in place. The index won't know until the
+/// instance is interred again, or exorcised, and then it's found by id. Until
+/// then a lookup by the new key misses it. `reindex` puts everything right.
+pub(crate) fn emit_index_methods(
+    buffer: &mut Buffer,
+    obj: &Object,
+    indices: &[StoreIndex],
+    config: &GraceConfig,
+    woog: &WoogStore,
+    domain: &Domain,
+) -> Result<()> {
+    if indices.is_empty() {
+        return Ok(());
+    }
+
+    let is_uber = config.is_uber_store();
+    let obj_ident = obj.as_ident();
+    let obj_type = obj.as_type(&Ownership::new_borrowed(), woog, domain);
+    let thing = get_value_wrapper(is_uber, config, obj, woog, domain);
+    let ids = ConflictIds {
+        vec: matches!(
+            config.get_optimization_level(),
+            crate::options::OptimizationLevel::Vec
+        ),
+        id_type: index_id_type(obj, config, woog, domain),
+    };
+    let (asyncness, dot_await, iterator, values) = match config.get_uber_store() {
+        Some(UberStoreOptions::AsyncRwLock) => (
+            "async ",
+            ".await",
+            "futures::Stream",
+            "futures::stream::iter(values)",
+        ),
+        _ => ("", "", "Iterator", "values.into_iter()"),
+    };
+
+    emit!(buffer, "/// Add [`{obj_type}`] to its indices.");
+    emit!(buffer, "///");
+    emit!(
+        buffer,
+        "/// A unique index that already has the key, for some other instance, is"
+    );
+    emit!(buffer, "/// left alone, and the conflict is recorded.");
+    emit!(
+        buffer,
+        "fn index_{obj_ident}(&mut self, {obj_ident}: &{obj_type}) {{"
+    );
+    for index in indices {
+        emit!(buffer, "let key = {};", index.key_of(&obj_ident));
+        emit_index_key(buffer, index, &obj_ident, &ids)?;
+    }
+    emit!(buffer, "}}");
+    emit!(buffer, "");
+
+    emit!(buffer, "/// Remove [`{obj_type}`] from its indices.");
+    emit!(buffer, "///");
+    emit!(
+        buffer,
+        "/// If it held a key in a unique index, the first instance that was left out"
+    );
+    emit!(buffer, "/// in its favor takes its place.");
+    if is_uber {
+        emit!(
+            buffer,
+            "/// It may have been changed in place, so if it's not under its current key"
+        );
+        emit!(buffer, "/// it's looked for by id.");
+    }
+    emit!(
+        buffer,
+        "fn unindex_{obj_ident}(&mut self, {obj_ident}: &{obj_type}) {{"
+    );
+    for index in indices {
+        emit!(buffer, "let key = {};", index.key_of(&obj_ident));
+        if is_uber {
+            emit!(buffer, "let key = if {} {{", index.holds(&obj_ident));
+            emit!(buffer, "Some(key)");
+            emit!(buffer, "}} else {{");
+            emit!(buffer, "{}", index.find_key(&obj_ident));
+            emit!(buffer, "}};");
+            emit!(buffer, "if let Some(key) = key {{");
+            emit_unindex_key(buffer, index, &obj_ident, &ids)?;
+            emit!(buffer, "}}");
+        } else {
+            emit_unindex_key(buffer, index, &obj_ident, &ids)?;
+        }
+        emit_prune_duplicate(buffer, index, &obj_ident, &ids)?;
+    }
+    emit!(buffer, "}}");
+    emit!(buffer, "");
+
+    // A Vec store instance is never interred twice, it gets a new slot.
+    if !ids.vec {
+        emit_rekey(buffer, obj, indices, is_uber, &ids, woog, domain)?;
+    }
+
+    for index in indices {
+        let name = &index.name;
+        let parameters = index.parameters();
+        let lookup = index.lookup();
+
+        if index.unique {
             emit!(
                 buffer,
-                "/// is that this directory can be checked into version control."
+                "/// Exhume [`{obj_type}`] from the store by {}.",
+                index.describe()
             );
+            emit!(buffer, "///");
+            if is_uber {
+                emit!(
+                    buffer,
+                    "pub {asyncness}fn exhume_{name}(&self, {parameters}) -> Option<{thing}> {{"
+                );
+            } else {
+                emit!(
+                    buffer,
+                    "pub fn exhume_{name}(&self, {parameters}) -> Option<&{thing}> {{"
+                );
+            }
+            emit!(buffer, "let id = *self.{name}.get({lookup})?;");
+            emit!(buffer, "self.exhume_{obj_ident}(&id){dot_await}");
+            emit!(buffer, "}}");
+        } else {
             emit!(
                 buffer,
-                "/// In fact, I intend to add automagic git integration as an option."
+                "/// Get an iterator over the [`{obj_type}`] instances with the given {}.",
+                index.describe()
             );
+            emit!(buffer, "///");
             if is_uber {
-                use UberStoreOptions::*;
-                match config.get_uber_store().unwrap() {
-                    Disabled => unreachable!(),
-                    AsyncRwLock => {
-                        emit!(
-                            buffer,
-                            "pub async fn persist<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {{"
-                        );
-                    }
-                    _ => {
-                        emit!(
-                            buffer,
-                            "pub fn persist<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {{"
-                        );
-                    }
-                }
+                emit!(
+                    buffer,
+                    "pub {asyncness}fn iter_{name}(&self, {parameters}) -> impl {iterator}<Item = {thing}> {{"
+                );
+                emit!(buffer, "let mut values = Vec::new();");
+                emit!(
+                    buffer,
+                    "for id in self.{name}.get({lookup}).into_iter().flatten() {{"
+                );
+                emit!(
+                    buffer,
+                    "if let Some({obj_ident}) = self.exhume_{obj_ident}(id){dot_await} {{"
+                );
+                emit!(buffer, "values.push({obj_ident});");
+                emit!(buffer, "}}");
+                emit!(buffer, "}}");
+                emit!(buffer, "{values}");
             } else {
                 emit!(
                     buffer,
-                    "pub fn persist<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {{"
+                    "pub fn iter_{name}(&self, {parameters}) -> impl Iterator<Item = &{thing}> + '_ {{"
+                );
+                emit!(
+                    buffer,
+                    "self.{name}.get({lookup}).into_iter().flatten().filter_map(move |id| self.exhume_{obj_ident}(id))"
                 );
             }
-            emit!(buffer, "let path = path.as_ref();");
-            emit!(buffer, "fs::create_dir_all(path)?;");
-            emit!(buffer, "");
-            // This is such a great joke! 🤣
-            emit!(buffer, "let path = path.join(\"{}.json\");", domain.name());
-            emit!(buffer, "fs::create_dir_all(&path)?;");
-            emit!(buffer, "");
+            emit!(buffer, "}}");
+        }
+        emit!(buffer, "");
+    }
 
-            for obj in objects {
-                let obj_ident = obj.as_ident();
-                let obj_type = obj.as_type(&Ownership::new_borrowed(), woog, domain);
+    Ok(())
+}
 
-                emit!(buffer, "// Persist {}.", obj.name);
-                emit!(buffer, "{{");
-                emit!(buffer, "let path = path.join(\"{}\");", obj.as_ident());
-                emit!(buffer, "fs::create_dir_all(&path)?;");
-                if timestamp {
-                    if is_uber {
-                        let (read, _write) = get_uber_read_write(config);
-                        emit!(
-                            buffer,
-                            "for {obj_ident}_tuple in self.{obj_ident}{read}.values() {{"
-                        );
-                    } else {
-                        emit!(
-                            buffer,
-                            "for {obj_ident}_tuple in self.{obj_ident}.values() {{"
-                        );
-                    }
-                    let id = if local_object_is_enum(obj, config, domain) {
-                        "id()"
-                    } else {
-                        "id"
-                    };
+/// How to get from an index's ids to the ids in an `IndexConflict`, and back
+///
+/// They are the same in the Vec store, slots. Everyone else's conflicts are
+/// `Uuid`s, and their ids may be typed.
+struct ConflictIds {
+    vec: bool,
+    id_type: String,
+}
 
-                    if is_uber {
-                        let (read, _write) = get_uber_read_write(config);
-                        emit!(
-                            buffer,
-                            "let path = path.join(format!(\"{{}}.json\", {obj_ident}_tuple.0{read}.{id}));"
-                        );
-                    } else {
-                        emit!(
-                            buffer,
-                            "let path = path.join(format!(\"{{}}.json\", {obj_ident}_tuple.0.{id}));"
-                        );
-                    };
+impl ConflictIds {
+    /// The conflict id for `id`, from the index
+    fn to_conflict(&self, id: &str) -> String {
+        if self.vec {
+            id.to_owned()
+        } else {
+            format!("Uuid::from({id})")
+        }
+    }
 
-                    emit!(buffer, "if path.exists() {{");
-                    emit!(buffer, "let file = fs::File::open(&path)?;");
-                    emit!(buffer, "let reader = io::BufReader::new(file);");
+    /// The index id for `id`, from a conflict
+    fn from_conflict(&self, id: &str) -> String {
+        if self.vec || self.id_type == "Uuid" {
+            id.to_owned()
+        } else {
+            format!("{}::from({id})", self.id_type)
+        }
+    }
+}
 
-                    if is_uber {
-                        let store_type = get_value_wrapper(is_uber, config, obj, woog, domain);
-                        use UberStoreOptions::*;
-                        match config.get_uber_store().unwrap() {
-                            Disabled => unreachable!(),
-                            AsyncRwLock => {
-                                emit!(
-                                    buffer,
-                                    "let on_disk: ({store_type}, SystemTime) = serde_json::from_reader(reader).map(|(a, b)| (Arc::new(RwLock::new(a)), b))?;"
-                                );
+/// Emit `rekey_{obj}`, which `inter_{obj}` calls when it replaces an instance
+///
+/// An index whose key hasn't changed is left alone, so that the instance keeps
+/// its place in a unique index. The uber stores' instances may have been
+/// changed in place, so what they were interred as is no help. Instead, an
+/// index that doesn't have it under its current key is searched by id.
+fn emit_rekey(
+    buffer: &mut Buffer,
+    obj: &Object,
+    indices: &[StoreIndex],
+    is_uber: bool,
+    ids: &ConflictIds,
+    woog: &WoogStore,
+    domain: &Domain,
+) -> Result<()> {
+    let obj_ident = obj.as_ident();
+    let obj_type = obj.as_type(&Ownership::new_borrowed(), woog, domain);
 
-                            }
-                            _ => {
-                                emit!(
-                                    buffer,
-                                    "let on_disk: ({store_type}, SystemTime) = serde_json::from_reader(reader)?;"
-                                );
-                            }
-                        }
-                    } else {
-                        emit!(
-                            buffer,
-                            "let on_disk: ({obj_type}, SystemTime) = serde_json::from_reader(reader)?;"
-                        );
-                    }
+    emit!(
+        buffer,
+        "/// Move [`{obj_type}`] to the keys it has now, from the ones it was interred with."
+    );
+    emit!(buffer, "///");
+    emit!(
+        buffer,
+        "/// An index whose key hasn't changed is left alone, so it keeps its place in"
+    );
+    emit!(buffer, "/// a unique index.");
+    if is_uber {
+        emit!(
+            buffer,
+            "fn rekey_{obj_ident}(&mut self, {obj_ident}: &{obj_type}) {{"
+        );
+    } else {
+        emit!(
+            buffer,
+            "fn rekey_{obj_ident}(&mut self, old: &{obj_type}, {obj_ident}: &{obj_type}) {{"
+        );
+    }
+    for index in indices {
+        if is_uber {
+            emit!(buffer, "let key = {};", index.key_of(&obj_ident));
+            emit!(buffer, "if {} {{", index.lacks(&obj_ident));
+            emit!(buffer, "let old = {};", index.find_key(&obj_ident));
+            emit!(buffer, "if let Some(key) = old {{");
+            emit_unindex_key(buffer, index, &obj_ident, ids)?;
+            emit!(buffer, "}}");
+            emit_prune_duplicate(buffer, index, &obj_ident, ids)?;
+            emit_index_key(buffer, index, &obj_ident, ids)?;
+            emit!(buffer, "}}");
+        } else {
+            emit!(buffer, "let key = {};", index.key_of("old"));
+            emit!(buffer, "if key != {} {{", index.key_of(&obj_ident));
+            emit_unindex_key(buffer, index, &obj_ident, ids)?;
+            emit_prune_duplicate(buffer, index, &obj_ident, ids)?;
+            emit!(buffer, "let key = {};", index.key_of(&obj_ident));
+            emit_index_key(buffer, index, &obj_ident, ids)?;
+            emit!(buffer, "}}");
+        }
+    }
+    emit!(buffer, "}}");
+    emit!(buffer, "");
 
-                    if is_uber {
-                        let (read, _write) = get_uber_read_write(config);
-                        emit!(buffer,
-                            "if on_disk.0{read}.to_owned() != {obj_ident}_tuple.0{read}.to_owned() {{"
-                        );
-                    } else {
-                        emit!(buffer, "if on_disk.0 != {obj_ident}_tuple.0 {{");
-                    }
+    Ok(())
+}
 
-                    emit!(buffer, "let file = fs::File::create(path)?;");
-                    emit!(buffer, "let mut writer = io::BufWriter::new(file);");
-                    if is_uber {
-                        use UberStoreOptions::*;
-                        let _store_type = get_value_wrapper(is_uber, config, obj, woog, domain);
-                        match config.get_uber_store().unwrap() {
-                            Disabled => unreachable!(),
-                            AsyncRwLock => {
-                                emit!(
-                                    buffer,
-                                    "serde_json::to_writer_pretty(&mut writer, &(&{obj_ident}_tuple.0.read().await.to_owned(), &{obj_ident}_tuple.1))?;"
-                                );
-                            }
-                            _ => {
-                                emit!(
-                                    buffer,
-                                    "serde_json::to_writer_pretty(&mut writer, &{obj_ident}_tuple)?;"
-                                );
-                            }
-                        }
-                    } else {
+/// File `instance` under `key`, in a single index
+fn emit_index_key(
+    buffer: &mut Buffer,
+    index: &StoreIndex,
+    instance: &str,
+    ids: &ConflictIds,
+) -> Result<()> {
+    let name = &index.name;
+
+    if index.unique {
+        emit!(buffer, "match self.{name}.get(&key).copied() {{");
+        emit!(buffer, "Some(id) if id != {instance}.id => {{");
+        emit!(buffer, "self.index_conflicts.push(IndexConflict {{");
+        emit!(buffer, "index: \"{name}\",");
+        emit!(buffer, "existing: {},", ids.to_conflict("id"));
+        emit!(
+            buffer,
+            "duplicate: {},",
+            ids.to_conflict(&format!("{instance}.id"))
+        );
+        emit!(buffer, "}});");
+        emit!(buffer, "}}");
+        emit!(buffer, "Some(_) => {{}}");
+        emit!(buffer, "None => {{");
+        emit!(buffer, "self.{name}.insert(key, {instance}.id);");
+        emit!(buffer, "}}");
+        emit!(buffer, "}}");
+    } else {
+        emit!(buffer, "let ids = self.{name}.entry(key).or_default();");
+        emit!(buffer, "if !ids.contains(&{instance}.id) {{");
+        emit!(buffer, "ids.push({instance}.id);");
+        emit!(buffer, "}}");
+    }
+
+    Ok(())
+}
+
+/// Take `instance` out from under `key`, in a single index
+///
+/// If it held the key in a unique index, the first conflict recorded against
+/// it is promoted, and the rest now conflict with the promoted instance.
+fn emit_unindex_key(
+    buffer: &mut Buffer,
+    index: &StoreIndex,
+    instance: &str,
+    ids: &ConflictIds,
+) -> Result<()> {
+    let name = &index.name;
+
+    if index.unique {
+        emit!(
+            buffer,
+            "if self.{name}.get(&key) == Some(&{instance}.id) {{"
+        );
+        emit!(buffer, "self.{name}.remove(&key);");
+        emit!(
+            buffer,
+            "let existing = {};",
+            ids.to_conflict(&format!("{instance}.id"))
+        );
+        emit!(
+            buffer,
+            "let at = self.index_conflicts.iter().position(|conflict| conflict.index == \"{name}\" && conflict.existing == existing);"
+        );
+        emit!(buffer, "if let Some(at) = at {{");
+        emit!(
+            buffer,
+            "let promoted = self.index_conflicts.remove(at).duplicate;"
+        );
+        emit!(buffer, "for conflict in &mut self.index_conflicts {{");
+        emit!(
+            buffer,
+            "if conflict.index == \"{name}\" && conflict.existing == existing {{"
+        );
+        emit!(buffer, "conflict.existing = promoted;");
+        emit!(buffer, "}}");
+        emit!(buffer, "}}");
+        emit!(
+            buffer,
+            "self.{name}.insert(key, {});",
+            ids.from_conflict("promoted")
+        );
+        emit!(buffer, "}}");
+        emit!(buffer, "}}");
+    } else {
+        emit!(buffer, "if let Some(ids) = self.{name}.get_mut(&key) {{");
+        emit!(buffer, "ids.retain(|id| *id != {instance}.id);");
+        emit!(buffer, "if ids.is_empty() {{");
+        emit!(buffer, "self.{name}.remove(&key);");
+        emit!(buffer, "}}");
+        emit!(buffer, "}}");
+    }
+
+    Ok(())
+}
+
+/// Forget any conflict that `instance` was the duplicate in
+///
+/// It's either going away, or about to be indexed again, which records it
+/// again if it still conflicts. Only unique indices have conflicts.
+fn emit_prune_duplicate(
+    buffer: &mut Buffer,
+    index: &StoreIndex,
+    instance: &str,
+    ids: &ConflictIds,
+) -> Result<()> {
+    if index.unique {
+        emit!(
+            buffer,
+            "self.index_conflicts.retain(|conflict| conflict.index != \"{}\" || conflict.duplicate != {});",
+            index.name,
+            ids.to_conflict(&format!("{instance}.id"))
+        );
+    }
+
+    Ok(())
+}
+
+/// Emit `reindex`, and `index_conflicts`
+///
+/// The indices are built from scratch, from the instances in the store.
+pub(crate) fn emit_reindex_method(
+    buffer: &mut Buffer,
+    objects: &[&&Object],
+    timestamp: bool,
+    config: &GraceConfig,
+    woog: &WoogStore,
+    domain: &Domain,
+) -> Result<()> {
+    let is_uber = config.is_uber_store();
+    let asyncness = match config.get_uber_store() {
+        Some(UberStoreOptions::AsyncRwLock) => "async ",
+        _ => "",
+    };
+    let unique = has_unique_indices(objects, config);
+
+    emit!(
+        buffer,
+        "/// Rebuild the indices from the instances in the store."
+    );
+    emit!(buffer, "///");
+    emit!(
+        buffer,
+        "/// The indices aren't persisted, so this is called when the store is loaded."
+    );
+    emit!(buffer, "pub {asyncness}fn reindex(&mut self) {{");
+    if unique {
+        emit!(buffer, "self.index_conflicts.clear();");
+    }
+    for obj in objects {
+        let indices = object_indices(obj, config, woog, domain)?;
+        if indices.is_empty() {
+            continue;
+        }
+
+        let obj_ident = obj.as_ident();
+        for index in &indices {
+            emit!(buffer, "self.{}.clear();", index.name);
+        }
+
+        if is_uber {
+            let (read, _write) = get_uber_read_write(config);
+            let value = if timestamp {
+                format!("{obj_ident}.0.clone()")
+            } else {
+                format!("{obj_ident}.clone()")
+            };
+            // The Vec store has empty slots, and the `Single` one isn't behind a lock.
+            let values = match (config.get_optimization_level(), config.get_uber_store()) {
+                (crate::options::OptimizationLevel::Vec, Some(UberStoreOptions::Single)) => {
+                    format!("self.{obj_ident}.iter().flatten()")
+                }
+                (crate::options::OptimizationLevel::Vec, _) => {
+                    format!("self.{obj_ident}{read}.iter().flatten()")
+                }
+                _ => format!("self.{obj_ident}{read}.values()"),
+            };
+            emit!(
+                buffer,
+                "let instances: Vec<_> = {values}.map(|{obj_ident}| {value}).collect();"
+            );
+            emit!(buffer, "for {obj_ident} in instances {{");
+            emit!(buffer, "let read = {obj_ident}{read};");
+            emit!(buffer, "self.index_{obj_ident}(&read);");
+            emit!(buffer, "}}");
+        } else {
+            emit!(
+                buffer,
+                "let instances = std::mem::take(&mut self.{obj_ident});"
+            );
+            emit!(buffer, "for {obj_ident} in instances.values() {{");
+            if timestamp {
+                emit!(buffer, "self.index_{obj_ident}(&{obj_ident}.0);");
+            } else {
+                emit!(buffer, "self.index_{obj_ident}({obj_ident});");
+            }
+            emit!(buffer, "}}");
+            emit!(buffer, "self.{obj_ident} = instances;");
+        }
+    }
+    emit!(buffer, "}}");
+    emit!(buffer, "");
+
+    if unique {
+        emit!(
+            buffer,
+            "/// Instances that collided with another in a unique index."
+        );
+        emit!(buffer, "///");
+        emit!(
+            buffer,
+            "/// The index keeps pointing at the instance that got there first. The"
+        );
+        emit!(
+            buffer,
+            "/// other one is still in the store, it's just not indexed. When the first"
+        );
+        emit!(
+            buffer,
+            "/// one is exorcised, or its key changes, the oldest conflict takes its place."
+        );
+        emit!(
+            buffer,
+            "pub fn index_conflicts(&self) -> &[IndexConflict] {{"
+        );
+        emit!(buffer, "&self.index_conflicts");
+        emit!(buffer, "}}");
+        emit!(buffer, "");
+    }
+
+    Ok(())
+}
+
+/// Emit a call to `reindex` on a freshly loaded store
+///
+/// Nothing is emitted unless there are indices. `store` needs to be mutable,
+/// and the async store is only loaded from async functions.
+pub(crate) fn emit_reindex(
+    buffer: &mut Buffer,
+    objects: &[&&Object],
+    store: &str,
+    config: &GraceConfig,
+) -> Result<()> {
+    if has_indices(objects, config) {
+        if let Some(UberStoreOptions::AsyncRwLock) = config.get_uber_store() {
+            emit!(buffer, "{store}.reindex().await;");
+        } else {
+            emit!(buffer, "{store}.reindex();");
+        }
+    }
+
+    Ok(())
+}
+
+/// Emit `IndexConflict`
+///
+/// The ids are `Uuid`s, except in the Vec store, where they are slots.
+pub(crate) fn emit_index_conflict(
+    buffer: &mut Buffer,
+    module: &str,
+    config: &GraceConfig,
+) -> Result<()> {
+    let id_type = if let crate::options::OptimizationLevel::Vec = config.get_optimization_level() {
+        "usize"
+    } else {
+        "Uuid"
+    };
+
+    buffer.block(
+        DirectiveKind::IgnoreOrig,
+        format!("{}-index-conflict", module),
+        |buffer| {
+            emit!(
+                buffer,
+                "/// Two instances with the same key, in a unique index"
+            );
+            emit!(buffer, "#[derive(Clone, Debug, PartialEq)]");
+            emit!(buffer, "pub struct IndexConflict {{");
+            emit!(buffer, "/// The index, e.g., `foo_by_name`");
+            emit!(buffer, "pub index: &'static str,");
+            emit!(buffer, "/// The instance that the index points at");
+            emit!(buffer, "pub existing: {id_type},");
+            emit!(buffer, "/// The instance that was left out of the index");
+            emit!(buffer, "pub duplicate: {id_type},");
+            emit!(buffer, "}}");
+
+            Ok(())
+        },
+    )?;
+
+    Ok(())
+}
+
+fn generate_store_persistence(
+    buffer: &mut Buffer,
+    objects: &[&&Object],
+    timestamp: bool,
+    is_meta: bool,
+    module: &str,
+    config: &GraceConfig,
+    woog: &WoogStore,
+    domain: &Domain,
+) -> Result<()> {
+    buffer.block(
+        DirectiveKind::IgnoreOrig,
+        format!("{}-object-store-persistence", module),
+        |buffer| {
+            let is_uber = config.is_uber_store();
+            let parallel = persist_in_parallel(config);
+
+            emit_persist_bincode(buffer, config)?;
+
+            emit!(buffer, "/// Persist the store.");
+            emit!(buffer, "///");
+            emit!(
+                buffer,
+                "/// The store is persisted as a directory of JSON files. The intention"
+            );
+            emit!(
+                buffer,
+                "/// is that this directory can be checked into version control."
+            );
+            if config.get_persist_git() {
+                emit!(
+                    buffer,
+                    "/// `persist_git` does just that, and `history_of_*` reads it back."
+                );
+            } else {
+                emit!(
+                    buffer,
+                    "/// Generate the store with `--persist-git` for git integration."
+                );
+            }
+            if is_uber {
+                use UberStoreOptions::*;
+                match config.get_uber_store().unwrap() {
+                    Disabled => unreachable!(),
+                    AsyncRwLock => {
                         emit!(
                             buffer,
-                            "serde_json::to_writer_pretty(&mut writer, &{obj_ident}_tuple)?;"
+                            "pub async fn persist<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {{"
                         );
                     }
-                    emit!(buffer, "}}");
-
-                    emit!(buffer, "}} else {{");
-                    emit!(buffer, "let file = fs::File::create(&path)?;");
-                    emit!(buffer, "let mut writer = io::BufWriter::new(file);");
-                    if is_uber {
-                        use UberStoreOptions::*;
-                        let _store_type = get_value_wrapper(is_uber, config, obj, woog, domain);
-                        match config.get_uber_store().unwrap() {
-                            Disabled => unreachable!(),
-                            AsyncRwLock => {
-                                emit!(
-                                    buffer,
-                                    "serde_json::to_writer_pretty(&mut writer, &(&{obj_ident}_tuple.0.read().await.to_owned(), &{obj_ident}_tuple.1))?;"
-                                );
-                            }
-                            _ => {
-                                emit!(
-                                    buffer,
-                                    "serde_json::to_writer_pretty(&mut writer, &{obj_ident}_tuple)?;"
-                                );
-                            }
-                        }
-                    } else {
+                    _ => {
                         emit!(
                             buffer,
-                            "serde_json::to_writer_pretty(&mut writer, &{obj_ident}_tuple)?;"
+                            "pub fn persist<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {{"
                         );
                     }
-
-                    emit!(buffer, "}}");
-
-                    emit!(buffer, "}}");
-
-                    // Now we need to delete any files that correspond to something
-                    // in the store that went away.
-                    emit!(buffer, "for file in fs::read_dir(&path)? {{");
-                    emit!(buffer, "let file = file?;");
-                    emit!(buffer, "let path = file.path();");
-                    emit!(
-                        buffer,
-                        "let file_name = path.file_name().unwrap().to_str().unwrap();"
-                    );
-                    emit!(buffer, "let id = file_name.split('.').next().unwrap();");
-                    emit!(buffer, "if let Ok(id) = Uuid::parse_str(id) {{");
-                    if is_uber {
-                        let (read, _write) = get_uber_read_write(config);
-                        emit!(buffer, "if !self.{obj_ident}{read}.contains_key(&id) {{");
-                    } else {
+                }
+            } else {
+                emit!(
+                    buffer,
+                    "pub fn persist<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {{"
+                );
+            }
+            emit!(buffer, "let path = path.as_ref();");
+            emit!(buffer, "fs::create_dir_all(path)?;");
+            emit!(buffer, "");
+            // This is such a great joke! 🤣
+            emit!(buffer, "let path = path.join(\"{}.json\");", domain.name());
+            emit!(buffer, "fs::create_dir_all(&path)?;");
+            emit!(buffer, "");
+            emit!(buffer, "// Persist the header.");
+            emit!(buffer, "{{");
+            emit!(buffer, "let file = fs::File::create(path.join(\"header.json\"))?;");
+            emit!(buffer, "let mut writer = io::BufWriter::new(file);");
+            emit!(
+                buffer,
+                "serde_json::to_writer_pretty(&mut writer, &StoreHeader::current())?;"
+            );
+            emit!(buffer, "}}");
+            emit!(buffer, "");
+            if timestamp {
+                emit!(buffer, "// Persist the tombstones.");
+                emit!(buffer, "{{");
+                emit!(
+                    buffer,
+                    "let file = fs::File::create(path.join(\"tombstones.json\"))?;"
+                );
+                emit!(buffer, "let mut writer = io::BufWriter::new(file);");
+                emit!(
+                    buffer,
+                    "serde_json::to_writer_pretty(&mut writer, &self.tombstones)?;"
+                );
+                emit!(buffer, "}}");
+                emit!(buffer, "");
+            }
+
+            if parallel {
+                emit!(
+                    buffer,
+                    "let jobs: Vec<Box<dyn Fn() -> io::Result<()> + Send + Sync + '_>> = vec!["
+                );
+            }
+            for obj in objects {
+                let obj_ident = obj.as_ident();
+                let obj_type = obj.as_type(&Ownership::new_borrowed(), woog, domain);
+
+                emit!(buffer, "// Persist {}.", obj.name);
+                emit_job_start(buffer, parallel)?;
+                emit!(buffer, "let path = path.join(\"{}\");", obj.as_ident());
+                emit!(buffer, "fs::create_dir_all(&path)?;");
+                if timestamp {
+                    if is_uber {
+                        let (read, _write) = get_uber_read_write(config);
+                        emit_for_each(
+                            buffer,
+                            parallel,
+                            &format!("{obj_ident}_tuple"),
+                            &format!("self.{obj_ident}{read}.values()"),
+                        )?;
+                    } else {
+                        emit_for_each(
+                            buffer,
+                            parallel,
+                            &format!("{obj_ident}_tuple"),
+                            &format!("self.{obj_ident}.values()"),
+                        )?;
+                    }
+                    let id = if local_object_is_enum(obj, config, domain) {
+                        "id()"
+                    } else {
+                        "id"
+                    };
+
+                    if is_uber {
+                        let (read, _write) = get_uber_read_write(config);
+                        emit!(
+                            buffer,
+                            "let path = path.join(format!(\"{{}}.json\", {obj_ident}_tuple.0{read}.{id}));"
+                        );
+                    } else {
+                        emit!(
+                            buffer,
+                            "let path = path.join(format!(\"{{}}.json\", {obj_ident}_tuple.0.{id}));"
+                        );
+                    };
+
+                    emit!(buffer, "if path.exists() {{");
+                    emit!(buffer, "let file = fs::File::open(&path)?;");
+                    emit!(buffer, "let reader = io::BufReader::new(file);");
+
+                    if is_uber {
+                        let store_type = get_value_wrapper(is_uber, config, obj, woog, domain);
+                        use UberStoreOptions::*;
+                        match config.get_uber_store().unwrap() {
+                            Disabled => unreachable!(),
+                            AsyncRwLock => {
+                                emit!(
+                                    buffer,
+                                    "let on_disk: ({store_type}, SystemTime) = serde_json::from_reader(reader).map(|(a, b)| (Arc::new(RwLock::new(a)), b))?;"
+                                );
+
+                            }
+                            _ => {
+                                emit!(
+                                    buffer,
+                                    "let on_disk: ({store_type}, SystemTime) = serde_json::from_reader(reader)?;"
+                                );
+                            }
+                        }
+                    } else {
+                        emit!(
+                            buffer,
+                            "let on_disk: ({obj_type}, SystemTime) = serde_json::from_reader(reader)?;"
+                        );
+                    }
+
+                    if is_uber {
+                        let (read, _write) = get_uber_read_write(config);
+                        emit!(buffer,
+                            "if on_disk.0{read}.to_owned() != {obj_ident}_tuple.0{read}.to_owned() {{"
+                        );
+                    } else {
+                        emit!(buffer, "if on_disk.0 != {obj_ident}_tuple.0 {{");
+                    }
+
+                    emit!(buffer, "let file = fs::File::create(path)?;");
+                    emit!(buffer, "let mut writer = io::BufWriter::new(file);");
+                    if is_uber {
+                        use UberStoreOptions::*;
+                        let _store_type = get_value_wrapper(is_uber, config, obj, woog, domain);
+                        match config.get_uber_store().unwrap() {
+                            Disabled => unreachable!(),
+                            AsyncRwLock => {
+                                emit!(
+                                    buffer,
+                                    "serde_json::to_writer_pretty(&mut writer, &(&{obj_ident}_tuple.0.read().await.to_owned(), &{obj_ident}_tuple.1))?;"
+                                );
+                            }
+                            _ => {
+                                emit!(
+                                    buffer,
+                                    "serde_json::to_writer_pretty(&mut writer, &{obj_ident}_tuple)?;"
+                                );
+                            }
+                        }
+                    } else {
+                        emit!(
+                            buffer,
+                            "serde_json::to_writer_pretty(&mut writer, &{obj_ident}_tuple)?;"
+                        );
+                    }
+                    emit!(buffer, "}}");
+
+                    emit!(buffer, "}} else {{");
+                    emit!(buffer, "let file = fs::File::create(&path)?;");
+                    emit!(buffer, "let mut writer = io::BufWriter::new(file);");
+                    if is_uber {
+                        use UberStoreOptions::*;
+                        let _store_type = get_value_wrapper(is_uber, config, obj, woog, domain);
+                        match config.get_uber_store().unwrap() {
+                            Disabled => unreachable!(),
+                            AsyncRwLock => {
+                                emit!(
+                                    buffer,
+                                    "serde_json::to_writer_pretty(&mut writer, &(&{obj_ident}_tuple.0.read().await.to_owned(), &{obj_ident}_tuple.1))?;"
+                                );
+                            }
+                            _ => {
+                                emit!(
+                                    buffer,
+                                    "serde_json::to_writer_pretty(&mut writer, &{obj_ident}_tuple)?;"
+                                );
+                            }
+                        }
+                    } else {
+                        emit!(
+                            buffer,
+                            "serde_json::to_writer_pretty(&mut writer, &{obj_ident}_tuple)?;"
+                        );
+                    }
+
+                    emit!(buffer, "}}");
+
+                    emit_for_each_end(buffer, parallel)?;
+
+                    // Now we need to delete any files that correspond to something
+                    // in the store that went away.
+                    emit!(buffer, "for file in fs::read_dir(&path)? {{");
+                    emit!(buffer, "let file = file?;");
+                    emit!(buffer, "let path = file.path();");
+                    emit!(
+                        buffer,
+                        "let file_name = path.file_name().unwrap().to_str().unwrap();"
+                    );
+                    emit!(buffer, "let id = file_name.split('.').next().unwrap();");
+                    emit!(buffer, "if let Ok(id) = Uuid::parse_str(id) {{");
+                    if local_object_has_id_type(obj, config, domain) {
+                        emit!(
+                            buffer,
+                            "let id = {}::from(id);",
+                            object_id_type(obj, config, woog, domain)
+                        );
+                    }
+                    if is_uber {
+                        let (read, _write) = get_uber_read_write(config);
+                        emit!(buffer, "if !self.{obj_ident}{read}.contains_key(&id) {{");
+                    } else {
                         emit!(buffer, "if !self.{obj_ident}.contains_key(&id) {{");
                     }
                     #[allow(clippy::overly_complex_bool_expr)]
@@ -1517,19 +2635,23 @@ fn generate_store_persistence(
                     emit!(buffer, "}}");
                     emit!(buffer, "}}");
                     emit!(buffer, "}}");
-                    emit!(buffer, "}}");
+                    emit_job_end(buffer, parallel)?;
                 } else {
                     if is_uber {
                         let (read, _write) = get_uber_read_write(config);
-                        emit!(
+                        emit_for_each(
                             buffer,
-                            "for {obj_ident} in self.{obj_ident}{read}.values() {{"
-                        );
+                            parallel,
+                            &obj_ident,
+                            &format!("self.{obj_ident}{read}.values()"),
+                        )?;
                     } else {
-                        emit!(
+                        emit_for_each(
                             buffer,
-                            "for {obj_ident} in self.{obj_ident}.values() {{"
-                        );
+                            parallel,
+                            &obj_ident,
+                            &format!("self.{obj_ident}.values()"),
+                        )?;
                     }
                     if is_uber {
                         let (read, _write) = get_uber_read_write(config);
@@ -1559,224 +2681,1848 @@ fn generate_store_persistence(
                     emit!(buffer, "let mut writer = io::BufWriter::new(file);");
                     emit!(
                         buffer,
-                        "serde_json::to_writer_pretty(&mut writer, &{obj_ident})?;"
+                        "serde_json::to_writer_pretty(&mut writer, &{obj_ident})?;"
+                    );
+                    emit_for_each_end(buffer, parallel)?;
+                    emit_job_end(buffer, parallel)?;
+                }
+                emit!(buffer, "");
+            }
+            if parallel {
+                emit!(buffer, "];");
+                emit!(buffer, "jobs.par_iter().try_for_each(|job| job())?;");
+                emit!(buffer, "");
+            }
+            emit!(buffer, "Ok(())");
+            emit!(buffer, "}}");
+            emit!(buffer, "");
+
+            emit_load_bincode(buffer, objects, config)?;
+
+            emit!(buffer, "/// Load the store.");
+            emit!(buffer, "///");
+            emit!(
+                buffer,
+                "/// The store is persisted as a directory of JSON files. The intention"
+            );
+            emit!(
+                buffer,
+                "/// is that this directory can be checked into version control."
+            );
+            if config.get_persist_git() {
+                emit!(
+                    buffer,
+                    "/// `persist_git` does just that, and `history_of_*` reads it back."
+                );
+            } else {
+                emit!(
+                    buffer,
+                    "/// Generate the store with `--persist-git` for git integration."
+                );
+            }
+            if is_uber {
+                use UberStoreOptions::*;
+                match config.get_uber_store().unwrap() {
+                    Disabled => unreachable!(),
+                    AsyncRwLock => {
+                        emit!(
+                            buffer,
+                            "pub async fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {{"
+                        );
+                    }
+                    _ => {
+                        emit!(
+                            buffer,
+                            "pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {{"
+                        );
+                    }
+                }
+            } else {
+                emit!(
+                    buffer,
+                    "pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {{"
+                );
+            }
+            emit!(buffer, "let from = path.as_ref();");
+            emit!(buffer, "let root = Self::check_header(from)?;");
+            emit!(buffer, "let path = root.join(\"{}.json\");", domain.name());
+            emit!(buffer, "");
+            if is_uber && !timestamp && !has_indices(objects, config) {
+                emit!(buffer, "let store = Self::new();");
+            } else {
+                emit!(buffer, "let mut store = Self::new();");
+            }
+            emit!(buffer, "");
+            if timestamp {
+                emit!(buffer, "// Load the tombstones.");
+                emit!(buffer, "let tombstones = path.join(\"tombstones.json\");");
+                emit!(buffer, "if tombstones.exists() {{");
+                emit!(buffer, "let file = fs::File::open(tombstones)?;");
+                emit!(
+                    buffer,
+                    "store.tombstones = serde_json::from_reader(io::BufReader::new(file))?;"
+                );
+                emit!(buffer, "}}");
+                emit!(buffer, "");
+            }
+
+            if parallel {
+                emit_parallel_load(
+                    buffer,
+                    objects,
+                    timestamp,
+                    config,
+                    woog,
+                    domain,
+                    emit_insert_instance,
+                )?;
+            } else {
+            for obj in objects {
+                emit!(buffer, "// Load {}.", obj.name);
+                emit!(buffer, "{{");
+                emit!(buffer, "let path = path.join(\"{}\");", obj.as_ident());
+                emit!(buffer, "let entries = fs::read_dir(path)?;");
+                emit!(buffer, "for entry in entries {{");
+                emit!(buffer, "let entry = entry?;");
+                emit!(buffer, "let path = entry.path();");
+                emit!(buffer, "let file = fs::File::open(path)?;");
+                emit!(buffer, "let reader = io::BufReader::new(file);");
+                emit_read_instance(
+                    buffer,
+                    obj,
+                    "from_reader(reader)",
+                    timestamp,
+                    config,
+                    woog,
+                    domain,
+                )?;
+                emit_insert_instance(buffer, obj, timestamp, config, domain)?;
+
+                emit!(buffer, "}}");
+                emit!(buffer, "}}");
+                emit!(buffer, "");
+            }
+            }
+            emit_reindex(buffer, objects, "store", config)?;
+            emit!(buffer, "");
+            emit_remove_migrated(buffer, config)?;
+            emit!(buffer, "Ok(store)");
+            emit!(buffer, "}}");
+            emit!(buffer, "");
+
+            // Loading the tombstones mutates the store too.
+            let new_store = if is_uber && !has_indices(objects, config) && !timestamp {
+                "let store = Self::new();"
+            } else {
+                "let mut store = Self::new();"
+            };
+            emit_document_persistence(
+                buffer,
+                objects,
+                timestamp,
+                new_store,
+                config,
+                woog,
+                domain,
+                emit_insert_instance,
+                store_values,
+            )?;
+
+            emit_streaming_load(buffer, objects, parallel, config, domain)?;
+
+            if config.get_persist_git() {
+                emit_git_persistence(buffer, objects, timestamp, config, woog, domain)?;
+            }
+
+            emit_check_header(buffer, config, domain)?;
+
+            Ok(())
+        },
+    )?;
+
+    Ok(())
+}
+
+/// Emit the header that goes along with a persisted store
+///
+/// The header records which domain, which revision of the model, and which
+/// store layout wrote the store. Loading a store written by something else
+/// used to fail deep inside serde, if it failed at all. Now it fails up front,
+/// with an error that says what's wrong.
+///
+/// This is shared with the Vec store.
+pub(crate) fn emit_store_header(
+    buffer: &mut Buffer,
+    module: &str,
+    config: &GraceConfig,
+    domain: &Domain,
+) -> Result<()> {
+    buffer.block(
+        DirectiveKind::IgnoreOrig,
+        format!("{}-store-header", module),
+        |buffer| {
+            emit!(buffer, "/// The domain that this store belongs to");
+            emit!(
+                buffer,
+                "pub const DOMAIN_ID: Uuid = Uuid::from_u128({:#x});",
+                Uuid::from_slice(domain.id().as_bytes()).unwrap().as_u128()
+            );
+            emit!(buffer, "/// The revision of the model that this store was generated from");
+            emit!(
+                buffer,
+                "pub const MODEL_HASH: &str = \"{}\";",
+                model_hash(domain).simple()
+            );
+            emit!(buffer, "/// The version of grace that generated this store");
+            emit!(
+                buffer,
+                "pub const GRACE_VERSION: &str = \"{}\";",
+                env!("CARGO_PKG_VERSION")
+            );
+            emit!(buffer, "/// The bytes that a bincode store starts with, ahead of its header");
+            emit!(buffer, "pub const BINCODE_MAGIC: [u8; 8] = *b\"grace\\0bc\";");
+            emit!(buffer, "");
+
+            emit!(buffer, "/// The header of a persisted store");
+            emit!(buffer, "///");
+            emit!(
+                buffer,
+                "/// This is written along with the store, and checked when it's loaded."
+            );
+            emit!(
+                buffer,
+                "#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]"
+            );
+            emit!(buffer, "pub struct StoreHeader {{");
+            emit!(buffer, "pub domain_id: Uuid,");
+            emit!(buffer, "pub model_hash: String,");
+            emit!(buffer, "pub grace_version: String,");
+            emit!(buffer, "pub optimization_level: String,");
+            emit!(buffer, "pub timestamps: bool,");
+            emit!(buffer, "}}");
+            emit!(buffer, "");
+
+            emit!(buffer, "impl StoreHeader {{");
+            emit!(buffer, "/// The header for a store written by this code");
+            emit!(buffer, "pub fn current() -> Self {{");
+            emit!(buffer, "Self {{");
+            emit!(buffer, "domain_id: DOMAIN_ID,");
+            emit!(buffer, "model_hash: MODEL_HASH.to_owned(),");
+            emit!(buffer, "grace_version: GRACE_VERSION.to_owned(),");
+            emit!(
+                buffer,
+                "optimization_level: \"{}\".to_owned(),",
+                config.get_optimization_level()
+            );
+            emit!(buffer, "timestamps: {},", config.get_persist_timestamps());
+            emit!(buffer, "}}");
+            emit!(buffer, "}}");
+            emit!(buffer, "");
+            emit!(buffer, "/// Check that a store with this header can be loaded");
+            emit!(buffer, "///");
+            emit!(
+                buffer,
+                "/// The grace version is informational, and isn't checked."
+            );
+            emit!(
+                buffer,
+                "pub fn check(&self) -> Result<(), StoreMismatch> {{"
+            );
+            emit!(buffer, "let current = Self::current();");
+            emit!(buffer, "if self.domain_id != current.domain_id {{");
+            emit!(buffer, "return Err(StoreMismatch::Domain {{");
+            emit!(buffer, "expected: current.domain_id,");
+            emit!(buffer, "found: self.domain_id,");
+            emit!(buffer, "}});");
+            emit!(buffer, "}}");
+            emit!(
+                buffer,
+                "if self.optimization_level != current.optimization_level || self.timestamps != current.timestamps {{"
+            );
+            emit!(buffer, "return Err(StoreMismatch::Layout {{");
+            emit!(buffer, "expected: current.layout(),");
+            emit!(buffer, "found: self.layout(),");
+            emit!(buffer, "}});");
+            emit!(buffer, "}}");
+            emit!(buffer, "if self.model_hash != current.model_hash {{");
+            emit!(buffer, "return Err(StoreMismatch::Model {{");
+            emit!(buffer, "expected: current.model_hash,");
+            emit!(buffer, "found: self.model_hash.clone(),");
+            emit!(buffer, "}});");
+            emit!(buffer, "}}");
+            emit!(buffer, "Ok(())");
+            emit!(buffer, "}}");
+            emit!(buffer, "");
+            emit!(buffer, "fn layout(&self) -> String {{");
+            emit!(buffer, "if self.timestamps {{");
+            emit!(
+                buffer,
+                "format!(\"{{}}, with timestamps\", self.optimization_level)"
+            );
+            emit!(buffer, "}} else {{");
+            emit!(buffer, "self.optimization_level.clone()");
+            emit!(buffer, "}}");
+            emit!(buffer, "}}");
+            emit!(buffer, "}}");
+            emit!(buffer, "");
+
+            emit!(
+                buffer,
+                "/// Why a persisted store can't be loaded by this code"
+            );
+            emit!(buffer, "#[derive(Clone, Debug, PartialEq)]");
+            emit!(buffer, "pub enum StoreMismatch {{");
+            emit!(buffer, "/// The store belongs to some other domain");
+            emit!(buffer, "Domain {{ expected: Uuid, found: Uuid }},");
+            emit!(
+                buffer,
+                "/// The store was written with a different optimization level, or timestamp setting"
+            );
+            emit!(buffer, "Layout {{ expected: String, found: String }},");
+            emit!(
+                buffer,
+                "/// The store was written by a different revision of the model"
+            );
+            emit!(buffer, "Model {{ expected: String, found: String }},");
+            emit!(buffer, "}}");
+            emit!(buffer, "");
+            emit!(buffer, "impl std::fmt::Display for StoreMismatch {{");
+            emit!(
+                buffer,
+                "fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {{"
+            );
+            emit!(buffer, "match self {{");
+            emit!(
+                buffer,
+                "Self::Domain {{ expected, found }} => write!(f, \"store belongs to domain {{found}}, expected {{expected}}\"),"
+            );
+            emit!(
+                buffer,
+                "Self::Layout {{ expected, found }} => write!(f, \"store layout is {{found}}, expected {{expected}}\"),"
+            );
+            emit!(
+                buffer,
+                "Self::Model {{ expected, found }} => write!(f, \"store was written by model revision {{found}}, expected {{expected}}; it needs to be migrated\"),"
+            );
+            emit!(buffer, "}}");
+            emit!(buffer, "}}");
+            emit!(buffer, "}}");
+            emit!(buffer, "");
+            emit!(buffer, "impl std::error::Error for StoreMismatch {{}}");
+            emit!(buffer, "");
+            emit!(buffer, "impl From<StoreMismatch> for io::Error {{");
+            emit!(buffer, "fn from(err: StoreMismatch) -> Self {{");
+            emit!(buffer, "io::Error::new(io::ErrorKind::InvalidData, err)");
+            emit!(buffer, "}}");
+            emit!(buffer, "}}");
+            emit!(buffer, "");
+
+            emit!(buffer, "/// A line in a JSON Lines store");
+            emit!(buffer, "///");
+            emit!(
+                buffer,
+                "/// `ty` is the name of the instance's type, `StoreHeader` for the header, or"
+            );
+            emit!(buffer, "/// `Tombstone` for an exorcised instance.");
+            emit!(
+                buffer,
+                "#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]"
+            );
+            emit!(buffer, "pub struct StoreRecord<T> {{");
+            emit!(buffer, "#[serde(rename = \"type\")]");
+            emit!(buffer, "pub ty: String,");
+            emit!(buffer, "pub instance: T,");
+            emit!(buffer, "}}");
+
+            Ok(())
+        },
+    )?;
+
+    Ok(())
+}
+
+/// Emit the function that checks a JSON store's header
+///
+/// It returns the directory to load from. That's the one passed in, unless
+/// the store needed migrating, in which case it's a scratch directory that
+/// holds the migrated store. The loader removes it when it's done.
+pub(crate) fn emit_check_header(
+    buffer: &mut Buffer,
+    config: &GraceConfig,
+    domain: &Domain,
+) -> Result<()> {
+    emit!(buffer, "/// Check the header of a persisted store.");
+    emit!(buffer, "///");
+    emit!(
+        buffer,
+        "/// A store without a header predates headers, and is loaded as is."
+    );
+    if config.get_migrate_from().is_some() {
+        emit!(
+            buffer,
+            "/// A store from the previous revision of the model is migrated first."
+        );
+    }
+    emit!(
+        buffer,
+        "fn check_header(path: &Path) -> io::Result<std::path::PathBuf> {{"
+    );
+    emit!(
+        buffer,
+        "let header = path.join(\"{}.json\").join(\"header.json\");",
+        domain.name()
+    );
+    emit!(buffer, "if !header.exists() {{");
+    emit!(buffer, "return Ok(path.to_path_buf());");
+    emit!(buffer, "}}");
+    emit!(buffer, "");
+    emit!(buffer, "let file = fs::File::open(header)?;");
+    emit!(
+        buffer,
+        "let header: StoreHeader = serde_json::from_reader(io::BufReader::new(file))?;"
+    );
+    emit!(buffer, "match header.check() {{");
+    emit!(buffer, "Ok(()) => Ok(path.to_path_buf()),");
+    if config.get_migrate_from().is_some() {
+        emit!(
+            buffer,
+            "Err(StoreMismatch::Model {{ found, .. }}) if found == super::migrate::FROM_MODEL_HASH => {{"
+        );
+        emit!(buffer, "let to = Self::scratch_dir();");
+        emit!(buffer, "super::migrate::migrate(path, &to)?;");
+        emit!(buffer, "Ok(to)");
+        emit!(buffer, "}}");
+    }
+    emit!(buffer, "Err(err) => Err(err.into()),");
+    emit!(buffer, "}}");
+    emit!(buffer, "}}");
+
+    if config.get_migrate_from().is_some() {
+        emit!(buffer, "");
+        emit!(
+            buffer,
+            "/// A scratch directory for migrating a store, that no one else is using"
+        );
+        emit!(buffer, "///");
+        emit!(buffer, "/// It's up to the caller to remove it.");
+        emit!(buffer, "fn scratch_dir() -> std::path::PathBuf {{");
+        emit!(
+            buffer,
+            "std::env::temp_dir().join(format!(\"{}-{{}}-{{}}\", MODEL_HASH, Uuid::new_v4()))",
+            domain.name()
+        );
+        emit!(buffer, "}}");
+    }
+
+    Ok(())
+}
+
+/// Remove the scratch directory of a migrated store, once it's loaded
+///
+/// `root` is the directory that `check_header` returned, and `from` is the one
+/// that was passed in.
+pub(crate) fn emit_remove_migrated(buffer: &mut Buffer, config: &GraceConfig) -> Result<()> {
+    if config.get_migrate_from().is_some() {
+        emit!(
+            buffer,
+            "// A migrated store is loaded from a scratch directory."
+        );
+        emit!(buffer, "if root != from {{");
+        emit!(buffer, "fs::remove_dir_all(&root)?;");
+        emit!(buffer, "}}");
+    }
+
+    Ok(())
+}
+
+/// Check the header of a bincode store
+///
+/// Bincode isn't self-describing, so there's no reading a store written by the
+/// previous revision of the model without the previous revision's types. We
+/// can at least say so, rather than that it needs migrating.
+fn emit_check_bincode_header(buffer: &mut Buffer, config: &GraceConfig) -> Result<()> {
+    if config.get_migrate_from().is_some() {
+        emit!(buffer, "match header.check() {{");
+        emit!(
+            buffer,
+            "Err(StoreMismatch::Model {{ found, .. }}) if found == super::migrate::FROM_MODEL_HASH => {{"
+        );
+        emit!(buffer, "return Err(io::Error::new(");
+        emit!(buffer, "io::ErrorKind::InvalidData,");
+        emit!(
+            buffer,
+            "\"bincode stores can't be migrated: persist it as JSON with the previous revision, and load that\","
+        );
+        emit!(buffer, "));");
+        emit!(buffer, "}}");
+        emit!(buffer, "result => result?,");
+        emit!(buffer, "}}");
+    } else {
+        emit!(buffer, "header.check()?;");
+    }
+
+    Ok(())
+}
+
+/// Emit `persist_bincode`
+///
+/// The async store isn't `Serialize`, so it's copied into a `Snapshot` first.
+///
+/// This is shared with the Vec store.
+pub(crate) fn emit_persist_bincode(buffer: &mut Buffer, config: &GraceConfig) -> Result<()> {
+    let (asyncness, store) = match config.get_uber_store() {
+        Some(UberStoreOptions::AsyncRwLock) => ("async ", "&self.snapshot().await"),
+        _ => ("", "&self"),
+    };
+
+    emit!(buffer, "/// Persist the store.");
+    emit!(buffer, "///");
+    emit!(
+        buffer,
+        "/// The store is persisted as a a bincode file, following a [`StoreHeader`]."
+    );
+    emit!(
+        buffer,
+        "pub {asyncness}fn persist_bincode<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {{"
+    );
+    emit!(buffer, "let path = path.as_ref();");
+    emit!(buffer, "let mut bin_file = fs::File::create(path)?;");
+    emit!(buffer, "bin_file.write_all(&BINCODE_MAGIC)?;");
+    emit!(
+        buffer,
+        "bincode::serialize_into(&mut bin_file, &StoreHeader::current()).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;"
+    );
+    emit!(
+        buffer,
+        "bincode::serialize_into(&mut bin_file, {store}).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;"
+    );
+    emit!(buffer, "Ok(())");
+    emit!(buffer, "}}\n");
+
+    Ok(())
+}
+
+/// Emit the functions that read a bincode store
+///
+/// A store starts with `BINCODE_MAGIC`, and then the header. Stores written
+/// before there was a header don't, and are loaded without checking anything,
+/// the same as a JSON store without a `header.json`.
+///
+/// This is shared with the Vec store.
+pub(crate) fn emit_load_bincode(
+    buffer: &mut Buffer,
+    objects: &[&&Object],
+    config: &GraceConfig,
+) -> Result<()> {
+    let (asyncness, dot_await) = match config.get_uber_store() {
+        Some(UberStoreOptions::AsyncRwLock) => ("async ", ".await"),
+        _ => ("", ""),
+    };
+
+    emit!(buffer, "/// Load the store.");
+    emit!(buffer, "///");
+    emit!(
+        buffer,
+        "/// The header is checked first, and loading fails if it doesn't match."
+    );
+    emit!(
+        buffer,
+        "pub {asyncness}fn from_bincode(code: &[u8]) -> io::Result<Self> {{"
+    );
+    emit!(buffer, "Self::load_bincode_from(code){dot_await}");
+    emit!(buffer, "}}\n");
+    emit!(
+        buffer,
+        "/// The store is as a bincode file, following a [`StoreHeader`]."
+    );
+    emit!(
+        buffer,
+        "pub {asyncness}fn load_bincode<P: AsRef<Path>>(path: P) -> io::Result<Self> {{"
+    );
+    emit!(buffer, "let path = path.as_ref();");
+    emit!(buffer, "let bin_file = fs::File::open(path)?;");
+    emit!(
+        buffer,
+        "Self::load_bincode_from(io::BufReader::new(bin_file)){dot_await}"
+    );
+    emit!(buffer, "}}\n");
+
+    emit!(
+        buffer,
+        "{asyncness}fn load_bincode_from<R: Read>(mut reader: R) -> io::Result<Self> {{"
+    );
+    emit!(buffer, "let mut magic = [0; 8];");
+    emit!(buffer, "reader.read_exact(&mut magic)?;");
+    emit!(buffer, "if magic != BINCODE_MAGIC {{");
+    emit!(
+        buffer,
+        "// There's no header, so this store predates them: it's all store."
+    );
+    emit!(
+        buffer,
+        "return Self::load_bincode_store(magic.as_slice().chain(reader)){dot_await};"
+    );
+    emit!(buffer, "}}");
+    emit!(
+        buffer,
+        "let header: StoreHeader = bincode::deserialize_from(&mut reader).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;"
+    );
+    emit_check_bincode_header(buffer, config)?;
+    emit!(buffer, "Self::load_bincode_store(reader){dot_await}");
+    emit!(buffer, "}}\n");
+
+    emit!(
+        buffer,
+        "{asyncness}fn load_bincode_store<R: Read>(reader: R) -> io::Result<Self> {{"
+    );
+    if !asyncness.is_empty() {
+        emit!(
+            buffer,
+            "let snapshot: Snapshot = bincode::deserialize_from(reader).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;"
+        );
+        emit!(buffer, "Ok(Self::from_snapshot(snapshot).await)");
+    } else if has_indices(objects, config) {
+        emit!(
+            buffer,
+            "let mut store: Self = bincode::deserialize_from(reader).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;"
+        );
+        emit_reindex(buffer, objects, "store", config)?;
+        emit!(buffer, "Ok(store)");
+    } else {
+        emit!(
+            buffer,
+            "bincode::deserialize_from(reader).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))"
+        );
+    }
+    emit!(buffer, "}}\n");
+
+    Ok(())
+}
+
+/// Load a single file store that was written by the previous revision
+///
+/// `explode` writes the old store into `old` as a directory. That's migrated
+/// into another scratch directory, and loaded from there, and then both are
+/// removed. This is the body of a match arm on the header check.
+fn emit_load_migrated(buffer: &mut Buffer, explode: &str, load: &str) -> Result<()> {
+    emit!(
+        buffer,
+        "Err(StoreMismatch::Model {{ found, .. }}) if found == super::migrate::FROM_MODEL_HASH => {{"
+    );
+    emit!(buffer, "let old = Self::scratch_dir();");
+    emit!(buffer, "{explode};");
+    emit!(buffer, "let new = Self::scratch_dir();");
+    emit!(buffer, "super::migrate::migrate(&old, &new)?;");
+    emit!(buffer, "fs::remove_dir_all(&old)?;");
+    emit!(buffer, "let store = {load};");
+    emit!(buffer, "fs::remove_dir_all(&new)?;");
+    emit!(buffer, "return store;");
+    emit!(buffer, "}}");
+    emit!(buffer, "result => result?,");
+
+    Ok(())
+}
+
+/// True if persist and load should fan out across threads
+///
+/// The `Single` store is built on `Rc`, so it's not going anywhere. And the
+/// `AsyncRwLock` store awaits inside the loops. Both are rejected when the
+/// options are validated, so this is just the option.
+pub(crate) fn persist_in_parallel(config: &GraceConfig) -> bool {
+    config.get_persist_parallel()
+}
+
+/// Start a loop over the instances of an object
+///
+/// In parallel, the loop body becomes a closure that returns an `io::Result`,
+/// so that `?` works the same either way.
+pub(crate) fn emit_for_each(
+    buffer: &mut Buffer,
+    parallel: bool,
+    pattern: &str,
+    iter: &str,
+) -> Result<()> {
+    if parallel {
+        emit!(
+            buffer,
+            "{iter}.par_bridge().try_for_each(|{pattern}| -> io::Result<()> {{"
+        );
+    } else {
+        emit!(buffer, "for {pattern} in {iter} {{");
+    }
+
+    Ok(())
+}
+
+/// End a loop started with [`emit_for_each`]
+pub(crate) fn emit_for_each_end(buffer: &mut Buffer, parallel: bool) -> Result<()> {
+    if parallel {
+        emit!(buffer, "Ok(())");
+        emit!(buffer, "}})?;");
+    } else {
+        emit!(buffer, "}}");
+    }
+
+    Ok(())
+}
+
+/// Start persisting an object
+///
+/// In parallel each object is a job, which is a boxed closure in `jobs`.
+/// Otherwise it's just a block.
+pub(crate) fn emit_job_start(buffer: &mut Buffer, parallel: bool) -> Result<()> {
+    if parallel {
+        emit!(buffer, "Box::new(|| {{");
+    } else {
+        emit!(buffer, "{{");
+    }
+
+    Ok(())
+}
+
+/// End a job started with [`emit_job_start`]
+pub(crate) fn emit_job_end(buffer: &mut Buffer, parallel: bool) -> Result<()> {
+    if parallel {
+        emit!(buffer, "Ok(())");
+        emit!(buffer, "}}),");
+    } else {
+        emit!(buffer, "}}");
+    }
+
+    Ok(())
+}
+
+/// Emit `StoreInstance`
+///
+/// This is what the parallel loader reads from disk, and what gets handed to
+/// the `load_streaming` callback. Each variant holds the same thing that the
+/// store holds, timestamp and all.
+pub(crate) fn emit_store_instance(
+    buffer: &mut Buffer,
+    objects: &[&&Object],
+    timestamp: bool,
+    module: &str,
+    config: &GraceConfig,
+    woog: &WoogStore,
+    domain: &Domain,
+) -> Result<()> {
+    let is_uber = config.is_uber_store();
+
+    buffer.block(
+        DirectiveKind::IgnoreOrig,
+        format!("{}-store-instance", module),
+        |buffer| {
+            emit!(buffer, "/// An instance, as read from a persisted store");
+            emit!(buffer, "#[derive(Debug)]");
+            emit!(buffer, "pub enum StoreInstance {{");
+            for obj in objects {
+                let obj_type = obj.as_type(&Ownership::new_borrowed(), woog, domain);
+                let store_type = get_value_wrapper(is_uber, config, obj, woog, domain);
+                if timestamp {
+                    emit!(buffer, "{obj_type}(({store_type}, SystemTime)),");
+                } else {
+                    emit!(buffer, "{obj_type}({store_type}),");
+                }
+            }
+            emit!(buffer, "}}");
+            emit!(buffer, "");
+
+            emit!(buffer, "impl StoreInstance {{");
+            emit!(buffer, "/// Read an instance from a file");
+            emit!(buffer, "///");
+            emit!(
+                buffer,
+                "/// `ty` is the name of the directory that the file is in."
+            );
+            emit!(
+                buffer,
+                "fn read(ty: &str, path: &Path) -> io::Result<Self> {{"
+            );
+            emit!(buffer, "let file = fs::File::open(path)?;");
+            emit!(buffer, "let reader = io::BufReader::new(file);");
+            emit!(buffer, "match ty {{");
+            for obj in objects {
+                let obj_ident = obj.as_ident();
+                let obj_type = obj.as_type(&Ownership::new_borrowed(), woog, domain);
+
+                emit!(buffer, "\"{obj_ident}\" => {{");
+                emit_read_instance(
+                    buffer,
+                    obj,
+                    "from_reader(reader)",
+                    timestamp,
+                    config,
+                    woog,
+                    domain,
+                )?;
+                emit!(buffer, "Ok(Self::{obj_type}({obj_ident}))");
+                emit!(buffer, "}}");
+            }
+            emit!(buffer, "_ => Err(io::Error::new(");
+            emit!(buffer, "io::ErrorKind::InvalidData,");
+            emit!(buffer, "format!(\"unknown type in store: {{ty}}\"),");
+            emit!(buffer, ")),");
+            emit!(buffer, "}}");
+            emit!(buffer, "}}");
+            emit!(buffer, "}}");
+
+            Ok(())
+        },
+    )?;
+
+    Ok(())
+}
+
+/// Emit the body of a parallel `load`
+///
+/// Every file is read, and deserialized, in parallel. The instances are
+/// inserted one at a time, since the store needs `&mut self`, in some cases.
+/// `path` is the `<domain>.json` directory, and `store` is already in scope.
+pub(crate) fn emit_parallel_load(
+    buffer: &mut Buffer,
+    objects: &[&&Object],
+    timestamp: bool,
+    config: &GraceConfig,
+    woog: &WoogStore,
+    domain: &Domain,
+    insert_instance: InsertInstanceFn,
+) -> Result<()> {
+    emit!(buffer, "let instances = Self::instance_files(&path)?");
+    emit!(buffer, ".into_par_iter()");
+    emit!(buffer, ".map(|(ty, path)| StoreInstance::read(ty, &path))");
+    emit!(buffer, ".collect::<io::Result<Vec<_>>>()?;");
+    emit!(buffer, "");
+    emit!(buffer, "for instance in instances {{");
+    emit!(buffer, "match instance {{");
+    for obj in objects {
+        let obj_ident = obj.as_ident();
+        let obj_type = obj.as_type(&Ownership::new_borrowed(), woog, domain);
+
+        emit!(buffer, "StoreInstance::{obj_type}({obj_ident}) => {{");
+        insert_instance(buffer, obj, timestamp, config, domain)?;
+        emit!(buffer, "}}");
+    }
+    emit!(buffer, "}}");
+    emit!(buffer, "}}");
+
+    Ok(())
+}
+
+/// Emit `instance_files` and `load_streaming`
+///
+/// These go in `impl ObjectStore`, and they don't care how the store is laid
+/// out, since they never touch it. When `parallel`, the files are read on a
+/// pool, and otherwise one after the other.
+pub(crate) fn emit_streaming_load(
+    buffer: &mut Buffer,
+    objects: &[&&Object],
+    parallel: bool,
+    config: &GraceConfig,
+    domain: &Domain,
+) -> Result<()> {
+    emit!(
+        buffer,
+        "/// Collect the files in a persisted store, along with their type."
+    );
+    emit!(
+        buffer,
+        "fn instance_files(path: &Path) -> io::Result<Vec<(&'static str, std::path::PathBuf)>> {{"
+    );
+    emit!(buffer, "let mut files = Vec::new();");
+    emit!(buffer, "for ty in [");
+    for obj in objects {
+        emit!(buffer, "\"{}\",", obj.as_ident());
+    }
+    emit!(buffer, "] {{");
+    emit!(buffer, "for entry in fs::read_dir(path.join(ty))? {{");
+    emit!(buffer, "files.push((ty, entry?.path()));");
+    emit!(buffer, "}}");
+    emit!(buffer, "}}");
+    emit!(buffer, "Ok(files)");
+    emit!(buffer, "}}");
+    emit!(buffer, "");
+
+    emit!(buffer, "/// Stream the store.");
+    emit!(buffer, "///");
+    if parallel {
+        emit!(
+            buffer,
+            "/// The instances are read in parallel, and handed to `f` one at a time,"
+        );
+    } else {
+        emit!(
+            buffer,
+            "/// The instances are read, and handed to `f`, one at a time,"
+        );
+    }
+    emit!(
+        buffer,
+        "/// without ever building the store. If `f` returns an error, reading stops,"
+    );
+    emit!(buffer, "/// and the error is returned.");
+    emit!(
+        buffer,
+        "pub fn load_streaming<P, F>(path: P, mut f: F) -> io::Result<()>"
+    );
+    emit!(buffer, "where");
+    emit!(buffer, "P: AsRef<Path>,");
+    emit!(buffer, "F: FnMut(StoreInstance) -> io::Result<()>,");
+    emit!(buffer, "{{");
+    emit!(buffer, "let from = path.as_ref();");
+    emit!(buffer, "let root = Self::check_header(from)?;");
+    emit!(
+        buffer,
+        "let files = Self::instance_files(&root.join(\"{}.json\"))?;",
+        domain.name()
+    );
+    emit!(buffer, "");
+    let migrate = config.get_migrate_from().is_some();
+    if parallel {
+        emit!(
+            buffer,
+            "// The channel is bounded so that the readers can't get too far ahead."
+        );
+        emit!(
+            buffer,
+            "let (sender, receiver) = std::sync::mpsc::sync_channel(1024);"
+        );
+        if migrate {
+            emit!(buffer, "let result = std::thread::scope(|scope| {{");
+        } else {
+            emit!(buffer, "std::thread::scope(|scope| {{");
+        }
+        emit!(buffer, "scope.spawn(move || {{");
+        emit!(
+            buffer,
+            "// Sending fails once the receiver is gone, and that's our cue to stop."
+        );
+        emit!(
+            buffer,
+            "let _ = files.into_par_iter().try_for_each_with(sender, |sender, (ty, path)| {{"
+        );
+        emit!(buffer, "sender.send(StoreInstance::read(ty, &path))");
+        emit!(buffer, "}});");
+        emit!(buffer, "}});");
+        emit!(buffer, "");
+        emit!(buffer, "for instance in receiver {{");
+        emit!(buffer, "f(instance?)?;");
+        emit!(buffer, "}}");
+        emit!(buffer, "Ok(())");
+        if migrate {
+            emit!(buffer, "}});");
+        } else {
+            emit!(buffer, "}})");
+        }
+    } else {
+        if migrate {
+            emit!(buffer, "let result = files");
+        } else {
+            emit!(buffer, "files");
+        }
+        emit!(buffer, ".into_iter()");
+        if migrate {
+            emit!(
+                buffer,
+                ".try_for_each(|(ty, path)| f(StoreInstance::read(ty, &path)?));"
+            );
+        } else {
+            emit!(
+                buffer,
+                ".try_for_each(|(ty, path)| f(StoreInstance::read(ty, &path)?))"
+            );
+        }
+    }
+    if migrate {
+        emit!(buffer, "");
+        emit_remove_migrated(buffer, config)?;
+        emit!(buffer, "result");
+    }
+    emit!(buffer, "}}");
+    emit!(buffer, "");
+
+    Ok(())
+}
+
+/// Emit `persist_git`, and `history_of_{obj}`
+///
+/// We shell out to `git`, rather than pull in a library. It's what the user
+/// would do at the command line anyway.
+pub(crate) fn emit_git_persistence(
+    buffer: &mut Buffer,
+    objects: &[&&Object],
+    timestamp: bool,
+    config: &GraceConfig,
+    woog: &WoogStore,
+    domain: &Domain,
+) -> Result<()> {
+    let (asyncness, wait) = match config.get_uber_store() {
+        Some(UberStoreOptions::AsyncRwLock) => ("async ", ".await"),
+        _ => ("", ""),
+    };
+
+    emit!(
+        buffer,
+        "/// Persist the store, and stage the changes in git."
+    );
+    emit!(buffer, "///");
+    emit!(
+        buffer,
+        "/// The store must be inside of a git repository. New, changed, and removed"
+    );
+    emit!(
+        buffer,
+        "/// instances are staged. If there is a `message`, and anything in the store"
+    );
+    emit!(buffer, "/// changed, the changes are committed.");
+    emit!(
+        buffer,
+        "pub {asyncness}fn persist_git<P: AsRef<Path>>(&self, path: P, message: Option<&str>) -> io::Result<()> {{"
+    );
+    emit!(buffer, "let path = path.as_ref();");
+    emit!(buffer, "self.persist(path){wait}?;");
+    if asyncness.is_empty() {
+        emit!(buffer, "Self::stage_in_git(path, message)");
+    } else {
+        emit!(buffer, "");
+        emit!(
+            buffer,
+            "// git is a blocking process, so keep it off of the executor."
+        );
+        emit!(buffer, "let path = path.to_path_buf();");
+        emit!(buffer, "let message = message.map(str::to_owned);");
+        match config.get_async_runtime() {
+            AsyncRuntime::AsyncStd => {
+                emit!(
+                    buffer,
+                    "async_std::task::spawn_blocking(move || Self::stage_in_git(&path, message.as_deref())).await"
+                );
+            }
+            AsyncRuntime::Tokio => {
+                emit!(
+                    buffer,
+                    "tokio::task::spawn_blocking(move || Self::stage_in_git(&path, message.as_deref()))"
+                );
+                emit!(buffer, ".await");
+                emit!(
+                    buffer,
+                    ".map_err(|e| io::Error::new(io::ErrorKind::Other, e))?"
+                );
+            }
+        }
+    }
+    emit!(buffer, "}}");
+    emit!(buffer, "");
+
+    emit!(
+        buffer,
+        "/// Stage the store in git, and commit it if there's a `message`."
+    );
+    emit!(
+        buffer,
+        "fn stage_in_git(path: &Path, message: Option<&str>) -> io::Result<()> {{"
+    );
+    emit!(buffer, "let store = \"{}.json\";", domain.name());
+    emit!(
+        buffer,
+        "Self::git(path, &[\"add\", \"--all\", \"--\", store])?;"
+    );
+    emit!(buffer, "if let Some(message) = message {{");
+    emit!(
+        buffer,
+        "// `diff --quiet` exits with 1 when there are differences."
+    );
+    emit!(buffer, "let status = std::process::Command::new(\"git\")");
+    emit!(buffer, ".current_dir(path)");
+    emit!(
+        buffer,
+        ".args([\"diff\", \"--cached\", \"--quiet\", \"--\", store])"
+    );
+    emit!(buffer, ".status()?;");
+    emit!(buffer, "match status.code() {{");
+    emit!(buffer, "Some(0) => {{}}");
+    emit!(buffer, "Some(1) => {{");
+    emit!(
+        buffer,
+        "Self::git(path, &[\"commit\", \"--quiet\", \"--message\", message, \"--\", store])?;"
+    );
+    emit!(buffer, "}}");
+    emit!(buffer, "_ => {{");
+    emit!(buffer, "return Err(io::Error::new(");
+    emit!(buffer, "io::ErrorKind::Other,");
+    emit!(buffer, "format!(\"git diff failed: {{status}}\"),");
+    emit!(buffer, "))");
+    emit!(buffer, "}}");
+    emit!(buffer, "}}");
+    emit!(buffer, "}}");
+    emit!(buffer, "");
+    emit!(buffer, "Ok(())");
+    emit!(buffer, "}}");
+    emit!(buffer, "");
+
+    for obj in objects {
+        let obj_ident = obj.as_ident();
+        let obj_type = obj.as_type(&Ownership::new_borrowed(), woog, domain);
+        let on_disk = if timestamp {
+            format!("({obj_type}, SystemTime)")
+        } else {
+            obj_type.clone()
+        };
+
+        emit!(buffer, "/// Get the history of a [`{obj_type}`] from git.");
+        emit!(buffer, "///");
+        emit!(
+            buffer,
+            "/// Returns each commit that touched the instance, newest first, along with"
+        );
+        emit!(
+            buffer,
+            "/// the instance as it was. Commits that removed the instance are skipped, as"
+        );
+        emit!(
+            buffer,
+            "/// are those where it doesn't deserialize, e.g., from an earlier revision of"
+        );
+        emit!(buffer, "/// the model.");
+        emit!(
+            buffer,
+            "pub fn history_of_{obj_ident}<P: AsRef<Path>>(path: P, id: impl std::fmt::Display) -> io::Result<Vec<(String, {on_disk})>> {{"
+        );
+        emit!(
+            buffer,
+            "let dir = path.as_ref().join(\"{}.json\").join(\"{obj_ident}\");",
+            domain.name()
+        );
+        emit!(buffer, "let file = format!(\"{{id}}.json\");");
+        emit!(
+            buffer,
+            "let log = Self::git(&dir, &[\"log\", \"--format=%H\", \"--\", &file])?;"
+        );
+        emit!(buffer, "");
+        emit!(buffer, "let mut history = Vec::new();");
+        emit!(
+            buffer,
+            "for commit in String::from_utf8_lossy(&log).lines() {{"
+        );
+        emit!(
+            buffer,
+            "// The `./` makes the path relative to `dir`, and not the top of the repository."
+        );
+        emit!(
+            buffer,
+            "if let Ok(contents) = Self::git(&dir, &[\"show\", &format!(\"{{commit}}:./{{file}}\")]) {{"
+        );
+        emit!(
+            buffer,
+            "if let Ok(instance) = serde_json::from_slice(&contents) {{"
+        );
+        emit!(buffer, "history.push((commit.to_owned(), instance));");
+        emit!(buffer, "}}");
+        emit!(buffer, "}}");
+        emit!(buffer, "}}");
+        emit!(buffer, "");
+        emit!(buffer, "Ok(history)");
+        emit!(buffer, "}}");
+        emit!(buffer, "");
+    }
+
+    emit!(buffer, "/// Run git in `dir`, and return what it printed.");
+    emit!(
+        buffer,
+        "fn git(dir: &Path, args: &[&str]) -> io::Result<Vec<u8>> {{"
+    );
+    emit!(buffer, "let output = std::process::Command::new(\"git\")");
+    emit!(buffer, ".current_dir(dir)");
+    emit!(buffer, ".args(args)");
+    emit!(buffer, ".output()?;");
+    emit!(buffer, "if output.status.success() {{");
+    emit!(buffer, "Ok(output.stdout)");
+    emit!(buffer, "}} else {{");
+    emit!(buffer, "Err(io::Error::new(");
+    emit!(buffer, "io::ErrorKind::Other,");
+    emit!(buffer, "format!(");
+    emit!(buffer, "\"git {{}} failed: {{}}\",");
+    emit!(buffer, "args.join(\" \"),");
+    emit!(buffer, "String::from_utf8_lossy(&output.stderr).trim()");
+    emit!(buffer, "),");
+    emit!(buffer, "))");
+    emit!(buffer, "}}");
+    emit!(buffer, "}}");
+    emit!(buffer, "");
+
+    Ok(())
+}
+
+/// The signature of the function that emits code to insert an instance into the store
+///
+/// The HashMap and Vec stores each have their own.
+pub(crate) type InsertInstanceFn =
+    fn(&mut Buffer, &Object, bool, &GraceConfig, &Domain) -> Result<()>;
+
+/// The signature of the function that returns an iterator over the values the store holds
+///
+/// The HashMap and Vec stores each have their own. The expression borrows
+/// `self`, and yields whatever the store holds, timestamp and all.
+pub(crate) type StoreValuesFn = fn(&Object, bool, &GraceConfig) -> String;
+
+/// Emit `Snapshot`, the async store without its locks
+///
+/// serde isn't async, so the async store can't take its locks inside
+/// `Serialize`. The single file persisters copy the store into one of these,
+/// awaiting each lock, and serialize that instead. It's read back the same
+/// way. The Vec store's snapshot drops the empty slots.
+///
+/// This is shared with the Vec store.
+pub(crate) fn emit_snapshot(
+    buffer: &mut Buffer,
+    objects: &[&&Object],
+    timestamp: bool,
+    config: &GraceConfig,
+    woog: &WoogStore,
+    domain: &Domain,
+) -> Result<()> {
+    let vec = matches!(
+        config.get_optimization_level(),
+        crate::options::OptimizationLevel::Vec
+    );
+
+    emit!(
+        buffer,
+        "/// The instances in the store, out from behind their locks"
+    );
+    emit!(buffer, "///");
+    emit!(
+        buffer,
+        "/// The single file persisters go through this, since serde can't await a lock."
+    );
+    emit!(buffer, "#[derive(Deserialize, Serialize)]");
+    emit!(buffer, "struct Snapshot {{");
+    for obj in objects {
+        let obj_ident = obj.as_ident();
+        let obj_type = obj.as_type(&Ownership::new_borrowed(), woog, domain);
+        let value_type = if timestamp {
+            format!("({obj_type}, SystemTime)")
+        } else {
+            obj_type
+        };
+
+        if vec {
+            emit!(buffer, "{obj_ident}: Vec<{value_type}>,");
+        } else {
+            let id_type = object_id_type(obj, config, woog, domain);
+            emit!(buffer, "{obj_ident}: HashMap<{id_type}, {value_type}>,");
+        }
+    }
+    if timestamp {
+        emit!(buffer, "#[serde(default)]");
+        emit!(
+            buffer,
+            "tombstones: HashMap<Uuid, (ObjectKind, SystemTime)>,"
+        );
+    }
+    emit!(buffer, "}}");
+    emit!(buffer, "");
+
+    Ok(())
+}
+
+/// Emit `snapshot` and `from_snapshot`
+///
+/// `from_snapshot` inserts the instances the same way that the loaders do,
+/// and then reindexes the store.
+#[allow(clippy::too_many_arguments)]
+fn emit_snapshot_methods(
+    buffer: &mut Buffer,
+    objects: &[&&Object],
+    timestamp: bool,
+    new_store: &str,
+    config: &GraceConfig,
+    woog: &WoogStore,
+    domain: &Domain,
+    insert_instance: InsertInstanceFn,
+    values: StoreValuesFn,
+) -> Result<()> {
+    let vec = matches!(
+        config.get_optimization_level(),
+        crate::options::OptimizationLevel::Vec
+    );
+
+    emit!(buffer, "/// Copy the instances out of the store.");
+    emit!(buffer, "async fn snapshot(&self) -> Snapshot {{");
+    for obj in objects {
+        let obj_ident = obj.as_ident();
+        let id = if local_object_is_enum(obj, config, domain) {
+            "id()"
+        } else {
+            "id"
+        };
+
+        if vec {
+            emit!(buffer, "let mut {obj_ident} = Vec::new();");
+        } else {
+            emit!(buffer, "let mut {obj_ident} = HashMap::default();");
+        }
+        emit!(
+            buffer,
+            "for instance in {} {{",
+            values(obj, timestamp, config)
+        );
+        if timestamp {
+            emit!(
+                buffer,
+                "let instance = (instance.0.read().await.clone(), instance.1);"
+            );
+        } else {
+            emit!(buffer, "let instance = instance.read().await.clone();");
+        }
+        if vec {
+            emit!(buffer, "{obj_ident}.push(instance);");
+        } else if timestamp {
+            emit!(buffer, "{obj_ident}.insert(instance.0.{id}, instance);");
+        } else {
+            emit!(buffer, "{obj_ident}.insert(instance.{id}, instance);");
+        }
+        emit!(buffer, "}}");
+    }
+    emit!(buffer, "");
+    emit!(buffer, "Snapshot {{");
+    for obj in objects {
+        emit!(buffer, "{},", obj.as_ident());
+    }
+    if timestamp {
+        emit!(buffer, "tombstones: self.tombstones.clone(),");
+    }
+    emit!(buffer, "}}");
+    emit!(buffer, "}}");
+    emit!(buffer, "");
+
+    emit!(
+        buffer,
+        "/// Build a store from the instances in `snapshot`."
+    );
+    emit!(
+        buffer,
+        "async fn from_snapshot(snapshot: Snapshot) -> Self {{"
+    );
+    emit!(buffer, "{new_store}");
+    for obj in objects {
+        let obj_ident = obj.as_ident();
+        let into = if vec { "into_iter" } else { "into_values" };
+
+        emit!(
+            buffer,
+            "for {obj_ident} in snapshot.{obj_ident}.{into}() {{"
+        );
+        if timestamp {
+            emit!(
+                buffer,
+                "let {obj_ident} = (Arc::new(RwLock::new({obj_ident}.0)), {obj_ident}.1);"
+            );
+        } else {
+            emit!(
+                buffer,
+                "let {obj_ident} = Arc::new(RwLock::new({obj_ident}));"
+            );
+        }
+        insert_instance(buffer, obj, timestamp, config, domain)?;
+        emit!(buffer, "}}");
+    }
+    if timestamp {
+        emit!(buffer, "store.tombstones = snapshot.tombstones;");
+    }
+    emit_reindex(buffer, objects, "store", config)?;
+    emit!(buffer, "store");
+    emit!(buffer, "}}");
+    emit!(buffer, "");
+
+    Ok(())
+}
+
+/// Emit the single file, and JSON Lines, persistence functions
+///
+/// The single file goes through the store's own `Serialize` and `Deserialize`
+/// impls, or through a `Snapshot` for the async store. JSON Lines writes each
+/// instance as it's iterated, with `values` supplying the iterator, so that it
+/// works for the Vec store too. Loading JSON Lines uses the same code as
+/// loading a directory, courtesy of [`emit_read_instance`] and
+/// `insert_instance`. `new_store` is the code that creates an empty store,
+/// bound to `store`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn emit_document_persistence(
+    buffer: &mut Buffer,
+    objects: &[&&Object],
+    timestamp: bool,
+    new_store: &str,
+    config: &GraceConfig,
+    woog: &WoogStore,
+    domain: &Domain,
+    insert_instance: InsertInstanceFn,
+    values: StoreValuesFn,
+) -> Result<()> {
+    let asyncness = match config.get_uber_store() {
+        Some(UberStoreOptions::AsyncRwLock) => "async ",
+        _ => "",
+    };
+    let migrate = config.get_migrate_from().is_some();
+
+    if !asyncness.is_empty() {
+        emit_snapshot_methods(
+            buffer,
+            objects,
+            timestamp,
+            new_store,
+            config,
+            woog,
+            domain,
+            insert_instance,
+            values,
+        )?;
+    }
+
+    emit!(buffer, "/// Persist the store as a single JSON file.");
+    emit!(buffer, "///");
+    emit!(
+        buffer,
+        "/// The file is an object with a `header` and a `store`, and it's pretty printed."
+    );
+    emit!(
+        buffer,
+        "/// The instances are sorted by id, so persisting the same store twice writes"
+    );
+    emit!(buffer, "/// the same file.");
+    emit!(
+        buffer,
+        "pub {asyncness}fn persist_json_file<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {{"
+    );
+    emit!(buffer, "#[derive(Serialize)]");
+    emit!(buffer, "struct Document {{");
+    emit!(buffer, "header: StoreHeader,");
+    emit!(buffer, "store: serde_json::Value,");
+    emit!(buffer, "}}");
+    emit!(buffer, "");
+    emit!(
+        buffer,
+        "// The maps in the store iterate in whatever order they like. A Value's"
+    );
+    emit!(
+        buffer,
+        "// objects are BTreeMaps, so going through one sorts them by key."
+    );
+    if asyncness.is_empty() {
+        emit!(buffer, "let store = serde_json::to_value(self)?;");
+    } else {
+        emit!(
+            buffer,
+            "let store = serde_json::to_value(self.snapshot().await)?;"
+        );
+    }
+    emit!(buffer, "let file = fs::File::create(path)?;");
+    emit!(buffer, "let mut writer = io::BufWriter::new(file);");
+    emit!(
+        buffer,
+        "serde_json::to_writer_pretty(&mut writer, &Document {{ header: StoreHeader::current(), store }})?;"
+    );
+    emit!(buffer, "writer.flush()");
+    emit!(buffer, "}}");
+    emit!(buffer, "");
+
+    emit!(buffer, "/// Load the store from a single JSON file.");
+    if migrate {
+        emit!(buffer, "///");
+        emit!(
+            buffer,
+            "/// A store from the previous revision of the model is migrated first."
+        );
+    }
+    emit!(
+        buffer,
+        "pub {asyncness}fn load_json_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {{"
+    );
+    emit!(buffer, "let path = path.as_ref();");
+    if migrate {
+        // The store won't deserialize if it's from the previous revision, so
+        // the header is read on it's own first.
+        emit!(buffer, "#[derive(Deserialize)]");
+        emit!(buffer, "struct Header {{");
+        emit!(buffer, "header: StoreHeader,");
+        emit!(buffer, "}}");
+        emit!(buffer, "#[derive(Deserialize)]");
+        emit!(buffer, "struct OldDocument {{");
+        emit!(buffer, "store: serde_json::Value,");
+        emit!(buffer, "}}");
+        emit!(buffer, "");
+        emit!(buffer, "let file = fs::File::open(path)?;");
+        emit!(
+            buffer,
+            "let Header {{ header }} = serde_json::from_reader(io::BufReader::new(file))?;"
+        );
+        emit!(buffer, "match header.check() {{");
+        let load = if asyncness.is_empty() {
+            "Self::load(&new)"
+        } else {
+            "Self::load(&new).await"
+        };
+        emit_load_migrated(
+            buffer,
+            "let file = fs::File::open(path)?;\nlet document: OldDocument = serde_json::from_reader(io::BufReader::new(file))?;\nsuper::migrate::explode_json_file(document.store, &old)?",
+            load,
+        )?;
+        emit!(buffer, "}}");
+        emit!(buffer, "");
+    }
+    emit!(buffer, "#[derive(Deserialize)]");
+    emit!(buffer, "struct Document {{");
+    emit!(buffer, "header: StoreHeader,");
+    if asyncness.is_empty() {
+        emit!(buffer, "store: ObjectStore,");
+    } else {
+        emit!(buffer, "store: Snapshot,");
+    }
+    emit!(buffer, "}}");
+    emit!(buffer, "");
+    emit!(buffer, "let file = fs::File::open(path)?;");
+    emit!(
+        buffer,
+        "let document: Document = serde_json::from_reader(io::BufReader::new(file))?;"
+    );
+    emit!(buffer, "document.header.check()?;");
+    if !asyncness.is_empty() {
+        emit!(buffer, "Ok(Self::from_snapshot(document.store).await)");
+    } else if has_indices(objects, config) {
+        emit!(buffer, "let mut store = document.store;");
+        emit_reindex(buffer, objects, "store", config)?;
+        emit!(buffer, "Ok(store)");
+    } else {
+        emit!(buffer, "Ok(document.store)");
+    }
+    emit!(buffer, "}}");
+    emit!(buffer, "");
+
+    emit!(buffer, "/// Persist the store as JSON Lines.");
+    emit!(buffer, "///");
+    emit!(
+        buffer,
+        "/// The first line is the header, and each line after that is a [`StoreRecord`]"
+    );
+    emit!(
+        buffer,
+        "/// holding a single instance. Since a later line for an instance replaces an"
+    );
+    emit!(
+        buffer,
+        "/// earlier one when loading, changes may be appended to the file."
+    );
+    if timestamp {
+        emit!(buffer, "///");
+        emit!(
+            buffer,
+            "/// The tombstones come last, each one a `Tombstone` record holding the id,"
+        );
+        emit!(buffer, "/// the kind of object, and when it was exorcised.");
+    }
+    emit!(
+        buffer,
+        "pub {asyncness}fn persist_jsonl<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {{"
+    );
+    emit!(buffer, "let file = fs::File::create(path)?;");
+    emit!(buffer, "let mut writer = io::BufWriter::new(file);");
+    emit!(
+        buffer,
+        "serde_json::to_writer(&mut writer, &StoreRecord {{ ty: \"StoreHeader\".to_owned(), instance: StoreHeader::current() }})?;"
+    );
+    emit!(buffer, "writeln!(writer)?;");
+    emit!(buffer, "");
+    for obj in objects {
+        let obj_type = obj.as_type(&Ownership::new_borrowed(), woog, domain);
+
+        emit!(buffer, "// Persist {}.", obj.name);
+        emit!(
+            buffer,
+            "for instance in {} {{",
+            values(obj, timestamp, config)
+        );
+        // The async lock isn't `Serialize`, so we have to reach inside.
+        if !asyncness.is_empty() {
+            if timestamp {
+                emit!(buffer, "let guard = instance.0.read().await;");
+                emit!(buffer, "let instance = (&*guard, instance.1);");
+            } else {
+                emit!(buffer, "let guard = instance.read().await;");
+                emit!(buffer, "let instance = &*guard;");
+            }
+        }
+        emit!(
+            buffer,
+            "serde_json::to_writer(&mut writer, &StoreRecord {{ ty: \"{obj_type}\".to_owned(), instance }})?;"
+        );
+        emit!(buffer, "writeln!(writer)?;");
+        emit!(buffer, "}}");
+        emit!(buffer, "");
+    }
+    if timestamp {
+        emit!(buffer, "// Persist the tombstones.");
+        emit!(buffer, "for instance in &self.tombstones {{");
+        emit!(
+            buffer,
+            "serde_json::to_writer(&mut writer, &StoreRecord {{ ty: \"Tombstone\".to_owned(), instance }})?;"
+        );
+        emit!(buffer, "writeln!(writer)?;");
+        emit!(buffer, "}}");
+        emit!(buffer, "");
+    }
+    emit!(buffer, "writer.flush()");
+    emit!(buffer, "}}");
+    emit!(buffer, "");
+
+    emit!(buffer, "/// Load the store from JSON Lines.");
+    emit!(buffer, "///");
+    emit!(
+        buffer,
+        "/// Blank lines are skipped, and the header is checked wherever it appears."
+    );
+    emit!(
+        buffer,
+        "pub {asyncness}fn load_jsonl<P: AsRef<Path>>(path: P) -> io::Result<Self> {{"
+    );
+    emit!(buffer, "let path = path.as_ref();");
+    emit!(buffer, "let file = fs::File::open(path)?;");
+    emit!(buffer, "{new_store}");
+    emit!(buffer, "");
+    emit!(buffer, "for line in io::BufReader::new(file).lines() {{");
+    emit!(buffer, "let line = line?;");
+    emit!(buffer, "if line.trim().is_empty() {{");
+    emit!(buffer, "continue;");
+    emit!(buffer, "}}");
+    emit!(
+        buffer,
+        "let record: StoreRecord<serde_json::Value> = serde_json::from_str(&line)?;"
+    );
+    emit!(buffer, "match record.ty.as_str() {{");
+    emit!(buffer, "\"StoreHeader\" => {{");
+    emit!(
+        buffer,
+        "let header: StoreHeader = serde_json::from_value(record.instance)?;"
+    );
+    if migrate {
+        emit!(buffer, "match header.check() {{");
+        let load = if asyncness.is_empty() {
+            "Self::load(&new)"
+        } else {
+            "Self::load(&new).await"
+        };
+        emit_load_migrated(buffer, "super::migrate::explode_jsonl(path, &old)?", load)?;
+        emit!(buffer, "}}");
+    } else {
+        emit!(buffer, "header.check()?;");
+    }
+    emit!(buffer, "}}");
+    if timestamp {
+        emit!(buffer, "\"Tombstone\" => {{");
+        emit!(
+            buffer,
+            "let (id, tombstone) = serde_json::from_value(record.instance)?;"
+        );
+        emit!(buffer, "store.tombstones.insert(id, tombstone);");
+        emit!(buffer, "}}");
+    }
+    for obj in objects {
+        emit!(
+            buffer,
+            "\"{}\" => {{",
+            obj.as_type(&Ownership::new_borrowed(), woog, domain)
+        );
+        emit_read_instance(
+            buffer,
+            obj,
+            "from_value(record.instance)",
+            timestamp,
+            config,
+            woog,
+            domain,
+        )?;
+        insert_instance(buffer, obj, timestamp, config, domain)?;
+        emit!(buffer, "}}");
+    }
+    emit!(buffer, "ty => {{");
+    emit!(buffer, "return Err(io::Error::new(");
+    emit!(buffer, "io::ErrorKind::InvalidData,");
+    emit!(buffer, "format!(\"unknown type in store: {{ty}}\"),");
+    emit!(buffer, "))");
+    emit!(buffer, "}}");
+    emit!(buffer, "}}");
+    emit!(buffer, "}}");
+    emit!(buffer, "");
+    emit_reindex(buffer, objects, "store", config)?;
+    emit!(buffer, "Ok(store)");
+    emit!(buffer, "}}");
+    emit!(buffer, "");
+
+    Ok(())
+}
+
+/// Deserialize an instance
+///
+/// `source` is the `serde_json` call that produces the instance, minus the
+/// `serde_json::`. The directory loader reads from a file, and the JSON Lines
+/// loader from a `Value`. Either way, it's the same deserializer. The result
+/// is bound to the object's identifier.
+pub(crate) fn emit_read_instance(
+    buffer: &mut Buffer,
+    obj: &Object,
+    source: &str,
+    timestamp: bool,
+    config: &GraceConfig,
+    woog: &WoogStore,
+    domain: &Domain,
+) -> Result<()> {
+    let is_uber = config.is_uber_store();
+    let obj_ident = obj.as_ident();
+
+    let store_type = get_value_wrapper(is_uber, config, obj, woog, domain);
+    if timestamp {
+        if is_uber {
+            use UberStoreOptions::*;
+            match config.get_uber_store().unwrap() {
+                Disabled => unreachable!(),
+                AsyncRwLock => {
+                    emit!(
+                        buffer,
+                        "let {obj_ident}: ({}, SystemTime) = serde_json::{source}.map(|(a, b)| (Arc::new(RwLock::new(a)), b))?;",
+                        store_type,
+                    );
+                }
+                _ => {
+                    emit!(
+                        buffer,
+                        "let {obj_ident}: ({}, SystemTime) = serde_json::{source}?;",
+                        store_type,
                     );
-                    emit!(buffer, "}}");
-                    emit!(buffer, "}}");
                 }
-                emit!(buffer, "");
             }
-            emit!(buffer, "Ok(())");
-            emit!(buffer, "}}");
-            emit!(buffer, "");
-
-            emit!(buffer, "/// Load the store.");
-            emit!(buffer, "///");
-            emit!(buffer, "pub fn from_bincode(code: &[u8]) -> io::Result<Self> {{");
-            emit!(buffer, "Ok(bincode::deserialize(code).unwrap())");
-            emit!(buffer, "}}\n");
+        } else {
             emit!(
                 buffer,
-                "/// The store is as a bincode file."
+                "let {obj_ident}: ({}, SystemTime) = serde_json::{source}?;",
+                store_type,
             );
+        }
+    } else {
+        if is_uber {
+            use UberStoreOptions::*;
+            match config.get_uber_store().unwrap() {
+                Disabled => unreachable!(),
+                AsyncRwLock => {
+                    emit!(
+                        buffer,
+                        "let {obj_ident}: {} = serde_json::{source}.map(|a| Arc::new(RwLock::new(a)))?;",
+                        store_type,
+                    );
+                }
+                _ => {
+                    emit!(
+                        buffer,
+                        "let {obj_ident}: {} = serde_json::{source}?;",
+                        store_type,
+                    );
+                }
+            }
+        } else {
             emit!(
                 buffer,
-                "pub fn load_bincode<P: AsRef<Path>>(path: P) -> io::Result<Self> {{"
+                "let {obj_ident}: {} = serde_json::{source}?;",
+                store_type,
             );
-            emit!(buffer, "let path = path.as_ref();");
-            emit!(buffer, "let bin_file = fs::File::open(path)?;");
-            emit!(buffer, "Ok(bincode::deserialize_from(bin_file).unwrap())");
-            emit!(buffer, "}}\n");
+        }
+    }
 
-            emit!(buffer, "/// Load the store.");
-            emit!(buffer, "///");
-            emit!(
-                buffer,
-                "/// The store is persisted as a directory of JSON files. The intention"
-            );
+    Ok(())
+}
+
+/// Iterate over the values in the store
+///
+/// The async lock is awaited, so for that store this goes in an async fn.
+fn store_values(obj: &Object, _timestamp: bool, config: &GraceConfig) -> String {
+    let obj_ident = obj.as_ident();
+
+    match config.get_uber_store() {
+        None | Some(UberStoreOptions::Disabled) => format!("self.{obj_ident}.values()"),
+        Some(_) => {
+            let (read, _write) = get_uber_read_write(config);
+            format!("self.{obj_ident}{read}.values()")
+        }
+    }
+}
+
+/// Insert an instance, deserialized by [`emit_read_instance`], into the store
+fn emit_insert_instance(
+    buffer: &mut Buffer,
+    obj: &Object,
+    timestamp: bool,
+    config: &GraceConfig,
+    domain: &Domain,
+) -> Result<()> {
+    let is_uber = config.is_uber_store();
+    let obj_ident = obj.as_ident();
+    let id = if local_object_is_enum(obj, config, domain) {
+        "id()"
+    } else {
+        "id"
+    };
+
+    if timestamp {
+        if object_has_name(obj, domain) {
+            if is_uber {
+                let (read, write) = get_uber_read_write(config);
+                emit!(
+                    buffer,
+                    "store.{obj_ident}_id_by_name{write}.insert({obj_ident}.0{read}.name.to_upper_camel_case(), ({obj_ident}.0{read}.{id}, {obj_ident}.1));"
+                );
+            } else {
+                emit!(
+                    buffer,
+                    "store.{obj_ident}_id_by_name.insert({obj_ident}.0.name.to_upper_camel_case(), ({obj_ident}.0.{id}, {obj_ident}.1));"
+                );
+            }
+        }
+        if is_uber {
+            let (read, write) = get_uber_read_write(config);
             emit!(
                 buffer,
-                "/// is that this directory can be checked into version control."
+                "store.{obj_ident}{write}.insert({obj_ident}.0{read}.{id}, {obj_ident}.clone());"
             );
+        } else {
             emit!(
                 buffer,
-                "/// In fact, I intend to add automagic git integration as an option."
+                "store.{obj_ident}.insert({obj_ident}.0.{id}, {obj_ident});"
             );
+        }
+    } else {
+        if object_has_name(obj, domain) {
             if is_uber {
-                use UberStoreOptions::*;
-                match config.get_uber_store().unwrap() {
-                    Disabled => unreachable!(),
-                    AsyncRwLock => {
-                        emit!(
-                            buffer,
-                            "pub async fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {{"
-                        );
-                    }
-                    _ => {
-                        emit!(
-                            buffer,
-                            "pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {{"
-                        );
-                    }
-                }
-            } else {
+                let (read, write) = get_uber_read_write(config);
                 emit!(
                     buffer,
-                    "pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {{"
+                    "store.{obj_ident}_id_by_name{write}.insert({obj_ident}{read}.name.to_upper_camel_case(), {obj_ident}{read}.{id});"
                 );
-            }
-            emit!(buffer, "let path = path.as_ref();");
-            emit!(buffer, "let path = path.join(\"{}.json\");", domain.name());
-            emit!(buffer, "");
-            if is_uber {
-                emit!(buffer, "let store = Self::new();");
             } else {
-                emit!(buffer, "let mut store = Self::new();");
-            }
-            emit!(buffer, "");
-
-            for obj in objects {
-                let obj_ident = obj.as_ident();
-                let _obj_type = obj.as_type(&Ownership::new_borrowed(), woog, domain);
-
-                emit!(buffer, "// Load {}.", obj.name);
-                emit!(buffer, "{{");
-                emit!(buffer, "let path = path.join(\"{}\");", obj.as_ident());
-                emit!(buffer, "let entries = fs::read_dir(path)?;");
-                emit!(buffer, "for entry in entries {{");
-                emit!(buffer, "let entry = entry?;");
-                emit!(buffer, "let path = entry.path();");
-                emit!(buffer, "let file = fs::File::open(path)?;");
-                emit!(buffer, "let reader = io::BufReader::new(file);");
-                let id = if local_object_is_enum(obj, config, domain) {
-                    "id()"
-                } else {
-                    "id"
-                };
-
-                let store_type = get_value_wrapper(is_uber, config, obj, woog, domain);
-                if timestamp {
-                    if is_uber {
-                        use UberStoreOptions::*;
-                        match config.get_uber_store().unwrap() {
-                            Disabled => unreachable!(),
-                            AsyncRwLock => {
-                                emit!(
-                                    buffer,
-                                    "let {obj_ident}: ({}, SystemTime) = serde_json::from_reader(reader).map(|(a, b)| (Arc::new(RwLock::new(a)), b))?;",
-                                    store_type,
-                                );
-                            }
-                            _ => {
-                                emit!(
-                                    buffer,
-                                    "let {obj_ident}: ({}, SystemTime) = serde_json::from_reader(reader)?;",
-                                    store_type,
-                                );
-                            }
-                        }
-                    } else {
-                        emit!(
-                            buffer,
-                            "let {obj_ident}: ({}, SystemTime) = serde_json::from_reader(reader)?;",
-                            store_type,
-                        );
-                    }
-
-                    if object_has_name(obj, domain) {
-                        if is_uber {
-                            let (read, write) = get_uber_read_write(config);
-                            emit!(
-                                buffer,
-                                "store.{obj_ident}_id_by_name{write}.insert({obj_ident}.0{read}.name.to_upper_camel_case(), ({obj_ident}.0{read}.{id}, {obj_ident}.1));"
-                            );
-                        } else {
-                            emit!(
-                                buffer,
-                                "store.{obj_ident}_id_by_name.insert({obj_ident}.0.name.to_upper_camel_case(), ({obj_ident}.0.{id}, {obj_ident}.1));"
-                            );
-                        }
-                    }
-                    if is_uber {
-                        let (read, write) = get_uber_read_write(config);
-                        emit!(
-                            buffer,
-                            "store.{obj_ident}{write}.insert({obj_ident}.0{read}.{id}, {obj_ident}.clone());"
-                        );
-                    } else {
-                        emit!(
-                            buffer,
-                            "store.{obj_ident}.insert({obj_ident}.0.{id}, {obj_ident});"
-                        );
-                    }
-                } else {
-                    if is_uber {
-                        use UberStoreOptions::*;
-                        match config.get_uber_store().unwrap() {
-                            Disabled => unreachable!(),
-                            AsyncRwLock => {
-                                emit!(
-                                    buffer,
-                                    "let {obj_ident}: {} = serde_json::from_reader(reader).map(|a| Arc::new(RwLock::new(a)), b))?;",
-                                    store_type,
-                                );
-                            }
-                            _ => {
-                                emit!(
-                                    buffer,
-                                    "let {obj_ident}: {} = serde_json::from_reader(reader)?;",
-                                    store_type,
-                                );
-                            }
-                        }
-                    } else {
-                        emit!(
-                            buffer,
-                            "let {obj_ident}: {} = serde_json::from_reader(reader)?;",
-                            store_type,
-                        );
-                    }
-
-                    if object_has_name(obj, domain) {
-                        if is_uber {
-
-let (read, write) = get_uber_read_write(config);
-                            emit!(
-                                buffer,
-                                "store.{obj_ident}_id_by_name{write}.insert({obj_ident}{read}.name.to_upper_camel_case(), {obj_ident}{read}.{id});"
-                            );
-                        } else {
-                            emit!(
-                                buffer,
-                                "store.{obj_ident}_id_by_name.insert({obj_ident}.name.to_upper_camel_case(), {obj_ident}.{id});"
-                            );
-                        }
-                    }
-                    if is_uber {
-let (read, write) = get_uber_read_write(config);
-                        emit!(
-                            buffer,
-                            "store.{obj_ident}{write}.insert({obj_ident}{read}.{id}, {obj_ident}.clone());"
-                        );
-                    } else {
-                        emit!(
-                            buffer,
-                            "store.{obj_ident}.insert({obj_ident}.{id}, {obj_ident});"
-                        );
-                    }
-                }
-
-                emit!(buffer, "}}");
-                emit!(buffer, "}}");
-                emit!(buffer, "");
+                emit!(
+                    buffer,
+                    "store.{obj_ident}_id_by_name.insert({obj_ident}.name.to_upper_camel_case(), {obj_ident}.{id});"
+                );
             }
-            emit!(buffer, "");
-            emit!(buffer, "Ok(store)");
-            emit!(buffer, "}}");
-
-            Ok(())
-        },
-    )?;
+        }
+        if is_uber {
+            let (read, write) = get_uber_read_write(config);
+            emit!(
+                buffer,
+                "store.{obj_ident}{write}.insert({obj_ident}{read}.{id}, {obj_ident}.clone());"
+            );
+        } else {
+            emit!(
+                buffer,
+                "store.{obj_ident}.insert({obj_ident}.{id}, {obj_ident});"
+            );
+        }
+    }
 
     Ok(())
 }
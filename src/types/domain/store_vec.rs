@@ -1,9 +1,10 @@
 //! Generate ObjectStore for use in sarzak Domain
 //!
-use std::fmt::Write;
+use std::{fmt::Write, sync::RwLock};
 
 use rustc_hash::{FxHashMap as HashMap, FxHashSet as HashSet};
 use sarzak::{
+    lu_dog::store::ObjectStore as LuDogStore,
     mc::{CompilerSnafu, FormatSnafu, Result},
     sarzak::types::Object,
     v2::domain::Domain,
@@ -14,8 +15,10 @@ use uuid::Uuid;
 
 use crate::{
     codegen::{
+        async_mutex,
         buffer::{emit, Buffer},
         diff_engine::DirectiveKind,
+        emit_async_lock_uses,
         generator::{CodeWriter, FileGenerator, GenerationAction},
         get_binary_referrers_sorted, get_subtypes_sorted_from_super_obj, local_object_is_enum,
         local_object_is_hybrid, local_object_is_singleton, local_object_is_subtype,
@@ -23,7 +26,18 @@ use crate::{
         render::{RenderConst, RenderIdent, RenderType},
     },
     options::{GraceConfig, UberStoreOptions},
-    types::ObjectStoreDefinition,
+    types::{
+        domain::store::{
+            emit_check_header, emit_document_persistence, emit_for_each, emit_for_each_end,
+            emit_git_persistence, emit_index_conflict, emit_index_fields, emit_index_methods,
+            emit_inter_index_doc, emit_job_end, emit_job_start, emit_load_bincode,
+            emit_parallel_load, emit_persist_bincode, emit_read_instance, emit_reindex,
+            emit_reindex_method, emit_remove_migrated, emit_snapshot, emit_store_header,
+            emit_store_instance, emit_streaming_load, has_indices, has_unique_indices,
+            object_indices, persist_in_parallel, store_derives_serde, StoreIndex,
+        },
+        ObjectStoreDefinition,
+    },
 };
 
 pub(crate) struct DomainStoreVecGenerator {
@@ -37,6 +51,7 @@ impl FileGenerator for DomainStoreVecGenerator {
         domain: &Domain,
         woog: &Option<&mut WoogStore>,
         imports: &Option<&HashMap<String, Domain>>,
+        lu_dog: &Option<&RwLock<LuDogStore>>,
         package: &str,
         module: &str,
         obj_id: Option<&Uuid>,
@@ -94,7 +109,7 @@ impl FileGenerator for DomainStoreVecGenerator {
                 }
 
                 self.definition.write_code(
-                    config, domain, woog, imports, package, module, obj_id, buffer,
+                    config, domain, woog, imports, lu_dog, package, module, obj_id, buffer,
                 )?;
 
                 Ok(())
@@ -132,6 +147,7 @@ impl DomainStoreVec {
                     let obj_ident = obj.as_ident();
                     let obj_type = obj.as_type(&Ownership::new_borrowed(), woog, domain);
                     let thing = get_value_wrapper(is_uber, config, obj, woog, domain);
+                    let indices = object_indices(obj, config, woog, domain)?;
 
                     // 🚦
                     // Generate inter_ methods
@@ -140,6 +156,7 @@ impl DomainStoreVec {
                         "/// Inter (insert) [`{obj_type}`] into the store.",
                     );
                     emit!(buffer, "///");
+                    emit_inter_index_doc(buffer, &indices)?;
                     emit!(buffer, "#[inline]");
 
                     if is_uber {
@@ -221,6 +238,7 @@ impl DomainStoreVec {
                                 emit!(buffer, "}} else {{");
                                 emit!(buffer, "log::debug!(target: \"store\", \"interring {{{obj_ident}:?}}.\");");
                                 emit!(buffer, "self.{obj_ident}{write}[_index] = Some({obj_ident}.clone());");
+                                emit_index_interred(buffer, &obj_ident, &indices, config)?;
                                 emit!(buffer, "{obj_ident}");
                                 emit!(buffer, "}}");
                             },
@@ -259,6 +277,7 @@ impl DomainStoreVec {
                                 emit!(buffer, "}} else {{");
                                 emit!(buffer, "log::debug!(target: \"store\", \"interring {{{obj_ident}:?}}.\");");
                                 emit!(buffer, "self.{obj_ident}{write}[_index] = Some({obj_ident}.clone());");
+                                emit_index_interred(buffer, &obj_ident, &indices, config)?;
                                 emit!(buffer, "{obj_ident}");
                                 emit!(buffer, "}}");
                             },
@@ -291,6 +310,7 @@ impl DomainStoreVec {
                                 emit!(buffer, "}} else {{");
                                 emit!(buffer, "log::debug!(target: \"store\", \"interring {{{obj_ident}:?}}.\");");
                                 emit!(buffer, "self.{obj_ident}[_index] = Some({obj_ident}.clone());");
+                                emit_index_interred(buffer, &obj_ident, &indices, config)?;
                                 emit!(buffer, "{obj_ident}");
                                 emit!(buffer, "}}");
                             },
@@ -537,6 +557,13 @@ impl DomainStoreVec {
                                 },
                                 store => panic!("{store} is not currently supported"),
                             }
+                            if !indices.is_empty() {
+                                let (read, _write) = get_uber_read_write(config);
+                                emit!(buffer, "if let Some(ref {obj_ident}) = result {{");
+                                emit!(buffer, "let read = {obj_ident}{read};");
+                                emit!(buffer, "self.unindex_{obj_ident}(&read);");
+                                emit!(buffer, "}}");
+                            }
                             emit!(buffer, "result");
                         }
                     } else if timestamp {
@@ -806,6 +833,14 @@ impl DomainStoreVec {
                         emit!(buffer, "}}");
                         emit!(buffer, "");
                     }
+
+                    // 🚦
+                    // Generate index methods
+                    emit_index_methods(buffer, obj, &indices, config, woog, domain)?;
+                }
+
+                if has_indices(objects, config) {
+                    emit_reindex_method(buffer, objects, timestamp, config, woog, domain)?;
                 }
 
                 Ok(())
@@ -825,6 +860,7 @@ impl CodeWriter for DomainStoreVec {
         domain: &Domain,
         woog: &Option<&mut WoogStore>,
         _imports: &Option<&HashMap<String, Domain>>,
+        _lu_dog: &Option<&RwLock<LuDogStore>>,
         _package: &str,
         module: &str,
         _obj_id: Option<&Uuid>,
@@ -989,16 +1025,16 @@ impl CodeWriter for DomainStoreVec {
                     } else {
                         emit!(buffer, "use std::{{io::{{self, prelude::*}}, fs, path::Path}};");
                     }
+                    if persist_in_parallel(config) {
+                        emit!(buffer, "use rayon::prelude::*;");
+                    }
                 }
                 if is_uber {
                     use UberStoreOptions::*;
                     match config.get_uber_store().unwrap() {
                         Disabled => unreachable!(),
                         AsyncRwLock => {
-                            emit!(buffer, "use async_std::sync::Arc;");
-                            emit!(buffer, "use async_std::sync::RwLock;");
-                            emit!(buffer, "use std::fmt;");
-                            emit!(buffer, "use serde::{{ser::SerializeStruct, Serializer, Deserializer, de::{{self, Visitor, MapAccess}}}};");
+                            emit_async_lock_uses(buffer, config)?;
                             emit!(buffer, "use futures::stream::{{self, StreamExt}};");
                         }
                         NDRwLock => {
@@ -1074,7 +1110,7 @@ impl CodeWriter for DomainStoreVec {
                     match config.get_uber_store().unwrap() {
                         StdRwLock | ParkingLotRwLock
                         | NDRwLock => emit!(buffer, "{obj_ident}_free_list: std::sync::Mutex<Vec<usize>>,"),
-                        AsyncRwLock => emit!(buffer, "{obj_ident}_free_list: async_std::sync::Mutex<Vec<usize>>,"),
+                        AsyncRwLock => emit!(buffer, "{obj_ident}_free_list: {}<Vec<usize>>,", async_mutex(config)),
 
                         Single => emit!(buffer, "{obj_ident}_free_list: Vec<usize>,"),
                         store => panic!("{store} is not currently supported"),
@@ -1153,188 +1189,24 @@ impl CodeWriter for DomainStoreVec {
                             emit!(buffer, "{obj_ident}_id_by_name: HashMap<String, usize>,");
                         }
                     }
+                    emit_index_fields(buffer, obj, config, woog, domain)?;
+                }
+                if has_unique_indices(&objects, config) {
+                    emit!(buffer, "/// Instances that collided with another in a unique index");
+                    if store_derives_serde(config) {
+                        emit!(buffer, "#[serde(skip)]");
+                    }
+                    emit!(buffer, "index_conflicts: Vec<IndexConflict>,");
                 }
                 emit!(buffer, "}}");
                 emit!(buffer, "");
 
-                if let UberStoreOptions::AsyncRwLock = config.get_uber_store().unwrap() {
-                    emit!(buffer, "impl Serialize for ObjectStore {{");
-                    emit!(buffer, "fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>");
-                    emit!(buffer, "where S: Serializer, {{");
-                    emit!(buffer, "let mut map = serializer.serialize_struct(\"ObjectStore\", {})?;", objects.len());
-                    for obj in &objects {
-                        let obj_ident = obj.as_ident();
-                        let obj_type = obj.as_type(&Ownership::new_borrowed(), woog, domain);
-                        emit!(buffer, r#"
-        let {obj_ident} = futures::executor::block_on(async {{ self.{obj_ident}.read().await }}).clone();
-        let values: Vec<{obj_type}> = {obj_ident}
-            .into_iter()
-            .filter_map(|{obj_ident}| {{
-                if let Some({obj_ident}) = {obj_ident} {{
-                    Some(futures::executor::block_on(async {{ {obj_ident}.read().await }}).clone())
-                }} else {{
-                    None
-                }}
-            }})
-            .collect();
-        map.serialize_field("{obj_ident}", &values)?;
-"#);
-                        // emit!(buffer, "let {obj_ident} = (*futures::executor::block_on(async {{self.{obj_ident}.read().await}})).clone();");
-                        // emit!(buffer, r#"map.serialize_entry("{obj_ident}".to_owned());"#);
-                        // emit!(buffer, "for v in {obj_ident}.iter() {{");
-                        // emit!(buffer, "// may as well compress it while we are here");
-                        // emit!(buffer, "if let Some(v) = v {{");
-                        // emit!(buffer, "seq.serialize_element(&(*futures::executor::block_on(async {{v.read().await}})).clone())?;");
-                        // emit!(buffer, "}}");
-                        // emit!(buffer, "}}");
-                        // emit!(buffer, "let result = map.end();\n");
-                    }
-                    emit!(buffer, "map.end()");
-                    emit!(buffer, "}}");
-                    emit!(buffer, "}}\n");
-
-                    emit!(buffer, "impl<'de> Deserialize<'de> for ObjectStore {{");
-                    emit!(buffer, "fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>");
-                    emit!(buffer, "where D: Deserializer<'de>, {{");
-                    emit!(buffer, "enum SerdeField {{");
-                    for obj in &objects {
-                        let obj_type = obj.as_type(&Ownership::new_borrowed(), woog, domain);
-                        emit!(
-                            buffer,
-                            "{obj_type},"
-                        );
-                    }
-                    emit!(buffer, "}}");
-                    emit!(buffer, "impl<'de> Deserialize<'de> for SerdeField {{");
-                    emit!(buffer, "fn deserialize<D>(deserializer: D) -> Result<SerdeField, D::Error>");
-                    emit!(buffer, "where D: Deserializer<'de>, {{");
-                    emit!(buffer, "struct FieldVisitor;");
-                    emit!(buffer, "impl<'de> Visitor<'de> for FieldVisitor {{");
-                    emit!(buffer, "type Value = SerdeField;");
-                    emit!(buffer, "fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {{");
-                    emit!(buffer, "formatter.write_str(\"field identifier\")");
-                    emit!(buffer, "}}");
-                    emit!(buffer, "fn visit_str<E>(self, value: &str) -> Result<SerdeField, E>");
-                    emit!(buffer, "where E: de::Error, {{");
-                    emit!(buffer, "match value {{");
-                    for obj in &objects {
-                        let obj_ident = obj.as_ident();
-                        let obj_type = obj.as_type(&Ownership::new_borrowed(), woog, domain);
-                        emit!(
-                            buffer,
-                            "\"{obj_ident}\" => Ok(SerdeField::{obj_type}),"
-                        );
-                    }
-                    emit!(buffer, "_ => Err(de::Error::unknown_field(value, FIELDS)),");
-                    emit!(buffer, "}}");
-                    emit!(buffer, "}}");
-                    emit!(buffer, "}}");
-                    emit!(buffer, "deserializer.deserialize_identifier(FieldVisitor)");
-                    emit!(buffer, "}}");
-                    emit!(buffer, "}}");
-                    emit!(buffer, "struct ObjectStoreVisitor;");
-                    emit!(buffer, "impl<'de> Visitor<'de> for ObjectStoreVisitor {{");
-                    emit!(buffer, "type Value = ObjectStore;");
-
-                    emit!(buffer, "fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {{");
-                    emit!(buffer, "formatter.write_str(\"struct ObjectStore\")");
-                    emit!(buffer, "}}");
-
-                    emit!(buffer, "fn visit_map<A>(self, mut map: A) -> Result<ObjectStore, A::Error>");
-                    emit!(buffer, "where A: MapAccess<'de>, {{");
-                    emit!(buffer, "let result = ObjectStore::new();");
-                    emit!(buffer, "let mut result = futures::executor::block_on(async {{ result.await }});");
-                    emit!(buffer, "while let Some(key) = map.next_key()? {{");
-                    emit!(buffer, "match key {{");
-                    for obj in &objects {
-                        let obj_ident = obj.as_ident();
-                        let obj_type = obj.as_type(&Ownership::new_borrowed(), woog, domain);
-
-                        emit!(
-                            buffer,
-                            r#"SerdeField::{obj_type} => {{
-                                let mut guard = futures::executor::block_on(result.{obj_ident}.write());
-                                let values: Vec<{obj_type}> = map.next_value()?;
-                                for value in values {{
-                                    guard.push(Some(Arc::new(RwLock::new(value))));
-                                }}
-                            }}"#
-                        );
-                    }
-                    emit!(buffer, "}}");
-                    emit!(buffer, "}}");
-                    emit!(buffer, "Ok(result)");
-                    emit!(buffer, "}}\n");
-
-                    // emit!(buffer, "fn visit_seq<A>(self, mut seq: A) -> Result<ObjectStore, A::Error>");
-                    // emit!(buffer, "where A: SeqAccess<'de>, {{");
-                    // emit!(buffer, "let result = ObjectStore::new();");
-                    // emit!(buffer, "let mut result = futures::executor::block_on(async {{ result.await }});");
-                    // // for obj in &objects {
-                    // //     let obj_ident = obj.as_ident();
-                    // //     let obj_type = obj.as_type(&Ownership::new_borrowed(), woog, domain);
-
-                    // //     emit!(
-                    // //         buffer,
-                    // //         r#"SerdeField::{obj_type} => futures::executor::block_on(async {{
-                    // //         let guard = result.{obj_ident}.write().await;
-                    // //         // This unwrap is unfortunate.
-                    // //         for value in map.next_value::<Vec<Option<{obj_type}>>>().unwrap() {{
-                    // //             let value = match value {{
-                    // //                 Some(value) => Some(Arc::new(RwLock::new(value))),
-                    // //                 None => None,
-                    // //             }};
-                    // //             guard.push(value);
-                    // //         }}
-                    // //     }}),"#,
-                    // //     );
-                    // // }
-                    // for (n, obj) in objects.iter().enumerate() {
-                    //     let obj_ident = obj.as_ident();
-
-                    //     if n == 0 {
-                    //         emit!(
-                    //             buffer,
-                    //             "result.{obj_ident} = Arc::new(RwLock::new(seq.next_element()?)).ok_or_else(|| de::Error::invalid_length({n}, &self))?;;",
-                    //         );
-                    //     }
-                    // }
-                    // emit!(buffer, "Ok(result)");
-                    // emit!(buffer, "}}");
-                    emit!(buffer, "}}\n");
-
-                    for obj in &objects {
-                        let _obj_ident = obj.as_ident();
-                        let obj_type = obj.as_type(&Ownership::new_borrowed(), woog, domain);
-
-                        emit!(buffer, "struct {obj_type}Visitor;");
-                        emit!(buffer, "impl<'de> Visitor<'de> for {obj_type}Visitor {{");
-                        emit!(buffer, "type Value = Arc<RwLock<HashMap<Uuid, Arc<RwLock<{obj_type}>>>>>;");
-                        // emit!(buffer, "type Value = Arc<RwLock<HashMap<Uuid, (Arc<RwLock<{obj_type}>>, SystemTime)>>>;");
-                        emit!(buffer, "fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {{");
-                        emit!(buffer, "formatter.write_str(\"{obj_type} map\")");
-                        emit!(buffer, "}}");
-                        emit!(buffer, "fn visit_map<M>(self, mut access: M) -> Result<Self::Value, M::Error>");
-                        emit!(buffer, "where M: MapAccess<'de>, {{");
-                        emit!(buffer, "let mut map = HashMap::default();");
-                        emit!(buffer, "while let Some((key, value)) = access.next_entry::<Uuid, {obj_type}>()? {{");
-                        // emit!(buffer, "while let Some((key, value)) = access.next_entry::<Uuid, ({obj_type}, SystemTime)>()? {{");
-                        emit!(buffer, "map.insert(key, Arc::new(RwLock::new(value)));");
-                        emit!(buffer, "}}");
-                        emit!(buffer, "Ok(Arc::new(RwLock::new(map)))");
-                        emit!(buffer, "}}}}\n");
-                    }
-                    emit!(buffer, "const FIELDS: &'static [&'static str] = &[");
-                    for obj in &objects {
-                        let obj_ident = obj.as_ident();
-                        emit!(buffer, "\"{obj_ident}\",");
+                if persist {
+                    if let UberStoreOptions::AsyncRwLock = config.get_uber_store().unwrap() {
+                        emit_snapshot(buffer, &objects, timestamp, config, woog, domain)?;
                     }
-                    emit!(buffer, "];");
-                    emit!(buffer, "deserializer.deserialize_struct(\"ObjectStore\", FIELDS, ObjectStoreVisitor)");
-                    emit!(buffer, "}}}}\n");
                 }
 
-
                 // impl ObjectStore
                 emit!(buffer, "impl ObjectStore {{");
                 if is_uber {
@@ -1362,7 +1234,7 @@ impl CodeWriter for DomainStoreVec {
                         use UberStoreOptions::*;
                         match config.get_uber_store().unwrap() {
                             StdRwLock | ParkingLotRwLock | NDRwLock => emit!(buffer, "{obj_ident}_free_list: std::sync::Mutex::new(Vec::new()),"),
-                            AsyncRwLock => emit!(buffer, "{obj_ident}_free_list: async_std::sync::Mutex::new(Vec::new()),"),
+                            AsyncRwLock => emit!(buffer, "{obj_ident}_free_list: {}::new(Vec::new()),", async_mutex(config)),
                             Single => emit!(buffer, "{obj_ident}_free_list: Vec::new(),"),
                             store => panic!("{store} is not currently supported"),
                         }
@@ -1396,7 +1268,12 @@ impl CodeWriter for DomainStoreVec {
                             emit!(buffer, "{obj_ident}_id_by_name: HashMap::default(),");
                         }
                     }
-
+                    for index in object_indices(obj, config, woog, domain)? {
+                        emit!(buffer, "{}: HashMap::default(),", index.name);
+                    }
+                }
+                if has_unique_indices(&objects, config) {
+                    emit!(buffer, "index_conflicts: Vec::new(),");
                 }
                 emit!(buffer, "}};");
                 emit!(buffer, "");
@@ -1487,6 +1364,18 @@ impl CodeWriter for DomainStoreVec {
 
                 emit!(buffer, "}}");
 
+                if has_unique_indices(&objects, config) {
+                    emit!(buffer, "");
+                    emit_index_conflict(buffer, module, config)?;
+                }
+
+                if persist {
+                    emit!(buffer, "");
+                    emit_store_header(buffer, module, config, domain)?;
+                    emit!(buffer, "");
+                    emit_store_instance(buffer, &objects, timestamp, module, config, woog, domain)?;
+                }
+
                 Ok(())
             },
         )?;
@@ -1532,26 +1421,9 @@ fn generate_store_persistence(
         format!("{}-object-store-persistence", module),
         |buffer| {
             let is_uber = config.is_uber_store();
+            let parallel = persist_in_parallel(config);
 
-            emit!(buffer, "/// Persist the store.");
-            emit!(buffer, "///");
-            emit!(
-                buffer,
-                "/// The store is persisted as a a bincode file."
-            );
-            emit!(
-                buffer,
-                "pub fn persist_bincode<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {{"
-            );
-            emit!(buffer, "let path = path.as_ref();");
-            emit!(buffer, "let mut bin_file = fs::File::create(path)?;");
-            emit!(
-                buffer,
-                "let encoded: Vec<u8> = bincode::serialize(&self).unwrap();"
-            );
-            emit!(buffer, "bin_file.write_all(&encoded)?;");
-            emit!(buffer, "Ok(())");
-            emit!(buffer, "}}\n");
+            emit_persist_bincode(buffer, config)?;
 
             emit!(buffer, "/// Persist the store.");
             emit!(buffer, "///");
@@ -1563,10 +1435,17 @@ fn generate_store_persistence(
                 buffer,
                 "/// is that this directory can be checked into version control."
             );
-            emit!(
-                buffer,
-                "/// In fact, I intend to add automagic git integration as an option."
-            );
+            if config.get_persist_git() {
+                emit!(
+                    buffer,
+                    "/// `persist_git` does just that, and `history_of_*` reads it back."
+                );
+            } else {
+                emit!(
+                    buffer,
+                    "/// Generate the store with `--persist-git` for git integration."
+                );
+            }
             if is_uber {
                 use UberStoreOptions::*;
                 match config.get_uber_store().unwrap() {
@@ -1597,7 +1476,23 @@ fn generate_store_persistence(
             emit!(buffer, "let path = path.join(\"{}.json\");", domain.name());
             emit!(buffer, "fs::create_dir_all(&path)?;");
             emit!(buffer, "");
+            emit!(buffer, "// Persist the header.");
+            emit!(buffer, "{{");
+            emit!(buffer, "let file = fs::File::create(path.join(\"header.json\"))?;");
+            emit!(buffer, "let mut writer = io::BufWriter::new(file);");
+            emit!(
+                buffer,
+                "serde_json::to_writer_pretty(&mut writer, &StoreHeader::current())?;"
+            );
+            emit!(buffer, "}}");
+            emit!(buffer, "");
 
+            if parallel {
+                emit!(
+                    buffer,
+                    "let jobs: Vec<Box<dyn Fn() -> io::Result<()> + Send + Sync + '_>> = vec!["
+                );
+            }
             for obj in objects {
                 let obj_ident = obj.as_ident();
                 let obj_type = obj.as_type(&Ownership::new_borrowed(), woog, domain);
@@ -1608,25 +1503,29 @@ fn generate_store_persistence(
                 };
 
                 emit!(buffer, "// Persist {}.", obj.name);
-                emit!(buffer, "{{");
+                emit_job_start(buffer, parallel)?;
                 emit!(buffer, "let path = path.join(\"{}\");", obj.as_ident());
                 emit!(buffer, "fs::create_dir_all(&path)?;");
                 if timestamp {
                     if is_uber {
                         let (read, _write) = get_uber_read_write(config);
-                        emit!(
+                        emit_for_each(
                             buffer,
-                            "for {obj_ident}_tuple in self.{obj_ident}{read}.values() {{"
-                        );
+                            parallel,
+                            &format!("{obj_ident}_tuple"),
+                            &format!("self.{obj_ident}{read}.values()"),
+                        )?;
                         emit!(
                             buffer,
                             "let path = path.join(format!(\"{{}}.json\", {obj_ident}_tuple.0{read}.{id}));"
                         );
                     } else {
-                        emit!(
+                        emit_for_each(
                             buffer,
-                            "for {obj_ident}_tuple in self.{obj_ident}.values() {{"
-                        );
+                            parallel,
+                            &format!("{obj_ident}_tuple"),
+                            &format!("self.{obj_ident}.values()"),
+                        )?;
                         emit!(
                             buffer,
                             "let path = path.join(format!(\"{{}}.json\", {obj_ident}_tuple.0.{id}));"
@@ -1730,7 +1629,7 @@ fn generate_store_persistence(
 
                     emit!(buffer, "}}");
 
-                    emit!(buffer, "}}");
+                    emit_for_each_end(buffer, parallel)?;
 
                     // Now we need to delete any files that correspond to something
                     // in the store that went away.
@@ -1773,14 +1672,19 @@ fn generate_store_persistence(
                     emit!(buffer, "}}");
                     emit!(buffer, "}}");
                     emit!(buffer, "}}");
-                    emit!(buffer, "}}");
+                    emit_job_end(buffer, parallel)?;
                 } else {
                     if is_uber {
                         let (read, _write) = get_uber_read_write(config);
                         use UberStoreOptions::*;
                         match config.get_uber_store().unwrap() {
                             AsyncRwLock | StdRwLock | ParkingLotRwLock | NDRwLock => {
-                                emit!(buffer, "for {obj_ident} in &*self.{obj_ident}{read} {{");
+                                emit_for_each(
+                                    buffer,
+                                    parallel,
+                                    &obj_ident,
+                                    &format!("self.{obj_ident}{read}.iter()"),
+                                )?;
                             },
                             Single => {
                                 emit!(buffer, "for {obj_ident} in &self.{obj_ident} {{");
@@ -1796,10 +1700,12 @@ fn generate_store_persistence(
                             "let path = path.join(format!(\"{{}}.json\", {obj_ident}{read}.{id}));"
                         );
                     } else {
-                        emit!(
+                        emit_for_each(
                             buffer,
-                            "for {obj_ident} in self.{obj_ident}.values() {{"
-                        );
+                            parallel,
+                            &obj_ident,
+                            &format!("self.{obj_ident}.values()"),
+                        )?;
                         emit!(
                             buffer,
                             "let path = path.join(format!(\"{{}}.json\", {obj_ident}.{id}));"
@@ -1834,32 +1740,21 @@ fn generate_store_persistence(
                         );
                     }
 
-                    emit!(buffer, "}}");
-                    emit!(buffer, "}}");
+                    emit_for_each_end(buffer, parallel)?;
+                    emit_job_end(buffer, parallel)?;
                 }
                 emit!(buffer, "");
             }
+            if parallel {
+                emit!(buffer, "];");
+                emit!(buffer, "jobs.par_iter().try_for_each(|job| job())?;");
+                emit!(buffer, "");
+            }
             emit!(buffer, "Ok(())");
             emit!(buffer, "}}");
             emit!(buffer, "");
 
-            emit!(buffer, "/// Load the store.");
-            emit!(buffer, "///");
-            emit!(buffer, "pub fn from_bincode(code: &[u8]) -> io::Result<Self> {{");
-            emit!(buffer, "Ok(bincode::deserialize(code).unwrap())");
-            emit!(buffer, "}}\n");
-            emit!(
-                buffer,
-                "/// The store is as a bincode file."
-            );
-            emit!(
-                buffer,
-                "pub fn load_bincode<P: AsRef<Path>>(path: P) -> io::Result<Self> {{"
-            );
-            emit!(buffer, "let path = path.as_ref();");
-            emit!(buffer, "let bin_file = fs::File::open(path)?;");
-            emit!(buffer, "Ok(bincode::deserialize_from(bin_file).unwrap())");
-            emit!(buffer, "}}\n");
+            emit_load_bincode(buffer, objects, config)?;
 
             emit!(buffer, "/// Load the store.");
             emit!(buffer, "///");
@@ -1871,10 +1766,17 @@ fn generate_store_persistence(
                 buffer,
                 "/// is that this directory can be checked into version control."
             );
-            emit!(
-                buffer,
-                "/// In fact, I intend to add automagic git integration as an option."
-            );
+            if config.get_persist_git() {
+                emit!(
+                    buffer,
+                    "/// `persist_git` does just that, and `history_of_*` reads it back."
+                );
+            } else {
+                emit!(
+                    buffer,
+                    "/// Generate the store with `--persist-git` for git integration."
+                );
+            }
             if is_uber {
                 use UberStoreOptions::*;
                 match config.get_uber_store().unwrap() {
@@ -1898,8 +1800,9 @@ fn generate_store_persistence(
                     "pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {{"
                 );
             }
-            emit!(buffer, "let path = path.as_ref();");
-            emit!(buffer, "let path = path.join(\"{}.json\");", domain.name());
+            emit!(buffer, "let from = path.as_ref();");
+            emit!(buffer, "let root = Self::check_header(from)?;");
+            emit!(buffer, "let path = root.join(\"{}.json\");", domain.name());
             emit!(buffer, "");
             emit!(buffer, "let mut store = Self::new();");
             if is_uber {
@@ -1911,10 +1814,18 @@ fn generate_store_persistence(
             }
             emit!(buffer, "");
 
+            if parallel {
+                emit_parallel_load(
+                    buffer,
+                    objects,
+                    timestamp,
+                    config,
+                    woog,
+                    domain,
+                    emit_insert_instance,
+                )?;
+            } else {
             for obj in objects {
-                let obj_ident = obj.as_ident();
-                let _obj_type = obj.as_type(&Ownership::new_borrowed(), woog, domain);
-
                 emit!(buffer, "// Load {}.", obj.name);
                 emit!(buffer, "{{");
                 emit!(buffer, "let path = path.join(\"{}\");", obj.as_ident());
@@ -1924,170 +1835,209 @@ fn generate_store_persistence(
                 emit!(buffer, "let path = entry.path();");
                 emit!(buffer, "let file = fs::File::open(path)?;");
                 emit!(buffer, "let reader = io::BufReader::new(file);");
-                let id = if local_object_is_enum(obj, config, domain) {
-                    "id()"
-                } else {
-                    "id"
-                };
+                emit_read_instance(
+                    buffer,
+                    obj,
+                    "from_reader(reader)",
+                    timestamp,
+                    config,
+                    woog,
+                    domain,
+                )?;
+                emit_insert_instance(buffer, obj, timestamp, config, domain)?;
 
-                let store_type = get_value_wrapper(is_uber, config, obj, woog, domain);
-                if timestamp {
-                    if is_uber {
-                        use UberStoreOptions::*;
-                        match config.get_uber_store().unwrap() {
-                            Disabled => unreachable!(),
-                            AsyncRwLock => {
-                                emit!(
-                                    buffer,
-                                    "let {obj_ident}: ({}, SystemTime) = serde_json::from_reader(reader).map(|(a, b)| (Arc::new(RwLock::new(a)), b))?;",
-                                    store_type,
-                                );
-                            }
-                            _ => {
-                                emit!(
-                                    buffer,
-                                    "let {obj_ident}: ({}, SystemTime) = serde_json::from_reader(reader)?;",
-                                    store_type,
-                                );
-                            }
-                        }
-                    } else {
-                        emit!(
-                            buffer,
-                            "let {obj_ident}: ({}, SystemTime) = serde_json::from_reader(reader)?;",
-                            store_type,
-                        );
-                    }
+                emit!(buffer, "}}");
+                emit!(buffer, "}}");
+                emit!(buffer, "");
+            }
+            }
+            emit!(buffer, "");
+            emit_reindex(buffer, objects, "store", config)?;
+            emit_remove_migrated(buffer, config)?;
+            emit!(buffer, "Ok(store)");
+            emit!(buffer, "}}");
+            emit!(buffer, "");
 
-                    if object_has_name(obj, domain) {
-                        if is_uber {
-                            let (read, write) = get_uber_read_write(config);
-                            use UberStoreOptions::*;
-                            match config.get_uber_store().unwrap() {
-                                AsyncRwLock | StdRwLock | ParkingLotRwLock | NDRwLock => {
-                                    emit!(
-                                        buffer,
-                                        "store.{obj_ident}_id_by_name{write}.insert({obj_ident}.0{read}.name.to_owned(), ({obj_ident}.0{read}.{id}, {obj_ident}.1));"
-                                    );
-                                }
-                                Single => {
-                                    emit!(
-                                        buffer,
-                                        "store.{obj_ident}_id_by_name.insert({obj_ident}.0{read}.name.to_owned(), ({obj_ident}.0{read}.{id}, {obj_ident}.1));"
-                                    );
-                                }
-                                store => panic!("{store} is not currently supported"),
-                            }
-                        } else {
-                            emit!(
-                                buffer,
-                                "store.{obj_ident}_id_by_name.insert({obj_ident}.0.name.to_owned(), ({obj_ident}.0.{id}, {obj_ident}.1));"
-                            );
-                        }
-                    }
-                    if is_uber {
-                        let (read, write) = get_uber_read_write(config);
+            let new_store = match config.get_uber_store() {
+                Some(UberStoreOptions::AsyncRwLock) => "let mut store = Self::new().await;",
+                _ => "let mut store = Self::new();",
+            };
+            emit_document_persistence(
+                buffer,
+                objects,
+                timestamp,
+                new_store,
+                config,
+                woog,
+                domain,
+                emit_insert_instance,
+                store_values,
+            )?;
+
+            emit_streaming_load(buffer, objects, parallel, config, domain)?;
+
+            if config.get_persist_git() {
+                emit_git_persistence(buffer, objects, timestamp, config, woog, domain)?;
+            }
+
+            emit_check_header(buffer, config, domain)?;
+
+            Ok(())
+        },
+    )?;
+
+    Ok(())
+}
+
+/// Add a freshly interred instance to its indices
+///
+/// A duplicate is handed back without being interred, so it's already indexed.
+fn emit_index_interred(
+    buffer: &mut Buffer,
+    obj_ident: &str,
+    indices: &[StoreIndex],
+    config: &GraceConfig,
+) -> Result<()> {
+    if !indices.is_empty() {
+        let (read, _write) = get_uber_read_write(config);
+        emit!(buffer, "{{");
+        emit!(buffer, "let read = {obj_ident}{read};");
+        emit!(buffer, "self.index_{obj_ident}(&read);");
+        emit!(buffer, "}}");
+    }
+
+    Ok(())
+}
+
+/// Iterate over the values in the store, skipping empty slots
+///
+/// The async lock is awaited, so for that store this goes in an async fn.
+fn store_values(obj: &Object, timestamp: bool, config: &GraceConfig) -> String {
+    let obj_ident = obj.as_ident();
+
+    use UberStoreOptions::*;
+    match config.get_uber_store() {
+        None | Some(Disabled) => format!("self.{obj_ident}.iter()"),
+        Some(Single) if timestamp => format!("self.{obj_ident}.borrow().iter().flatten()"),
+        Some(Single) => format!("self.{obj_ident}.iter().flatten()"),
+        Some(StdMutex) | Some(ParkingLotMutex) => {
+            let (read, _write) = get_uber_read_write(config);
+            format!("self.{obj_ident}{read}.iter()")
+        }
+        Some(_) => {
+            let (read, _write) = get_uber_read_write(config);
+            format!("self.{obj_ident}{read}.iter().flatten()")
+        }
+    }
+}
+
+/// Insert an instance, deserialized by [`emit_read_instance`], into the store
+fn emit_insert_instance(
+    buffer: &mut Buffer,
+    obj: &Object,
+    timestamp: bool,
+    config: &GraceConfig,
+    domain: &Domain,
+) -> Result<()> {
+    let is_uber = config.is_uber_store();
+    let obj_ident = obj.as_ident();
+    let id = if local_object_is_enum(obj, config, domain) {
+        "id()"
+    } else {
+        "id"
+    };
+
+    if timestamp {
+        if object_has_name(obj, domain) {
+            if is_uber {
+                let (read, write) = get_uber_read_write(config);
+                use UberStoreOptions::*;
+                match config.get_uber_store().unwrap() {
+                    AsyncRwLock | StdRwLock | ParkingLotRwLock | NDRwLock => {
                         emit!(
                             buffer,
-                            "store.{obj_ident}{write}.insert({obj_ident}.0{read}.{id}, {obj_ident}.clone());"
+                            "store.{obj_ident}_id_by_name{write}.insert({obj_ident}.0{read}.name.to_owned(), ({obj_ident}.0{read}.{id}, {obj_ident}.1));"
                         );
-                    } else {
+                    }
+                    Single => {
                         emit!(
                             buffer,
-                            "store.{obj_ident}.insert({obj_ident}.0.{id}, {obj_ident});"
+                            "store.{obj_ident}_id_by_name.insert({obj_ident}.0{read}.name.to_owned(), ({obj_ident}.0{read}.{id}, {obj_ident}.1));"
                         );
                     }
-                } else {
-                    if is_uber {
-                        use UberStoreOptions::*;
-                        match config.get_uber_store().unwrap() {
-                            Disabled => unreachable!(),
-                            AsyncRwLock => {
-                                emit!(
-                                    buffer,
-                                    "let {obj_ident}: {} = serde_json::from_reader(reader).map(|a| Arc::new(RwLock::new(a)))?;",
-                                    store_type,
-                                );
-                            }
-                            _ => {
-                                emit!(
-                                    buffer,
-                                    "let {obj_ident}: {} = serde_json::from_reader(reader)?;",
-                                    store_type,
-                                );
-                            }
-                        }
-                    } else {
+                    store => panic!("{store} is not currently supported"),
+                }
+            } else {
+                emit!(
+                    buffer,
+                    "store.{obj_ident}_id_by_name.insert({obj_ident}.0.name.to_owned(), ({obj_ident}.0.{id}, {obj_ident}.1));"
+                );
+            }
+        }
+        if is_uber {
+            let (read, write) = get_uber_read_write(config);
+            emit!(
+                buffer,
+                "store.{obj_ident}{write}.insert({obj_ident}.0{read}.{id}, {obj_ident}.clone());"
+            );
+        } else {
+            emit!(
+                buffer,
+                "store.{obj_ident}.insert({obj_ident}.0.{id}, {obj_ident});"
+            );
+        }
+    } else {
+        if object_has_name(obj, domain) {
+            if is_uber {
+                let (read, write) = get_uber_read_write(config);
+                use UberStoreOptions::*;
+                match config.get_uber_store().unwrap() {
+                    AsyncRwLock | StdRwLock | ParkingLotRwLock | NDRwLock => {
                         emit!(
                             buffer,
-                            "let {obj_ident}: {} = serde_json::from_reader(reader)?;",
-                            store_type,
+                            "store.{obj_ident}_id_by_name{write}.insert({obj_ident}{read}.name.to_owned(), {obj_ident}{read}.{id});"
                         );
                     }
-
-                    if object_has_name(obj, domain) {
-                        if is_uber {
-                            let (read, write) = get_uber_read_write(config);
-                            use UberStoreOptions::*;
-                            match config.get_uber_store().unwrap() {
-                                AsyncRwLock| StdRwLock | ParkingLotRwLock | NDRwLock => {
-                                    emit!(
-                                        buffer,
-                                        "store.{obj_ident}_id_by_name{write}.insert({obj_ident}{read}.name.to_owned(), {obj_ident}{read}.{id});"
-                                    );
-                                }
-                                Single => {
-                                    emit!(
-                                        buffer,
-                                        "store.{obj_ident}_id_by_name.insert({obj_ident}{read}.name.to_owned(), {obj_ident}{read}.{id});"
-                                    );
-                                }
-                                store => panic!("{store} is not currently supported"),
-                            }
-                        } else {
-                            emit!(
-                                buffer,
-                                "store.{obj_ident}_id_by_name.insert({obj_ident}.name.to_owned(), {obj_ident}.{id});"
-                            );
-                        }
-                    }
-                    if is_uber {
-                        let (read, write) = get_uber_read_write(config);
-                        use UberStoreOptions::*;
-                        match config.get_uber_store().unwrap() {
-                            AsyncRwLock | StdRwLock | ParkingLotRwLock |  NDRwLock => {
-                                emit!(
-                                    buffer,
-                                    "store.{obj_ident}{write}.insert({obj_ident}{read}.{id}, Some({obj_ident}.clone()));"
-                                );
-                            }
-                            Single => {
-                                emit!(
-                                    buffer,
-                                    "store.{obj_ident}.insert({obj_ident}{read}.{id}, Some({obj_ident}.clone()));"
-                                );
-                            }
-                            store => panic!("{store} is not currently supported"),
-                        }
-                    } else {
+                    Single => {
                         emit!(
                             buffer,
-                            "store.{obj_ident}.insert({obj_ident}.{id}, {obj_ident});"
+                            "store.{obj_ident}_id_by_name.insert({obj_ident}{read}.name.to_owned(), {obj_ident}{read}.{id});"
                         );
                     }
+                    store => panic!("{store} is not currently supported"),
                 }
-
-                emit!(buffer, "}}");
-                emit!(buffer, "}}");
-                emit!(buffer, "");
+            } else {
+                emit!(
+                    buffer,
+                    "store.{obj_ident}_id_by_name.insert({obj_ident}.name.to_owned(), {obj_ident}.{id});"
+                );
             }
-            emit!(buffer, "");
-            emit!(buffer, "Ok(store)");
-            emit!(buffer, "}}");
-
-            Ok(())
-        },
-    )?;
+        }
+        if is_uber {
+            let (read, write) = get_uber_read_write(config);
+            use UberStoreOptions::*;
+            match config.get_uber_store().unwrap() {
+                AsyncRwLock | StdRwLock | ParkingLotRwLock | NDRwLock => {
+                    emit!(
+                        buffer,
+                        "store.{obj_ident}{write}.insert({obj_ident}{read}.{id}, Some({obj_ident}.clone()));"
+                    );
+                }
+                Single => {
+                    emit!(
+                        buffer,
+                        "store.{obj_ident}.insert({obj_ident}{read}.{id}, Some({obj_ident}.clone()));"
+                    );
+                }
+                store => panic!("{store} is not currently supported"),
+            }
+        } else {
+            emit!(
+                buffer,
+                "store.{obj_ident}.insert({obj_ident}.{id}, {obj_ident});"
+            );
+        }
+    }
 
     Ok(())
 }
@@ -1,12 +1,14 @@
 //! Domain Struct Generation
 //!
 //! Your one-stop-shop for everything to do with structs in Rust!
-use std::fmt::Write;
+use std::{fmt::Write, sync::RwLock};
 
 use log;
 use rustc_hash::{FxHashMap as HashMap, FxHashSet as HashSet};
 use sarzak::{
+    lu_dog::store::ObjectStore as LuDogStore,
     mc::{CompilerSnafu, FormatSnafu, Result},
+    sarzak::types::Object,
     v2::domain::Domain,
     woog::{store::ObjectStore as WoogStore, types::Ownership},
 };
@@ -17,11 +19,12 @@ use crate::{
     codegen::{
         buffer::{emit, Buffer},
         diff_engine::DirectiveKind,
-        emit_object_comments, find_store,
+        emit_async_lock_uses, emit_object_comments, emit_typed_id_uses, emit_uuid_mode_uses,
+        find_store,
         generator::CodeWriter,
         get_assoc_referent_from_referrer_sorted,
         get_assoc_referrer_obj_from_obj_via_assoc_referent, get_binary_referents_sorted,
-        get_binary_referrers_sorted, get_objs_for_assoc_referrers_sorted,
+        get_binary_referrers_sorted, get_method_parameters, get_objs_for_assoc_referrers_sorted,
         get_objs_for_binary_referents_sorted, get_objs_for_binary_referrers_sorted,
         get_subtypes_sorted, local_object_is_hybrid, object_is_hybrid,
         render::{
@@ -35,6 +38,7 @@ use crate::{
         domain::rels::{
             generate_assoc_referent_rels, generate_assoc_referrer_rels,
             generate_binary_referent_rels, generate_binary_referrer_rels, generate_subtype_rels,
+            NavMethods,
         },
         MethodImplementation, TypeDefinition, TypeImplementation, TypeImports,
     },
@@ -57,6 +61,7 @@ impl CodeWriter for Imports {
         domain: &Domain,
         woog: &Option<&mut WoogStore>,
         imports: &Option<&HashMap<String, Domain>>,
+        _lu_dog: &Option<&RwLock<LuDogStore>>,
         _package: &str,
         module: &str,
         obj_id: Option<&Uuid>,
@@ -117,8 +122,7 @@ impl CodeWriter for Imports {
                     match config.get_uber_store().unwrap() {
                         Disabled => unreachable!(),
                         AsyncRwLock => {
-                            emit!(buffer, "use async_std::sync::Arc;");
-                            emit!(buffer, "use async_std::sync::RwLock;");
+                            emit_async_lock_uses(buffer, config)?;
                             emit!(buffer, "use futures::stream::{{self, StreamExt}};");
                         }
                         NDRwLock => {
@@ -156,6 +160,15 @@ impl CodeWriter for Imports {
                 // if config.get_optimization_level() == &crate::options::OptimizationLevel::None {
                 emit!(buffer, "use uuid::Uuid;");
                 // }
+                // Imported objects only get an extension trait, and it doesn't need these.
+                if !config.is_imported(&obj.id) {
+                    // Only the constructor makes an id, and we only render one
+                    // if woog has it.
+                    if woog.iter_object_method().any(|m| m.object == obj.id) {
+                        emit_uuid_mode_uses(buffer, module, config)?;
+                    }
+                    emit_typed_id_uses(buffer, module, config)?;
+                }
                 emit!(buffer, "");
 
                 // Add the use statements from the options.
@@ -273,6 +286,7 @@ impl CodeWriter for Struct {
         domain: &Domain,
         woog: &Option<&mut WoogStore>,
         _imports: &Option<&HashMap<String, Domain>>,
+        _lu_dog: &Option<&RwLock<LuDogStore>>,
         _package: &str,
         _module: &str,
         obj_id: Option<&Uuid>,
@@ -380,6 +394,7 @@ impl CodeWriter for DomainImplementation {
         domain: &Domain,
         woog: &Option<&mut WoogStore>,
         imports: &Option<&HashMap<String, Domain>>,
+        lu_dog: &Option<&RwLock<LuDogStore>>,
         package: &str,
         module: &str,
         obj_id: Option<&Uuid>,
@@ -425,6 +440,7 @@ impl CodeWriter for DomainImplementation {
                         domain,
                         woog,
                         imports,
+                        lu_dog,
                         package,
                         module,
                         Some(obj_id),
@@ -457,6 +473,7 @@ impl CodeWriter for EqImpl {
         domain: &Domain,
         _woog: &Option<&mut WoogStore>,
         _imports: &Option<&HashMap<String, Domain>>,
+        _lu_dog: &Option<&RwLock<LuDogStore>>,
         _package: &str,
         _module: &str,
         obj_id: Option<&Uuid>,
@@ -543,6 +560,7 @@ impl CodeWriter for StructNewImpl {
         domain: &Domain,
         woog: &Option<&mut WoogStore>,
         imports: &Option<&HashMap<String, Domain>>,
+        _lu_dog: &Option<&RwLock<LuDogStore>>,
         _package: &str,
         _module: &str,
         obj_id: Option<&Uuid>,
@@ -575,6 +593,297 @@ impl CodeWriter for StructNewImpl {
     }
 }
 
+/// Domain Struct Builder Method Implementation
+///
+/// This generates a `builder` method on the object, which hands back an empty
+/// `FooBuilder`. The builder itself is generated by [`StructBuilder`].
+pub(crate) struct StructBuilderImpl;
+
+impl StructBuilderImpl {
+    pub(crate) fn new() -> Box<dyn MethodImplementation> {
+        Box::new(Self)
+    }
+}
+
+impl MethodImplementation for StructBuilderImpl {}
+
+impl CodeWriter for StructBuilderImpl {
+    fn write_code(
+        &self,
+        config: &GraceConfig,
+        domain: &Domain,
+        woog: &Option<&mut WoogStore>,
+        _imports: &Option<&HashMap<String, Domain>>,
+        _lu_dog: &Option<&RwLock<LuDogStore>>,
+        _package: &str,
+        _module: &str,
+        obj_id: Option<&Uuid>,
+        buffer: &mut Buffer,
+    ) -> Result<()> {
+        ensure!(
+            obj_id.is_some(),
+            CompilerSnafu {
+                description: "obj_id is required by StructBuilderImpl"
+            }
+        );
+        let obj_id = obj_id.unwrap();
+        let obj = domain.sarzak().exhume_object(obj_id).unwrap();
+        ensure!(
+            woog.is_some(),
+            CompilerSnafu {
+                description: "woog is required by StructBuilderImpl"
+            }
+        );
+        let woog = woog.as_ref().unwrap();
+
+        let params = match get_builder_params(obj, config, woog, domain) {
+            Some(params) => params,
+            None => return Ok(()),
+        };
+        let lifetime = if params.iter().any(|(_, ty, _)| ty.contains('&')) {
+            "<'a>"
+        } else {
+            ""
+        };
+        let obj_type = obj.as_type(&Ownership::new_borrowed(), woog, domain);
+
+        buffer.block(
+            DirectiveKind::IgnoreOrig,
+            format!("{}-struct-impl-builder", obj.as_ident()),
+            |buffer| {
+                emit!(buffer, "/// Start building a new [`{obj_type}`].");
+                emit!(
+                    buffer,
+                    "pub fn builder{lifetime}() -> {obj_type}Builder{lifetime} {{"
+                );
+                emit!(buffer, "{obj_type}Builder::default()");
+                emit!(buffer, "}}");
+
+                Ok(())
+            },
+        )
+    }
+}
+
+/// Domain Struct Builder Generator / CodeWriter
+///
+/// This generates `FooBuilder`, which has a setter for each argument to
+/// `Foo::new`, and a `build` method that takes the store. `build` checks that
+/// all of the required arguments were set, and then calls `new`. That way we
+/// don't have to duplicate all of the logic in `new`, and the builder stays in
+/// sync with whatever the model says.
+///
+/// Optional, i.e., conditional, referentials may be left unset.
+///
+/// 🚧 The check for required arguments happens at runtime. A typestate builder
+/// would make it a compile time check, but the generated code would be
+/// enormous.
+pub(crate) struct StructBuilder;
+
+impl StructBuilder {
+    pub(crate) fn new() -> Box<dyn TypeImplementation> {
+        Box::new(Self)
+    }
+}
+
+impl TypeImplementation for StructBuilder {}
+
+impl CodeWriter for StructBuilder {
+    fn write_code(
+        &self,
+        config: &GraceConfig,
+        domain: &Domain,
+        woog: &Option<&mut WoogStore>,
+        _imports: &Option<&HashMap<String, Domain>>,
+        _lu_dog: &Option<&RwLock<LuDogStore>>,
+        _package: &str,
+        _module: &str,
+        obj_id: Option<&Uuid>,
+        buffer: &mut Buffer,
+    ) -> Result<()> {
+        ensure!(
+            obj_id.is_some(),
+            CompilerSnafu {
+                description: "obj_id is required by StructBuilder"
+            }
+        );
+        let obj_id = obj_id.unwrap();
+        let obj = domain.sarzak().exhume_object(obj_id).unwrap();
+        ensure!(
+            woog.is_some(),
+            CompilerSnafu {
+                description: "woog is required by StructBuilder"
+            }
+        );
+        let woog = woog.as_ref().unwrap();
+
+        let params = match get_builder_params(obj, config, woog, domain) {
+            Some(params) => params,
+            None => return Ok(()),
+        };
+
+        let obj_type = obj.as_type(&Ownership::new_borrowed(), woog, domain);
+        let builder_type = format!("{obj_type}Builder");
+        let has_lifetime = params.iter().any(|(_, ty, _)| ty.contains('&'));
+        let lifetime = if has_lifetime { "<'a>" } else { "" };
+        // The parameter types are references, so we need to give them a name.
+        let with_lifetime = |ty: &str| {
+            if has_lifetime {
+                ty.replace('&', "&'a ")
+            } else {
+                ty.to_owned()
+            }
+        };
+
+        let (store_name, store_type) = params
+            .iter()
+            .find(|(name, _, _)| name == "store")
+            .map(|(name, ty, _)| (name.clone(), ty.clone()))
+            .unwrap();
+        let params: Vec<_> = params
+            .into_iter()
+            .filter(|(name, _, _)| name != "store")
+            .collect();
+
+        let is_async = if config.is_uber_store() {
+            matches!(
+                config.get_uber_store().unwrap(),
+                UberStoreOptions::AsyncRwLock
+            )
+        } else {
+            false
+        };
+
+        let return_type = if config.is_uber_store() {
+            use UberStoreOptions::*;
+            match config.get_uber_store().unwrap() {
+                Disabled => unreachable!(),
+                Single => format!("Rc<RefCell<{obj_type}>>"),
+                StdRwLock | ParkingLotRwLock | AsyncRwLock | NDRwLock => {
+                    format!("Arc<RwLock<{obj_type}>>")
+                }
+                StdMutex | ParkingLotMutex => format!("Arc<Mutex<{obj_type}>>"),
+            }
+        } else {
+            obj_type.clone()
+        };
+
+        buffer.block(
+            DirectiveKind::IgnoreOrig,
+            format!("{}-builder-definition", obj.as_ident()),
+            |buffer| {
+                emit!(buffer, "/// Builder for [`{obj_type}`]");
+                emit!(buffer, "///");
+                emit!(
+                    buffer,
+                    "/// Set the fields by name, and then call `build` to intern the instance."
+                );
+                emit!(buffer, "#[derive(Default)]");
+                emit!(buffer, "pub struct {builder_type}{lifetime} {{");
+                for (name, ty, is_optional) in &params {
+                    if *is_optional {
+                        emit!(buffer, "{name}: {},", with_lifetime(ty));
+                    } else {
+                        emit!(buffer, "{name}: Option<{}>,", with_lifetime(ty));
+                    }
+                }
+                emit!(buffer, "}}");
+
+                Ok(())
+            },
+        )?;
+        emit!(buffer, "");
+
+        buffer.block(
+            DirectiveKind::IgnoreOrig,
+            format!("{}-builder-implementation", obj.as_ident()),
+            |buffer| {
+                emit!(buffer, "impl{lifetime} {builder_type}{lifetime} {{");
+
+                for (name, ty, is_optional) in &params {
+                    let ty = with_lifetime(ty);
+                    let ty = if *is_optional {
+                        ty.strip_prefix("Option<")
+                            .and_then(|ty| ty.strip_suffix('>'))
+                            .unwrap_or(&ty)
+                            .to_owned()
+                    } else {
+                        ty
+                    };
+
+                    emit!(buffer, "/// Set `{name}`.");
+                    emit!(buffer, "pub fn {name}(mut self, {name}: {ty}) -> Self {{");
+                    emit!(buffer, "self.{name} = Some({name});");
+                    emit!(buffer, "self");
+                    emit!(buffer, "}}");
+                    emit!(buffer, "");
+                }
+
+                emit!(
+                    buffer,
+                    "/// Inter a new [`{obj_type}`] in the store, and return it."
+                );
+                emit!(buffer, "///");
+                emit!(
+                    buffer,
+                    "/// Returns an error naming the first required field that wasn't set."
+                );
+                emit!(
+                    buffer,
+                    "pub {}fn build(self, {store_name}: {store_type}) -> Result<{return_type}, String> {{",
+                    if is_async { "async " } else { "" }
+                );
+                for (name, _, is_optional) in &params {
+                    if *is_optional {
+                        emit!(buffer, "let {name} = self.{name};");
+                    } else {
+                        emit!(
+                            buffer,
+                            "let {name} = self.{name}.ok_or(\"{builder_type}: `{name}` is required\")?;"
+                        );
+                    }
+                }
+                write!(buffer, "Ok({obj_type}::new(").context(FormatSnafu)?;
+                for (name, _, _) in &params {
+                    write!(buffer, "{name},").context(FormatSnafu)?;
+                }
+                if is_async {
+                    emit!(buffer, "{store_name}).await)");
+                } else {
+                    emit!(buffer, "{store_name}))");
+                }
+                emit!(buffer, "}}");
+                emit!(buffer, "}}");
+
+                Ok(())
+            },
+        )
+    }
+}
+
+/// Find the parameters to `new` for the builder
+///
+/// Returns `None` if there is no `new` method, or if it doesn't take anything
+/// but the store. No sense building a builder for that.
+fn get_builder_params(
+    obj: &Object,
+    config: &GraceConfig,
+    woog: &WoogStore,
+    domain: &Domain,
+) -> Option<Vec<(String, String, bool)>> {
+    let method = woog
+        .iter_object_method()
+        .filter(|m| m.object == obj.id)
+        .find(|m| m.r25_function(woog)[0].name == "new")?;
+
+    let params = get_method_parameters(method, config, woog, domain);
+    if params.len() > 1 {
+        Some(params)
+    } else {
+        None
+    }
+}
+
 /// Domain Relationship Navigation Implementation
 ///
 /// This generates relationship navigation methods for a type. A method will be
@@ -597,6 +906,7 @@ impl CodeWriter for StructRelNavImpl {
         domain: &Domain,
         woog: &Option<&mut WoogStore>,
         _imports: &Option<&HashMap<String, Domain>>,
+        _lu_dog: &Option<&RwLock<LuDogStore>>,
         _package: &str,
         module: &str,
         obj_id: Option<&Uuid>,
@@ -619,9 +929,27 @@ impl CodeWriter for StructRelNavImpl {
         let woog = woog.as_ref().unwrap();
 
         generate_binary_referrer_rels(buffer, config, module, obj, woog, domain)?;
-        generate_binary_referent_rels(buffer, config, module, obj, "id", woog, domain)?;
+        generate_binary_referent_rels(
+            buffer,
+            &mut NavMethods::Inherent,
+            config,
+            module,
+            obj,
+            "id",
+            woog,
+            domain,
+        )?;
         generate_assoc_referrer_rels(buffer, config, module, obj, woog, domain)?;
-        generate_assoc_referent_rels(buffer, config, module, obj, "id", woog, domain)?;
+        generate_assoc_referent_rels(
+            buffer,
+            &mut NavMethods::Inherent,
+            config,
+            module,
+            obj,
+            "id",
+            woog,
+            domain,
+        )?;
         generate_subtype_rels(buffer, config, module, obj, woog, domain)?;
 
         Ok(())
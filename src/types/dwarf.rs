@@ -1,5 +1,7 @@
 pub(crate) mod chacha;
 pub(crate) mod dwarf;
+pub(crate) mod plugin;
 
 pub(crate) use chacha::{ChaChaBuilder, ChaChaFile};
-pub(crate) use dwarf::{DwarfBuilder, DwarfFile};
+pub(crate) use dwarf::{DwarfBuilder, DwarfFile, DwarfHarness};
+pub(crate) use plugin::{PluginBuilder, PluginLib, PluginManifest};
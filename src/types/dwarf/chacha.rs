@@ -1,13 +1,17 @@
 //! ChaCha File Generation
 //!
 //! This is where we generate code for use in the next stage of the compiler.
-use std::{fmt::Write, sync::Arc};
+use std::{
+    fmt::Write,
+    sync::{Arc, RwLock},
+};
 
+use rayon::prelude::*;
 use rustc_hash::FxHashMap as HashMap;
 use sarzak::{
-    lu_dog::types::ValueType,
+    lu_dog::{store::ObjectStore as LuDogStore, types::ValueType},
     mc::{CompilerSnafu, FormatSnafu, Result},
-    sarzak::types::{Object, Ty},
+    sarzak::types::{External, Object, Ty},
     v2::domain::Domain,
     woog::{store::ObjectStore as WoogStore, Ownership},
 };
@@ -16,18 +20,18 @@ use uuid::Uuid;
 
 use crate::{
     codegen::{
+        attribute_external,
         buffer::{emit, Buffer},
-        collect_attributes,
+        collect_attribute_externals, collect_attributes, collect_rel_navs, emit_async_lock_uses,
         generator::{CodeWriter, FileGenerator, GenerationAction},
-        get_subtypes_sorted_from_super_obj, object_is_enum, object_is_hybrid, object_is_singleton,
-        object_is_supertype,
+        get_subtypes_sorted_from_super_obj, model_hash, object_is_enum, object_is_hybrid,
+        object_is_singleton, object_is_supertype,
         render::{RenderConst, RenderIdent, RenderType},
-        AttributeBuilder,
+        AttributeBuilder, DwarfExternal,
     },
-    options::{GraceConfig, UberStoreOptions},
+    options::{GraceConfig, UberStoreOptions, UuidMode},
     s_read,
-    target::dwarf::LU_DOG,
-    types::ChaChaDefinition,
+    types::{domain::store::object_has_name, ChaChaDefinition},
     Lock,
 };
 
@@ -71,6 +75,7 @@ impl FileGenerator for ChaChaGenerator {
         domain: &Domain,
         woog: &Option<&mut WoogStore>,
         imports: &Option<&HashMap<String, Domain>>,
+        lu_dog: &Option<&RwLock<LuDogStore>>,
         package: &str,
         module: &str,
         obj_id: Option<&Uuid>,
@@ -93,7 +98,7 @@ impl FileGenerator for ChaChaGenerator {
         // format!("{}-dwarf-file", module),
         // |buffer| {
         self.definition.write_code(
-            config, domain, woog, imports, package, module, obj_id, buffer,
+            config, domain, woog, imports, lu_dog, package, module, obj_id, buffer,
         )?;
 
         // Ok(())
@@ -135,6 +140,7 @@ impl CodeWriter for ChaChaFile {
         domain: &Domain,
         woog: &Option<&mut WoogStore>,
         imports: &Option<&HashMap<String, Domain>>,
+        lu_dog: &Option<&RwLock<LuDogStore>>,
         _package: &str,
         _module: &str,
         _obj_id: Option<&Uuid>,
@@ -146,9 +152,14 @@ impl CodeWriter for ChaChaFile {
                 description: "woog is required by ChaChaModule"
             }
         );
-        let woog = woog.as_ref().unwrap();
-
-        let _lu_dog = &LU_DOG;
+        let woog: &WoogStore = woog.as_ref().unwrap();
+        ensure!(
+            lu_dog.is_some(),
+            CompilerSnafu {
+                description: "lu_dog is required by ChaChaModule"
+            }
+        );
+        let lu_dog = lu_dog.unwrap();
 
         let domain_name = domain.name().as_ident();
         let domain_type = domain.name().as_type(&Ownership::new_owned(), woog, domain);
@@ -158,10 +169,10 @@ impl CodeWriter for ChaChaFile {
 
         use UberStoreOptions::*;
         let (ref_type, new_ref) = match config.get_uber_store().unwrap() {
+            // These are rejected by `DwarfConfig::validate` before we get here.
             Disabled => unreachable!(),
             AsyncRwLock => {
-                emit!(buffer, "use async_std::sync::Arc;");
-                emit!(buffer, "use async_std::sync::RwLock;");
+                emit_async_lock_uses(buffer, config)?;
                 emit!(buffer, "use futures::stream::{{self, StreamExt}};");
                 ("Arc<RwLock", "Arc::new(RwLock::new")
             }
@@ -183,7 +194,7 @@ impl CodeWriter for ChaChaFile {
             StdMutex => {
                 emit!(buffer, "use std::sync::Arc;");
                 emit!(buffer, "use std::sync::Mutex;");
-                ("Arc<RwLock", "Arc::new(Mutex::new")
+                ("Arc<Mutex", "Arc::new(Mutex::new")
             }
             ParkingLotRwLock => {
                 emit!(buffer, "use std::sync::Arc;");
@@ -193,13 +204,25 @@ impl CodeWriter for ChaChaFile {
             ParkingLotMutex => {
                 emit!(buffer, "use std::sync::Arc;");
                 emit!(buffer, "use parking_lot::Mutex;");
-                ("Arc<RwLock", "Arc::new(Mutex::new")
+                ("Arc<Mutex", "Arc::new(Mutex::new")
             }
         };
 
         let (read, write) = get_uber_read_write(config);
 
         emit!(buffer, "use std::{{path::Path, fmt::{{self, Display}}}};");
+        // These are for attributes with external types.
+        for ext in collect_attribute_externals(config, domain) {
+            match DwarfExternal::new(ext, config) {
+                DwarfExternal::SystemTime => {
+                    emit!(buffer, "use std::time::{{Duration, UNIX_EPOCH}};")
+                }
+                DwarfExternal::PathBuf => emit!(buffer, "use std::path::PathBuf;"),
+                DwarfExternal::StringBacked | DwarfExternal::Opaque => {
+                    emit!(buffer, "use {}::{};", ext.path, ext.name)
+                }
+            }
+        }
         emit!(buffer, "");
 
         emit!(buffer, "use abi_stable::{{export_root_module, prefix_type::PrefixTypeTrait, sabi_extern_fn, sabi_trait::prelude::{{TD_CanDowncast, TD_Opaque}}, std_types::{{RErr, ROk, ROption, RBox, RResult, RStr, RString, RVec}}}};");
@@ -212,6 +235,92 @@ impl CodeWriter for ChaChaFile {
         emit!(buffer, "pub mod types;");
         emit!(buffer, "pub use store::ObjectStore;");
         emit!(buffer, "pub use types::*;");
+        if config.get_uuid_mode() != &UuidMode::Random {
+            emit!(
+                buffer,
+                "pub const UUID_NS: Uuid = uuid!(\"{}\");",
+                Uuid::from_slice(domain.id().as_bytes()).unwrap()
+            );
+            emit!(buffer, "");
+            emit!(
+                buffer,
+                "/// Render a constructor argument for the name of a v5 UUID"
+            );
+            emit!(buffer, "///");
+            emit!(
+                buffer,
+                "/// Proxies are named by their id. Their debug output includes the plugin,"
+            );
+            emit!(buffer, "/// and that's different every time.");
+            emit!(buffer, "fn uuid_name(arg: &FfiValue) -> String {{");
+            emit!(buffer, "match arg {{");
+            emit!(
+                buffer,
+                "FfiValue::ProxyType(proxy) => proxy.id.to_string(),"
+            );
+            emit!(
+                buffer,
+                "FfiValue::Option(ROption::RSome(inner)) => format!(\"Some({{}})\", uuid_name(inner)),"
+            );
+            emit!(
+                buffer,
+                "FfiValue::Vector(values) => format!(\"[{{}}]\", values.iter().map(uuid_name).collect::<Vec<_>>().join(\", \")),"
+            );
+            emit!(buffer, "arg => format!(\"{{arg:?}}\"),");
+            emit!(buffer, "}}");
+            emit!(buffer, "}}");
+        }
+        // The plugin keeps its own seed. The domain store only has one if it
+        // was generated with seeded ids too, and we can't count on that.
+        if config.get_uuid_mode() == &UuidMode::Seeded {
+            emit!(buffer, "");
+            emit!(
+                buffer,
+                "static UUID_SEED: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);"
+            );
+            emit!(buffer, "");
+            emit!(
+                buffer,
+                "/// Set the seed used to generate deterministic UUIDs."
+            );
+            emit!(buffer, "pub fn seed_uuids(seed: u64) {{");
+            emit!(
+                buffer,
+                "UUID_SEED.store(seed, std::sync::atomic::Ordering::SeqCst);"
+            );
+            emit!(buffer, "}}");
+            emit!(buffer, "");
+            emit!(buffer, "fn next_uuid_seed() -> u64 {{");
+            emit!(
+                buffer,
+                "UUID_SEED.fetch_add(1, std::sync::atomic::Ordering::SeqCst)"
+            );
+            emit!(buffer, "}}");
+        }
+        emit!(buffer, "");
+        emit!(
+            buffer,
+            "/// The name by which the interpreter knows this plugin."
+        );
+        emit!(buffer, "pub const PLUGIN_NAME: &str = \"{domain_name}\";");
+        emit!(
+            buffer,
+            "/// The id of the domain that this plugin was generated from."
+        );
+        emit!(
+            buffer,
+            "pub const DOMAIN_ID: &str = \"{}\";",
+            Uuid::from_slice(domain.id().as_bytes()).unwrap()
+        );
+        emit!(
+            buffer,
+            "/// A hash of the model that this plugin was generated from."
+        );
+        emit!(
+            buffer,
+            "pub const MODEL_HASH: &str = \"{}\";",
+            model_hash(domain).simple()
+        );
         if config.is_sarzak() {
             emit!(
                 buffer,
@@ -247,7 +356,9 @@ impl CodeWriter for ChaChaFile {
             buffer,
             r#"/// Exports the root module of this library.
 ///
-/// This code isn't run until the layout of the type it returns is checked."#
+/// This code isn't run until the layout of the type it returns is checked.
+/// The interpreter compares `version` against the model revision that it
+/// expects before it calls `new`."#
         );
         if !config.is_meta_model() {
             emit!(buffer, "#[export_root_module]");
@@ -255,7 +366,7 @@ impl CodeWriter for ChaChaFile {
         emit!(
             buffer,
             r#"pub fn instantiate_root_module() -> PluginModRef {{
-    PluginModule {{ name, id, new }}.leak_into_prefix()
+    PluginModule {{ name, id, version, new }}.leak_into_prefix()
 }}
 "#
         );
@@ -264,7 +375,7 @@ impl CodeWriter for ChaChaFile {
             buffer,
             r#"#[sabi_extern_fn]
 pub fn name() -> RStr<'static> {{
-    "{domain_name}".into()
+    PLUGIN_NAME.into()
 }}
 "#
         );
@@ -273,7 +384,31 @@ pub fn name() -> RStr<'static> {{
             buffer,
             r#"#[sabi_extern_fn]
 pub fn id() -> RStr<'static> {{
-    "{domain_name}".into()
+    DOMAIN_ID.into()
+}}
+"#
+        );
+
+        emit!(
+            buffer,
+            r#"/// The model revision that this plugin was built from.
+#[sabi_extern_fn]
+pub fn version() -> RStr<'static> {{
+    MODEL_HASH.into()
+}}
+
+/// Check that the caller was built from the same model as we were.
+///
+/// Mixing revisions is asking for trouble: the functions and fields that the
+/// caller knows about may not be the ones that we have.
+pub fn check_compatibility(domain_id: &str, model_hash: &str) -> Result<(), Error> {{
+    if domain_id != DOMAIN_ID {{
+        return Err(Error::Uber(format!("{{PLUGIN_NAME}}: plugin is for domain {{DOMAIN_ID}}, not {{domain_id}}").into()));
+    }}
+    if model_hash != MODEL_HASH {{
+        return Err(Error::Uber(format!("{{PLUGIN_NAME}}: plugin was built from model revision {{MODEL_HASH}}, not {{model_hash}}").into()));
+    }}
+    Ok(())
 }}
 "#
         );
@@ -281,24 +416,31 @@ pub fn id() -> RStr<'static> {{
         emit!(
             buffer,
             r#"/// Instantiates the plugin.
+///
+/// The store module is generated separately, so we make sure that it was
+/// generated from the same model as we were before we hand it out.
 #[sabi_extern_fn]
 pub fn new(args: RVec<FfiValue>) -> RResult<PluginType, Error> {{
-    match (|| {{
+    match (|| -> Result<{domain_type}Store, Error> {{
+        check_compatibility(&store::DOMAIN_ID.to_string(), store::MODEL_HASH)?;
+
         if args.len() == 0 {{
             Ok({domain_type}Store {{
                 store: {new_ref}(ObjectStore::new())),
             }})
         }} else if args.len() == 1 {{
             if let FfiValue::String(path) = &args[0] {{
-                let store = ObjectStore::load(Path::new(&path.as_str())).unwrap();
+                let store = ObjectStore::load(Path::new(&path.as_str())).map_err(|e| {{
+                    Error::Uber(format!("{domain_type}Store::new: unable to load store from `path` ({{path}}): {{e}}").into())
+                }})?;
                 Ok({domain_type}Store {{
                     store: {new_ref}(store)),
                 }})
             }} else {{
-                Err(Error::Uber("Invalid arguments".into()))
+                Err(Error::Uber(format!("{domain_type}Store::new: expected a string for `path`, found {{:?}}", args[0]).into()))
             }}
         }} else {{
-            Err(Error::Uber("Invalid arguments".into()))
+            Err(Error::Uber(format!("{domain_type}Store::new: expected 0 or 1 arguments, found {{}}", args.len()).into()))
         }}
     }})() {{
         Ok(this) => ROk(Plugin_TO::from_value(this, TD_Opaque)),
@@ -349,12 +491,52 @@ struct {domain_type}Store {{
                         }}
 
                         if let FfiValue::String(path) = args.pop().unwrap() {{
-                            self.store.{read}.persist(Path::new(&path.as_str())).unwrap();
+                            self.store.{read}.persist(Path::new(&path.as_str())).map_err(|e| {{
+                                Error::Uber(format!("ObjectStore::persist: unable to write store to `path` ({{path}}): {{e}}").into())
+                            }})?;
                             Ok(FfiValue::Empty)
                         }} else {{
-                            Err(Error::Uber("Invalid path".into()))
+                            Err(Error::Uber("ObjectStore::persist: expected a string for `path`".into()))
                         }}
                     }}
+                    "check_compatibility" => {{
+                        if args.len() != 2 {{
+                            return Err(Error::Uber(format!("ObjectStore::check_compatibility: expected 2 arguments, found {{}}", args.len()).into()));
+                        }}
+
+                        match (&args[0], &args[1]) {{
+                            (FfiValue::String(domain_id), FfiValue::String(model_hash)) => {{
+                                check_compatibility(domain_id.as_str(), model_hash.as_str())?;
+                                Ok(FfiValue::Empty)
+                            }}
+                            _ => Err(Error::Uber(format!("ObjectStore::check_compatibility: expected strings for `domain_id` and `model_hash`, found {{args:?}}").into())),
+                        }}
+                    }}
+                    "load" => {{
+                        if args.len() != 1 {{
+                            return Err(Error::Uber("Expected 1 argument".into()));
+                        }}
+
+                        if let FfiValue::String(path) = args.pop().unwrap() {{
+                            let store = ObjectStore::load(Path::new(&path.as_str())).map_err(|e| {{
+                                Error::Uber(format!("ObjectStore::load: unable to load store from `path` ({{path}}): {{e}}").into())
+                            }})?;
+                            // Swap the contents so that any proxies that we've
+                            // handed out see the loaded store.
+                            *self.store.{write} = store;
+                            Ok(FfiValue::PlugIn(Plugin_TO::from_value(self.clone(), TD_Opaque)))
+                        }} else {{
+                            Err(Error::Uber("ObjectStore::load: expected a string for `path`".into()))
+                        }}
+                    }}
+                    "temp_path" => {{
+                        if !args.is_empty() {{
+                            return Err(Error::Uber("Expected 0 arguments".into()));
+                        }}
+
+                        let path = std::env::temp_dir().join(format!("{{PLUGIN_NAME}}-{{}}", Uuid::new_v4()));
+                        Ok(FfiValue::String(path.to_string_lossy().into_owned().into()))
+                    }}
 "#
         );
 
@@ -382,12 +564,14 @@ struct {domain_type}Store {{
                         }}
 
                         if let FfiValue::PlugIn({obj_ident}) = args.pop().unwrap() {{
-                            let {obj_ident} = {obj_ident}.obj.downcast_into::<{obj_type}Proxy>().unwrap();
+                            let {obj_ident} = {obj_ident}.obj.downcast_into::<{obj_type}Proxy>().map_err(|_| {{
+                                Error::Uber("ObjectStore::inter_{obj_ident}: argument `{obj_ident}` is not a {obj_type}".into())
+                            }})?;
                             self.store.{write}
                                 .inter_{obj_ident}({obj_ident}.inner.clone());
                             Ok(FfiValue::Empty)
                         }} else {{
-                            Err(Error::Uber("Invalid {obj_type}".into()))
+                            Err(Error::Uber("ObjectStore::inter_{obj_ident}: expected a {obj_type} for `{obj_ident}`".into()))
                         }}
                     }}"#
             );
@@ -399,7 +583,9 @@ struct {domain_type}Store {{
                             return Err(Error::Uber("Expected 1 argument".into()));
                         }}
                         if let FfiValue::Uuid(id) = args.pop().unwrap() {{
-                            let {obj_ident} = self.store.{read}.exhume_{obj_ident}(&id.into()).unwrap();
+                            let {obj_ident} = self.store.{read}.exhume_{obj_ident}(&id.into()).ok_or_else(|| {{
+                                Error::Uber(format!("ObjectStore::exhume_{obj_ident}: no {obj_type} with id `{{id}}`").into())
+                            }})?;
                             let {obj_ident}_proxy = {obj_type}Proxy {{
                                 // 🚧 This bothers me deeply. I know that I've given
                                 // this some thought already, and I really need to
@@ -418,10 +604,88 @@ struct {domain_type}Store {{
 
                             Ok(FfiValue::ProxyType(proxy))
                         }} else {{
-                            Err(Error::Uber("Invalid id".into()))
+                            Err(Error::Uber("ObjectStore::exhume_{obj_ident}: expected a Uuid for `id`".into()))
+                        }}
+                    }}"#
+            );
+
+            emit!(
+                buffer,
+                r#""exorcise_{obj_ident}" => {{
+                        if args.len() != 1 {{
+                            return Err(Error::Uber("Expected 1 argument".into()));
+                        }}
+                        if let FfiValue::Uuid(id) = args.pop().unwrap() {{
+                            let {obj_ident} = self.store.{write}.exorcise_{obj_ident}(&id.into());
+                            match {obj_ident} {{
+                                Some({obj_ident}) => {{
+                                    let id = {obj_ident}.{read}.{id};
+                                    let this = {obj_type}Proxy {{
+                                        inner: {obj_ident},
+                                        store: self.store.clone(),
+                                    }};
+                                    let plugin = Plugin_TO::from_value(this, TD_CanDowncast);
+                                    let proxy = FfiProxy {{
+                                        module: module.into(),
+                                        ty: {obj_const}_ID.into(),
+                                        id: id.into(),
+                                        plugin,
+                                    }};
+                                    Ok(FfiValue::Option(ROption::RSome(RBox::new(FfiValue::ProxyType(proxy)))))
+                                }}
+                                None => Ok(FfiValue::Option(ROption::RNone)),
+                            }}
+                        }} else {{
+                            Err(Error::Uber("ObjectStore::exorcise_{obj_ident}: expected a Uuid for `id`".into()))
                         }}
                     }}"#
             );
+
+            emit!(
+                buffer,
+                r#""iter_{obj_ident}" => {{
+                        if !args.is_empty() {{
+                            return Err(Error::Uber("Expected 0 arguments".into()));
+                        }}
+                        let mut instances = Vec::new();
+                        for {obj_ident} in self.store.{read}.iter_{obj_ident}() {{
+                            let id = {obj_ident}.{read}.{id};
+                            let this = {obj_type}Proxy {{
+                                inner: {obj_ident}.clone(),
+                                store: self.store.clone(),
+                            }};
+                            let plugin = Plugin_TO::from_value(this, TD_CanDowncast);
+                            let proxy = FfiProxy {{
+                                module: module.into(),
+                                ty: {obj_const}_ID.into(),
+                                id: id.into(),
+                                plugin,
+                            }};
+
+                            instances.push(FfiValue::ProxyType(proxy));
+                        }}
+                        Ok(FfiValue::Vector(instances.into()))
+                    }}"#
+            );
+
+            if object_has_name(obj, domain) {
+                emit!(
+                    buffer,
+                    r#""exhume_{obj_ident}_id_by_name" => {{
+                        if args.len() != 1 {{
+                            return Err(Error::Uber("Expected 1 argument".into()));
+                        }}
+                        if let FfiValue::String(name) = args.pop().unwrap() {{
+                            match self.store.{read}.exhume_{obj_ident}_id_by_name(name.as_str()) {{
+                                Some(id) => Ok(FfiValue::Option(ROption::RSome(RBox::new(FfiValue::Uuid(id.into()))))),
+                                None => Ok(FfiValue::Option(ROption::RNone)),
+                            }}
+                        }} else {{
+                            Err(Error::Uber("ObjectStore::exhume_{obj_ident}_id_by_name: expected a string for `name`".into()))
+                        }}
+                    }}"#
+                );
+            }
         }
 
         emit!(
@@ -453,7 +717,7 @@ struct {domain_type}Store {{
                 "id"
             };
 
-            let attrs: Vec<Attribute> = collect_attributes(obj, domain);
+            let attrs: Vec<Attribute> = collect_attributes(obj, lu_dog, domain);
 
             emit!(buffer, r#""{obj_type}" => match func {{"#);
 
@@ -545,53 +809,60 @@ struct {domain_type}Store {{
     }}
 
     fn name(&self) -> RStr<'_> {{
-        "merlin".into()
+        PLUGIN_NAME.into()
     }}
 }}
 "#
         );
 
-        for obj in &objects {
-            let obj_type = obj.as_type(&Ownership::new_owned(), woog, domain);
-            let obj_const = obj.as_const();
-            let is_enum = object_is_enum(obj, config, imports, domain)?;
-            let is_singleton = object_is_singleton(obj, config, imports, domain)?;
-            let is_imported = config.is_imported(&obj.id);
-
-            if is_imported {
-                continue;
-            }
+        // Each object gets its own proxy. They don't depend on each other, so we
+        // render them in parallel and then stitch them together, in order.
+        let fragments = objects
+            .par_iter()
+            .map(|obj| -> Result<Buffer> {
+                let mut fragment = Buffer::new();
+                let buffer = &mut fragment;
+
+                let obj_type = obj.as_type(&Ownership::new_owned(), woog, domain);
+                let obj_const = obj.as_const();
+                let is_enum = object_is_enum(obj, config, imports, domain)?;
+                let is_singleton = object_is_singleton(obj, config, imports, domain)?;
+                let is_imported = config.is_imported(&obj.id);
+
+                if is_imported {
+                    return Ok(fragment);
+                }
 
-            let id = if is_enum || is_singleton {
-                "id()"
-            } else {
-                "id"
-            };
+                let id = if is_enum || is_singleton {
+                    "id()"
+                } else {
+                    "id"
+                };
 
-            let attrs: Vec<Attribute> = collect_attributes(obj, domain);
+                let attrs: Vec<Attribute> = collect_attributes(obj, lu_dog, domain);
 
-            // The id of the object that will be backing the dwarf type.
-            emit!(
-                buffer,
-                "const {obj_const}_ID: Uuid = uuid!(\"{}\");\n",
-                obj.id
-            );
+                // The id of the object that will be backing the dwarf type.
+                emit!(
+                    buffer,
+                    "const {obj_const}_ID: Uuid = uuid!(\"{}\");\n",
+                    obj.id
+                );
 
-            //
-            // Generate the proxy type
-            emit!(buffer, "#[derive(Clone, Debug)]",);
-            emit!(buffer, "pub struct {obj_type}Proxy {{");
-            emit!(buffer, "inner: {ref_type}<{obj_type}>>,");
-            emit!(buffer, "store: {ref_type}<ObjectStore>>,");
-            emit!(buffer, "}}\n");
-
-            //
-            // This is the implementation of the proxy type. We only need one
-            // method so far, and that's basically a default replacement. Default
-            // doesn't work because we need a pointer back to the store.
-            emit!(
-                buffer,
-                r#"impl Plugin for {obj_type}Proxy {{
+                //
+                // Generate the proxy type
+                emit!(buffer, "#[derive(Clone, Debug)]",);
+                emit!(buffer, "pub struct {obj_type}Proxy {{");
+                emit!(buffer, "inner: {ref_type}<{obj_type}>>,");
+                emit!(buffer, "store: {ref_type}<ObjectStore>>,");
+                emit!(buffer, "}}\n");
+
+                //
+                // This is the implementation of the proxy type. We only need one
+                // method so far, and that's basically a default replacement. Default
+                // doesn't work because we need a pointer back to the store.
+                emit!(
+                    buffer,
+                    r#"impl Plugin for {obj_type}Proxy {{
     fn invoke_func(
         &mut self,
         module: RStr<'_>,
@@ -613,37 +884,37 @@ struct {domain_type}Store {{
                         if let FfiValue::String(field) = args.pop().unwrap() {{
                             match field.as_str() {{
 "#
-            );
+                );
 
-            for attr in &attrs {
-                let attr_name = attr.name.as_ident();
+                for attr in &attrs {
+                    let attr_name = attr.name.as_ident();
 
-                let (ty, ty_ty) = value_type_to_string(&attr.ty, woog, config, domain);
+                    let (ty, ty_ty) = value_type_to_string(&attr.ty, woog, lu_dog, config, domain);
 
-                emit!(buffer, r#""{attr_name}" => "#);
-                if attr_name == "id" {
-                    emit!(buffer, "Ok(FfiValue::Uuid(self.inner.{read}.{id}.into())),");
-                } else {
-                    match ty {
-                        "Boolean" => emit!(
-                            buffer,
-                            "Ok(FfiValue::Boolean(self.inner.{read}.{attr_name}.into())),"
-                        ),
-                        "Float" => emit!(
-                            buffer,
-                            "Ok(FfiValue::Float(self.inner.{read}.{attr_name}.into())),"
-                        ),
-                        "Imported" => emit!(
-                            buffer,
-                            r#"Err(Error::Uber("Imported object not supported.".into())),"#
-                        ),
-                        "Integer" => emit!(
-                            buffer,
-                            "Ok(FfiValue::Integer(self.inner.{read}.{attr_name}.into())),"
-                        ),
-                        "Option" => emit!(
-                            buffer,
-                            r#"
+                    emit!(buffer, r#""{attr_name}" => "#);
+                    if attr_name == "id" {
+                        emit!(buffer, "Ok(FfiValue::Uuid(self.inner.{read}.{id}.into())),");
+                    } else {
+                        match ty {
+                            "Boolean" => emit!(
+                                buffer,
+                                "Ok(FfiValue::Boolean(self.inner.{read}.{attr_name}.into())),"
+                            ),
+                            "Float" => emit!(
+                                buffer,
+                                "Ok(FfiValue::Float(self.inner.{read}.{attr_name}.into())),"
+                            ),
+                            "Imported" => emit!(
+                                buffer,
+                                r#"Err(Error::Uber("Imported object not supported.".into())),"#
+                            ),
+                            "Integer" => emit!(
+                                buffer,
+                                "Ok(FfiValue::Integer(self.inner.{read}.{attr_name}.into())),"
+                            ),
+                            "Option" => emit!(
+                                buffer,
+                                r#"
                                     match self.inner.{read}.{attr_name} {{
                                         Some({attr_name}) => Ok(FfiValue::Option(ROption::RSome(
                                             RBox::new(FfiValue::Uuid({attr_name}.into())),
@@ -651,19 +922,21 @@ struct {domain_type}Store {{
                                         None => Ok(FfiValue::Option(ROption::RNone)),
                                     }}
                             "#,
-                        ),
-                        "String" => emit!(
-                            buffer,
-                            "Ok(FfiValue::String(self.inner.{read}.{attr_name}.clone().into())),"
-                        ),
-                        "UserType" => {
-                            let type_const = ty_ty.as_const();
-                            let type_ident = ty_ty.as_ident();
-
-                            emit!(
+                            ),
+                            "String" => emit!(
                                 buffer,
-                                r#"{{let {attr_name} =
-                                        self.store.{read}.exhume_{type_ident}(&self.inner.{read}.{attr_name}).unwrap();
+                                "Ok(FfiValue::String(self.inner.{read}.{attr_name}.clone().into())),"
+                            ),
+                            "UserType" => {
+                                let type_const = ty_ty.as_const();
+                                let type_ident = ty_ty.as_ident();
+
+                                emit!(
+                                    buffer,
+                                    r#"{{let {attr_name} =
+                                        self.store.{read}.exhume_{type_ident}(&self.inner.{read}.{attr_name}).ok_or_else(|| {{
+                                            Error::Uber(format!("{obj_type}::get_field_value: no {ty_ty} with id `{{}}` for `{attr_name}`", self.inner.{read}.{attr_name}).into())
+                                        }})?;
 
                                     let this = {ty_ty}Proxy {{
                                         inner: {attr_name},
@@ -678,28 +951,46 @@ struct {domain_type}Store {{
                                     }};
                                     Ok(FfiValue::ProxyType(proxy))
                                 }}"#
-                            );
-                        }
-                        "Uuid" => {
-                            emit!(
+                                );
+                            }
+                            "Uuid" => {
+                                emit!(
+                                    buffer,
+                                    "Ok(FfiValue::Uuid(self.inner.{read}.{attr_name}.into())),"
+                                )
+                            }
+                            "SystemTime" => emit!(
                                 buffer,
-                                "Ok(FfiValue::Uuid(self.inner.{read}.{attr_name}.into())),"
-                            )
-                        }
-                        foo => {
-                            dbg!(foo);
-                            // unreachable!()
+                                r#"self.inner.{read}.{attr_name}.duration_since(UNIX_EPOCH).map(|d| FfiValue::Float(d.as_secs_f64().into())).map_err(|e| {{
+                                Error::Uber(format!("{obj_type}::get_field_value: `{attr_name}` predates the epoch: {{e}}").into())
+                            }}),"#
+                            ),
+                            "PathBuf" => emit!(
+                                buffer,
+                                "Ok(FfiValue::String(self.inner.{read}.{attr_name}.to_string_lossy().to_string().into())),"
+                            ),
+                            "StringBacked" => emit!(
+                                buffer,
+                                "Ok(FfiValue::String(self.inner.{read}.{attr_name}.to_string().into())),"
+                            ),
+                            "Opaque" => emit!(
+                                buffer,
+                                r#"Err(Error::Uber("{obj_type}::get_field_value: `{attr_name}` is an opaque {ty_ty} and can't be passed to dwarf".into())),"#
+                            ),
+                            foo => {
+                                dbg!(foo);
+                                // unreachable!()
+                            }
                         }
                     }
                 }
-            }
 
-            emit!(buffer, "_ => Err(Error::Uber(\"Invalid field\".into())),");
-            emit!(buffer, "}}");
-            emit!(
-                buffer,
-                r#"}} else {{
-                            Err(Error::Uber("Invalid Object".into()))
+                emit!(buffer, "_ => Err(Error::Uber(format!(\"{obj_type}::get_field_value: invalid field `{{field}}`\").into())),");
+                emit!(buffer, "}}");
+                emit!(
+                    buffer,
+                    r#"}} else {{
+                            Err(Error::Uber("{obj_type}::get_field_value: expected a string for `field`".into()))
                         }}
                     }}
                     "set_field_value" => {{
@@ -714,32 +1005,50 @@ struct {domain_type}Store {{
                             let value: Value = args.pop().unwrap().into();
                             match field.as_str() {{
 "#
-            );
+                );
 
-            for attr in &attrs {
-                if attr.name == "id" {
-                    continue;
-                }
+                for attr in &attrs {
+                    if attr.name == "id" {
+                        continue;
+                    }
 
-                let attr_ident = attr.name.as_ident();
+                    let attr_ident = attr.name.as_ident();
+                    let context = format!("{obj_type}::set_field_value");
 
-                emit!(
-                    buffer,
-                    r#""{attr_ident}" => {{
-                                    self.inner.{write}.{attr_ident} = value.try_into().map_err(|e| {{
-                                        Error::Uber(
-                                            format!("Error converting value: {{e}}").into(),
-                                        )
-                                    }})?
+                    match attribute_external(&attr.ty, domain) {
+                        Some(ext) if DwarfExternal::new(ext, config) == DwarfExternal::Opaque => {
+                            emit!(
+                                buffer,
+                                r#""{attr_ident}" => {{
+                                    return Err(Error::Uber(
+                                        "{context}: `{attr_ident}` is an opaque {} and can't be set from dwarf".into(),
+                                    ))
+                                }}"#,
+                                ext.name
+                            );
+                        }
+                        ext => {
+                            let value = render_value_conversion(
+                                "value",
+                                &attr_ident,
+                                &context,
+                                ext.map(|ext| (ext, DwarfExternal::new(ext, config))),
+                            );
+                            emit!(
+                                buffer,
+                                r#""{attr_ident}" => {{
+                                    self.inner.{write}.{attr_ident} = {value}
                                 }}"#
-                );
-            }
+                            );
+                        }
+                    }
+                }
 
-            emit!(
-                buffer,
-                r#"field => {{
+                emit!(
+                    buffer,
+                    r#"field => {{
                                     return Err(Error::Uber(
-                                        format!("Invalid field {{field}}").into(),
+                                        format!("{obj_type}::set_field_value: invalid field `{{field}}`").into(),
                                     ))
                                 }}
                             }}
@@ -747,11 +1056,20 @@ struct {domain_type}Store {{
                             Ok(FfiValue::Empty)
                         }} else {{
                             Err(Error::Uber(
-                                format!("Invalid field type: {{field:?}}").into(),
+                                format!("{obj_type}::set_field_value: expected a string for `field`, found {{field:?}}").into(),
                             ))
                         }}
-                    }}
-                    func => Err(Error::Uber(format!("Invalid function: {{func:?}}").into())),
+                    }}"#
+                );
+
+                // Singletons don't have relationship navigation methods.
+                if !is_singleton {
+                    render_rel_navs(obj, config, imports, woog, domain, buffer)?;
+                }
+
+                emit!(
+                    buffer,
+                    r#"func => Err(Error::Uber(format!("Invalid function: {{func:?}}").into())),
                 }},
                 ty => Err(Error::Uber(format!("Invalid type {{ty:?}}").into())),
             }}
@@ -764,49 +1082,74 @@ struct {domain_type}Store {{
     }}
 }}
 "#
-            );
+                );
 
-            //
-            // Write the Display implementation
-            emit!(buffer, "impl Display for {obj_type}Proxy {{");
-            emit!(
-                buffer,
-                "fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {{"
-            );
-            // write a line for each attribute
-            for (idx, attr) in attrs.iter().enumerate() {
-                let attr_name = attr.name.as_ident();
+                //
+                // Write the Display implementation
+                emit!(buffer, "impl Display for {obj_type}Proxy {{");
+                emit!(
+                    buffer,
+                    "fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {{"
+                );
+                // write a line for each attribute
+                for (idx, attr) in attrs.iter().enumerate() {
+                    let attr_name = attr.name.as_ident();
 
-                if idx == 0 {
-                    emit!(buffer, "writeln!(f, \"{obj_type}({{{{\")?;",);
-                } else {
-                    emit!(buffer, "?;");
-                }
+                    if idx == 0 {
+                        emit!(buffer, "writeln!(f, \"{obj_type}({{{{\")?;",);
+                    } else {
+                        emit!(buffer, "?;");
+                    }
 
-                if attr_name == "id" {
-                    write!(
-                        buffer,
-                        "writeln!(f, \"\t{attr_name}: {{:?}},\", self.inner.{read}.{id})",
-                    )
-                    .context(FormatSnafu)?;
-                } else {
-                    write!(
-                        buffer,
-                        "writeln!(f, \"\t{attr_name}: {{:?}},\", self.inner.{read}.{attr_name})",
-                    )
-                    .context(FormatSnafu)?;
+                    if attr_name == "id" {
+                        write!(
+                            buffer,
+                            "writeln!(f, \"\t{attr_name}: {{:?}},\", self.inner.{read}.{id})",
+                        )
+                        .context(FormatSnafu)?;
+                    } else {
+                        write!(
+                            buffer,
+                            "writeln!(f, \"\t{attr_name}: {{:?}},\", self.inner.{read}.{attr_name})",
+                        )
+                        .context(FormatSnafu)?;
+                    }
                 }
-            }
-            emit!(buffer, "?;");
-            emit!(buffer, "writeln!(f, \"}}}})\")");
-            emit!(buffer, "}}");
-            emit!(buffer, "}}\n");
+                emit!(buffer, "?;");
+                emit!(buffer, "writeln!(f, \"}}}})\")");
+                emit!(buffer, "}}");
+                emit!(buffer, "}}\n");
+
+                Ok(fragment)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        for fragment in fragments {
+            *buffer += fragment;
         }
 
         Ok(())
     }
 }
 
+/// Generate the statement that creates the `id` for a new instance
+///
+/// For deterministic ids the name is built from the type and `id_args`, which
+/// is rendered from the arguments by `uuid_name` before they are converted.
+fn render_ctor_id(obj_type: &str, len: usize, config: &GraceConfig) -> String {
+    let name = match (config.get_uuid_mode(), len) {
+        (UuidMode::Random, _) => return "let id = Uuid::new_v4();".to_owned(),
+        (UuidMode::Arguments, 0) => format!("\"{obj_type}\""),
+        (UuidMode::Arguments, _) => format!("format!(\"{obj_type}:{{}}\", id_args)"),
+        (UuidMode::Seeded, 0) => format!("format!(\"{{}}:{obj_type}\", next_uuid_seed())"),
+        (UuidMode::Seeded, _) => {
+            format!("format!(\"{{}}:{obj_type}:{{}}\", next_uuid_seed(), id_args)")
+        }
+    };
+
+    format!("let id = Uuid::new_v5(&UUID_NS, {name}.as_bytes());")
+}
+
 fn render_ctor(
     method_name: &str,
     obj: &Object,
@@ -834,26 +1177,53 @@ fn render_ctor(
 
     let (read, write) = get_uber_read_write(config);
 
+    // This is what we call ourselves in error messages.
+    let ctor_name = match parent_obj {
+        Some(parent) => format!(
+            "{}::{method_name}",
+            parent.as_type(&Ownership::new_owned(), woog, domain)
+        ),
+        None => format!("{obj_type}::{method_name}"),
+    };
+
     emit!(buffer, "\"{}\" => {{", method_name);
 
+    // Opaque externals don't come from dwarf, so they don't count.
+    let opaque = attrs
+        .iter()
+        .filter(|attr| match attribute_external(&attr.ty, domain) {
+            Some(ext) => DwarfExternal::new(ext, config) == DwarfExternal::Opaque,
+            None => false,
+        })
+        .count();
+
+    // A hybrid takes it's own attributes, and then the subtype, unless the
+    // subtype is a singleton. Then we just use the const.
     let len = if let Some(parent) = parent_obj {
+        let subtype_len = if is_singleton { 0 } else { 1 };
         if object_is_enum(parent, config, imports, domain)? {
-            1
+            subtype_len
         } else {
-            attrs.len() - 1
+            attrs.len() - 1 - opaque + subtype_len
         }
     } else {
-        attrs.len() - 1
+        attrs.len() - 1 - opaque
     };
 
     emit!(
         buffer,
         r#"if args.len() != {len} {{
-                            return Err(Error::Uber("Expected {len} arguments".into()));
+                            return Err(Error::Uber("{ctor_name}: expected {len} arguments".into()));
                         }}"#
     );
 
     if len > 0 {
+        if config.get_uuid_mode() != &UuidMode::Random {
+            emit!(
+                buffer,
+                "let id_args = args.iter().map(uuid_name).collect::<Vec<_>>().join(\":\");"
+            );
+        }
         emit!(
             buffer,
             r#"                 let mut value_args: Vec<Value> = Vec::new();
@@ -864,6 +1234,8 @@ fn render_ctor(
         );
     }
 
+    let make_id = render_ctor_id(&obj_type, len, config);
+
     // dbg!(&obj_ident, is_enum, is_singleton, is_hybrid);
 
     // if (is_enum || is_singleton) && !parent_is_hybrid {
@@ -875,7 +1247,9 @@ fn render_ctor(
             emit!(
                 buffer,
                 r#"match (|| -> Result<{ref_type}<{parent_type}>>, Error> {{
-                            let {obj_ident} = self.store.{read}.exhume_{parent_ident}(&{obj_const}).unwrap();
+                            let {obj_ident} = self.store.{read}.exhume_{parent_ident}(&{obj_const}).ok_or_else(|| {{
+                                Error::Uber("{ctor_name}: {obj_type} is missing from the store".into())
+                            }})?;
 
                             Ok({obj_ident})
                         }})() {{
@@ -896,23 +1270,17 @@ fn render_ctor(
             );
         }
     } else {
-        if let Some(parent) = parent_obj {
-            let is_singleton = object_is_singleton(parent, config, imports, domain)?;
+        // The subtype needs to come after the attributes so that it lines up
+        // with the order of the arguments.
+        let subtype = if let Some(parent) = parent_obj {
             let is_enum = object_is_enum(parent, config, imports, domain)?;
 
-            // dbg!(&parent, is_singleton, is_enum);
-
             let parent_type = parent.as_type(&Ownership::new_owned(), woog, domain);
-            // let prelude = format!(
-            //     "let id = Uuid::new_v4();
-            //  let {obj_ident} = {parent_type} {{
-            //     id,"
-            // );
             let prelude = if is_enum {
                 format!("let {obj_ident} = {parent_type}")
             } else {
                 format!(
-                    "let id = Uuid::new_v4();
+                    "{make_id}
              let {obj_ident} = {parent_type} {{
                 id,"
                 )
@@ -924,30 +1292,24 @@ fn render_ctor(
                 {prelude}"
             );
 
-            //                                                 v yes! v
-            // If this is a singleton (should we be checking enum too?) then we don't
-            // expect the subtype to be passed in. This is actually sort of silly because
-            // it's making things overly complex here. I could move that to the dwarf
-            // code, but it's generated too. Probably a wash?
+            // If the subtype is a singleton then we don't expect it to be passed
+            // in. We just use the const.
             if is_singleton {
-                emit!(
-                    buffer,
+                Some(format!(
                     "subtype: {parent_type}Enum::{obj_type}({obj_const}),"
-                )
+                ))
             } else if is_enum {
-                emit!(
-                    buffer,
+                Some(format!(
                     r#"::{obj_type}(value_args.pop().unwrap().try_into().map_err(|e| {{
-                        Error::Uber(format!("Error converting value: {{e}}").into())
+                        Error::Uber(format!("{ctor_name}: error converting `{obj_ident}`: {{e}}").into())
                     }})?);"#
-                );
+                ))
             } else {
-                emit!(
-                    buffer,
+                Some(format!(
                     r#"subtype: {parent_type}Enum::{obj_type}(value_args.pop().unwrap().try_into().map_err(|e| {{
-                        Error::Uber(format!("Error converting value: {{e}}").into())
+                        Error::Uber(format!("{ctor_name}: error converting `{obj_ident}`: {{e}}").into())
                     }})?),"#
-                );
+                ))
             }
         } else {
             // singletons/enums don't have an id attribute.
@@ -955,7 +1317,7 @@ fn render_ctor(
                 format!("let {obj_ident} = {obj_type} {{ //kts")
             } else {
                 format!(
-                    "let id = Uuid::new_v4();
+                    "{make_id}
              let {obj_ident} = {obj_type} {{
                 id,"
                 )
@@ -965,21 +1327,35 @@ fn render_ctor(
                 "match (|| -> Result<{obj_type}, Error> {{
                         {prelude}"
             );
-        }
+
+            None
+        };
 
         for attr in attrs {
             if attr.name != "id" {
                 let attr_ident = attr.name.as_ident();
+                let ext = attribute_external(&attr.ty, domain)
+                    .map(|ext| (ext, DwarfExternal::new(ext, config)));
 
-                emit!(
-                    buffer,
-                    r#"{attr_ident}: value_args.pop().unwrap().try_into().map_err(|e| {{
-                                    Error::Uber(format!("Error converting value: {{e}}").into())
-                                }})?,"#
-                );
+                // Opaque externals aren't passed in, we make them here.
+                if let Some((ext, DwarfExternal::Opaque)) = ext {
+                    emit!(buffer, "{attr_ident}: {}::{}(),", ext.name, ext.ctor);
+                } else {
+                    let value = render_value_conversion(
+                        "value_args.pop().unwrap()",
+                        &attr_ident,
+                        &ctor_name,
+                        ext,
+                    );
+                    emit!(buffer, "{attr_ident}: {value},");
+                }
             }
         }
 
+        if let Some(subtype) = subtype {
+            emit!(buffer, "{subtype}");
+        }
+
         // This is the case where we are a subtype, and we need to create the parent
         // object.
         if let Some(parent) = parent_obj {
@@ -1057,14 +1433,142 @@ fn render_ctor(
     Ok(())
 }
 
+/// Generate the `rN_*` arms for a proxy's `invoke_func`
+///
+/// The proxy forwards to the navigation method on the inner type, and then
+/// wraps whatever comes back in proxies of its own. The method returns a
+/// `Vec`, so we use what we know about the relationship to decide if dwarf gets
+/// a vector, an option, or a plain old proxy.
+fn render_rel_navs(
+    obj: &Object,
+    config: &GraceConfig,
+    imports: &Option<&HashMap<String, Domain>>,
+    woog: &WoogStore,
+    domain: &Domain,
+    buffer: &mut Buffer,
+) -> Result<()> {
+    let (read, _write) = get_uber_read_write(config);
+    let obj_type = obj.as_type(&Ownership::new_owned(), woog, domain);
+
+    for nav in collect_rel_navs(obj, config, domain) {
+        let name = &nav.name;
+        let target = nav.target;
+        let target_type = target.as_type(&Ownership::new_owned(), woog, domain);
+        let target_ident = target.as_ident();
+        let target_const = target.as_const();
+        let id = if object_is_enum(target, config, imports, domain)?
+            || object_is_singleton(target, config, imports, domain)?
+        {
+            "id()"
+        } else {
+            "id"
+        };
+        let binding = if nav.many { "let" } else { "let mut" };
+
+        emit!(
+            buffer,
+            r#""{name}" => {{
+                        if !args.is_empty() {{
+                            return Err(Error::Uber("Expected 0 arguments".into()));
+                        }}
+
+                        {binding} {target_ident} = self
+                            .inner
+                            .{read}
+                            .{name}(&self.store.{read})
+                            .into_iter()
+                            .map(|{target_ident}| {{
+                                let id = {target_ident}.{read}.{id};
+                                let this = {target_type}Proxy {{
+                                    inner: {target_ident},
+                                    store: self.store.clone(),
+                                }};
+                                let plugin = Plugin_TO::from_value(this, TD_CanDowncast);
+                                FfiValue::ProxyType(FfiProxy {{
+                                    module: module.into(),
+                                    ty: {target_const}_ID.into(),
+                                    id: id.into(),
+                                    plugin,
+                                }})
+                            }})
+                            .collect::<Vec<_>>();
+"#
+        );
+
+        if nav.many {
+            emit!(buffer, "Ok(FfiValue::Vector({target_ident}.into()))");
+        } else if nav.conditional {
+            emit!(
+                buffer,
+                r#"match {target_ident}.pop() {{
+                            Some({target_ident}) => Ok(FfiValue::Option(ROption::RSome(RBox::new({target_ident})))),
+                            None => Ok(FfiValue::Option(ROption::RNone)),
+                        }}"#
+            );
+        } else {
+            emit!(
+                buffer,
+                r#"{target_ident}.pop().ok_or_else(|| {{
+                            Error::Uber("{obj_type}::{name}: no {target_type} found".into())
+                        }})"#
+            );
+        }
+        emit!(buffer, "}}");
+    }
+
+    Ok(())
+}
+
+/// Render an expression that converts a dwarf `Value` into an attribute value
+///
+/// `value` is the expression that yields the `Value`, and `context` is the
+/// type and function that we use in error messages. Opaque externals don't
+/// convert, so don't ask.
+fn render_value_conversion(
+    value: &str,
+    attr_ident: &str,
+    context: &str,
+    external: Option<(&External, DwarfExternal)>,
+) -> String {
+    let convert = format!(
+        r#"{value}.try_into().map_err(|e| {{
+                                    Error::Uber(format!("{context}: error converting `{attr_ident}`: {{e}}").into())
+                                }})?"#
+    );
+
+    match external {
+        None => convert,
+        Some((_, DwarfExternal::SystemTime)) => {
+            format!(
+                r#"{{ let secs: f64 = {convert};
+                                let since = Duration::try_from_secs_f64(secs).map_err(|e| {{
+                                    Error::Uber(format!("{context}: error converting `{attr_ident}`: {{e}}").into())
+                                }})?;
+                                UNIX_EPOCH.checked_add(since).ok_or_else(|| {{
+                                    Error::Uber(format!("{context}: `{attr_ident}` is out of range: {{secs}}").into())
+                                }})? }}"#
+            )
+        }
+        Some((_, DwarfExternal::PathBuf)) => {
+            format!("{{ let path: String = {convert}; PathBuf::from(path) }}")
+        }
+        Some((ext, DwarfExternal::StringBacked)) => {
+            format!(
+                "{{ let inner: String = {convert}; {}::from(inner) }}",
+                ext.name
+            )
+        }
+        Some((_, DwarfExternal::Opaque)) => unreachable!(),
+    }
+}
+
 fn value_type_to_string<'a>(
     ty: &Arc<Lock<ValueType>>,
     woog: &WoogStore,
+    lu_dog: &RwLock<LuDogStore>,
     config: &GraceConfig,
     domain: &Domain,
 ) -> (&'a str, String) {
-    let lu_dog = &LU_DOG;
-
     match &*s_read!(ty) {
         ValueType::Reference(ref id) => {
             let inner = {
@@ -1074,7 +1578,7 @@ fn value_type_to_string<'a>(
                 reference.r35_value_type(&lu_dog)[0].clone()
             };
 
-            let (ty, ty_ty) = value_type_to_string(&inner, woog, config, domain);
+            let (ty, ty_ty) = value_type_to_string(&inner, woog, lu_dog, config, domain);
             // dbg!(ty, ty_ty);
             if ty == "Imported" {
                 ("Imported", ty_ty)
@@ -1083,7 +1587,7 @@ fn value_type_to_string<'a>(
             }
             // (
             //     "UserType",
-            //     value_type_to_string(&inner, woog, config, domain).1,
+            //     value_type_to_string(&inner, woog, lu_dog, config, domain).1,
             // )
         }
         ValueType::Ty(ref id) => {
@@ -1105,7 +1609,16 @@ fn value_type_to_string<'a>(
                 Ty::Integer(_) => ("Integer", "".to_owned()),
                 Ty::Float(_) => ("Float", "".to_owned()),
                 Ty::SUuid(_) => ("Uuid", "".to_owned()),
-                Ty::External(_) => ("ext_what_to_do", "".to_owned()),
+                Ty::External(ref id) => {
+                    let ext = domain.sarzak().exhume_external(id).unwrap();
+                    let kind = match DwarfExternal::new(ext, config) {
+                        DwarfExternal::SystemTime => "SystemTime",
+                        DwarfExternal::PathBuf => "PathBuf",
+                        DwarfExternal::StringBacked => "StringBacked",
+                        DwarfExternal::Opaque => "Opaque",
+                    };
+                    (kind, ext.name.to_owned())
+                }
             }
         }
         ValueType::WoogOption(ref id) => {
@@ -1117,7 +1630,7 @@ fn value_type_to_string<'a>(
             };
             (
                 "Option",
-                value_type_to_string(&inner, woog, config, domain).1,
+                value_type_to_string(&inner, woog, lu_dog, config, domain).1,
             )
         }
         ValueType::WoogStruct(ref id) => {
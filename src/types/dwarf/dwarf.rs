@@ -1,14 +1,18 @@
 //! Dwarf File Generation
 //!
 //! This is where we generate code for use in the next stage of the compiler.
-use std::{fmt::Write, sync::Arc};
+use std::{
+    fmt::Write,
+    sync::{Arc, RwLock},
+};
 
 use heck::ToUpperCamelCase;
+use rayon::prelude::*;
 use rustc_hash::{FxHashMap as HashMap, FxHashSet as HashSet};
 use sarzak::{
-    lu_dog::types::ValueType,
+    lu_dog::{store::ObjectStore as LuDogStore, types::ValueType},
     mc::{CompilerSnafu, FormatSnafu, Result},
-    sarzak::types::{Object, Ty},
+    sarzak::types::{Cardinality, Conditionality, Object, Ty},
     v2::domain::Domain,
     woog::store::ObjectStore as WoogStore,
 };
@@ -17,17 +21,19 @@ use uuid::Uuid;
 
 use crate::{
     codegen::{
+        attribute_external,
         buffer::{emit, Buffer},
-        collect_attributes, emit_object_comments,
+        collect_attribute_externals, collect_attributes, collect_rel_navs, emit_object_comments,
         generator::{CodeWriter, FileGenerator, GenerationAction},
-        get_subtypes_sorted_from_super_obj, object_is_enum, object_is_hybrid, object_is_singleton,
+        get_assoc_referent_from_referrer_sorted, get_binary_referents_sorted,
+        get_binary_referrers_sorted, get_subtypes_sorted, get_subtypes_sorted_from_super_obj,
+        model_hash, object_is_enum, object_is_hybrid, object_is_singleton,
         render::RenderIdent,
-        AttributeBuilder,
+        AttributeBuilder, DwarfExternal,
     },
     options::GraceConfig,
     s_read,
-    target::dwarf::LU_DOG,
-    types::DwarfDefinition,
+    types::{domain::store::object_has_name, DwarfDefinition},
     Lock,
 };
 
@@ -71,6 +77,7 @@ impl FileGenerator for DwarfGenerator {
         domain: &Domain,
         woog: &Option<&mut WoogStore>,
         imports: &Option<&HashMap<String, Domain>>,
+        lu_dog: &Option<&RwLock<LuDogStore>>,
         package: &str,
         module: &str,
         obj_id: Option<&Uuid>,
@@ -90,7 +97,7 @@ impl FileGenerator for DwarfGenerator {
         // format!("{}-dwarf-file", module),
         // |buffer| {
         self.definition.write_code(
-            config, domain, woog, imports, package, module, obj_id, buffer,
+            config, domain, woog, imports, lu_dog, package, module, obj_id, buffer,
         )?;
 
         // Ok(())
@@ -101,6 +108,17 @@ impl FileGenerator for DwarfGenerator {
     }
 }
 
+struct Attribute {
+    pub name: String,
+    pub ty: Arc<Lock<ValueType>>,
+}
+
+impl AttributeBuilder<Attribute> for Attribute {
+    fn new(name: String, ty: Arc<Lock<ValueType>>) -> Self {
+        Attribute { name, ty }
+    }
+}
+
 /// Dwarf Generator / CodeWriter
 ///
 pub(crate) struct DwarfFile;
@@ -120,6 +138,7 @@ impl CodeWriter for DwarfFile {
         domain: &Domain,
         woog: &Option<&mut WoogStore>,
         imports: &Option<&HashMap<String, Domain>>,
+        lu_dog: &Option<&RwLock<LuDogStore>>,
         _package: &str,
         module: &str,
         _obj_id: Option<&Uuid>,
@@ -131,20 +150,19 @@ impl CodeWriter for DwarfFile {
                 description: "woog is required by DwarfModule"
             }
         );
-        let woog = woog.as_ref().unwrap();
-
-        let _lu_dog = &LU_DOG;
-
-        struct Attribute {
-            pub name: String,
-            pub ty: Arc<Lock<ValueType>>,
-        }
-
-        impl AttributeBuilder<Attribute> for Attribute {
-            fn new(name: String, ty: Arc<Lock<ValueType>>) -> Self {
-                Attribute { name, ty }
+        let woog: &WoogStore = woog.as_ref().unwrap();
+        ensure!(
+            lu_dog.is_some(),
+            CompilerSnafu {
+                description: "lu_dog is required by DwarfModule"
             }
-        }
+        );
+        let lu_dog = lu_dog.unwrap();
+
+        let is_opaque = |attr: &Attribute| match attribute_external(&attr.ty, domain) {
+            Some(ext) => DwarfExternal::new(ext, config) == DwarfExternal::Opaque,
+            None => false,
+        };
 
         let mut objects: Vec<&Object> = domain.sarzak().iter_object().collect();
         objects.sort_by(|a, b| a.name.cmp(&b.name));
@@ -177,6 +195,15 @@ impl CodeWriter for DwarfFile {
         // Generate code for the ObjectStore
         // let store_type = module.as_type(&Ownership::new_owned(), woog, domain);
         let store_type = module.sanitize().to_upper_camel_case();
+        emit!(
+            buffer,
+            r#"// The model that this file, and the plugin, were generated from.
+const DOMAIN_ID: string = "{}";
+const MODEL_HASH: string = "{}";
+"#,
+            Uuid::from_slice(domain.id().as_bytes()).unwrap(),
+            model_hash(domain).simple()
+        );
         emit!(
             buffer,
             r#"// This annotation tells the interpreter that the struct will be a proxy for
@@ -191,10 +218,19 @@ impl {store_type}Store {{
     // will invoke it in the plugin.
     #[proxy(store = "{module}", object = "ObjectStore", func = "new")]
     fn new() -> Self;
+    // This loads into this store, so any proxies that you are holding see the
+    // loaded instances.
     #[proxy(store = "{module}", object = "ObjectStore", func = "load")]
-    fn load(path: string) -> Self;
+    fn load(self, path: string) -> Self;
     #[proxy(store = "{module}", object = "ObjectStore", func = "persist")]
-    fn save(self) -> Self;
+    fn save(self, path: string);
+    // A fresh path in the system temp directory, for scratch stores.
+    #[proxy(store = "{module}", object = "ObjectStore", func = "temp_path")]
+    fn temp_path(self) -> string;
+    // Call this with `DOMAIN_ID` and `MODEL_HASH` to make sure that the plugin
+    // was built from the same model as this file. It errors if not.
+    #[proxy(store = "{module}", object = "ObjectStore", func = "check_compatibility")]
+    fn check_compatibility(self, domain_id: string, model_hash: string);
 "#
         );
 
@@ -214,101 +250,90 @@ impl {store_type}Store {{
     #[proxy(store = "{module}", object = "ObjectStore", func = "inter_{obj_ident}")]
     fn inter_{obj_ident}(self, {obj_ident}: {obj_type});
     #[proxy(store = "{module}", object = "ObjectStore", func = "exhume_{obj_ident}")]
-    fn exhume_{obj_ident}(self, {obj_ident}: Uuid) -> {obj_type};"#
+    fn exhume_{obj_ident}(self, {obj_ident}: Uuid) -> {obj_type};
+    #[proxy(store = "{module}", object = "ObjectStore", func = "exorcise_{obj_ident}")]
+    fn exorcise_{obj_ident}(self, {obj_ident}: Uuid) -> Option<{obj_type}>;
+    #[proxy(store = "{module}", object = "ObjectStore", func = "iter_{obj_ident}")]
+    fn iter_{obj_ident}(self) -> [{obj_type}];"#
             );
+
+            if object_has_name(obj, domain) {
+                emit!(
+                    buffer,
+                    r#"    #[proxy(store = "{module}", object = "ObjectStore", func = "exhume_{obj_ident}_id_by_name")]
+    fn exhume_{obj_ident}_id_by_name(self, name: string) -> Option<Uuid>;"#
+                );
+            }
         }
 
         emit!(buffer, "}}\n");
 
-        for obj in &objects {
-            let is_enum = object_is_enum(obj, config, imports, domain)?;
-            let is_hybrid = object_is_hybrid(obj, config, imports, domain)?;
-            let is_singleton = object_is_singleton(obj, config, imports, domain)?;
+        // Dwarf can't see inside opaque externals, but it still needs to know
+        // that they exist.
+        for ext in collect_attribute_externals(config, domain) {
+            if DwarfExternal::new(ext, config) == DwarfExternal::Opaque {
+                let ext_type = ext.name.sanitize().to_upper_camel_case();
+                emit!(
+                    buffer,
+                    "// `{ext_type}` is external to this domain, and opaque to dwarf.\nstruct {ext_type} {{}}\n"
+                );
+            }
+        }
 
-            //
-            // Emit the type definition
-            //
-            let obj_type = obj.name.sanitize().to_upper_camel_case();
-            let store_type = obj.name.to_owned();
-            emit_object_comments(&obj.description, "// ", "", buffer)?;
-            emit!(
-                buffer,
-                r#"// This tells the interpreter that this struct is a proxy for an object called
-// "{obj_type}" in the store named "{module}"; declared above.
-#[proxy(store = "{module}", object = "{store_type}")]"#
-            );
-            emit!(buffer, "struct {} {{", obj_type,);
+        // The objects don't depend on each other, so we render them in parallel
+        // and then stitch them together, in order.
+        let fragments = objects
+            .par_iter()
+            .map(|obj| -> Result<Buffer> {
+                let mut fragment = Buffer::new();
+                let buffer = &mut fragment;
 
-            let attrs: Vec<Attribute> = collect_attributes(obj, domain);
-            for attr in &attrs {
-                let ty = value_type_to_string(&attr.ty, woog, domain);
-                emit!(buffer, "    {}: {},", attr.name, ty);
-            }
-            emit!(buffer, "    // Non-formalizing relationships");
-
-            // 🚧 We need to attach reverse pointers for relationships that we
-            // don't formalize. We also need to add pointers on the super- and
-            // sub-types
-            // if is_super || is_hybrid {
-            //     emit!(buffer, "    subtype")
-            // }
-
-            emit!(buffer, "}}\n");
-
-            //
-            // Emit the impl block
-            //
-            emit!(buffer, "impl {} {{", obj_type,);
-
-            //
-            // Emit the constructor
-            //
-            if !is_enum && !is_hybrid {
+                let is_enum = object_is_enum(obj, config, imports, domain)?;
+                let is_hybrid = object_is_hybrid(obj, config, imports, domain)?;
+
+                //
+                // Emit the type definition
+                //
+                let obj_type = obj.name.sanitize().to_upper_camel_case();
+                let store_type = obj.name.to_owned();
+                emit_object_comments(&obj.description, "// ", "", buffer)?;
                 emit!(
                     buffer,
-                    r#"    #[proxy(store = "{module}", object = "{store_type}", func = "new")]"#
+                    r#"// This tells the interpreter that this struct is a proxy for an object called
+// "{obj_type}" in the store named "{module}"; declared above.
+#[proxy(store = "{module}", object = "{store_type}")]"#
                 );
-                write!(buffer, "    fn new(").context(FormatSnafu)?;
+                emit!(buffer, "struct {} {{", obj_type,);
 
-                let mut ft = true;
-                let mut iter = attrs.iter();
-                loop {
-                    match iter.next() {
-                        Some(attr) => {
-                            if attr.name == "id" {
-                                continue;
-                            }
-                            if !ft {
-                                write!(buffer, ", ").context(FormatSnafu)?;
-                            } else {
-                                ft = false;
-                            }
-                            let ty = value_type_to_string(&attr.ty, woog, domain);
-                            write!(buffer, "{}: {}", attr.name, ty).context(FormatSnafu)?;
-                        }
-                        None => break,
-                    }
+                let attrs: Vec<Attribute> = collect_attributes(obj, lu_dog, domain);
+                for attr in &attrs {
+                    let ty = value_type_to_string(&attr.ty, woog, lu_dog, config, domain);
+                    emit!(buffer, "    {}: {},", attr.name, ty);
                 }
-                writeln!(buffer, ") -> Self;\n").context(FormatSnafu)?;
-            } else {
-                let subtypes = get_subtypes_sorted_from_super_obj!(obj, domain.sarzak());
+                emit!(buffer, "}}\n");
 
-                for subtype in subtypes {
-                    let s_obj = subtype.r15_object(domain.sarzak())[0];
-                    let s_obj_type = s_obj.name.sanitize().to_upper_camel_case();
-                    let s_obj_ident = s_obj.as_ident();
+                //
+                // Emit the impl block
+                //
+                emit!(buffer, "impl {} {{", obj_type,);
 
+                //
+                // Emit the constructor
+                //
+                if !is_enum && !is_hybrid {
                     emit!(
                         buffer,
-                        r#"    #[proxy(store = "{module}", object = "{store_type}", func = "new_{s_obj_ident}")]"#
+                        r#"    #[proxy(store = "{module}", object = "{store_type}", func = "new")]"#
                     );
-                    write!(buffer, "    fn new_{s_obj_ident}(").context(FormatSnafu)?;
+                    write!(buffer, "    fn new(").context(FormatSnafu)?;
+
                     let mut ft = true;
                     let mut iter = attrs.iter();
                     loop {
                         match iter.next() {
                             Some(attr) => {
-                                if attr.name == "id" {
+                                // Opaque externals are made by the plugin.
+                                if attr.name == "id" || is_opaque(attr) {
                                     continue;
                                 }
                                 if !ft {
@@ -316,60 +341,172 @@ impl {store_type}Store {{
                                 } else {
                                     ft = false;
                                 }
-                                let ty = value_type_to_string(&attr.ty, woog, domain);
+                                let ty =
+                                    value_type_to_string(&attr.ty, woog, lu_dog, config, domain);
                                 write!(buffer, "{}: {}", attr.name, ty).context(FormatSnafu)?;
                             }
                             None => break,
                         }
                     }
-                    if !is_singleton && !is_enum {
-                        if !ft {
-                            write!(buffer, ", ").context(FormatSnafu)?;
+                    writeln!(buffer, ") -> Self;\n").context(FormatSnafu)?;
+                } else {
+                    let subtypes = get_subtypes_sorted_from_super_obj!(obj, domain.sarzak());
+
+                    for subtype in subtypes {
+                        let s_obj = subtype.r15_object(domain.sarzak())[0];
+                        let s_obj_type = s_obj.name.sanitize().to_upper_camel_case();
+                        let s_obj_ident = s_obj.as_ident();
+
+                        emit!(
+                            buffer,
+                            r#"    #[proxy(store = "{module}", object = "{store_type}", func = "new_{s_obj_ident}")]"#
+                        );
+                        write!(buffer, "    fn new_{s_obj_ident}(").context(FormatSnafu)?;
+                        let mut ft = true;
+                        let mut iter = attrs.iter();
+                        loop {
+                            match iter.next() {
+                                Some(attr) => {
+                                    if attr.name == "id" || is_opaque(attr) {
+                                        continue;
+                                    }
+                                    if !ft {
+                                        write!(buffer, ", ").context(FormatSnafu)?;
+                                    } else {
+                                        ft = false;
+                                    }
+                                    let ty =
+                                        value_type_to_string(&attr.ty, woog, lu_dog, config, domain);
+                                    write!(buffer, "{}: {}", attr.name, ty).context(FormatSnafu)?;
+                                }
+                                None => break,
+                            }
                         }
-                        writeln!(buffer, "{s_obj_ident}: {s_obj_type}").context(FormatSnafu)?;
+                        // The subtype goes last, after any attributes that belong to
+                        // the supertype. Singleton subtypes are consts, so there's
+                        // nothing to pass in.
+                        if !object_is_singleton(s_obj, config, imports, domain)? {
+                            if !ft {
+                                write!(buffer, ", ").context(FormatSnafu)?;
+                            }
+                            write!(buffer, "{s_obj_ident}: {s_obj_type}").context(FormatSnafu)?;
+                        }
+                        writeln!(buffer, ") -> Self;\n").context(FormatSnafu)?;
                     }
-                    writeln!(buffer, ") -> Self;\n").context(FormatSnafu)?;
                 }
-            }
 
-            emit!(
-                buffer,
-                r#"    #[proxy(store = "{module}", object = "{store_type}", func = "instances")]
+                emit!(
+                    buffer,
+                    r#"    #[proxy(store = "{module}", object = "{store_type}", func = "instances")]
     fn instances() -> [Self];
 "#
-            );
+                );
 
-            //
-            // Generate the help() method
-            //
-            emit!(buffer, "    fn help() -> () {{");
-            emit_object_comments(
-                // What a cheat!
-                // Oh, man, what did I do? This was for the original parser,
-                // whatever it's for.
-                // 🚧 Fix this.
-                &obj.description.replace('\"', "\u{201d}"),
-                "        print(\"",
-                "\\n\");",
-                buffer,
-            )?;
-            emit!(buffer, "    }}");
-            emit!(buffer, "");
-
-            //
-            // Generate the info() method
-            //
-            emit!(buffer, "    fn info() -> () {{");
-            emit!(buffer, "        print(\"struct {} {{\\n\");", obj_type,);
-            for attr in &attrs {
-                let ty = value_type_to_string(&attr.ty, woog, domain);
-                emit!(buffer, "        print(\"    {}: {},\\n\");", attr.name, ty);
-            }
-            emit!(buffer, "        print(\"}}\\n\");");
-            emit!(buffer, "    }}");
+                //
+                // Emit the relationship navigation methods. Singletons don't have
+                // any, they're just consts.
+                //
+                if !object_is_singleton(obj, config, imports, domain)? {
+                    for nav in collect_rel_navs(obj, config, domain) {
+                        let name = &nav.name;
+                        let target = nav.target.name.sanitize().to_upper_camel_case();
+                        let ret = if nav.many {
+                            format!("[{target}]")
+                        } else if nav.conditional {
+                            format!("Option<{target}>")
+                        } else {
+                            target
+                        };
+
+                        emit!(
+                            buffer,
+                            r#"    #[proxy(store = "{module}", object = "{store_type}", func = "{name}")]
+    fn {name}(self) -> {ret};"#
+                        );
+                    }
+                    emit!(buffer, "");
+                }
+
+                //
+                // Generate the help() method
+                //
+                // The description is wrapped first, and then escaped, so that
+                // wrapping can't split an escape sequence in half.
+                emit!(buffer, "    fn help() -> () {{");
+                let mut description = Buffer::new();
+                emit_object_comments(&obj.description, "", "", &mut description)?;
+                for line in description.dump().lines() {
+                    emit!(buffer, "        print(\"{}\\n\");", escape_dwarf_string(line));
+                }
+                emit!(buffer, "    }}");
+                emit!(buffer, "");
+
+                //
+                // Generate the info() method
+                //
+                emit!(buffer, "    fn info() -> () {{");
+                emit!(buffer, "        print(\"struct {} {{\\n\");", obj_type,);
+                for attr in &attrs {
+                    let ty = value_type_to_string(&attr.ty, woog, lu_dog, config, domain);
+                    emit!(
+                        buffer,
+                        "        print(\"    {}: {},\\n\");",
+                        escape_dwarf_string(&attr.name),
+                        escape_dwarf_string(&ty)
+                    );
+                }
+                emit!(buffer, "        print(\"}}\\n\");");
+
+                let mut sections = vec![
+                    ("Relationships", describe_rels(obj, config, domain)),
+                    ("Supertype", describe_supertypes(obj, domain)),
+                    ("Subtypes", describe_subtypes(obj, domain)),
+                ];
+                if !object_is_singleton(obj, config, imports, domain)? {
+                    let mut funcs = Vec::new();
+                    if !is_enum && !is_hybrid {
+                        funcs.push(format!("{obj_type}::new"));
+                    } else {
+                        for subtype in get_subtypes_sorted_from_super_obj!(obj, domain.sarzak()) {
+                            let s_obj = subtype.r15_object(domain.sarzak())[0];
+                            funcs.push(format!("{obj_type}::new_{}", s_obj.as_ident()));
+                        }
+                    }
+                    funcs.push(format!("{obj_type}::instances"));
+                    let obj_ident = obj.as_ident();
+                    let module_type = module.sanitize().to_upper_camel_case();
+                    for func in ["inter", "exhume", "exorcise", "iter"] {
+                        funcs.push(format!("{module_type}Store::{func}_{obj_ident}"));
+                    }
+                    if object_has_name(obj, domain) {
+                        funcs.push(format!("{module_type}Store::exhume_{obj_ident}_id_by_name"));
+                    }
+                    for nav in collect_rel_navs(obj, config, domain) {
+                        funcs.push(format!("{obj_type}.{}", nav.name));
+                    }
+                    sections.push(("Store functions", funcs));
+                }
+
+                for (title, lines) in sections {
+                    if lines.is_empty() {
+                        continue;
+                    }
+                    emit!(buffer, "        print(\"\\n{title}:\\n\");");
+                    for line in lines {
+                        emit!(buffer, "        print(\"    {}\\n\");", escape_dwarf_string(&line));
+                    }
+                }
+                emit!(buffer, "    }}");
+
+                emit!(buffer, "}}");
+                emit!(buffer, "");
+
+                Ok(fragment)
+            })
+            .collect::<Result<Vec<_>>>()?;
 
-            emit!(buffer, "}}");
-            emit!(buffer, "");
+        for fragment in fragments {
+            *buffer += fragment;
         }
 
         //         Ok(())
@@ -380,9 +517,480 @@ impl {store_type}Store {{
     }
 }
 
-fn value_type_to_string(ty: &Arc<Lock<ValueType>>, woog: &WoogStore, domain: &Domain) -> String {
-    let lu_dog = &LU_DOG;
+/// Dwarf Test Harness Generator / CodeWriter
+///
+/// This writes a dwarf program that exercises the plugin through the proxies
+/// declared in the dwarf file. For each object that we can make, we construct
+/// one, inter it, exhume it, and round-trip it's fields. Then the store is
+/// persisted, loaded, and we check that everything came back.
+///
+/// Objects are made in dependency order, so that referential attributes have
+/// something to point at. Anything that we can't make, say because it refers
+/// to a singleton, or there's a cycle, is skipped with a comment saying why.
+///
+/// It prints `FAIL` for anything that doesn't come back the way it went in.
+pub(crate) struct DwarfHarness;
+
+impl DwarfHarness {
+    pub(crate) fn new() -> Box<dyn DwarfDefinition> {
+        Box::new(Self)
+    }
+}
+
+impl DwarfDefinition for DwarfHarness {}
+
+impl CodeWriter for DwarfHarness {
+    fn write_code(
+        &self,
+        config: &GraceConfig,
+        domain: &Domain,
+        woog: &Option<&mut WoogStore>,
+        imports: &Option<&HashMap<String, Domain>>,
+        lu_dog: &Option<&RwLock<LuDogStore>>,
+        _package: &str,
+        module: &str,
+        _obj_id: Option<&Uuid>,
+        buffer: &mut Buffer,
+    ) -> Result<()> {
+        ensure!(
+            woog.is_some(),
+            CompilerSnafu {
+                description: "woog is required by DwarfHarness"
+            }
+        );
+        let woog: &WoogStore = woog.as_ref().unwrap();
+        ensure!(
+            lu_dog.is_some(),
+            CompilerSnafu {
+                description: "lu_dog is required by DwarfHarness"
+            }
+        );
+        let lu_dog = lu_dog.unwrap();
+
+        let file_name = domain.name().as_ident();
+        let store_type = format!("{}Store", module.sanitize().to_upper_camel_case());
+
+        let mut objects: Vec<&Object> = domain
+            .sarzak()
+            .iter_object()
+            .filter(|obj| !config.is_imported(&obj.id))
+            .collect();
+        objects.sort_by(|a, b| a.name.cmp(&b.name));
+
+        // These are the types that we can't make. The plugin hands them out,
+        // but there's no constructor to call.
+        let mut singletons = HashSet::default();
+        for obj in &objects {
+            if object_is_singleton(obj, config, imports, domain)? {
+                singletons.insert(obj.name.sanitize().to_upper_camel_case());
+            }
+        }
+
+        // Keep going around until we stop making progress. What's left can't
+        // be made.
+        let mut made: HashMap<String, String> = HashMap::default();
+        let mut lines: Vec<String> = Vec::new();
+        let mut pending: Vec<&Object> = objects
+            .iter()
+            .filter(|obj| !singletons.contains(&obj.name.sanitize().to_upper_camel_case()))
+            .cloned()
+            .collect();
+
+        loop {
+            let mut still_pending = Vec::new();
+
+            for obj in pending.iter().cloned() {
+                match harness_ctor(
+                    obj,
+                    &made,
+                    &singletons,
+                    woog,
+                    lu_dog,
+                    config,
+                    imports,
+                    domain,
+                )? {
+                    HarnessCtor::Call(call) => {
+                        let obj_type = obj.name.sanitize().to_upper_camel_case();
+                        let obj_ident = obj.as_ident();
+
+                        lines.push(format!("    // {obj_type}"));
+                        lines.push(format!("    let {obj_ident} = {call};"));
+                        lines.push(format!("    store.inter_{obj_ident}({obj_ident});"));
+                        lines.push(format!(
+                            "    let exhumed = store.exhume_{obj_ident}({obj_ident}.id);"
+                        ));
+                        lines.push(format!("    if exhumed.id != {obj_ident}.id {{"));
+                        lines.push(format!(
+                            "        print(\"FAIL: exhume_{obj_ident} returned the wrong {obj_type}\\n\");"
+                        ));
+                        lines.push("    }".to_owned());
+
+                        // Round trip the fields that we know how to write.
+                        let attrs: Vec<Attribute> = collect_attributes(obj, lu_dog, domain);
+                        for attr in &attrs {
+                            if attr.name == "id" {
+                                continue;
+                            }
+                            let ty = value_type_to_string(&attr.ty, woog, lu_dog, config, domain);
+                            if let Some(value) =
+                                harness_value(&ty, &format!("{obj_type} {}", attr.name), true)
+                            {
+                                let name = &attr.name;
+                                lines.push(format!("    {obj_ident}.{name} = {value};"));
+                                lines.push(format!("    if {obj_ident}.{name} != {value} {{"));
+                                lines.push(format!(
+                                    "        print(\"FAIL: {obj_type}.{name} didn't round trip\\n\");"
+                                ));
+                                lines.push("    }".to_owned());
+                            }
+                        }
+                        lines.push(String::new());
+
+                        made.insert(obj_type, obj_ident);
+                    }
+                    HarnessCtor::Missing(_) => still_pending.push(obj),
+                }
+            }
+
+            if still_pending.len() == pending.len() {
+                pending = still_pending;
+                break;
+            }
+            pending = still_pending;
+        }
+
+        for obj in &pending {
+            if let HarnessCtor::Missing(missing) = harness_ctor(
+                obj,
+                &made,
+                &singletons,
+                woog,
+                lu_dog,
+                config,
+                imports,
+                domain,
+            )? {
+                lines.push(format!(
+                    "    // Skipping `{}`: there's no way to make a `{missing}` to give it.",
+                    obj.name.sanitize().to_upper_camel_case()
+                ));
+            }
+        }
+
+        emit!(buffer, "use {file_name}::{store_type};");
+        let mut made_types: Vec<&String> = made.keys().collect();
+        made_types.sort();
+        for ty in made_types {
+            emit!(buffer, "use {file_name}::{ty};");
+        }
+        emit!(buffer, "");
+
+        emit!(buffer, "fn main() -> () {{");
+        emit!(buffer, "    let store = {store_type}::new();");
+        emit!(
+            buffer,
+            "    store.check_compatibility(\"{}\", \"{}\");",
+            Uuid::from_slice(domain.id().as_bytes()).unwrap(),
+            model_hash(domain).simple()
+        );
+        emit!(buffer, "");
+        for line in &lines {
+            emit!(buffer, "{line}");
+        }
+
+        // Persist, load, and make sure everything is still there.
+        emit!(buffer, "    let path = store.temp_path();");
+        emit!(buffer, "    store.save(path);");
+        emit!(buffer, "    let store = store.load(path);");
+        let mut made: Vec<(&String, &String)> = made.iter().collect();
+        made.sort();
+        for (obj_type, obj_ident) in made {
+            emit!(
+                buffer,
+                "    let reloaded = store.exhume_{obj_ident}({obj_ident}.id);"
+            );
+            emit!(buffer, "    if reloaded.id != {obj_ident}.id {{");
+            emit!(
+                buffer,
+                "        print(\"FAIL: {obj_type} didn't survive persist and load\\n\");"
+            );
+            emit!(buffer, "    }}");
+        }
+        emit!(buffer, "");
+        emit!(buffer, "    print(\"{file_name}: done\\n\");");
+        emit!(buffer, "}}");
+
+        Ok(())
+    }
+}
+
+enum HarnessCtor {
+    /// The constructor call, ready to go.
+    Call(String),
+    /// We need one of these first.
+    Missing(String),
+}
+
+/// Build a constructor call for the harness
+///
+/// Supertypes are built with the first subtype that we can lay our hands on.
+#[allow(clippy::too_many_arguments)]
+fn harness_ctor(
+    obj: &Object,
+    made: &HashMap<String, String>,
+    singletons: &HashSet<String>,
+    woog: &WoogStore,
+    lu_dog: &RwLock<LuDogStore>,
+    config: &GraceConfig,
+    imports: &Option<&HashMap<String, Domain>>,
+    domain: &Domain,
+) -> Result<HarnessCtor> {
+    let obj_type = obj.name.sanitize().to_upper_camel_case();
+
+    let mut args = Vec::new();
+    let attrs: Vec<Attribute> = collect_attributes(obj, lu_dog, domain);
+    for attr in &attrs {
+        if attr.name == "id" {
+            continue;
+        }
+        // Opaque externals are made by the plugin.
+        if let Some(ext) = attribute_external(&attr.ty, domain) {
+            if DwarfExternal::new(ext, config) == DwarfExternal::Opaque {
+                continue;
+            }
+        }
+
+        let ty = value_type_to_string(&attr.ty, woog, lu_dog, config, domain);
+        match harness_value(&ty, &format!("{obj_type} {}", attr.name), false) {
+            Some(value) => args.push(value),
+            None => match made.get(&ty) {
+                Some(var) => args.push(var.clone()),
+                None => return Ok(HarnessCtor::Missing(ty)),
+            },
+        }
+    }
+
+    if object_is_enum(obj, config, imports, domain)?
+        || object_is_hybrid(obj, config, imports, domain)?
+    {
+        let subtypes = get_subtypes_sorted_from_super_obj!(obj, domain.sarzak());
+        let mut missing = None;
+        for subtype in subtypes {
+            let s_obj = subtype.r15_object(domain.sarzak())[0];
+            let s_obj_type = s_obj.name.sanitize().to_upper_camel_case();
+            let s_obj_ident = s_obj.as_ident();
+
+            if singletons.contains(&s_obj_type) {
+                return Ok(HarnessCtor::Call(format!(
+                    "{obj_type}::new_{s_obj_ident}({})",
+                    args.join(", ")
+                )));
+            } else if let Some(var) = made.get(&s_obj_type) {
+                let mut args = args.clone();
+                args.push(var.clone());
+                return Ok(HarnessCtor::Call(format!(
+                    "{obj_type}::new_{s_obj_ident}({})",
+                    args.join(", ")
+                )));
+            } else if missing.is_none() {
+                missing = Some(s_obj_type);
+            }
+        }
+
+        Ok(HarnessCtor::Missing(missing.unwrap_or(obj_type)))
+    } else {
+        Ok(HarnessCtor::Call(format!(
+            "{obj_type}::new({})",
+            args.join(", ")
+        )))
+    }
+}
+
+/// Escape a string so that it's a valid dwarf string literal body
+///
+/// This used to just swap double quotes for curly ones, which was a cheat.
+/// Dwarf strings take the usual backslash escapes, so we use those.
+fn escape_dwarf_string(input: &str) -> String {
+    let mut result = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c {
+            '\\' => result.push_str("\\\\"),
+            '"' => result.push_str("\\\""),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c => result.push(c),
+        }
+    }
+
+    result
+}
+
+/// How many of the thing on the other side there are, in English
+///
+/// No cardinality means one, which is what you get from the referrer side.
+fn multiplicity(card: Option<&Cardinality>, cond: &Conditionality) -> &'static str {
+    match (card, cond) {
+        (Some(Cardinality::Many(_)), Conditionality::Unconditional(_)) => "one or more",
+        (Some(Cardinality::Many(_)), Conditionality::Conditional(_)) => "zero or more",
+        (_, Conditionality::Unconditional(_)) => "exactly one",
+        (_, Conditionality::Conditional(_)) => "zero or one",
+    }
+}
+
+/// Describe the relationships that an object participates in, for `info()`
+///
+/// These read like the model: "R1: Foo is owned by exactly one Bar". The
+/// referrer side is always one, so only the conditionality matters there.
+fn describe_rels(obj: &Object, config: &GraceConfig, domain: &Domain) -> Vec<String> {
+    let mut result = Vec::new();
+    let obj_type = obj.name.sanitize().to_upper_camel_case();
+    let name = |obj: &Object| {
+        let ty = obj.name.sanitize().to_upper_camel_case();
+        if config.is_imported(&obj.id) {
+            format!("{ty} (imported)")
+        } else {
+            ty
+        }
+    };
+
+    for referrer in get_binary_referrers_sorted!(obj, domain.sarzak()) {
+        let binary = referrer.r6_binary(domain.sarzak())[0];
+        let referent = binary.r5_referent(domain.sarzak())[0];
+        let r_obj = referent.r16_object(domain.sarzak())[0];
+        let cond = referrer.r11_conditionality(domain.sarzak())[0];
+
+        result.push(format!(
+            "R{}: {obj_type} {} {} {}, formalized by {}",
+            binary.number,
+            referrer.description,
+            multiplicity(None, cond),
+            name(r_obj),
+            referrer.referential_attribute
+        ));
+    }
+
+    for referent in get_binary_referents_sorted!(obj, domain.sarzak()) {
+        let binary = referent.r5_binary(domain.sarzak())[0];
+        let referrer = binary.r6_referrer(domain.sarzak())[0];
+        let r_obj = referrer.r17_object(domain.sarzak())[0];
+        let cond = referent.r12_conditionality(domain.sarzak())[0];
+        let card = referrer.r9_cardinality(domain.sarzak())[0];
 
+        result.push(format!(
+            "R{}: {obj_type} {} {} {}",
+            binary.number,
+            referent.description,
+            multiplicity(Some(card), cond),
+            name(r_obj)
+        ));
+    }
+
+    for assoc_referrer in obj.r26_associative_referrer(domain.sarzak()) {
+        let assoc = assoc_referrer.r21_associative(domain.sarzak())[0];
+        for referent in get_assoc_referent_from_referrer_sorted!(assoc_referrer, domain.sarzak()) {
+            let an_ass = referent.r22_an_associative_referent(domain.sarzak())[0];
+            let r_obj = referent.r25_object(domain.sarzak())[0];
+
+            result.push(format!(
+                "R{}: {obj_type} associates exactly one {}, formalized by {}",
+                assoc.number,
+                name(r_obj),
+                an_ass.referential_attribute
+            ));
+        }
+    }
+
+    for assoc_referent in obj.r25_associative_referent(domain.sarzak()) {
+        let an_ass = assoc_referent.r22_an_associative_referent(domain.sarzak())[0];
+        let assoc = an_ass.r22_associative(domain.sarzak())[0];
+        let referrer = assoc.r21_associative_referrer(domain.sarzak())[0];
+        let card = assoc_referent.r88_cardinality(domain.sarzak())[0];
+        let cond = assoc_referent.r77_conditionality(domain.sarzak())[0];
+        let r_obj = referrer.r26_object(domain.sarzak())[0];
+
+        result.push(format!(
+            "R{}: {obj_type} is associated by {} {}",
+            assoc.number,
+            multiplicity(Some(card), cond),
+            name(r_obj)
+        ));
+    }
+
+    result
+}
+
+/// The supertypes of an object, for `info()`
+fn describe_supertypes(obj: &Object, domain: &Domain) -> Vec<String> {
+    get_subtypes_sorted!(obj, domain.sarzak())
+        .iter()
+        .map(|subtype| {
+            let isa = subtype.r27_isa(domain.sarzak())[0];
+            let supertype = isa.r13_supertype(domain.sarzak())[0];
+            let s_obj = supertype.r14_object(domain.sarzak())[0];
+
+            format!(
+                "R{}: {}",
+                isa.number,
+                s_obj.name.sanitize().to_upper_camel_case()
+            )
+        })
+        .collect()
+}
+
+/// The subtypes of an object, for `info()`
+fn describe_subtypes(obj: &Object, domain: &Domain) -> Vec<String> {
+    if obj.r14_supertype(domain.sarzak()).is_empty() {
+        return Vec::new();
+    }
+
+    get_subtypes_sorted_from_super_obj!(obj, domain.sarzak())
+        .iter()
+        .map(|subtype| {
+            let isa = subtype.r27_isa(domain.sarzak())[0];
+            let s_obj = subtype.r15_object(domain.sarzak())[0];
+
+            format!(
+                "R{}: {}",
+                isa.number,
+                s_obj.name.sanitize().to_upper_camel_case()
+            )
+        })
+        .collect()
+}
+
+/// A value for a dwarf type, if it's something that we can write down
+///
+/// `tag` goes into strings so that you can tell them apart. `changed` gives a
+/// different value, for setting fields after construction. Options are only
+/// good for constructors, since we aren't sure what comes back when you read
+/// a `None`.
+///
+/// Floats stay positive, since `SystemTime` externals are floats too, and
+/// those can't go back before the epoch.
+fn harness_value(ty: &str, tag: &str, changed: bool) -> Option<String> {
+    match (ty, changed) {
+        ("string", false) => Some(format!("\"{}\"", escape_dwarf_string(tag))),
+        ("string", true) => Some(format!("\"{} changed\"", escape_dwarf_string(tag))),
+        ("int", false) => Some("42".to_owned()),
+        ("int", true) => Some("-7".to_owned()),
+        ("float", false) => Some("42.0".to_owned()),
+        ("float", true) => Some("7.5".to_owned()),
+        ("bool", false) => Some("true".to_owned()),
+        ("bool", true) => Some("false".to_owned()),
+        (ty, false) if ty.starts_with("Option<") => Some("None".to_owned()),
+        _ => None,
+    }
+}
+
+fn value_type_to_string(
+    ty: &Arc<Lock<ValueType>>,
+    woog: &WoogStore,
+    lu_dog: &RwLock<LuDogStore>,
+    config: &GraceConfig,
+    domain: &Domain,
+) -> String {
     match *s_read!(ty) {
         ValueType::Char(_) => "char".to_owned(),
         ValueType::Empty(_) => "()".to_owned(),
@@ -406,7 +1014,10 @@ fn value_type_to_string(ty: &Arc<Lock<ValueType>>, woog: &WoogStore, domain: &Do
                 let list = s_read!(list);
                 list.r36_value_type(&lu_dog)[0].clone()
             };
-            format!("Vec<{}>", &value_type_to_string(&inner, woog, domain))
+            format!(
+                "Vec<{}>",
+                &value_type_to_string(&inner, woog, lu_dog, config, domain)
+            )
         }
         ValueType::Range(_) => "<range>".to_owned(),
         ValueType::Reference(ref id) => {
@@ -417,7 +1028,7 @@ fn value_type_to_string(ty: &Arc<Lock<ValueType>>, woog: &WoogStore, domain: &Do
                 reference.r35_value_type(&lu_dog)[0].clone()
             };
 
-            value_type_to_string(&inner, woog, domain)
+            value_type_to_string(&inner, woog, lu_dog, config, domain)
         }
         ValueType::Ty(ref id) => {
             let ty = domain.sarzak().exhume_ty(id).unwrap();
@@ -431,7 +1042,14 @@ fn value_type_to_string(ty: &Arc<Lock<ValueType>>, woog: &WoogStore, domain: &Do
                 Ty::Integer(_) => "int".to_owned(),
                 Ty::Float(_) => "float".to_owned(),
                 Ty::SUuid(_) => "Uuid".to_owned(),
-                Ty::External(_) => "ext_what_to_do".to_owned(),
+                Ty::External(ref id) => {
+                    let ext = domain.sarzak().exhume_external(id).unwrap();
+                    match DwarfExternal::new(ext, config) {
+                        DwarfExternal::SystemTime => "float".to_owned(),
+                        DwarfExternal::PathBuf | DwarfExternal::StringBacked => "string".to_owned(),
+                        DwarfExternal::Opaque => ext.name.sanitize().to_upper_camel_case(),
+                    }
+                }
             }
         }
         ValueType::Unknown(_) => "<unknown>".to_owned(),
@@ -443,7 +1061,10 @@ fn value_type_to_string(ty: &Arc<Lock<ValueType>>, woog: &WoogStore, domain: &Do
                 option.r2_value_type(&lu_dog)[0].clone()
             };
 
-            format!("Option<{}>", &value_type_to_string(&inner, woog, domain))
+            format!(
+                "Option<{}>",
+                &value_type_to_string(&inner, woog, lu_dog, config, domain)
+            )
         }
         ValueType::WoogStruct(ref id) => {
             let lu_dog = lu_dog.read().unwrap();
@@ -483,3 +1104,45 @@ impl Sanitize for &str {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_dwarf_string_quotes() {
+        assert_eq!(escape_dwarf_string(r#"say "hi""#), r#"say \"hi\""#);
+    }
+
+    #[test]
+    fn test_escape_dwarf_string_backslashes() {
+        assert_eq!(escape_dwarf_string(r"C:\dwarf\"), r"C:\\dwarf\\");
+        // A backslash in front of a quote must not end up escaping the quote.
+        assert_eq!(escape_dwarf_string(r#"\""#), r#"\\\""#);
+    }
+
+    #[test]
+    fn test_escape_dwarf_string_whitespace() {
+        assert_eq!(escape_dwarf_string("one\ntwo"), r"one\ntwo");
+        assert_eq!(escape_dwarf_string("one\r\ntwo"), r"one\r\ntwo");
+        assert_eq!(escape_dwarf_string("one\ttwo"), r"one\ttwo");
+    }
+
+    #[test]
+    fn test_escape_dwarf_string_braces() {
+        // Braces aren't special in dwarf strings. They are in `emit!`, which
+        // is why the escaped string always goes in as an argument, like this.
+        let escaped = escape_dwarf_string("Option<{T}>");
+        assert_eq!(escaped, "Option<{T}>");
+        assert_eq!(
+            format!("print(\"{}\\n\");", escaped),
+            "print(\"Option<{T}>\\n\");"
+        );
+    }
+
+    #[test]
+    fn test_escape_dwarf_string_leaves_the_rest_alone() {
+        assert_eq!(escape_dwarf_string("plain 🦀 text"), "plain 🦀 text");
+        assert_eq!(escape_dwarf_string(""), "");
+    }
+}
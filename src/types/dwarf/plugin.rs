@@ -0,0 +1,194 @@
+//! Plugin Crate Generation
+//!
+//! When the plugin layout is `crate` we generate a complete `cdylib` crate
+//! around the chacha file. This is the skeleton: `Cargo.toml` and `src/lib.rs`.
+//! They are only written if they don't already exist, so feel free to edit.
+use std::{fmt::Write, sync::RwLock};
+
+use rustc_hash::FxHashMap as HashMap;
+use sarzak::{
+    lu_dog::store::ObjectStore as LuDogStore,
+    mc::{CompilerSnafu, FormatSnafu, Result},
+    v2::domain::Domain,
+    woog::store::ObjectStore as WoogStore,
+};
+use snafu::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    codegen::{
+        buffer::{emit, Buffer},
+        generator::{CodeWriter, FileGenerator, GenerationAction},
+        render::RenderIdent,
+    },
+    options::{GraceConfig, UberStoreOptions},
+    types::PluginDefinition,
+};
+
+pub(crate) struct PluginBuilder {
+    definition: Option<Box<dyn PluginDefinition>>,
+}
+
+impl PluginBuilder {
+    pub(crate) fn new() -> Self {
+        PluginBuilder { definition: None }
+    }
+
+    pub(crate) fn definition(mut self, definition: Box<dyn PluginDefinition>) -> Self {
+        self.definition = Some(definition);
+
+        self
+    }
+
+    pub(crate) fn build(self) -> Result<Box<PluginGenerator>> {
+        ensure!(
+            self.definition.is_some(),
+            CompilerSnafu {
+                description: "missing PluginDefinition"
+            }
+        );
+
+        Ok(Box::new(PluginGenerator {
+            definition: self.definition.unwrap(),
+        }))
+    }
+}
+
+pub(crate) struct PluginGenerator {
+    definition: Box<dyn PluginDefinition>,
+}
+
+impl FileGenerator for PluginGenerator {
+    fn generate(
+        &self,
+        config: &GraceConfig,
+        domain: &Domain,
+        woog: &Option<&mut WoogStore>,
+        imports: &Option<&HashMap<String, Domain>>,
+        lu_dog: &Option<&RwLock<LuDogStore>>,
+        package: &str,
+        module: &str,
+        obj_id: Option<&Uuid>,
+        buffer: &mut Buffer,
+    ) -> Result<GenerationAction> {
+        self.definition.write_code(
+            config, domain, woog, imports, lu_dog, package, module, obj_id, buffer,
+        )?;
+
+        Ok(GenerationAction::Write)
+    }
+}
+
+/// Plugin Cargo.toml Generator / CodeWriter
+///
+/// The dependencies are the ones that the generated store and plugin use. The
+/// lock crate depends on the uber store option, and dwarf is pinned to the
+/// revision in the options.
+pub(crate) struct PluginManifest;
+
+impl PluginManifest {
+    pub(crate) fn new() -> Box<dyn PluginDefinition> {
+        Box::new(Self)
+    }
+}
+
+impl PluginDefinition for PluginManifest {}
+
+impl CodeWriter for PluginManifest {
+    fn write_code(
+        &self,
+        config: &GraceConfig,
+        domain: &Domain,
+        _woog: &Option<&mut WoogStore>,
+        _imports: &Option<&HashMap<String, Domain>>,
+        _lu_dog: &Option<&RwLock<LuDogStore>>,
+        _package: &str,
+        _module: &str,
+        _obj_id: Option<&Uuid>,
+        buffer: &mut Buffer,
+    ) -> Result<()> {
+        let crate_name = domain.name().as_ident();
+        // This is checked when the options are validated.
+        let dwarf_rev = config.get_dwarf_rev().unwrap();
+
+        emit!(
+            buffer,
+            r#"[package]
+name = "{crate_name}"
+version = "0.1.0"
+edition = "2021"
+
+[lib]
+crate-type = ["cdylib"]
+
+[dependencies]
+abi_stable = "0.11.1"
+bincode = "1.3.3"
+dwarf = {{ git = "https://github.com/uberFoo/dwarf", rev = "{dwarf_rev}" }}
+log = "0.4.17"
+rustc-hash = "1.1.0"
+serde = {{ version = "1.0.152", features = ["derive"] }}
+serde_json = "1.0.91"
+uuid = {{ version = "1.2.2", features = ["serde", "v4", "v5"] }}"#
+        );
+
+        use UberStoreOptions::*;
+        match config.get_uber_store().unwrap() {
+            NDRwLock => emit!(buffer, r#"no_deadlocks = "1.3.0""#),
+            ParkingLotRwLock | ParkingLotMutex => emit!(
+                buffer,
+                r#"parking_lot = {{ version = "0.12.1", features = ["serde"] }}"#
+            ),
+            // The rest are either std, or rejected when the options are
+            // validated. `AsyncRwLock` is one of the latter, so there's no
+            // need for `futures`, or an async runtime.
+            _ => {}
+        }
+
+        if config.get_persist_parallel() {
+            emit!(buffer, r#"rayon = "1.7.0""#);
+        }
+
+        if config.get_tracy() {
+            emit!(buffer, r#"tracy-client = "0.15.2""#);
+        }
+
+        Ok(())
+    }
+}
+
+/// Plugin lib.rs Generator / CodeWriter
+///
+/// All this does is pull in the plugin module. The `#[export_root_module]`
+/// attribute works just fine from down there.
+pub(crate) struct PluginLib;
+
+impl PluginLib {
+    pub(crate) fn new() -> Box<dyn PluginDefinition> {
+        Box::new(Self)
+    }
+}
+
+impl PluginDefinition for PluginLib {}
+
+impl CodeWriter for PluginLib {
+    fn write_code(
+        &self,
+        _config: &GraceConfig,
+        domain: &Domain,
+        _woog: &Option<&mut WoogStore>,
+        _imports: &Option<&HashMap<String, Domain>>,
+        _lu_dog: &Option<&RwLock<LuDogStore>>,
+        _package: &str,
+        _module: &str,
+        _obj_id: Option<&Uuid>,
+        buffer: &mut Buffer,
+    ) -> Result<()> {
+        for line in domain.description().lines() {
+            emit!(buffer, "//! {}", line);
+        }
+        emit!(buffer, "pub mod {};", domain.name().as_ident());
+
+        Ok(())
+    }
+}
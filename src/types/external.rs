@@ -1,9 +1,10 @@
 //! A type for generating an external type.
 //!
-use std::fmt::Write;
+use std::{fmt::Write, sync::RwLock};
 
 use rustc_hash::FxHashMap as HashMap;
 use sarzak::{
+    lu_dog::store::ObjectStore as LuDogStore,
     mc::{CompilerSnafu, FormatSnafu, Result},
     v2::domain::Domain,
     woog::{store::ObjectStore as WoogStore, types::Ownership},
@@ -61,6 +62,7 @@ impl FileGenerator for ExternalGenerator {
         domain: &Domain,
         woog_opt: &Option<&mut WoogStore>,
         imports: &Option<&HashMap<String, Domain>>,
+        lu_dog: &Option<&RwLock<LuDogStore>>,
         package: &str,
         module: &str,
         obj_id: Option<&Uuid>,
@@ -321,6 +323,7 @@ impl FileGenerator for ExternalGenerator {
                         domain,
                         woog_opt,
                         imports,
+                        lu_dog,
                         package,
                         module,
                         Some(obj_id),
@@ -1,7 +1,12 @@
 //! A type for not generating anything
 //!
+use std::sync::RwLock;
+
 use rustc_hash::FxHashMap as HashMap;
-use sarzak::{mc::Result, v2::domain::Domain, woog::store::ObjectStore as WoogStore};
+use sarzak::{
+    lu_dog::store::ObjectStore as LuDogStore, mc::Result, v2::domain::Domain,
+    woog::store::ObjectStore as WoogStore,
+};
 use uuid::Uuid;
 
 use crate::{
@@ -27,6 +32,7 @@ impl FileGenerator for NullGenerator {
         _domain: &Domain,
         _woog: &Option<&mut WoogStore>,
         _imports: &Option<&HashMap<String, Domain>>,
+        _lu_dog: &Option<&RwLock<LuDogStore>>,
         _package: &str,
         _module: &str,
         _obj_id: Option<&Uuid>,
@@ -1,10 +1,11 @@
 //! Svm File Generation
 //!
 //! This is where we generate code for use in the next stage of the compiler.
-use std::fmt::Write;
+use std::{fmt::Write, sync::RwLock};
 
 use rustc_hash::FxHashMap as HashMap;
 use sarzak::{
+    lu_dog::store::ObjectStore as LuDogStore,
     mc::{CompilerSnafu, FormatSnafu, Result},
     v2::domain::Domain,
     woog::store::ObjectStore as WoogStore,
@@ -71,6 +72,7 @@ impl FileGenerator for SvmGenerator {
         domain: &Domain,
         woog: &Option<&mut WoogStore>,
         imports: &Option<&HashMap<String, Domain>>,
+        lu_dog: &Option<&RwLock<LuDogStore>>,
         package: &str,
         module: &str,
         obj_id: Option<&Uuid>,
@@ -86,7 +88,7 @@ impl FileGenerator for SvmGenerator {
             format!("{}-svm-file", module),
             |buffer| {
                 self.definition.write_code(
-                    config, domain, woog, imports, package, module, obj_id, buffer,
+                    config, domain, woog, imports, lu_dog, package, module, obj_id, buffer,
                 )?;
 
                 Ok(())
@@ -117,6 +119,7 @@ impl CodeWriter for SvmModule {
         _domain: &Domain,
         woog: &Option<&mut WoogStore>,
         _imports: &Option<&HashMap<String, Domain>>,
+        _lu_dog: &Option<&RwLock<LuDogStore>>,
         _package: &str,
         module: &str,
         _obj_id: Option<&Uuid>,
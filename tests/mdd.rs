@@ -4,8 +4,8 @@ use std::process::{self, ExitCode};
 
 use env_logger;
 use grace::{
-    DomainConfig, DwarfConfig, GraceCompilerOptions, ModelCompiler, OptimizationLevel,
-    SarzakModelCompiler, Target, UberStoreOptions,
+    AsyncRuntime, DomainConfig, DwarfConfig, GraceCompilerOptions, ModelCompiler,
+    OptimizationLevel, SarzakModelCompiler, Target, UberStoreOptions, UuidMode,
 };
 use log;
 use sarzak::domain::DomainBuilder;
@@ -14,15 +14,26 @@ const VERBOSITY: u8 = 0;
 
 macro_rules! test_target_domain {
     ($name:ident, $domain:literal, $path:literal) => {
+        test_target_domain!(
+            $name,
+            $domain,
+            $path,
+            features = [],
+            DomainConfig {
+                persist: true,
+                ..Default::default()
+            }
+        );
+    };
+    ($name:ident, $domain:literal, $path:literal, features = [$($features:literal),*], $config:expr) => {
         #[test]
+        /// The domains that need something extra from the mdd crate are behind
+        /// features, so that the rest of the tests don't have to build them.
         fn $name() -> Result<ExitCode, std::io::Error> {
             let _ = env_logger::builder().is_test(true).try_init();
 
             let mut options = GraceCompilerOptions::default();
-            options.target = Target::Domain(DomainConfig {
-                persist: true,
-                ..Default::default()
-            });
+            options.target = Target::Domain($config);
             if let Some(ref mut derive) = options.derive {
                 derive.push("Clone".to_string());
                 derive.push("PartialEq".to_string());
@@ -61,8 +72,13 @@ macro_rules! test_target_domain {
                 })?;
 
             // Run cargo test
-            let mut child = process::Command::new("cargo")
-                .arg("test")
+            let features: &[&str] = &[$($features),*];
+            let mut command = process::Command::new("cargo");
+            command.arg("test");
+            if !features.is_empty() {
+                command.arg("--features").arg(features.join(","));
+            }
+            let mut child = command
                 .arg(format!("domain::{}::tests", $domain))
                 // .arg("--")
                 // .arg("--nocapture")
@@ -889,6 +905,175 @@ test_target_dwarf!(
     "one_to_one_dwarf",
     "tests/mdd/models/one_to_one.json"
 );
+test_target_domain!(
+    one_to_one_try_from,
+    "one_to_one_try_from",
+    "tests/mdd/models/one_to_one.json",
+    features = ["one_to_one_try_from"],
+    DomainConfig {
+        from_module: Some("domain::one_to_one".to_string()),
+        from_path: Some("tests/mdd/models/one_to_one.json".into()),
+        try_from: true,
+        persist: true,
+        ..Default::default()
+    }
+);
+test_target_domain!(
+    one_to_one_tokio,
+    "one_to_one_tokio",
+    "tests/mdd/models/one_to_one.json",
+    features = ["one_to_one_tokio"],
+    DomainConfig {
+        persist: true,
+        uber_store: UberStoreOptions::AsyncRwLock,
+        async_runtime: AsyncRuntime::Tokio,
+        ..Default::default()
+    }
+);
+test_target_domain!(
+    one_to_one_migrate,
+    "one_to_one_migrate",
+    "tests/mdd/models/one_to_one.json",
+    features = ["one_to_one_migrate"],
+    DomainConfig {
+        migrate_from: Some("tests/mdd/models/one_to_one.json".into()),
+        persist: true,
+        ..Default::default()
+    }
+);
+test_target_domain!(
+    one_to_one_json,
+    "one_to_one_json",
+    "tests/mdd/models/one_to_one.json",
+    features = ["one_to_one_json"],
+    DomainConfig {
+        persist: true,
+        ..Default::default()
+    }
+);
+test_target_domain!(
+    one_to_one_parallel,
+    "one_to_one_parallel",
+    "tests/mdd/models/one_to_one.json",
+    features = ["one_to_one_parallel"],
+    DomainConfig {
+        persist: true,
+        persist_parallel: true,
+        uber_store: UberStoreOptions::StdRwLock,
+        ..Default::default()
+    }
+);
+test_target_domain!(
+    one_to_one_tombstones,
+    "one_to_one_tombstones",
+    "tests/mdd/models/one_to_one.json",
+    features = ["one_to_one_tombstones"],
+    DomainConfig {
+        persist: true,
+        persist_timestamps: true,
+        ..Default::default()
+    }
+);
+test_target_domain!(
+    one_to_one_git,
+    "one_to_one_git",
+    "tests/mdd/models/one_to_one.json",
+    features = ["one_to_one_git"],
+    DomainConfig {
+        persist: true,
+        persist_git: true,
+        ..Default::default()
+    }
+);
+test_target_domain!(
+    one_to_one_indices,
+    "one_to_one_indices",
+    "tests/mdd/models/one_to_one_indices.json",
+    features = ["one_to_one_indices"],
+    DomainConfig {
+        persist: true,
+        ..Default::default()
+    }
+);
+test_target_domain!(
+    one_to_one_indices_rwlock,
+    "one_to_one_indices_rwlock",
+    "tests/mdd/models/one_to_one_indices.json",
+    features = ["one_to_one_indices_rwlock"],
+    DomainConfig {
+        persist: true,
+        uber_store: UberStoreOptions::StdRwLock,
+        ..Default::default()
+    }
+);
+test_target_domain!(
+    one_to_one_indices_vec,
+    "one_to_one_indices_vec",
+    "tests/mdd/models/one_to_one_indices.json",
+    features = ["one_to_one_indices_vec"],
+    DomainConfig {
+        persist: true,
+        optimization_level: OptimizationLevel::Vec,
+        uber_store: UberStoreOptions::StdRwLock,
+        ..Default::default()
+    }
+);
+test_target_domain!(
+    one_to_one_uuid_arguments,
+    "one_to_one_uuid_arguments",
+    "tests/mdd/models/one_to_one.json",
+    features = ["one_to_one_uuid_arguments"],
+    DomainConfig {
+        persist: true,
+        uuid_mode: UuidMode::Arguments,
+        ..Default::default()
+    }
+);
+test_target_domain!(
+    one_to_one_uuid_seeded,
+    "one_to_one_uuid_seeded",
+    "tests/mdd/models/one_to_one.json",
+    features = ["one_to_one_uuid_seeded"],
+    DomainConfig {
+        persist: true,
+        uuid_mode: UuidMode::Seeded,
+        ..Default::default()
+    }
+);
+test_target_domain!(
+    one_to_one_builders,
+    "one_to_one_builders",
+    "tests/mdd/models/one_to_one.json",
+    features = ["one_to_one_builders"],
+    DomainConfig {
+        persist: true,
+        builders: true,
+        ..Default::default()
+    }
+);
+test_target_domain!(
+    one_to_one_typed_ids,
+    "one_to_one_typed_ids",
+    "tests/mdd/models/one_to_one.json",
+    features = ["one_to_one_typed_ids"],
+    DomainConfig {
+        persist: true,
+        typed_ids: true,
+        ..Default::default()
+    }
+);
+test_target_domain!(
+    one_to_one_typed_ids_mutex,
+    "one_to_one_typed_ids_mutex",
+    "tests/mdd/models/one_to_one.json",
+    features = ["one_to_one_typed_ids_mutex"],
+    DomainConfig {
+        persist: true,
+        typed_ids: true,
+        uber_store: UberStoreOptions::StdMutex,
+        ..Default::default()
+    }
+);
 
 //
 // One to many
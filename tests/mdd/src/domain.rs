@@ -31,9 +31,39 @@ pub mod one_to_many_rwlock_vec;
 pub mod one_to_many_ts;
 pub mod one_to_many_vec;
 pub mod one_to_one;
+#[cfg(feature = "one_to_one_builders")]
+pub mod one_to_one_builders;
+#[cfg(feature = "one_to_one_git")]
+pub mod one_to_one_git;
+#[cfg(feature = "one_to_one_indices")]
+pub mod one_to_one_indices;
+#[cfg(feature = "one_to_one_indices_rwlock")]
+pub mod one_to_one_indices_rwlock;
+#[cfg(feature = "one_to_one_indices_vec")]
+pub mod one_to_one_indices_vec;
+#[cfg(feature = "one_to_one_json")]
+pub mod one_to_one_json;
+#[cfg(feature = "one_to_one_migrate")]
+pub mod one_to_one_migrate;
+#[cfg(feature = "one_to_one_parallel")]
+pub mod one_to_one_parallel;
 pub mod one_to_one_rwlock;
 pub mod one_to_one_rwlock_vec;
+#[cfg(feature = "one_to_one_tokio")]
+pub mod one_to_one_tokio;
+#[cfg(feature = "one_to_one_tombstones")]
+pub mod one_to_one_tombstones;
+#[cfg(feature = "one_to_one_try_from")]
+pub mod one_to_one_try_from;
 pub mod one_to_one_ts;
+#[cfg(feature = "one_to_one_typed_ids")]
+pub mod one_to_one_typed_ids;
+#[cfg(feature = "one_to_one_typed_ids_mutex")]
+pub mod one_to_one_typed_ids_mutex;
+#[cfg(feature = "one_to_one_uuid_arguments")]
+pub mod one_to_one_uuid_arguments;
+#[cfg(feature = "one_to_one_uuid_seeded")]
+pub mod one_to_one_uuid_seeded;
 pub mod one_to_one_vec;
 pub mod sarzak;
 pub mod sarzak_rwlock;
@@ -42,4 +42,34 @@ mod tests {
         let st = SimpleSupertype::new_simple_subtype_a(true, &z, &mut isa_store);
         let _ao = AnotherObject::new(&st, &obj, &mut store);
     }
+
+    #[test]
+    fn test_ext() {
+        let mut store = ObjectStore::new();
+        let mut sarzak_store = SarzakStore::new();
+        let mut isa_store = IsaStore::new();
+
+        let obj = Object::new(
+            "Just a simple object".to_owned(),
+            "SO".to_owned(),
+            "Simple Object".to_owned(),
+            &mut sarzak_store,
+        );
+        let lonely = Object::new(
+            "Nothing points at me".to_owned(),
+            "LO".to_owned(),
+            "Lonely Object".to_owned(),
+            &mut sarzak_store,
+        );
+
+        let oh_boy = OhBoy::new("The Wall Street Journal".to_owned(), &mut isa_store);
+        let z = SimpleSubtypeA::new_oh_boy(&oh_boy, &mut isa_store);
+        let st = SimpleSupertype::new_simple_subtype_a(true, &z, &mut isa_store);
+        let ao = AnotherObject::new(&st, &obj, &mut store);
+
+        // These come from the extension traits on the imported objects.
+        assert_eq!(&ao, obj.r1c_another_object(&store)[0]);
+        assert_eq!(&ao, st.r2c_another_object(&store)[0]);
+        assert!(lonely.r1c_another_object(&store).is_empty());
+    }
 }
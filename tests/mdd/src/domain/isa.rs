@@ -68,6 +68,34 @@ mod tests {
         assert_eq!(&baz, b.r4_baz(&store)[0]);
     }
 
+    #[test]
+    fn test_persist_hybrid() -> std::io::Result<()> {
+        let path = std::env::temp_dir().join(format!("isa-{}", Uuid::new_v4()));
+        let mut store = ObjectStore::new();
+
+        // SimpleSupertype is a hybrid with an attribute of it's own.
+        let oh_boy = OhBoy::new("The Wall Street Journal".to_owned(), &mut store);
+        let z = SimpleSubtypeA::new_oh_boy(&oh_boy, &mut store);
+        let a = SimpleSupertype::new_simple_subtype_a(true, &z, &mut store);
+        let b = SimpleSupertype::new_simple_subtype_b(false, &mut store);
+        let baz = Baz::new(1.0, &b, &mut store);
+
+        store.persist(&path)?;
+        let store = ObjectStore::load(&path)?;
+        std::fs::remove_dir_all(&path)?;
+
+        let select_a = store.exhume_simple_supertype(&a.id).unwrap();
+        let select_b = store.exhume_simple_supertype(&b.id).unwrap();
+        assert_eq!(&a, select_a);
+        assert_eq!(&b, select_b);
+        assert!(select_a.state);
+        assert!(!select_b.state);
+
+        assert_eq!(select_b, baz.r4_simple_supertype(&store)[0]);
+
+        Ok(())
+    }
+
     #[test]
     fn test_init() {
         let store = ObjectStore::new();
@@ -99,4 +99,39 @@ mod tests {
 
         assert!(p_2.r8_parameter(&store).len() == 0);
     }
+
+    #[test]
+    fn test_bincode() -> std::io::Result<()> {
+        let path = std::env::temp_dir().join(format!("one_to_one-{}.bin", Uuid::new_v4()));
+        let mut store = ObjectStore::new();
+
+        let fred = Referent::new("fred".to_owned(), &mut store);
+        let a = A::new(42, &fred, &mut store);
+
+        store.persist_bincode(&path)?;
+        let store = ObjectStore::load_bincode(&path)?;
+        std::fs::remove_file(&path)?;
+
+        assert_eq!(Some(&fred), store.exhume_referent(&fred.id));
+        assert_eq!(Some(&a), store.exhume_a(&a.id));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_bincode_headerless() -> std::io::Result<()> {
+        let mut store = ObjectStore::new();
+
+        let fred = Referent::new("fred".to_owned(), &mut store);
+        let a = A::new(42, &fred, &mut store);
+
+        // This is what persist_bincode wrote before there was a header.
+        let code = bincode::serialize(&store).unwrap();
+        let store = ObjectStore::from_bincode(&code)?;
+
+        assert_eq!(Some(&fred), store.exhume_referent(&fred.id));
+        assert_eq!(Some(&a), store.exhume_a(&a.id));
+
+        Ok(())
+    }
 }
@@ -0,0 +1,75 @@
+//! One to One Domain
+//!
+//! This file will eventually be generated.
+//!
+//! The purpose of this domain is to help me develop and test domain code generation.
+//! This one has builders.
+use uuid::{uuid, Uuid};
+
+pub mod store;
+pub mod types;
+
+pub use store::ObjectStore;
+pub use types::*;
+
+// one_to_one
+pub const UUID_NS: Uuid = uuid!("2b22547d-74d3-5dd4-94a9-122a2ac9341f");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build() {
+        let mut store = ObjectStore::new();
+
+        let fred = Referent::builder()
+            .name("fred".to_owned())
+            .build(&mut store)
+            .unwrap();
+        // The order of the setters doesn't matter.
+        let a = A::builder()
+            .ptr(&fred)
+            .number(42)
+            .build(&mut store)
+            .unwrap();
+
+        assert_eq!(Some(&fred), store.exhume_referent(&fred.id));
+        assert_eq!(Some(&a), store.exhume_a(&a.id));
+        assert_eq!(42, a.number);
+        assert_eq!(&fred, a.r1_referent(&store)[0]);
+    }
+
+    #[test]
+    fn test_optional() {
+        let mut store = ObjectStore::new();
+
+        let fred = Referent::new("fred".to_owned(), &mut store);
+
+        // Conditional referentials may be left unset.
+        let c = C::builder().like_water(1.162).build(&mut store).unwrap();
+        assert_eq!(None, c.ptr);
+
+        let c = C::builder()
+            .like_water(1.162)
+            .ptr(&fred)
+            .build(&mut store)
+            .unwrap();
+        assert_eq!(&fred, c.r3_referent(&store)[0]);
+    }
+
+    #[test]
+    fn test_missing_field() {
+        let mut store = ObjectStore::new();
+
+        let result = A::builder().number(42).build(&mut store);
+        assert_eq!(Err("ABuilder: `ptr` is required".to_owned()), result);
+
+        let result = B::builder().build(&mut store);
+        assert!(result.is_err());
+
+        // Nothing was interred.
+        assert_eq!(0, store.iter_a().count());
+        assert_eq!(0, store.iter_b().count());
+    }
+}
@@ -0,0 +1,67 @@
+//! One to One Domain
+//!
+//! This file will eventually be generated.
+//!
+//! The purpose of this domain is to help me develop and test domain code generation.
+//! This one persists to git.
+use uuid::{uuid, Uuid};
+
+pub mod store;
+pub mod types;
+
+pub use store::ObjectStore;
+pub use types::*;
+
+// one_to_one
+pub const UUID_NS: Uuid = uuid!("2b22547d-74d3-5dd4-94a9-122a2ac9341f");
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, io, path::Path, process::Command};
+
+    use super::*;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .current_dir(dir)
+            .args(args)
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {} failed", args.join(" "));
+    }
+
+    #[test]
+    fn test_history() -> io::Result<()> {
+        let path = std::env::temp_dir().join(format!("one_to_one_git-{}", Uuid::new_v4()));
+        fs::create_dir_all(&path)?;
+        git(&path, &["init", "--quiet"]);
+        git(&path, &["config", "user.name", "mdd"]);
+        git(&path, &["config", "user.email", "mdd@example.com"]);
+        git(
+            &path,
+            &["commit", "--quiet", "--allow-empty", "--message", "init"],
+        );
+
+        let mut store = ObjectStore::new();
+
+        let fred = Referent::new("fred".to_owned(), &mut store);
+        store.persist_git(&path, Some("fred"))?;
+
+        let mut frederick = fred.clone();
+        frederick.name = "frederick".to_owned();
+        store.inter_referent(frederick.clone());
+        store.persist_git(&path, Some("frederick"))?;
+
+        // Nothing changed, so there's nothing to commit.
+        store.persist_git(&path, Some("nothing"))?;
+
+        let history = ObjectStore::history_of_referent(&path, fred.id)?;
+        fs::remove_dir_all(&path)?;
+
+        assert_eq!(2, history.len());
+        assert_eq!(frederick, history[0].1);
+        assert_eq!(fred, history[1].1);
+
+        Ok(())
+    }
+}
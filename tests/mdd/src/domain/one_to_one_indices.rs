@@ -0,0 +1,162 @@
+//! One to One Domain
+//!
+//! This file will eventually be generated.
+//!
+//! The purpose of this domain is to help me develop and test domain code generation.
+//! This one is built with indices on `Referent.name`, and `A.number`.
+use uuid::{uuid, Uuid};
+
+pub mod store;
+pub mod types;
+
+pub use store::ObjectStore;
+pub use types::*;
+
+// one_to_one
+pub const UUID_NS: Uuid = uuid!("2b22547d-74d3-5dd4-94a9-122a2ac9341f");
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, io};
+
+    use super::store::IndexConflict;
+    use super::*;
+
+    #[test]
+    fn test_unique_index() {
+        let mut store = ObjectStore::new();
+
+        let fred = Referent::new("fred".to_owned(), &mut store);
+        let _gene = Referent::new("gene".to_owned(), &mut store);
+
+        assert_eq!(Some(&fred), store.exhume_referent_by_name("fred"));
+        assert_eq!(None, store.exhume_referent_by_name("bob"));
+
+        store.exorcise_referent(&fred.id);
+        assert_eq!(None, store.exhume_referent_by_name("fred"));
+    }
+
+    #[test]
+    fn test_index_conflict() {
+        let mut store = ObjectStore::new();
+
+        let fred = Referent::new("fred".to_owned(), &mut store);
+        let impostor = Referent::new("fred".to_owned(), &mut store);
+
+        assert_eq!(
+            &[IndexConflict {
+                index: "referent_by_name",
+                existing: fred.id,
+                duplicate: impostor.id,
+            }],
+            store.index_conflicts()
+        );
+        assert_eq!(Some(&fred), store.exhume_referent_by_name("fred"));
+        assert_eq!(Some(&impostor), store.exhume_referent(&impostor.id));
+    }
+
+    #[test]
+    fn test_promote_conflict() {
+        let mut store = ObjectStore::new();
+
+        let fred = Referent::new("fred".to_owned(), &mut store);
+        let impostor = Referent::new("fred".to_owned(), &mut store);
+        let another = Referent::new("fred".to_owned(), &mut store);
+
+        // The first one left out takes fred's place, and the other one is
+        // now in conflict with it.
+        store.exorcise_referent(&fred.id);
+        assert_eq!(Some(&impostor), store.exhume_referent_by_name("fred"));
+        assert_eq!(
+            &[IndexConflict {
+                index: "referent_by_name",
+                existing: impostor.id,
+                duplicate: another.id,
+            }],
+            store.index_conflicts()
+        );
+
+        // Exorcising a duplicate forgets its conflict.
+        store.exorcise_referent(&another.id);
+        assert!(store.index_conflicts().is_empty());
+        assert_eq!(Some(&impostor), store.exhume_referent_by_name("fred"));
+
+        store.exorcise_referent(&impostor.id);
+        assert_eq!(None, store.exhume_referent_by_name("fred"));
+    }
+
+    #[test]
+    fn test_inter_again() {
+        let mut store = ObjectStore::new();
+
+        let fred = Referent::new("fred".to_owned(), &mut store);
+        let impostor = Referent::new("fred".to_owned(), &mut store);
+
+        // Interring it unchanged doesn't cost it its place.
+        store.inter_referent(fred.clone());
+        assert_eq!(Some(&fred), store.exhume_referent_by_name("fred"));
+        assert_eq!(1, store.index_conflicts().len());
+
+        // Renaming it moves it, and the impostor is promoted.
+        let freddie = Referent {
+            name: "freddie".to_owned(),
+            ..fred.clone()
+        };
+        store.inter_referent(freddie.clone());
+        assert_eq!(Some(&freddie), store.exhume_referent_by_name("freddie"));
+        assert_eq!(Some(&impostor), store.exhume_referent_by_name("fred"));
+        assert!(store.index_conflicts().is_empty());
+
+        // Renaming the duplicate, when there is one, resolves the conflict.
+        let gene = Referent::new("fred".to_owned(), &mut store);
+        assert_eq!(1, store.index_conflicts().len());
+        let gene = Referent {
+            name: "gene".to_owned(),
+            ..gene
+        };
+        store.inter_referent(gene.clone());
+        assert_eq!(Some(&gene), store.exhume_referent_by_name("gene"));
+        assert!(store.index_conflicts().is_empty());
+    }
+
+    #[test]
+    fn test_index() {
+        let mut store = ObjectStore::new();
+
+        let tgt = Referent::new("hugh".to_owned(), &mut store);
+        let a_0 = A::new(42, &tgt, &mut store);
+        let a_1 = A::new(42, &tgt, &mut store);
+        let a_2 = A::new(7, &tgt, &mut store);
+
+        let mut a = store.iter_a_by_number(42).map(|a| a.id).collect::<Vec<_>>();
+        a.sort();
+        let mut expected = vec![a_0.id, a_1.id];
+        expected.sort();
+        assert_eq!(expected, a);
+
+        store.exorcise_a(&a_2.id);
+        assert_eq!(0, store.iter_a_by_number(7).count());
+
+        store.inter_a(A { number: 7, ..a_0 });
+        assert_eq!(vec![&a_1], store.iter_a_by_number(42).collect::<Vec<_>>());
+        assert_eq!(1, store.iter_a_by_number(7).count());
+    }
+
+    #[test]
+    fn test_reindex_on_load() -> io::Result<()> {
+        let path = std::env::temp_dir().join(format!("one_to_one_indices-{}", Uuid::new_v4()));
+        let mut store = ObjectStore::new();
+
+        let fred = Referent::new("fred".to_owned(), &mut store);
+        let a = A::new(42, &fred, &mut store);
+
+        store.persist(&path)?;
+        let store = ObjectStore::load(&path)?;
+        fs::remove_dir_all(&path)?;
+
+        assert_eq!(Some(&fred), store.exhume_referent_by_name("fred"));
+        assert_eq!(vec![&a], store.iter_a_by_number(42).collect::<Vec<_>>());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,87 @@
+//! One to One Domain
+//!
+//! This file will eventually be generated.
+//!
+//! The purpose of this domain is to help me develop and test domain code generation.
+//! This one is built with indices on `Referent.name`, and `A.number`, in an RwLock uber store.
+use uuid::{uuid, Uuid};
+
+pub mod store;
+pub mod types;
+
+pub use store::ObjectStore;
+pub use types::*;
+
+// one_to_one
+pub const UUID_NS: Uuid = uuid!("2b22547d-74d3-5dd4-94a9-122a2ac9341f");
+
+#[cfg(test)]
+mod tests {
+    use super::store::IndexConflict;
+    use super::*;
+
+    #[test]
+    fn test_inter_changed_in_place() {
+        let mut store = ObjectStore::new();
+
+        let fred = Referent::new("fred".to_owned(), &mut store);
+        let impostor = Referent::new("fred".to_owned(), &mut store);
+
+        // It's renamed in place, and interred again. That's the very same
+        // instance, so the index has to find where it was by id.
+        fred.write().unwrap().name = "freddie".to_owned();
+        store.inter_referent(fred.clone());
+
+        let found = store.exhume_referent_by_name("freddie").unwrap();
+        assert_eq!(&*fred.read().unwrap(), &*found.read().unwrap());
+        let found = store.exhume_referent_by_name("fred").unwrap();
+        assert_eq!(&*impostor.read().unwrap(), &*found.read().unwrap());
+        assert!(store.index_conflicts().is_empty());
+    }
+
+    #[test]
+    fn test_inter_again() {
+        let mut store = ObjectStore::new();
+
+        let fred = Referent::new("fred".to_owned(), &mut store);
+        let impostor = Referent::new("fred".to_owned(), &mut store);
+        let conflict = IndexConflict {
+            index: "referent_by_name",
+            existing: fred.read().unwrap().id,
+            duplicate: impostor.read().unwrap().id,
+        };
+
+        // Interring either of them unchanged leaves things as they were.
+        store.inter_referent(fred.clone());
+        store.inter_referent(impostor.clone());
+
+        let found = store.exhume_referent_by_name("fred").unwrap();
+        assert_eq!(&*fred.read().unwrap(), &*found.read().unwrap());
+        assert_eq!(&[conflict], store.index_conflicts());
+    }
+
+    #[test]
+    fn test_exorcise_changed() {
+        let mut store = ObjectStore::new();
+
+        let tgt = Referent::new("hugh".to_owned(), &mut store);
+        let a = A::new(42, &tgt, &mut store);
+        a.write().unwrap().number = 7;
+
+        let id = a.read().unwrap().id;
+        store.exorcise_a(&id);
+        assert_eq!(0, store.iter_a_by_number(42).count());
+        assert_eq!(0, store.iter_a_by_number(7).count());
+
+        // Nothing is left under the old key, for it to collide with.
+        let fred = Referent::new("fred".to_owned(), &mut store);
+        fred.write().unwrap().name = "freddie".to_owned();
+        let id = fred.read().unwrap().id;
+        store.exorcise_referent(&id);
+
+        let gene = Referent::new("fred".to_owned(), &mut store);
+        let found = store.exhume_referent_by_name("fred").unwrap();
+        assert_eq!(&*gene.read().unwrap(), &*found.read().unwrap());
+        assert!(store.index_conflicts().is_empty());
+    }
+}
@@ -0,0 +1,134 @@
+//! One to One Domain
+//!
+//! This file will eventually be generated.
+//!
+//! The purpose of this domain is to help me develop and test domain code generation.
+//! This one is built with indices on `Referent.name`, and `A.number`, in the Vec store.
+use uuid::{uuid, Uuid};
+
+pub mod store;
+pub mod types;
+
+pub use store::ObjectStore;
+pub use types::*;
+
+// one_to_one
+pub const UUID_NS: Uuid = uuid!("2b22547d-74d3-5dd4-94a9-122a2ac9341f");
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, io};
+
+    use super::store::IndexConflict;
+    use super::*;
+
+    #[test]
+    fn test_unique_index() {
+        let mut store = ObjectStore::new();
+
+        let fred = Referent::new("fred".to_owned(), &mut store);
+        let _gene = Referent::new("gene".to_owned(), &mut store);
+
+        let found = store.exhume_referent_by_name("fred").unwrap();
+        assert_eq!(&*fred.read().unwrap(), &*found.read().unwrap());
+        assert!(store.exhume_referent_by_name("bob").is_none());
+
+        let id = fred.read().unwrap().id;
+        store.exorcise_referent(&id);
+        assert!(store.exhume_referent_by_name("fred").is_none());
+    }
+
+    #[test]
+    fn test_index_conflict() {
+        let mut store = ObjectStore::new();
+
+        let fred = Referent::new("fred".to_owned(), &mut store);
+        let impostor = Referent::new("fred".to_owned(), &mut store);
+
+        assert_eq!(
+            &[IndexConflict {
+                index: "referent_by_name",
+                existing: fred.read().unwrap().id,
+                duplicate: impostor.read().unwrap().id,
+            }],
+            store.index_conflicts()
+        );
+        let found = store.exhume_referent_by_name("fred").unwrap();
+        assert_eq!(&*fred.read().unwrap(), &*found.read().unwrap());
+    }
+
+    #[test]
+    fn test_promote_conflict() {
+        let mut store = ObjectStore::new();
+
+        let fred = Referent::new("fred".to_owned(), &mut store);
+        let impostor = Referent::new("fred".to_owned(), &mut store);
+
+        let id = fred.read().unwrap().id;
+        store.exorcise_referent(&id);
+        let found = store.exhume_referent_by_name("fred").unwrap();
+        assert_eq!(&*impostor.read().unwrap(), &*found.read().unwrap());
+        assert!(store.index_conflicts().is_empty());
+    }
+
+    #[test]
+    fn test_exorcise_changed() {
+        let mut store = ObjectStore::new();
+
+        // It's renamed in place, behind the index's back.
+        let fred = Referent::new("fred".to_owned(), &mut store);
+        fred.write().unwrap().name = "freddie".to_owned();
+
+        let id = fred.read().unwrap().id;
+        store.exorcise_referent(&id);
+
+        // Nothing is left behind under the old name. The slot is recycled
+        // first, so that a stale key can't point at the new fred by accident.
+        let _spacer = Referent::new("spacer".to_owned(), &mut store);
+        let gene = Referent::new("fred".to_owned(), &mut store);
+        let found = store.exhume_referent_by_name("fred").unwrap();
+        assert_eq!(&*gene.read().unwrap(), &*found.read().unwrap());
+        assert!(store.index_conflicts().is_empty());
+        assert!(store.exhume_referent_by_name("freddie").is_none());
+    }
+
+    #[test]
+    fn test_index() {
+        let mut store = ObjectStore::new();
+
+        let tgt = Referent::new("hugh".to_owned(), &mut store);
+        let a_0 = A::new(42, &tgt, &mut store);
+        let a_1 = A::new(42, &tgt, &mut store);
+        let a_2 = A::new(7, &tgt, &mut store);
+
+        let mut a = store
+            .iter_a_by_number(42)
+            .map(|a| a.read().unwrap().id)
+            .collect::<Vec<_>>();
+        a.sort();
+        let mut expected = vec![a_0.read().unwrap().id, a_1.read().unwrap().id];
+        expected.sort();
+        assert_eq!(expected, a);
+
+        let id = a_2.read().unwrap().id;
+        store.exorcise_a(&id);
+        assert_eq!(0, store.iter_a_by_number(7).count());
+    }
+
+    #[test]
+    fn test_reindex_on_load() -> io::Result<()> {
+        let path = std::env::temp_dir().join(format!("one_to_one_indices_vec-{}", Uuid::new_v4()));
+        let mut store = ObjectStore::new();
+
+        let fred = Referent::new("fred".to_owned(), &mut store);
+
+        store.persist(&path)?;
+        let store = ObjectStore::load(&path)?;
+        fs::remove_dir_all(&path)?;
+
+        let found = store.exhume_referent_by_name("fred").unwrap();
+        assert_eq!(&*fred.read().unwrap(), &*found.read().unwrap());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,171 @@
+//! One to One Domain
+//!
+//! This file will eventually be generated.
+//!
+//! The purpose of this domain is to help me develop and test domain code generation.
+//! This one is persisted as a single JSON file, and as JSON Lines.
+use uuid::{uuid, Uuid};
+
+pub mod store;
+pub mod types;
+
+pub use store::ObjectStore;
+pub use types::*;
+
+// one_to_one
+pub const UUID_NS: Uuid = uuid!("2b22547d-74d3-5dd4-94a9-122a2ac9341f");
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, io, io::Write};
+
+    use super::*;
+    use store::StoreRecord;
+
+    #[test]
+    fn test_round_trip() -> io::Result<()> {
+        let path = std::env::temp_dir().join(format!("one_to_one_json-{}.json", Uuid::new_v4()));
+        let mut store = ObjectStore::new();
+
+        let tgt_0 = Referent::new("fred".to_owned(), &mut store);
+        let tgt_1 = Referent::new("gene".to_owned(), &mut store);
+        let a = A::new(42, &tgt_0, &mut store);
+        let b = B::new(true, &tgt_1, &mut store);
+        let c = C::new(1.162, None, &mut store);
+
+        store.persist_json_file(&path)?;
+        let store = ObjectStore::load_json_file(&path)?;
+        fs::remove_file(&path)?;
+
+        assert_eq!(Some(&tgt_0), store.exhume_referent(&tgt_0.id));
+        assert_eq!(Some(&tgt_1), store.exhume_referent(&tgt_1.id));
+        assert_eq!(Some(&a), store.exhume_a(&a.id));
+        assert_eq!(Some(&b), store.exhume_b(&b.id));
+        assert_eq!(Some(&c), store.exhume_c(&c.id));
+
+        assert_eq!(&tgt_0, a.r1_referent(&store)[0]);
+        assert_eq!(&tgt_1, b.r2_referent(&store)[0]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_deterministic() -> io::Result<()> {
+        let path = std::env::temp_dir().join(format!("one_to_one_json-{}.json", Uuid::new_v4()));
+        let mut store = ObjectStore::new();
+
+        let referents: Vec<Referent> = (0..16)
+            .map(|n| Referent::new(format!("referent {n}"), &mut store))
+            .collect();
+        for (n, referent) in referents.iter().enumerate() {
+            A::new(n as i64, referent, &mut store);
+        }
+
+        store.persist_json_file(&path)?;
+        let first = fs::read(&path)?;
+        store.persist_json_file(&path)?;
+        assert_eq!(first, fs::read(&path)?);
+
+        // A store put together in another order is written the same.
+        let mut reversed = ObjectStore::new();
+        for referent in referents.iter().rev() {
+            reversed.inter_referent(referent.clone());
+        }
+        let mut a: Vec<A> = store.iter_a().cloned().collect();
+        a.reverse();
+        for a in a {
+            reversed.inter_a(a);
+        }
+        reversed.persist_json_file(&path)?;
+        assert_eq!(first, fs::read(&path)?);
+
+        // And so is a store that's been loaded.
+        let loaded = ObjectStore::load_json_file(&path)?;
+        loaded.persist_json_file(&path)?;
+        let text = fs::read_to_string(&path)?;
+        fs::remove_file(&path)?;
+        assert_eq!(first, text.as_bytes());
+
+        // The instances are sorted by id.
+        let mut ids: Vec<String> = referents.iter().map(|r| r.id.to_string()).collect();
+        ids.sort();
+        let positions: Vec<usize> = ids
+            .iter()
+            .map(|id| text.find(&format!("\"{id}\": {{")).unwrap())
+            .collect();
+        assert!(positions.windows(2).all(|w| w[0] < w[1]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jsonl_round_trip() -> io::Result<()> {
+        let path = std::env::temp_dir().join(format!("one_to_one_json-{}.jsonl", Uuid::new_v4()));
+        let mut store = ObjectStore::new();
+
+        let tgt_0 = Referent::new("fred".to_owned(), &mut store);
+        let tgt_1 = Referent::new("gene".to_owned(), &mut store);
+        let a = A::new(42, &tgt_0, &mut store);
+        let b = B::new(true, &tgt_1, &mut store);
+        let c = C::new(1.162, None, &mut store);
+
+        store.persist_jsonl(&path)?;
+        let store = ObjectStore::load_jsonl(&path)?;
+        fs::remove_file(&path)?;
+
+        assert_eq!(Some(&tgt_0), store.exhume_referent(&tgt_0.id));
+        assert_eq!(Some(&tgt_1), store.exhume_referent(&tgt_1.id));
+        assert_eq!(Some(&a), store.exhume_a(&a.id));
+        assert_eq!(Some(&b), store.exhume_b(&b.id));
+        assert_eq!(Some(&c), store.exhume_c(&c.id));
+
+        assert_eq!(&tgt_0, a.r1_referent(&store)[0]);
+        assert_eq!(&tgt_1, b.r2_referent(&store)[0]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jsonl_append() -> io::Result<()> {
+        let path = std::env::temp_dir().join(format!("one_to_one_json-{}.jsonl", Uuid::new_v4()));
+        let mut store = ObjectStore::new();
+
+        let tgt_0 = Referent::new("fred".to_owned(), &mut store);
+        let tgt_1 = Referent::new("gene".to_owned(), &mut store);
+        let a = A::new(42, &tgt_0, &mut store);
+
+        store.persist_jsonl(&path)?;
+
+        // Append a change to fred, and a new referent, with some blank lines
+        // mixed in.
+        let freddie = Referent {
+            name: "freddie".to_owned(),
+            ..tgt_0.clone()
+        };
+        let mut other = ObjectStore::new();
+        let tgt_2 = Referent::new("harry".to_owned(), &mut other);
+        {
+            let mut file = fs::OpenOptions::new().append(true).open(&path)?;
+            writeln!(file)?;
+            for referent in [&freddie, &tgt_2] {
+                let record = StoreRecord {
+                    ty: "Referent".to_owned(),
+                    instance: referent,
+                };
+                writeln!(file, "{}", serde_json::to_string(&record)?)?;
+                writeln!(file, "  ")?;
+            }
+        }
+
+        let store = ObjectStore::load_jsonl(&path)?;
+        fs::remove_file(&path)?;
+
+        assert_eq!(Some(&freddie), store.exhume_referent(&tgt_0.id));
+        assert_eq!(Some(&tgt_1), store.exhume_referent(&tgt_1.id));
+        assert_eq!(Some(&tgt_2), store.exhume_referent(&tgt_2.id));
+        assert_eq!(3, store.iter_referent().count());
+        assert_eq!(&freddie, a.r1_referent(&store)[0]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,44 @@
+//! One to One Domain
+//!
+//! This file will eventually be generated.
+//!
+//! The purpose of this domain is to help me develop and test domain code generation.
+//! This one migrates stores persisted by the plain `one_to_one` domain.
+use uuid::{uuid, Uuid};
+
+pub mod migrate;
+pub mod store;
+pub mod types;
+
+pub use store::ObjectStore;
+pub use types::*;
+
+// one_to_one
+pub const UUID_NS: Uuid = uuid!("2b22547d-74d3-5dd4-94a9-122a2ac9341f");
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, io};
+
+    use super::*;
+
+    #[test]
+    fn test_migrate() -> io::Result<()> {
+        let path = std::env::temp_dir().join(format!("one_to_one_migrate-{}", Uuid::new_v4()));
+        let (from, to) = (path.join("from"), path.join("to"));
+        let mut store = ObjectStore::new();
+
+        let fred = Referent::new("fred".to_owned(), &mut store);
+        let a = A::new(42, &fred, &mut store);
+
+        store.persist(&from)?;
+        migrate::migrate(&from, &to)?;
+        let store = ObjectStore::load(&to)?;
+        fs::remove_dir_all(&path)?;
+
+        assert_eq!(Some(&fred), store.exhume_referent(&fred.id));
+        assert_eq!(Some(&a), store.exhume_a(&a.id));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,59 @@
+//! One to One Domain
+//!
+//! This file will eventually be generated.
+//!
+//! The purpose of this domain is to help me develop and test domain code generation.
+//! This one persists in parallel.
+use uuid::{uuid, Uuid};
+
+pub mod store;
+pub mod types;
+
+pub use store::ObjectStore;
+pub use types::*;
+
+// one_to_one
+pub const UUID_NS: Uuid = uuid!("2b22547d-74d3-5dd4-94a9-122a2ac9341f");
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, io};
+
+    use super::*;
+
+    #[test]
+    fn test_persist() -> io::Result<()> {
+        let path = std::env::temp_dir().join(format!("one_to_one_parallel-{}", Uuid::new_v4()));
+        let mut store = ObjectStore::new();
+
+        let tgt = Referent::new("fred".to_owned(), &mut store);
+        let a = A::new(42, &tgt, &mut store);
+        let b = B::new(true, &tgt, &mut store);
+
+        store.persist(&path)?;
+        let loaded = ObjectStore::load(&path)?;
+
+        let mut count = 0;
+        ObjectStore::load_streaming(&path, |_| {
+            count += 1;
+            Ok(())
+        })?;
+        fs::remove_dir_all(&path)?;
+
+        assert_eq!(3, count);
+
+        let id = tgt.read().unwrap().id;
+        let select_tgt = loaded.exhume_referent(&id).unwrap();
+        assert_eq!(&*tgt.read().unwrap(), &*select_tgt.read().unwrap());
+
+        let id = a.read().unwrap().id;
+        let select_a = loaded.exhume_a(&id).unwrap();
+        assert_eq!(&*a.read().unwrap(), &*select_a.read().unwrap());
+
+        let id = b.read().unwrap().id;
+        let select_b = loaded.exhume_b(&id).unwrap();
+        assert_eq!(&*b.read().unwrap(), &*select_b.read().unwrap());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,96 @@
+//! One to One Domain
+//!
+//! This file will eventually be generated.
+//!
+//! The purpose of this domain is to help me develop and test domain code generation.
+//! This one is the async store, on tokio.
+use uuid::{uuid, Uuid};
+
+pub mod store;
+pub mod types;
+
+pub use store::ObjectStore;
+pub use types::*;
+
+// one_to_one
+pub const UUID_NS: Uuid = uuid!("2b22547d-74d3-5dd4-94a9-122a2ac9341f");
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, io};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_persist() -> io::Result<()> {
+        let path = std::env::temp_dir().join(format!("one_to_one_tokio-{}", Uuid::new_v4()));
+        let mut store = ObjectStore::new();
+
+        let fred = Referent::new("fred".to_owned(), &mut store).await;
+        let id = fred.read().await.id;
+
+        store.persist(&path).await?;
+        let loaded = ObjectStore::load(&path).await?;
+        fs::remove_dir_all(&path)?;
+
+        let select_fred = loaded.exhume_referent(&id).await.unwrap();
+        assert_eq!(*fred.read().await, *select_fred.read().await);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_jsonl() -> io::Result<()> {
+        let path = std::env::temp_dir().join(format!("one_to_one_tokio-{}.jsonl", Uuid::new_v4()));
+        let mut store = ObjectStore::new();
+
+        let fred = Referent::new("fred".to_owned(), &mut store).await;
+        let id = fred.read().await.id;
+
+        store.persist_jsonl(&path).await?;
+        let loaded = ObjectStore::load_jsonl(&path).await?;
+        fs::remove_file(&path)?;
+
+        let select_fred = loaded.exhume_referent(&id).await.unwrap();
+        assert_eq!(*fred.read().await, *select_fred.read().await);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_json_file() -> io::Result<()> {
+        let path = std::env::temp_dir().join(format!("one_to_one_tokio-{}.json", Uuid::new_v4()));
+        let mut store = ObjectStore::new();
+
+        let fred = Referent::new("fred".to_owned(), &mut store).await;
+        let id = fred.read().await.id;
+
+        store.persist_json_file(&path).await?;
+        let loaded = ObjectStore::load_json_file(&path).await?;
+        fs::remove_file(&path)?;
+
+        let select_fred = loaded.exhume_referent(&id).await.unwrap();
+        assert_eq!(*fred.read().await, *select_fred.read().await);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_bincode() -> io::Result<()> {
+        let path = std::env::temp_dir().join(format!("one_to_one_tokio-{}.bin", Uuid::new_v4()));
+        let mut store = ObjectStore::new();
+
+        let fred = Referent::new("fred".to_owned(), &mut store).await;
+        let a = A::new(42, &fred, &mut store).await;
+        let id = a.read().await.id;
+
+        store.persist_bincode(&path).await?;
+        let loaded = ObjectStore::load_bincode(&path).await?;
+        fs::remove_file(&path)?;
+
+        let select_a = loaded.exhume_a(&id).await.unwrap();
+        assert_eq!(*a.read().await, *select_a.read().await);
+
+        Ok(())
+    }
+}
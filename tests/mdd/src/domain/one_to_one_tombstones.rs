@@ -0,0 +1,59 @@
+//! One to One Domain
+//!
+//! This file will eventually be generated.
+//!
+//! The purpose of this domain is to help me develop and test domain code generation.
+//! This one has timestamps, and therefore tombstones.
+use uuid::{uuid, Uuid};
+
+pub mod store;
+pub mod types;
+
+pub use store::ObjectStore;
+pub use types::*;
+
+// one_to_one
+pub const UUID_NS: Uuid = uuid!("2b22547d-74d3-5dd4-94a9-122a2ac9341f");
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs, io, thread,
+        time::{Duration, SystemTime},
+    };
+
+    use super::store::ObjectKind;
+    use super::*;
+
+    #[test]
+    fn test_tombstones() -> io::Result<()> {
+        let path = std::env::temp_dir().join(format!("one_to_one_tombstones-{}", Uuid::new_v4()));
+        let mut store = ObjectStore::new();
+
+        let fred = Referent::new("fred".to_owned(), &mut store);
+        let gene = Referent::new("gene".to_owned(), &mut store);
+
+        thread::sleep(Duration::from_millis(10));
+        let t = SystemTime::now();
+        thread::sleep(Duration::from_millis(10));
+
+        store.exorcise_referent(&gene.id);
+        assert_eq!(
+            vec![(ObjectKind::Referent, gene.id)],
+            store.changes_since(t)
+        );
+
+        store.persist_jsonl(&path)?;
+        let store = ObjectStore::load_jsonl(&path)?;
+        fs::remove_file(&path)?;
+
+        assert_eq!(
+            vec![(ObjectKind::Referent, gene.id)],
+            store.changes_since(t)
+        );
+        assert_eq!(Some(&fred), store.exhume_referent(&fred.id));
+        assert_eq!(None, store.exhume_referent(&gene.id));
+
+        Ok(())
+    }
+}
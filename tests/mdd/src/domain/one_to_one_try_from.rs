@@ -0,0 +1,40 @@
+//! One to One Domain
+//!
+//! This file will eventually be generated.
+//!
+//! The purpose of this domain is to help me develop and test domain code generation.
+//! This one is extruded from `one_to_one`, using `TryFrom`.
+use uuid::{uuid, Uuid};
+
+pub mod from;
+pub mod store;
+pub mod types;
+
+pub use store::ObjectStore;
+pub use types::*;
+
+// one_to_one
+pub const UUID_NS: Uuid = uuid!("2b22547d-74d3-5dd4-94a9-122a2ac9341f");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::one_to_one;
+
+    #[test]
+    fn test_try_from() {
+        let mut from = one_to_one::ObjectStore::new();
+
+        let fred = one_to_one::Referent::new("fred".to_owned(), &mut from);
+        let a = one_to_one::A::new(42, &fred, &mut from);
+
+        let store = ObjectStore::try_from(&from).unwrap();
+
+        let referent = store.exhume_referent(&fred.id).unwrap();
+        assert_eq!(fred.name, referent.name);
+
+        let select_a = store.exhume_a(&a.id).unwrap();
+        assert_eq!(a.number, select_a.number);
+        assert_eq!(referent, select_a.r1_referent(&store)[0]);
+    }
+}
@@ -0,0 +1,87 @@
+//! One to One Domain
+//!
+//! This file will eventually be generated.
+//!
+//! The purpose of this domain is to help me develop and test domain code generation.
+//! This one has typed ids.
+use uuid::{uuid, Uuid};
+
+pub mod store;
+pub mod types;
+
+pub use store::ObjectStore;
+pub use types::*;
+
+// one_to_one
+pub const UUID_NS: Uuid = uuid!("2b22547d-74d3-5dd4-94a9-122a2ac9341f");
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, io};
+
+    use super::*;
+    use crate::domain::one_to_one;
+
+    #[test]
+    fn test_typed_ids() {
+        let mut store = ObjectStore::new();
+
+        let fred = Referent::new("fred".to_owned(), &mut store);
+        let a = A::new(42, &fred, &mut store);
+
+        let fred_id: ReferentId = fred.id;
+        let a_ptr: ReferentId = a.ptr;
+        assert_eq!(fred_id, a_ptr);
+
+        assert_eq!(Some(&fred), store.exhume_referent(&fred_id));
+        assert_eq!(Some(&a), store.exhume_a(&a.id));
+        assert_eq!(Some(&fred_id), store.exhume_referent_id_by_name("fred"));
+        assert_eq!(&fred, a.r1_referent(&store)[0]);
+
+        // They are still UUIDs underneath.
+        let uuid: Uuid = fred_id.into();
+        assert_eq!(fred_id, ReferentId::from(uuid));
+        assert_eq!(uuid.to_string(), fred_id.to_string());
+    }
+
+    #[test]
+    fn test_round_trip() -> io::Result<()> {
+        let path = std::env::temp_dir().join(format!("one_to_one_typed_ids-{}", Uuid::new_v4()));
+        let mut store = ObjectStore::new();
+
+        let fred = Referent::new("fred".to_owned(), &mut store);
+        let a = A::new(42, &fred, &mut store);
+
+        store.persist(&path)?;
+        let store = ObjectStore::load(&path)?;
+        fs::remove_dir_all(&path)?;
+
+        assert_eq!(Some(&fred), store.exhume_referent(&fred.id));
+        assert_eq!(Some(&a), store.exhume_a(&a.id));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_untyped() -> io::Result<()> {
+        let path = std::env::temp_dir().join(format!("one_to_one_typed_ids-{}", Uuid::new_v4()));
+
+        // This one was generated without typed ids.
+        let mut untyped = one_to_one::ObjectStore::new();
+        let fred = one_to_one::Referent::new("fred".to_owned(), &mut untyped);
+        let a = one_to_one::A::new(42, &fred, &mut untyped);
+        untyped.persist(&path)?;
+
+        let store = ObjectStore::load(&path)?;
+        fs::remove_dir_all(&path)?;
+
+        let select_fred = store.exhume_referent(&fred.id.into()).unwrap();
+        assert_eq!(fred.name, select_fred.name);
+
+        let select_a = store.exhume_a(&a.id.into()).unwrap();
+        assert_eq!(a.number, select_a.number);
+        assert_eq!(select_fred, select_a.r1_referent(&store)[0]);
+
+        Ok(())
+    }
+}
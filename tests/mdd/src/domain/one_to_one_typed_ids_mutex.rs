@@ -0,0 +1,62 @@
+//! One to One Domain
+//!
+//! This file will eventually be generated.
+//!
+//! The purpose of this domain is to help me develop and test domain code generation.
+//! This one has typed ids, in a mutex uber store.
+use uuid::{uuid, Uuid};
+
+pub mod store;
+pub mod types;
+
+pub use store::ObjectStore;
+pub use types::*;
+
+// one_to_one
+pub const UUID_NS: Uuid = uuid!("2b22547d-74d3-5dd4-94a9-122a2ac9341f");
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, io};
+
+    use super::*;
+
+    #[test]
+    fn test_typed_ids() {
+        let mut store = ObjectStore::new();
+
+        let fred = Referent::new("fred".to_owned(), &mut store);
+        let a = A::new(42, &fred, &mut store);
+
+        let fred_id: ReferentId = fred.lock().unwrap().id;
+        let a_id: AId = a.lock().unwrap().id;
+        assert_eq!(fred_id, a.lock().unwrap().ptr);
+
+        let select_fred = store.exhume_referent(&fred_id).unwrap();
+        assert_eq!(*fred.lock().unwrap(), *select_fred.lock().unwrap());
+        let select_a = store.exhume_a(&a_id).unwrap();
+        assert_eq!(*a.lock().unwrap(), *select_a.lock().unwrap());
+
+        assert_eq!(Some(fred_id), store.exhume_referent_id_by_name("fred"));
+    }
+
+    #[test]
+    fn test_round_trip() -> io::Result<()> {
+        let path =
+            std::env::temp_dir().join(format!("one_to_one_typed_ids_mutex-{}", Uuid::new_v4()));
+        let mut store = ObjectStore::new();
+
+        let fred = Referent::new("fred".to_owned(), &mut store);
+        let fred_id = fred.lock().unwrap().id;
+
+        store.persist(&path)?;
+        let store = ObjectStore::load(&path)?;
+        fs::remove_dir_all(&path)?;
+
+        let select_fred = store.exhume_referent(&fred_id).unwrap();
+        assert_eq!(*fred.lock().unwrap(), *select_fred.lock().unwrap());
+        assert_eq!(Some(fred_id), store.exhume_referent_id_by_name("fred"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,53 @@
+//! One to One Domain
+//!
+//! This file will eventually be generated.
+//!
+//! The purpose of this domain is to help me develop and test domain code generation.
+//! This one derives ids from the constructor arguments.
+use uuid::{uuid, Uuid};
+
+pub mod store;
+pub mod types;
+
+pub use store::ObjectStore;
+pub use types::*;
+
+// one_to_one
+pub const UUID_NS: Uuid = uuid!("2b22547d-74d3-5dd4-94a9-122a2ac9341f");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_arguments() {
+        let mut one = ObjectStore::new();
+        let mut two = ObjectStore::new();
+
+        let fred = Referent::new("fred".to_owned(), &mut one);
+        let fred_two = Referent::new("fred".to_owned(), &mut two);
+        assert_eq!(fred.id, fred_two.id);
+
+        // Referents contribute their id.
+        let a = A::new(42, &fred, &mut one);
+        let a_two = A::new(42, &fred_two, &mut two);
+        assert_eq!(a.id, a_two.id);
+
+        let c = C::new(1.162, None, &mut one);
+        let c_two = C::new(1.162, None, &mut two);
+        assert_eq!(c.id, c_two.id);
+    }
+
+    #[test]
+    fn test_different_arguments() {
+        let mut store = ObjectStore::new();
+
+        let fred = Referent::new("fred".to_owned(), &mut store);
+        let gene = Referent::new("gene".to_owned(), &mut store);
+        assert_ne!(fred.id, gene.id);
+
+        let a = A::new(42, &fred, &mut store);
+        let b = A::new(42, &gene, &mut store);
+        assert_ne!(a.id, b.id);
+    }
+}
@@ -0,0 +1,63 @@
+//! One to One Domain
+//!
+//! This file will eventually be generated.
+//!
+//! The purpose of this domain is to help me develop and test domain code generation.
+//! This one derives ids from a seed that the store keeps.
+use uuid::{uuid, Uuid};
+
+pub mod store;
+pub mod types;
+
+pub use store::ObjectStore;
+pub use types::*;
+
+// one_to_one
+pub const UUID_NS: Uuid = uuid!("2b22547d-74d3-5dd4-94a9-122a2ac9341f");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(seed: u64) -> Vec<Uuid> {
+        let mut store = ObjectStore::new();
+        store.seed_uuids(seed);
+
+        let fred = Referent::new("fred".to_owned(), &mut store);
+        let a = A::new(42, &fred, &mut store);
+        let b = B::new(true, &fred, &mut store);
+        let c = C::new(1.162, None, &mut store);
+        let fred_again = Referent::new("fred".to_owned(), &mut store);
+
+        vec![fred.id, a.id, b.id, c.id, fred_again.id]
+    }
+
+    #[test]
+    fn test_same_seed() {
+        assert_eq!(run(42), run(42));
+    }
+
+    #[test]
+    fn test_different_seed() {
+        assert_ne!(run(42), run(43));
+    }
+
+    #[test]
+    fn test_same_arguments() {
+        // The seed moves on, so these don't collide.
+        let ids = run(42);
+        assert_ne!(ids[0], ids[4]);
+    }
+
+    #[test]
+    fn test_stores_are_independent() {
+        let mut one = ObjectStore::new();
+        let mut two = ObjectStore::new();
+
+        let fred = Referent::new("fred".to_owned(), &mut one);
+        Referent::new("gene".to_owned(), &mut one);
+        let fred_two = Referent::new("fred".to_owned(), &mut two);
+
+        assert_eq!(fred.id, fred_two.id);
+    }
+}